@@ -0,0 +1,47 @@
+//! Time parsing an export the size of a large store's catalog. Run with and without the `rayon`
+//! feature to compare:
+//!
+//! ```sh
+//! cargo bench --bench parse
+//! cargo bench --bench parse --features rayon
+//! ```
+
+use std::hint::black_box;
+
+use abc_product::{AbcProduct, records::ColumnLayout, testing};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+/// How many products the generated export has
+const ROWS: usize = 150_000;
+
+/// Repeat the first row of `sample` `ROWS` times, giving each copy its own sku in column
+/// `sku_column`
+fn repeat_rows(sample: &[u8], sku_column: usize) -> Vec<u8> {
+    let sample = String::from_utf8_lossy(sample);
+    let row: Vec<&str> = sample.lines().next().unwrap().split('\t').collect();
+    let mut out = String::new();
+    for n in 0..ROWS {
+        let mut fields = row.clone();
+        let sku = format!("SKU{:06}", n);
+        fields[sku_column] = &sku;
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+    out.into_bytes()
+}
+
+fn parse(c: &mut Criterion) {
+    let layout = ColumnLayout::default();
+    let item = repeat_rows(testing::SAMPLE_ITEM_DATA, layout.sku);
+    let posted = repeat_rows(testing::SAMPLE_ITEM_POSTED_DATA, layout.posted_sku);
+
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(10);
+    group.bench_function("from_readers", |b| {
+        b.iter(|| AbcProduct::from_readers(black_box(&item[..]), black_box(&posted[..])).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,744 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{Duration, NaiveDate};
+use rust_decimal::{Decimal, RoundingStrategy, prelude::FromPrimitive};
+
+use crate::{
+    AbcGroup, AbcProduct, AbcProductsBySku, catalog::AbcCatalog, classify::Classifier,
+    forecast::Forecast, invoice::AbcInvoice,
+};
+
+/// How many days before a stock-out are used to estimate how fast the product was selling
+const VELOCITY_DAYS: i64 = 28;
+
+/// A rule that customers who buy `sku` also tend to buy `with`, as found by
+/// [`frequently_bought_together`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Association {
+    /// The sku bought first
+    pub sku: String,
+    /// The sku bought along with it
+    pub with: String,
+    /// The share of all invoices that contain both skus, from 0 to 1
+    pub support: f64,
+    /// The share of invoices containing `sku` that also contain `with`, from 0 to 1
+    pub confidence: f64,
+    /// How much more often the two skus are bought together than if they were unrelated. Above
+    /// 1 means they go together, 1 means no relationship
+    pub lift: f64,
+}
+
+/// Find pairs of products that are often bought on the same invoice, for suggesting related
+/// products and planning endcaps.
+///
+/// Each invoice counts as one basket of the skus sold on it, however many of each were sold.
+/// Returned items, lines without a sku, and invoices with fewer than two skus still count
+/// towards the total number of invoices, but cannot form pairs.
+///
+/// # Arguments
+/// * `invoices` - The sales history to look through
+/// * `min_support` - The smallest share of all invoices, from 0 to 1, that a pair must appear
+///   on to be reported. Raise this to ignore pairs that only happened by chance
+///
+/// # Returns
+/// Both directions of every pair that meets `min_support`, sorted by sku and then by
+/// confidence, strongest first
+///
+/// # Example
+/// ```rust,no_run
+/// use abc_product::{analytics, invoice::AbcInvoice, relations::Relations};
+///
+/// let invoices = AbcInvoice::from_db_export("./invoice.data", "./invoice_line.data").unwrap();
+/// let rules = analytics::frequently_bought_together(invoices.values(), 0.01);
+/// let relations = Relations::from_associations(&rules, 0.25);
+/// ```
+pub fn frequently_bought_together<'a>(
+    invoices: impl IntoIterator<Item = &'a AbcInvoice>,
+    min_support: f64,
+) -> Vec<Association> {
+    let mut total = 0usize;
+    let mut singles: HashMap<String, usize> = HashMap::new();
+    let mut pairs: HashMap<(String, String), usize> = HashMap::new();
+    for invoice in invoices {
+        total += 1;
+        let basket: BTreeSet<String> = invoice
+            .lines()
+            .iter()
+            .filter(|l| l.qty().is_sign_positive() && !l.qty().is_zero())
+            .filter_map(|l| l.sku())
+            .collect();
+        let basket: Vec<String> = basket.into_iter().collect();
+        for (i, a) in basket.iter().enumerate() {
+            *singles.entry(a.to_string()).or_default() += 1;
+            for b in &basket[i + 1..] {
+                *pairs.entry((a.to_string(), b.to_string())).or_default() += 1;
+            }
+        }
+    }
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let total = total as f64;
+    let mut rules = Vec::new();
+    for ((a, b), count) in pairs {
+        let support = count as f64 / total;
+        if support < min_support {
+            continue;
+        }
+        for (sku, with) in [(&a, &b), (&b, &a)] {
+            let confidence = count as f64 / singles[sku] as f64;
+            rules.push(Association {
+                sku: sku.to_string(),
+                with: with.to_string(),
+                support,
+                confidence,
+                lift: confidence / (singles[with] as f64 / total),
+            });
+        }
+    }
+    rules.sort_by(|x, y| {
+        x.sku
+            .cmp(&y.sku)
+            .then(y.confidence.total_cmp(&x.confidence))
+            .then_with(|| x.with.cmp(&y.with))
+    });
+    rules
+}
+
+/// A stretch of time that a product was out of stock, as found by [`stockouts`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stockout {
+    /// The sku of the product
+    pub sku: String,
+    /// The date of the first snapshot with no stock on hand
+    pub start: NaiveDate,
+    /// The date of the first snapshot with stock back on hand. [`None`] if it is still out
+    pub end: Option<NaiveDate>,
+    /// How many days the product was out, counting up to the latest snapshot if it is still out
+    pub days: i64,
+    /// The units sold per day in the four weeks before the stock-out, estimated from how much
+    /// the stock on hand fell between snapshots
+    pub velocity: f64,
+    /// The units that would likely have sold had the product been in stock
+    pub lost_units: f64,
+    /// The lost units at the list price when the product ran out
+    pub lost_sales: Decimal,
+}
+
+/// Find every time `sku` ran out of stock, how long it stayed out, and the sales that were
+/// likely lost while it was out.
+///
+/// Lost sales are estimated from the product's velocity before it ran out, so they are only as
+/// good as the snapshots: daily snapshots give the best estimates, and a product with no
+/// snapshots in the four weeks before running out has a velocity of zero.
+///
+/// # Arguments
+/// * `history` - Dated snapshots of the catalog, such as daily exports, in any order.
+///   Snapshots that do not list `sku` are skipped
+/// * `sku` - The product to look at
+///
+/// # Returns
+/// Each stock-out, in date order
+pub fn stockouts(history: &[(NaiveDate, AbcProductsBySku)], sku: &str) -> Vec<Stockout> {
+    let mut series: Vec<(NaiveDate, f64, Decimal)> = history
+        .iter()
+        .filter_map(|(date, products)| products.get(sku).map(|p| (*date, p.stock(), p.list())))
+        .collect();
+    series.sort_by_key(|(date, _, _)| *date);
+    let Some((latest, _, _)) = series.last().copied() else {
+        return Vec::new();
+    };
+
+    let velocity_before = |start: NaiveDate| {
+        let window: Vec<&(NaiveDate, f64, Decimal)> = series
+            .iter()
+            .filter(|(date, _, _)| *date >= start - Duration::days(VELOCITY_DAYS) && *date <= start)
+            .collect();
+        let (Some(first), Some(last)) = (window.first(), window.last()) else {
+            return 0.0;
+        };
+        let days = (last.0 - first.0).num_days();
+        if days == 0 {
+            return 0.0;
+        }
+        // Rises in stock are receipts rather than sales, so only the falls are counted
+        let sold: f64 = window.windows(2).map(|w| (w[0].1 - w[1].1).max(0.0)).sum();
+        sold / days as f64
+    };
+
+    let mut found: Vec<Stockout> = Vec::new();
+    let mut open: Option<(NaiveDate, Decimal)> = None;
+    for (date, stock, list) in &series {
+        match open {
+            None if *stock <= 0.0 => open = Some((*date, *list)),
+            Some((start, start_list)) if *stock > 0.0 => {
+                found.push(stockout(
+                    sku,
+                    start,
+                    Some(*date),
+                    *date,
+                    start_list,
+                    velocity_before(start),
+                ));
+                open = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((start, list)) = open {
+        found.push(stockout(
+            sku,
+            start,
+            None,
+            latest,
+            list,
+            velocity_before(start),
+        ));
+    }
+    found
+}
+
+/// Build a [`Stockout`] that lasted from `start` until `until`
+fn stockout(
+    sku: &str,
+    start: NaiveDate,
+    end: Option<NaiveDate>,
+    until: NaiveDate,
+    list: Decimal,
+    velocity: f64,
+) -> Stockout {
+    let days = (until - start).num_days();
+    let lost_units = velocity * days as f64;
+    Stockout {
+        sku: sku.to_string(),
+        start,
+        end,
+        days,
+        velocity,
+        lost_units,
+        lost_sales: (Decimal::from_f64(lost_units).unwrap_or_default() * list)
+            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+    }
+}
+
+/// A product whose min and max stock levels in ABC differ from the levels its sales and lead
+/// times call for, as found by [`minmax_recommendations`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinMaxChange {
+    /// The sku of the product
+    pub sku: String,
+    /// The description of the product, so buyers can review the change without looking it up
+    pub desc: String,
+    /// The min currently set in ABC. [`None`] if it is not known
+    pub current_min: Option<f64>,
+    /// The max currently set in ABC. [`None`] if it is not known
+    pub current_max: Option<f64>,
+    /// The suggested min, which is the reorder point rounded up to a whole unit
+    pub min: f64,
+    /// The suggested max, which adds the demand expected over one review cycle to the min
+    pub max: f64,
+}
+
+/// Suggested min and max stock levels for buyers to review, as returned by
+/// [`minmax_recommendations`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MinMaxReport {
+    /// Every product whose levels should change, sorted by sku
+    pub changes: Vec<MinMaxChange>,
+    /// Tab delimited lines of sku, min, and max to import into ABC once the changes are approved
+    pub import: String,
+}
+
+/// Compare the min and max stock levels set in ABC against the levels that sales and lead
+/// times call for, so that reorder settings keep up with demand instead of being set once and
+/// forgotten.
+///
+/// The suggested min is [`Forecast::reorder_point`] and the suggested max adds the demand
+/// expected over `review_days`, both rounded up to whole units. Products with no sales get a
+/// min and max of zero, but only show up if they currently have levels set, so that slow
+/// movers stop being restocked without the report listing every dead item.
+///
+/// # Arguments
+/// * `products` - The products to check, with their current min and max. See
+///   [`crate::records::ColumnLayout::min_stock`]
+/// * `forecast` - Sales and lead time history for the products
+/// * `service_level` - The chance of not running out while waiting for an order, such as 0.95
+/// * `review_days` - How many days pass between checks of what needs to be ordered
+///
+/// # Returns
+/// A [`MinMaxReport`] listing every product whose levels should change alongside the import
+/// for ABC
+pub fn minmax_recommendations(
+    products: &AbcProductsBySku,
+    forecast: &Forecast,
+    service_level: f64,
+    review_days: f64,
+) -> MinMaxReport {
+    let mut changes: Vec<MinMaxChange> = products
+        .values()
+        .filter_map(|product| {
+            let sku = product.sku();
+            let (demand, _) = forecast.demand(&sku);
+            let (current_min, current_max) = (product.min_stock(), product.max_stock());
+            if demand <= 0.0 && current_min.is_none() && current_max.is_none() {
+                return None;
+            }
+            let (min, max) = if demand <= 0.0 {
+                (0.0, 0.0)
+            } else {
+                let reorder_point = forecast.reorder_point(&sku, service_level);
+                let min = reorder_point.ceil();
+                (min, min.max((reorder_point + demand * review_days).ceil()))
+            };
+            (current_min != Some(min) || current_max != Some(max)).then(|| MinMaxChange {
+                desc: product.desc(),
+                sku,
+                current_min,
+                current_max,
+                min,
+                max,
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| a.sku.cmp(&b.sku));
+    let import = changes
+        .iter()
+        .map(|c| format!("{}\t{}\t{}\n", c.sku, c.min, c.max))
+        .collect();
+    MinMaxReport { changes, import }
+}
+
+/// The cents a price ends in, as counted by [`price_endings`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PriceEnding {
+    /// Prices like 5.99
+    NinetyNine,
+    /// Prices like 5.95
+    NinetyFive,
+    /// Whole dollar prices like 6.00
+    Zero,
+    /// Every other price
+    Other,
+}
+
+impl PriceEnding {
+    /// The ending of `price`, looking only at its first two decimal places
+    pub fn of(price: Decimal) -> Self {
+        let cents = (price.abs().trunc_with_scale(2) * Decimal::ONE_HUNDRED) % Decimal::ONE_HUNDRED;
+        if cents == Decimal::from(99) {
+            Self::NinetyNine
+        } else if cents == Decimal::from(95) {
+            Self::NinetyFive
+        } else if cents.is_zero() {
+            Self::Zero
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// How many list prices in a group end each way, as returned by [`price_endings`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriceEndings {
+    /// Prices ending in .99
+    pub ninety_nine: usize,
+    /// Prices ending in .95
+    pub ninety_five: usize,
+    /// Prices ending in .00
+    pub zero: usize,
+    /// Prices with any other ending
+    pub other: usize,
+}
+
+impl PriceEndings {
+    /// How many prices were counted
+    pub fn total(&self) -> usize {
+        self.ninety_nine + self.ninety_five + self.zero + self.other
+    }
+
+    /// How many prices end with `ending`
+    pub fn count(&self, ending: PriceEnding) -> usize {
+        match ending {
+            PriceEnding::NinetyNine => self.ninety_nine,
+            PriceEnding::NinetyFive => self.ninety_five,
+            PriceEnding::Zero => self.zero,
+            PriceEnding::Other => self.other,
+        }
+    }
+
+    /// The fraction of prices, from 0 to 1, that end with `ending`. Zero if there are no prices
+    pub fn share(&self, ending: PriceEnding) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.count(ending) as f64 / total as f64,
+        }
+    }
+}
+
+/// Count how many list prices in each discount group end in .99, .95, .00, or anything else,
+/// to find groups whose pricing is inconsistent before cleaning it up with a rounding rule.
+///
+/// # Arguments
+/// * `products` - The products whose prices to count
+///
+/// # Returns
+/// The counts for each group, with products that have no group under [`None`]
+///
+/// # Example
+/// ```rust
+/// use abc_product::{AbcGroup, analytics::{self, PriceEnding}, testing};
+///
+/// let endings = analytics::price_endings(&testing::sample_export());
+/// assert_eq!(endings[&None].count(PriceEnding::NinetyNine), 1);
+/// assert_eq!(endings[&AbcGroup::new('A')].count(PriceEnding::Other), 1);
+/// ```
+pub fn price_endings(products: &AbcProductsBySku) -> BTreeMap<Option<AbcGroup>, PriceEndings> {
+    let mut by_group: BTreeMap<Option<AbcGroup>, PriceEndings> = BTreeMap::new();
+    for product in products.values() {
+        let counts = by_group.entry(product.group()).or_default();
+        match PriceEnding::of(product.list()) {
+            PriceEnding::NinetyNine => counts.ninety_nine += 1,
+            PriceEnding::NinetyFive => counts.ninety_five += 1,
+            PriceEnding::Zero => counts.zero += 1,
+            PriceEnding::Other => counts.other += 1,
+        }
+    }
+    by_group
+}
+
+/// The class of a product in an ABC (Pareto) analysis, as assigned by
+/// [`AbcCatalog::classify_abc`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ParetoClass {
+    /// The few products that make up most of the value, which deserve the tightest counts and
+    /// reorder points
+    A,
+    /// The products in between
+    B,
+    /// The long tail of products that add little value each
+    C,
+}
+
+/// What to rank products by in [`AbcCatalog::classify_abc`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParetoBasis<'a> {
+    /// The cost of the stock on hand, to focus counting and dead stock work
+    StockValue,
+    /// The cost of the units sold over some period, such as the counts returned by
+    /// [`crate::invoice::units_sold`], to focus replenishment
+    UnitsSold(&'a HashMap<String, f64>),
+}
+
+impl ParetoBasis<'_> {
+    /// The value of `product` on this basis at cost, rounded to the cent. Never less than zero
+    fn value(&self, product: &AbcProduct) -> Decimal {
+        let units = match self {
+            Self::StockValue => product.stock(),
+            Self::UnitsSold(sold) => sold.get(&product.sku()).copied().unwrap_or_default(),
+        };
+        (Decimal::from_f64(units).unwrap_or_default() * product.cost())
+            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+            .max(Decimal::ZERO)
+    }
+}
+
+/// Where the classes of [`AbcCatalog::classify_abc`] divide, as cumulative percents of the
+/// total value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParetoThresholds {
+    /// Products are class A until this percent of the value is reached. 80 by default
+    pub a: Decimal,
+    /// Products are class B until this percent of the value is reached, and class C after.
+    /// 95 by default
+    pub b: Decimal,
+}
+
+impl Default for ParetoThresholds {
+    fn default() -> Self {
+        ParetoThresholds {
+            a: Decimal::from(80),
+            b: Decimal::from(95),
+        }
+    }
+}
+
+/// A product ranked by [`AbcCatalog::classify_abc`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParetoRank {
+    /// The sku of the product
+    pub sku: String,
+    /// The value the product was ranked by, at cost
+    pub value: Decimal,
+    /// The percent of the total value made up by this product and every product ranked above
+    /// it, rounded to two decimal places
+    pub cumulative: Decimal,
+    /// The class of the product
+    pub class: ParetoClass,
+}
+
+impl AbcCatalog {
+    /// Sort products into A, B, and C classes by how much of the catalog's value they make up,
+    /// so that counting and replenishment effort goes where the money is.
+    ///
+    /// A product's class is decided by the share of the value held by the products ranked
+    /// above it, so the most valuable product is always class A no matter how large its own
+    /// share. Products with no value are always class C. Only
+    /// [`crate::classify::ItemKind::Inventory`] items are ranked.
+    ///
+    /// # Arguments
+    /// * `basis` - What to rank products by
+    /// * `thresholds` - Where the classes divide
+    /// * `classifier` - Decides which products are inventory
+    ///
+    /// # Returns
+    /// Every inventory product, most valuable first and by sku when the value ties
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use abc_product::{
+    ///     analytics::{ParetoBasis, ParetoClass, ParetoThresholds},
+    ///     catalog::AbcCatalog,
+    ///     classify::Classifier,
+    ///     testing,
+    /// };
+    ///
+    /// let catalog = AbcCatalog::new(testing::sample_export());
+    /// let sold = HashMap::from([("123456".to_string(), 40.0), ("ABC123".to_string(), 1.0)]);
+    /// let ranks = catalog.classify_abc(
+    ///     ParetoBasis::UnitsSold(&sold),
+    ///     ParetoThresholds::default(),
+    ///     &Classifier::default(),
+    /// );
+    /// // 40 x 1.23 is worth more than 1 x 5.23
+    /// assert_eq!(ranks[0].sku, "123456");
+    /// assert_eq!(ranks[0].class, ParetoClass::A);
+    /// assert_eq!(ranks[1].class, ParetoClass::B);
+    /// ```
+    pub fn classify_abc(
+        &self,
+        basis: ParetoBasis<'_>,
+        thresholds: ParetoThresholds,
+        classifier: &Classifier,
+    ) -> Vec<ParetoRank> {
+        let mut ranked: Vec<(Decimal, String)> = self
+            .iter()
+            .filter(|p| classifier.is_inventory(p))
+            .map(|p| (basis.value(p), p.sku()))
+            .collect();
+        ranked.sort_by(|(a, p), (b, q)| b.cmp(a).then_with(|| p.cmp(q)));
+        let total: Decimal = ranked.iter().map(|(value, _)| *value).sum();
+
+        let mut running = Decimal::ZERO;
+        ranked
+            .into_iter()
+            .map(|(value, sku)| {
+                let share = |amount: Decimal| {
+                    if total.is_zero() {
+                        Decimal::ZERO
+                    } else {
+                        amount / total * Decimal::ONE_HUNDRED
+                    }
+                };
+                let above = share(running);
+                running += value;
+                let class = if value.is_zero() || above >= thresholds.b {
+                    ParetoClass::C
+                } else if above >= thresholds.a {
+                    ParetoClass::B
+                } else {
+                    ParetoClass::A
+                };
+                ParetoRank {
+                    sku,
+                    value,
+                    cumulative: share(running)
+                        .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+                    class,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Sku, invoice::AbcInvoice};
+
+    #[test]
+    fn test_frequently_bought_together() {
+        let invoice = "1\t2024-01-01\n2\t2024-01-02\n3\t2024-01-03\n4\t2024-01-04\n";
+        let lines = "1\tFAUCET\t\t1\t50\n1\tSUPPLY\t\t2\t5\n\
+                     2\tFAUCET\t\t1\t50\n2\tSUPPLY\t\t1\t5\n\
+                     3\tFAUCET\t\t1\t50\n\
+                     4\tSUPPLY\t\t1\t5\n4\tTAPE\t\t-1\t2\n";
+        let invoices = AbcInvoice::from_readers(invoice.as_bytes(), lines.as_bytes()).unwrap();
+        let rules = frequently_bought_together(invoices.values(), 0.25);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].sku, "FAUCET");
+        assert_eq!(rules[0].with, "SUPPLY");
+        assert_eq!(rules[0].support, 0.5);
+        assert!((rules[0].confidence - 2.0 / 3.0).abs() < 1e-9);
+        assert!((rules[0].lift - (2.0 / 3.0) / 0.75).abs() < 1e-9);
+
+        assert!(frequently_bought_together(invoices.values(), 0.6).is_empty());
+    }
+
+    #[test]
+    fn test_stockouts() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 6, d).unwrap();
+        let snapshot = |stock: f64| {
+            let product =
+                crate::AbcProductBuilder::from(crate::testing::sample_export()["123456"].clone())
+                    .with_stock(stock)
+                    .build()
+                    .unwrap();
+            AbcProductsBySku::from([(Sku::new("123456"), product)])
+        };
+        // Selling 2 a day, out from the 4th to the 9th, then out again from the 12th
+        let history: Vec<(NaiveDate, AbcProductsBySku)> = [
+            (1, 6.0),
+            (2, 4.0),
+            (3, 2.0),
+            (4, 0.0),
+            (6, 0.0),
+            (9, 10.0),
+            (12, -1.0),
+            (14, 0.0),
+        ]
+        .into_iter()
+        .map(|(d, stock)| (day(d), snapshot(stock)))
+        .collect();
+
+        let found = stockouts(&history, "123456");
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            (found[0].start, found[0].end, found[0].days),
+            (day(4), Some(day(9)), 5)
+        );
+        assert_eq!(found[0].velocity, 2.0);
+        assert_eq!(found[0].lost_units, 10.0);
+        assert_eq!(found[0].lost_sales, Decimal::new(5990, 2));
+        assert_eq!(
+            (found[1].start, found[1].end, found[1].days),
+            (day(12), None, 2)
+        );
+        assert!(stockouts(&history, "ABC123").is_empty());
+    }
+
+    #[test]
+    fn test_minmax_recommendations() {
+        // 14 units a week of 123456 for two weeks, nothing of ABC123
+        let invoice = "1\t2024-01-01\n2\t2024-01-08\n";
+        let lines = "1\t123456\tPRODUCT A\t14\t5.99\n2\t123456\tPRODUCT A\t14\t5.99\n";
+        let invoices = AbcInvoice::from_readers(invoice.as_bytes(), lines.as_bytes()).unwrap();
+        let forecast = Forecast::new(
+            invoices.values(),
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+        )
+        .with_default_lead_time(3.0);
+
+        let mut products = crate::testing::sample_export();
+        let report = minmax_recommendations(&products, &forecast, 0.95, 7.0);
+        // 2 a day over a 3 day lead time with no variance, plus a week of demand
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!((report.changes[0].min, report.changes[0].max), (6.0, 20.0));
+        assert_eq!(report.import, "123456\t6\t20\n");
+
+        let set = |product: &crate::AbcProduct, min: f64, max: f64| {
+            crate::AbcProductBuilder::from(product.clone())
+                .with_min_stock(min)
+                .with_max_stock(max)
+                .build()
+                .unwrap()
+        };
+        products.insert(Sku::new("123456"), set(&products["123456"], 6.0, 20.0));
+        products.insert(Sku::new("ABC123"), set(&products["ABC123"], 2.0, 4.0));
+        let report = minmax_recommendations(&products, &forecast, 0.95, 7.0);
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.changes[0].current_max, Some(4.0));
+        assert_eq!(report.import, "ABC123\t0\t0\n");
+    }
+
+    #[test]
+    fn test_price_endings() {
+        assert_eq!(
+            PriceEnding::of(Decimal::new(599, 2)),
+            PriceEnding::NinetyNine
+        );
+        assert_eq!(
+            PriceEnding::of(Decimal::new(12950, 3)),
+            PriceEnding::NinetyFive
+        );
+        assert_eq!(PriceEnding::of(Decimal::from(6)), PriceEnding::Zero);
+        assert_eq!(
+            PriceEnding::of(Decimal::new(5999, 3)),
+            PriceEnding::NinetyNine
+        );
+        assert_eq!(PriceEnding::of(Decimal::new(812, 2)), PriceEnding::Other);
+
+        let mut products = crate::testing::sample_export();
+        let whole = crate::AbcProductBuilder::from(products["123456"].clone())
+            .with_sku("WHOLE")
+            .with_list(Decimal::from(10))
+            .build()
+            .unwrap();
+        products.insert(Sku::new("WHOLE"), whole);
+        let endings = price_endings(&products);
+        assert_eq!(endings.len(), 2);
+        let ungrouped = endings[&None];
+        assert_eq!((ungrouped.ninety_nine, ungrouped.zero), (1, 1));
+        assert_eq!(ungrouped.share(PriceEnding::Zero), 0.5);
+        assert_eq!(endings[&AbcGroup::new('A')].total(), 1);
+    }
+
+    #[test]
+    fn test_classify_abc() {
+        let products: AbcProductsBySku = [("A1", 70), ("B1", 15), ("B2", 10), ("C1", 5), ("Z1", 0)]
+            .into_iter()
+            .map(|(sku, stock)| {
+                let product = AbcProduct::new()
+                    .with_sku(sku)
+                    .with_desc("WIDGET")
+                    .with_list(Decimal::TWO)
+                    .with_cost(Decimal::ONE)
+                    .with_stock(stock as f64)
+                    .build()
+                    .unwrap();
+                (Sku::new(sku), product)
+            })
+            .collect();
+        let catalog = AbcCatalog::new(products);
+
+        let ranks = catalog.classify_abc(
+            ParetoBasis::StockValue,
+            ParetoThresholds::default(),
+            &Classifier::default(),
+        );
+        let classes: Vec<(&str, ParetoClass)> =
+            ranks.iter().map(|r| (r.sku.as_str(), r.class)).collect();
+        assert_eq!(
+            classes,
+            vec![
+                ("A1", ParetoClass::A),
+                ("B1", ParetoClass::A),
+                ("B2", ParetoClass::B),
+                ("C1", ParetoClass::C),
+                ("Z1", ParetoClass::C),
+            ]
+        );
+        assert_eq!(ranks[2].cumulative, Decimal::from(95));
+
+        let strict = ParetoThresholds {
+            a: Decimal::from(50),
+            b: Decimal::from(75),
+        };
+        let ranks = catalog.classify_abc(ParetoBasis::StockValue, strict, &Classifier::default());
+        assert_eq!(ranks[0].class, ParetoClass::A);
+        assert_eq!(ranks[1].class, ParetoClass::B);
+        assert_eq!(ranks[2].class, ParetoClass::C);
+    }
+}
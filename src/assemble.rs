@@ -0,0 +1,371 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use crate::{AbcParseError, AbcProductBuilder, AbcProductsBySku, ProductField, Sku};
+
+/// How [`CatalogAssembler`] settles two sources giving different values for the same field of
+/// the same product. Fields a source leaves unset never conflict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Fail with [`AssembleError::Conflict`]
+    Error,
+    /// Keep the value from the fragment that was added first
+    FirstWins,
+    /// Keep the value from the fragment that was added last
+    LastWins,
+    /// Keep the value from the source that appears earliest in the list. Sources that are not
+    /// listed rank below every listed source, and are settled by which was added first
+    Priority(Vec<String>),
+}
+
+#[derive(Debug)]
+pub enum AssembleError {
+    /// A fragment from the named source has no sku, so it cannot be matched to a product
+    MissingSku(String),
+    /// Two sources disagree about a field while using [`ConflictPolicy::Error`]
+    Conflict {
+        /// The sku of the product
+        sku: String,
+        /// The field the sources disagree about
+        field: ProductField,
+        /// The names of the two sources, in the order their fragments were added
+        sources: (String, String),
+    },
+    /// After combining every fragment, the product with this sku is still missing a required
+    /// field
+    Incomplete(String, AbcParseError),
+}
+
+impl std::fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingSku(source) => write!(f, "A fragment from `{}` has no sku", source),
+            Self::Conflict {
+                sku,
+                field,
+                sources,
+            } => write!(
+                f,
+                "`{}` and `{}` disagree about the {} of sku `{}`",
+                sources.0,
+                sources.1,
+                field.name(),
+                sku
+            ),
+            Self::Incomplete(sku, e) => write!(f, "Sku `{}` is incomplete: {:?}", sku, e),
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+/// Take `src` into `dst` if `dst` is still unset, or report whether the two disagree
+fn merge_opt<T: PartialEq + Clone>(dst: &mut Option<T>, src: &Option<T>) -> bool {
+    match (dst.as_ref(), src) {
+        (None, Some(_)) => {
+            *dst = src.clone();
+            false
+        }
+        (Some(d), Some(s)) => d != s,
+        _ => false,
+    }
+}
+
+/// Like [`merge_opt`] for lists, where an empty list counts as unset
+fn merge_vec<T: PartialEq + Clone>(dst: &mut Vec<T>, src: &[T]) -> bool {
+    if dst.is_empty() {
+        *dst = src.to_vec();
+        false
+    } else {
+        !src.is_empty() && dst.as_slice() != src
+    }
+}
+
+/// Like [`merge_opt`] for each key of a map, so that sources setting different keys never
+/// conflict
+fn merge_map(dst: &mut BTreeMap<String, String>, src: &BTreeMap<String, String>) -> bool {
+    let mut conflict = false;
+    for (key, value) in src {
+        match dst.get(key) {
+            None => {
+                dst.insert(key.to_string(), value.to_string());
+            }
+            Some(existing) => conflict |= existing != value,
+        }
+    }
+    conflict
+}
+
+/// Fill the unset fields of `dst` from `src`
+///
+/// # Returns
+/// The fields where both were set to different values. `dst` keeps its own values for these
+fn merge(dst: &mut AbcProductBuilder, src: &AbcProductBuilder) -> Vec<ProductField> {
+    let checks = [
+        (ProductField::Desc, merge_opt(&mut dst.desc, &src.desc)),
+        (ProductField::Upcs, merge_vec(&mut dst.upcs, &src.upcs)),
+        (
+            ProductField::RawUpcs,
+            merge_vec(&mut dst.raw_upcs, &src.raw_upcs),
+        ),
+        (ProductField::List, merge_opt(&mut dst.list, &src.list)),
+        (ProductField::Cost, merge_opt(&mut dst.cost, &src.cost)),
+        (
+            ProductField::AvgCost,
+            merge_opt(&mut dst.avg_cost, &src.avg_cost),
+        ),
+        (ProductField::Stock, merge_opt(&mut dst.stock, &src.stock)),
+        (ProductField::Group, merge_opt(&mut dst.group, &src.group)),
+        (
+            ProductField::Weight,
+            merge_opt(&mut dst.weight, &src.weight),
+        ),
+        (
+            ProductField::LastSold,
+            merge_opt(&mut dst.last_sold, &src.last_sold),
+        ),
+        (
+            ProductField::AltSkus,
+            merge_vec(&mut dst.alt_skus, &src.alt_skus),
+        ),
+        (
+            ProductField::CoreSku,
+            merge_opt(&mut dst.core_sku, &src.core_sku),
+        ),
+        (
+            ProductField::WarrantyMonths,
+            merge_opt(&mut dst.warranty_months, &src.warranty_months),
+        ),
+        (
+            ProductField::NetContents,
+            merge_opt(&mut dst.net_contents, &src.net_contents),
+        ),
+        (
+            ProductField::CountryOfOrigin,
+            merge_opt(&mut dst.country_of_origin, &src.country_of_origin),
+        ),
+        (
+            ProductField::MinStock,
+            merge_opt(&mut dst.min_stock, &src.min_stock),
+        ),
+        (
+            ProductField::MaxStock,
+            merge_opt(&mut dst.max_stock, &src.max_stock),
+        ),
+        (
+            ProductField::Related,
+            merge_vec(&mut dst.related, &src.related),
+        ),
+        (
+            ProductField::Attributes,
+            merge_map(&mut dst.attributes, &src.attributes),
+        ),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, conflict)| *conflict)
+        .map(|(field, _)| field)
+        .collect()
+}
+
+/// Collects partial products from any number of sources, such as the item and posted files,
+/// a notes file, or a vendor cross reference, and combines them into a single catalog.
+///
+/// Each fragment is an [`AbcProductBuilder`] with its sku and whichever fields its source knows
+/// about. Fragments can be added from several threads at once, since [`CatalogAssembler::add`]
+/// only needs a shared reference.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{AbcProduct, assemble::{CatalogAssembler, ConflictPolicy}};
+/// use rust_decimal::Decimal;
+///
+/// let assembler = CatalogAssembler::new(ConflictPolicy::Error);
+/// std::thread::scope(|s| {
+///     s.spawn(|| assembler.add("item", AbcProduct::new()
+///         .with_sku("A1")
+///         .with_desc("DRILL")
+///         .with_list(Decimal::new(9999, 2))
+///         .with_cost(Decimal::new(5000, 2))));
+///     s.spawn(|| assembler.add("posted", AbcProduct::new().with_sku("A1").with_stock(3.0)));
+/// });
+/// let products = assembler.finish().unwrap();
+/// assert_eq!(products["A1"].stock(), 3.0);
+/// ```
+#[derive(Debug)]
+pub struct CatalogAssembler {
+    policy: ConflictPolicy,
+    fragments: Mutex<Vec<(String, AbcProductBuilder)>>,
+}
+
+impl CatalogAssembler {
+    /// Create an empty assembler that settles conflicts using `policy`
+    pub fn new(policy: ConflictPolicy) -> Self {
+        CatalogAssembler {
+            policy,
+            fragments: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a partial product from the source named `source`
+    pub fn add(&self, source: &str, fragment: AbcProductBuilder) {
+        self.fragments
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((source.to_string(), fragment));
+    }
+
+    /// Add every product of an already parsed catalog as fragments from `source`
+    pub fn add_products(&self, source: &str, products: &AbcProductsBySku) {
+        for product in products.values() {
+            self.add(source, AbcProductBuilder::from(product.clone()));
+        }
+    }
+
+    /// Combine every fragment added so far into complete products
+    ///
+    /// # Errors
+    /// An [`AssembleError`] if a fragment has no sku, sources conflict under
+    /// [`ConflictPolicy::Error`], or a product is still missing required fields
+    pub fn finish(self) -> Result<AbcProductsBySku, AssembleError> {
+        let fragments = self
+            .fragments
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner());
+
+        let mut by_sku: HashMap<Sku, Vec<(String, AbcProductBuilder)>> = HashMap::new();
+        for (source, fragment) in fragments {
+            let Some(sku) = fragment.sku.clone() else {
+                return Err(AssembleError::MissingSku(source));
+            };
+            by_sku.entry(sku).or_default().push((source, fragment));
+        }
+
+        let rank = |source: &str| match &self.policy {
+            ConflictPolicy::Priority(order) => order
+                .iter()
+                .position(|s| s == source)
+                .unwrap_or(order.len()),
+            _ => 0,
+        };
+        let mut products = AbcProductsBySku::new();
+        for (sku, mut fragments) in by_sku {
+            // Order the fragments so that the winning value of every field comes first
+            match self.policy {
+                ConflictPolicy::LastWins => fragments.reverse(),
+                ConflictPolicy::Priority(_) => fragments.sort_by_key(|(source, _)| rank(source)),
+                _ => {}
+            }
+
+            let mut fragments = fragments.into_iter();
+            let Some((first_source, mut merged)) = fragments.next() else {
+                continue;
+            };
+            for (source, fragment) in fragments {
+                let conflicts = merge(&mut merged, &fragment);
+                if let (ConflictPolicy::Error, Some(field)) = (&self.policy, conflicts.first()) {
+                    return Err(AssembleError::Conflict {
+                        sku: sku.to_string(),
+                        field: *field,
+                        sources: (first_source, source),
+                    });
+                }
+            }
+            let product = merged
+                .build()
+                .map_err(|e| AssembleError::Incomplete(sku.to_string(), e))?;
+            products.insert(sku, product);
+        }
+        Ok(products)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbcProduct;
+    use rust_decimal::Decimal;
+
+    fn assemble(policy: ConflictPolicy) -> Result<AbcProductsBySku, AssembleError> {
+        let assembler = CatalogAssembler::new(policy);
+        assembler.add(
+            "item",
+            AbcProduct::new()
+                .with_sku("A1")
+                .with_desc("DRILL")
+                .with_list(Decimal::new(9999, 2))
+                .with_cost(Decimal::new(5000, 2)),
+        );
+        assembler.add("posted", AbcProduct::new().with_sku("A1").with_stock(3.0));
+        assembler.add(
+            "notes",
+            AbcProduct::new().with_sku("A1").with_desc("CORDLESS DRILL"),
+        );
+        assembler.finish()
+    }
+
+    #[test]
+    fn test_assemble_policies() {
+        assert!(matches!(
+            assemble(ConflictPolicy::Error),
+            Err(AssembleError::Conflict {
+                field: ProductField::Desc,
+                ..
+            })
+        ));
+        assert_eq!(
+            assemble(ConflictPolicy::FirstWins).unwrap()["A1"].desc(),
+            "DRILL"
+        );
+        let last = assemble(ConflictPolicy::LastWins).unwrap();
+        assert_eq!(last["A1"].desc(), "CORDLESS DRILL");
+        assert_eq!(last["A1"].stock(), 3.0);
+        let priority = ConflictPolicy::Priority(vec!["notes".to_string()]);
+        assert_eq!(assemble(priority).unwrap()["A1"].desc(), "CORDLESS DRILL");
+    }
+
+    #[test]
+    fn test_assemble_attributes() {
+        let assembler = CatalogAssembler::new(ConflictPolicy::Error);
+        assembler.add(
+            "item",
+            AbcProduct::new()
+                .with_sku("A1")
+                .with_desc("DRILL")
+                .with_list(Decimal::ONE)
+                .with_cost(Decimal::ONE)
+                .with_stock(1.0)
+                .with_attr("color", "RED"),
+        );
+        assembler.add(
+            "sidecar",
+            AbcProduct::new()
+                .with_sku("A1")
+                .with_attr("voltage", "18")
+                .with_related(&["B2".to_string()]),
+        );
+        let products = assembler.finish().unwrap();
+        assert_eq!(products["A1"].attr("color").as_deref(), Some("RED"));
+        assert_eq!(products["A1"].attr("voltage").as_deref(), Some("18"));
+        assert_eq!(products["A1"].related(), vec!["B2"]);
+
+        let assembler = CatalogAssembler::new(ConflictPolicy::Error);
+        assembler.add(
+            "item",
+            AbcProduct::new().with_sku("A1").with_attr("color", "RED"),
+        );
+        assembler.add(
+            "sidecar",
+            AbcProduct::new().with_sku("A1").with_attr("color", "BLUE"),
+        );
+        assert!(matches!(
+            assembler.finish(),
+            Err(AssembleError::Conflict {
+                field: ProductField::Attributes,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,438 @@
+use std::{collections::BTreeMap, io::Read};
+
+use crate::{AbcParseError, AbcProductsBySku, Sku, input};
+
+/// Product attributes that ABC has no field for, such as finish or color, kept in a sidecar
+/// file of key and value pairs for each sku. Once applied, they are available from
+/// [`crate::AbcProduct::attr`] and can be written as extra columns with
+/// [`crate::export::CsvOptions::with_attribute_columns`].
+///
+/// # Example
+/// ```rust
+/// use abc_product::{attributes::Attributes, testing};
+///
+/// let mut products = testing::sample_export();
+/// Attributes::new()
+///     .with_attr("123456", "finish", "Brushed nickel")
+///     .apply(&mut products);
+/// assert_eq!(products["123456"].attr("finish").unwrap(), "Brushed nickel");
+/// assert_eq!(products["ABC123"].attr("finish"), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attributes {
+    by_sku: BTreeMap<Sku, BTreeMap<String, String>>,
+}
+
+impl Attributes {
+    /// Create an empty [`Attributes`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the attribute `key` of `sku` to `value`
+    pub fn with_attr(self, sku: &str, key: &str, value: &str) -> Self {
+        let mut by_sku = self.by_sku;
+        by_sku
+            .entry(Sku::new(sku))
+            .or_default()
+            .insert(key.trim().to_string(), value.to_string());
+        Attributes { by_sku }
+    }
+
+    /// Load attributes from a comma separated file with a header row, where the first column
+    /// is the sku and every other column is an attribute named by its header, such as
+    /// `sku,finish,color`. Empty cells are skipped.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file of attributes
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if the file cannot be read or has no header row
+    pub fn from_csv(path: &str) -> Result<Self, AbcParseError> {
+        Self::from_csv_reader(std::fs::File::open(path).map_err(csv::Error::from)?)
+    }
+
+    /// Like [`Attributes::from_csv`], reading from any reader rather than a path on disk
+    pub(crate) fn from_csv_reader(data: impl Read) -> Result<Self, AbcParseError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(input::normalize(data).map_err(csv::Error::from)?);
+        let headers = reader.headers()?.clone();
+        if headers.len() < 2 {
+            return Err(AbcParseError::missing(None, 1, Some(1), "attribute"));
+        }
+        let mut attributes = Attributes::new();
+        for record in reader.into_records() {
+            let record = record?;
+            let Some(sku) = record.get(0).map(str::trim).filter(|s| !s.is_empty()) else {
+                continue;
+            };
+            for (key, value) in headers.iter().zip(record.iter()).skip(1) {
+                if !value.trim().is_empty() {
+                    attributes = attributes.with_attr(sku, key, value.trim());
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// Load attributes from TOML with one table per sku, such as:
+    ///
+    /// ```toml
+    /// [123456]
+    /// finish = "Brushed nickel"
+    /// length_in = 12
+    /// ```
+    ///
+    /// Values that are not strings are kept as their TOML text, so `12` becomes `"12"`
+    ///
+    /// # Errors
+    /// A [`toml::de::Error`] if the TOML is malformed. Top level keys that are not tables are
+    /// skipped
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        let table: toml::Table = s.parse()?;
+        let mut attributes = Attributes::new();
+        for (sku, values) in table {
+            let Some(values) = values.as_table() else {
+                continue;
+            };
+            for (key, value) in values {
+                let value = match value {
+                    toml::Value::String(s) => s.to_string(),
+                    other => other.to_string(),
+                };
+                attributes = attributes.with_attr(&sku, key, &value);
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// Every attribute of `sku`, keyed by name. `sku` is normalized with [`Sku::new`] first
+    pub fn get(&self, sku: &str) -> Option<&BTreeMap<String, String>> {
+        self.by_sku.get(&Sku::new(sku))
+    }
+
+    /// Add the attributes of every product in `products` to it. Attributes from the sidecar
+    /// replace any the product already has with the same name
+    pub fn apply(&self, products: &mut AbcProductsBySku) {
+        for product in products.values_mut() {
+            if let Some(attributes) = self.by_sku.get(&product.sku) {
+                product.attributes.extend(
+                    attributes
+                        .iter()
+                        .map(|(key, value)| (key.to_string(), value.to_string())),
+                );
+            }
+        }
+    }
+}
+
+/// The kind of value an attribute must hold, as checked by [`AttributeSchema::validate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttributeType {
+    /// Any text
+    #[default]
+    Text,
+    /// A number, such as `12` or `0.75`
+    Number,
+    /// A whole number, such as `12`
+    Integer,
+    /// `true` or `false`, or `yes` or `no`, in any case
+    Boolean,
+}
+
+impl AttributeType {
+    /// Whether `value` is of this type
+    pub fn accepts(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            Self::Text => true,
+            Self::Number => value.parse::<f64>().is_ok_and(f64::is_finite),
+            Self::Integer => value.parse::<i64>().is_ok(),
+            Self::Boolean => ["true", "false", "yes", "no"]
+                .iter()
+                .any(|b| value.eq_ignore_ascii_case(b)),
+        }
+    }
+}
+
+impl std::fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Number => "number",
+            Self::Integer => "whole number",
+            Self::Boolean => "true or false",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A problem with one product's attributes, as found by [`AttributeSchema::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttributeIssue {
+    /// The product's group requires an attribute it does not have
+    Missing {
+        /// The sku of the product
+        sku: String,
+        /// The attribute that is missing
+        key: String,
+    },
+    /// The value is not of the type the schema requires
+    WrongType {
+        /// The sku of the product
+        sku: String,
+        /// The attribute with the wrong type
+        key: String,
+        /// The value that was found
+        value: String,
+        /// The type the schema requires
+        expected: AttributeType,
+    },
+    /// The value is not one of the values the schema allows
+    NotAllowed {
+        /// The sku of the product
+        sku: String,
+        /// The attribute with the value that is not allowed
+        key: String,
+        /// The value that was found
+        value: String,
+    },
+}
+
+impl AttributeIssue {
+    /// The sku of the product with the issue
+    pub fn sku(&self) -> &str {
+        match self {
+            Self::Missing { sku, .. }
+            | Self::WrongType { sku, .. }
+            | Self::NotAllowed { sku, .. } => sku,
+        }
+    }
+}
+
+impl std::fmt::Display for AttributeIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { sku, key } => {
+                write!(f, "Sku `{}` is missing required attribute `{}`", sku, key)
+            }
+            Self::WrongType {
+                sku,
+                key,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Attribute `{}` of sku `{}` is `{}`, but must be a {}",
+                key, sku, value, expected
+            ),
+            Self::NotAllowed { sku, key, value } => write!(
+                f,
+                "Attribute `{}` of sku `{}` is `{}`, which is not one of its allowed values",
+                key, sku, value
+            ),
+        }
+    }
+}
+
+/// Rules that supplemental attributes must follow: which attributes each discount group
+/// requires, and the type and allowed values of each attribute. Used to keep attribute
+/// spreadsheets consistent before they reach a storefront.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{
+///     attributes::{AttributeSchema, AttributeType, Attributes},
+///     testing,
+/// };
+///
+/// let mut products = testing::sample_export();
+/// Attributes::new()
+///     .with_attr("ABC123", "finish", "Chrome")
+///     .with_attr("ABC123", "length_in", "twelve")
+///     .apply(&mut products);
+/// let schema = AttributeSchema::new()
+///     .with_required("A", "finish")
+///     .with_allowed("finish", &["Chrome", "Brushed nickel"])
+///     .with_type("length_in", AttributeType::Number);
+/// let issues = schema.validate(&products);
+/// assert_eq!(issues.len(), 1);
+/// assert_eq!(issues[0].sku(), "ABC123");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttributeSchema {
+    required: BTreeMap<String, Vec<String>>,
+    types: BTreeMap<String, AttributeType>,
+    allowed: BTreeMap<String, Vec<String>>,
+}
+
+impl AttributeSchema {
+    /// Create a schema with no rules, which every product passes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require every product in discount group `group` to have the attribute `key`
+    pub fn with_required(self, group: &str, key: &str) -> Self {
+        let mut required = self.required;
+        required
+            .entry(group.trim().to_string())
+            .or_default()
+            .push(key.to_string());
+        AttributeSchema { required, ..self }
+    }
+
+    /// Require values of the attribute `key` to be of type `kind`
+    pub fn with_type(self, key: &str, kind: AttributeType) -> Self {
+        let mut types = self.types;
+        types.insert(key.to_string(), kind);
+        AttributeSchema { types, ..self }
+    }
+
+    /// Only allow the attribute `key` to be one of `values`
+    pub fn with_allowed(self, key: &str, values: &[&str]) -> Self {
+        let mut allowed = self.allowed;
+        allowed.insert(
+            key.to_string(),
+            values.iter().map(|v| v.to_string()).collect(),
+        );
+        AttributeSchema { allowed, ..self }
+    }
+
+    /// Check the attributes of every product in `products` against this schema
+    ///
+    /// # Returns
+    /// Every issue found, sorted by sku. Missing attributes come first for each sku, followed by
+    /// problems with the values it has in the order of their names
+    pub fn validate(&self, products: &AbcProductsBySku) -> Vec<AttributeIssue> {
+        let mut skus: Vec<&Sku> = products.keys().collect();
+        skus.sort();
+        let mut issues = Vec::new();
+        for sku in skus {
+            let product = &products[sku];
+            let attributes = &product.attributes;
+            if let Some(required) = product
+                .group()
+                .and_then(|g| self.required.get(&g.to_string()))
+            {
+                for key in required {
+                    if attributes.get(key).is_none_or(|v| v.trim().is_empty()) {
+                        issues.push(AttributeIssue::Missing {
+                            sku: sku.to_string(),
+                            key: key.to_string(),
+                        });
+                    }
+                }
+            }
+            for (key, value) in attributes {
+                if let Some(expected) = self.types.get(key)
+                    && !expected.accepts(value)
+                {
+                    issues.push(AttributeIssue::WrongType {
+                        sku: sku.to_string(),
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        expected: *expected,
+                    });
+                }
+                if let Some(allowed) = self.allowed.get(key)
+                    && !allowed.contains(value)
+                {
+                    issues.push(AttributeIssue::NotAllowed {
+                        sku: sku.to_string(),
+                        key: key.to_string(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_attributes() {
+        let data = "sku,finish,color\n123456,Brushed nickel,\nABC123,,Red\n,Ignored,\n";
+        let attributes = Attributes::from_csv_reader(data.as_bytes()).unwrap();
+        let mut products = sample_export();
+        attributes.apply(&mut products);
+        assert_eq!(
+            products["123456"].attributes(),
+            BTreeMap::from([("finish".to_string(), "Brushed nickel".to_string())])
+        );
+        assert_eq!(products["ABC123"].attr("color").unwrap(), "Red");
+        assert_eq!(products["ABC123"].attr("finish"), None);
+
+        assert!(Attributes::from_csv_reader("sku\n123456\n".as_bytes()).is_err());
+
+        let padded = Attributes::new().with_attr(" abc123 ", "finish", "Chrome");
+        assert_eq!(padded.get("ABC123").unwrap()["finish"], "Chrome");
+        let mut products = sample_export();
+        padded.apply(&mut products);
+        assert_eq!(products["ABC123"].attr("finish").unwrap(), "Chrome");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_attributes_from_toml() {
+        let attributes = Attributes::from_toml(
+            "[123456]\nfinish = \"Brushed nickel\"\nlength_in = 12\n\n[ABC123]\ncolor = \"Red\"\n",
+        )
+        .unwrap();
+        assert_eq!(attributes.get("123456").unwrap()["length_in"], "12");
+        assert_eq!(attributes.get("ABC123").unwrap()["color"], "Red");
+    }
+
+    #[test]
+    fn test_attribute_schema() {
+        let mut products = sample_export();
+        Attributes::new()
+            .with_attr("123456", "length_in", "12.5")
+            .with_attr("123456", "finish", "Gold")
+            .with_attr("ABC123", "length_in", "12.5")
+            .with_attr("ABC123", "lead_free", "Yes")
+            .apply(&mut products);
+        let schema = AttributeSchema::new()
+            .with_required("A", "finish")
+            .with_required("A", "lead_free")
+            .with_type("length_in", AttributeType::Integer)
+            .with_type("lead_free", AttributeType::Boolean)
+            .with_allowed("finish", &["Chrome"]);
+        assert_eq!(
+            schema.validate(&products),
+            vec![
+                AttributeIssue::NotAllowed {
+                    sku: "123456".to_string(),
+                    key: "finish".to_string(),
+                    value: "Gold".to_string(),
+                },
+                AttributeIssue::WrongType {
+                    sku: "123456".to_string(),
+                    key: "length_in".to_string(),
+                    value: "12.5".to_string(),
+                    expected: AttributeType::Integer,
+                },
+                AttributeIssue::Missing {
+                    sku: "ABC123".to_string(),
+                    key: "finish".to_string(),
+                },
+                AttributeIssue::WrongType {
+                    sku: "ABC123".to_string(),
+                    key: "length_in".to_string(),
+                    value: "12.5".to_string(),
+                    expected: AttributeType::Integer,
+                },
+            ]
+        );
+        assert!(AttributeSchema::new().validate(&products).is_empty());
+        assert!(AttributeType::Number.accepts(" 12.5"));
+        assert!(!AttributeType::Number.accepts("NaN"));
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+use crate::invoice::{AbcInvoice, units_sold};
+
+/// How many weeks are in each period of a quarter
+const PATTERN: [i64; 3] = [4, 5, 4];
+
+/// A 4-5-4 retail calendar, where every quarter is split into periods of 4, 5, and 4 whole
+/// weeks so that periods always start on the same weekday and compare fairly year over year.
+///
+/// Each fiscal year starts on the `week_start` day nearest the first of `start_month`, and is
+/// named for the calendar year it starts in. Years therefore have 52 weeks, or 53 every five or
+/// six years, with the extra week added to the last period. The default is the National Retail
+/// Federation calendar: years start on the Sunday nearest February 1st.
+///
+/// # Example
+/// ```rust
+/// use abc_product::calendar::RetailCalendar;
+/// use chrono::NaiveDate;
+///
+/// let calendar = RetailCalendar::default();
+/// let period = calendar.period_of(NaiveDate::from_ymd_opt(2024, 3, 15).unwrap());
+/// assert_eq!((period.fiscal_year, period.period), (2024, 2));
+/// assert_eq!(period.start, NaiveDate::from_ymd_opt(2024, 3, 3).unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetailCalendar {
+    start_month: u32,
+    week_start: Weekday,
+}
+
+impl Default for RetailCalendar {
+    fn default() -> Self {
+        RetailCalendar {
+            start_month: 2,
+            week_start: Weekday::Sun,
+        }
+    }
+}
+
+/// One period of a [`RetailCalendar`]. Periods sort in date order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RetailPeriod {
+    /// The fiscal year, named for the calendar year it starts in
+    pub fiscal_year: i32,
+    /// The period of the fiscal year, from 1 to 12
+    pub period: u32,
+    /// The quarter of the fiscal year, from 1 to 4
+    pub quarter: u32,
+    /// The first day of the period
+    pub start: NaiveDate,
+    /// The last day of the period
+    pub end: NaiveDate,
+}
+
+impl RetailPeriod {
+    /// How many weeks the period has: 4 or 5, or 6 for the last period of a 53 week year
+    pub fn weeks(&self) -> i64 {
+        ((self.end - self.start).num_days() + 1) / 7
+    }
+
+    /// Whether `date` falls within the period
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start && date <= self.end
+    }
+}
+
+impl RetailCalendar {
+    /// Create a calendar whose years start on the `week_start` day nearest the first of
+    /// `start_month`
+    ///
+    /// # Returns
+    /// [`None`] if `start_month` is not from 1 to 12
+    pub fn new(start_month: u32, week_start: Weekday) -> Option<Self> {
+        (1..=12).contains(&start_month).then_some(RetailCalendar {
+            start_month,
+            week_start,
+        })
+    }
+
+    /// The month, from 1 to 12, that fiscal years start near
+    pub fn start_month(&self) -> u32 {
+        self.start_month
+    }
+
+    /// The day every week, and so every period and year, starts on
+    pub fn week_start(&self) -> Weekday {
+        self.week_start
+    }
+
+    /// The first day of `fiscal_year`
+    pub fn year_start(&self, fiscal_year: i32) -> NaiveDate {
+        // `new` only allows months from 1 to 12, so the first of the month always exists
+        let first = NaiveDate::from_ymd_opt(fiscal_year, self.start_month, 1).unwrap_or_default();
+        let back = (first.weekday().num_days_from_sunday() + 7
+            - self.week_start.num_days_from_sunday())
+            % 7;
+        let before = first - Duration::days(back as i64);
+        if back <= 3 {
+            before
+        } else {
+            before + Duration::days(7)
+        }
+    }
+
+    /// How many weeks `fiscal_year` has, either 52 or 53
+    pub fn weeks_in_year(&self, fiscal_year: i32) -> i64 {
+        (self.year_start(fiscal_year + 1) - self.year_start(fiscal_year)).num_days() / 7
+    }
+
+    /// The fiscal year `date` falls in
+    pub fn fiscal_year_of(&self, date: NaiveDate) -> i32 {
+        let year = date.year();
+        if date < self.year_start(year) {
+            year - 1
+        } else if date >= self.year_start(year + 1) {
+            year + 1
+        } else {
+            year
+        }
+    }
+
+    /// The week of its fiscal year that `date` falls in, from 1 to 53
+    pub fn week_of(&self, date: NaiveDate) -> u32 {
+        let start = self.year_start(self.fiscal_year_of(date));
+        ((date - start).num_days() / 7) as u32 + 1
+    }
+
+    /// The 12 periods of `fiscal_year`, in order
+    pub fn periods(&self, fiscal_year: i32) -> Vec<RetailPeriod> {
+        let extra_week = self.weeks_in_year(fiscal_year) - 52;
+        let mut start = self.year_start(fiscal_year);
+        (0..12u32)
+            .map(|i| {
+                let mut weeks = PATTERN[i as usize % 3];
+                if i == 11 {
+                    weeks += extra_week;
+                }
+                let period = RetailPeriod {
+                    fiscal_year,
+                    period: i + 1,
+                    quarter: i / 3 + 1,
+                    start,
+                    end: start + Duration::days(weeks * 7 - 1),
+                };
+                start = period.end + Duration::days(1);
+                period
+            })
+            .collect()
+    }
+
+    /// The period `date` falls in
+    pub fn period_of(&self, date: NaiveDate) -> RetailPeriod {
+        let periods = self.periods(self.fiscal_year_of(date));
+        // The periods cover the whole year, so one of them always contains the date
+        periods
+            .iter()
+            .find(|p| p.contains(date))
+            .copied()
+            .unwrap_or(periods[11])
+    }
+}
+
+/// Count the units of each sku sold in each retail period, net of returns, so that sales
+/// reports line up with the merchandising calendar rather than calendar months. Invoices
+/// without a date are skipped.
+///
+/// # Arguments
+/// * `invoices` - The invoices to count
+/// * `calendar` - The calendar whose periods to count by
+///
+/// # Returns
+/// A map of every period with at least one sale to the units sold of each sku in that period.
+/// See [`crate::invoice::units_sold`]
+pub fn units_sold_by_period<'a>(
+    invoices: impl IntoIterator<Item = &'a AbcInvoice>,
+    calendar: &RetailCalendar,
+) -> BTreeMap<RetailPeriod, HashMap<String, f64>> {
+    let mut by_period: BTreeMap<RetailPeriod, Vec<&AbcInvoice>> = BTreeMap::new();
+    for invoice in invoices {
+        if let Some(date) = invoice.date() {
+            by_period
+                .entry(calendar.period_of(date))
+                .or_default()
+                .push(invoice);
+        }
+    }
+    by_period
+        .into_iter()
+        .map(|(period, invoices)| (period, units_sold(invoices, period.start, period.end)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_retail_calendar() {
+        let calendar = RetailCalendar::default();
+        // National Retail Federation fiscal years
+        assert_eq!(calendar.year_start(2023), date(2023, 1, 29));
+        assert_eq!(calendar.year_start(2024), date(2024, 2, 4));
+        assert_eq!(calendar.weeks_in_year(2023), 53);
+        assert_eq!(calendar.weeks_in_year(2024), 52);
+
+        let periods = calendar.periods(2023);
+        assert_eq!(
+            periods.iter().map(|p| p.weeks()).collect::<Vec<i64>>(),
+            vec![4, 5, 4, 4, 5, 4, 4, 5, 4, 4, 5, 5]
+        );
+        assert_eq!(periods[11].end, date(2024, 2, 3));
+        assert_eq!(periods[3].quarter, 2);
+
+        assert_eq!(calendar.fiscal_year_of(date(2024, 2, 3)), 2023);
+        assert_eq!(calendar.week_of(date(2024, 2, 3)), 53);
+        assert_eq!(calendar.period_of(date(2024, 2, 4)).period, 1);
+
+        let july = RetailCalendar::new(7, Weekday::Mon).unwrap();
+        assert_eq!(july.year_start(2024), date(2024, 7, 1));
+        assert!(RetailCalendar::new(13, Weekday::Mon).is_none());
+    }
+
+    #[test]
+    fn test_units_sold_by_period() {
+        let invoice = "5001\t2024-03-01\t\t\n5002\t2024-03-02\t\t\n5003\t2024-03-03\t\t\n";
+        let lines = "5001\t123456\tPRODUCT A\t2\t5.99\n\
+                     5002\t123456\tPRODUCT A\t1\t5.99\n\
+                     5003\t123456\tPRODUCT A\t4\t5.99\n";
+        let invoices = AbcInvoice::from_readers(invoice.as_bytes(), lines.as_bytes()).unwrap();
+        let sold = units_sold_by_period(invoices.values(), &RetailCalendar::default());
+        let by_period: Vec<(u32, f64)> = sold
+            .iter()
+            .map(|(p, units)| (p.period, units["123456"]))
+            .collect();
+        // Periods 1 and 2 of 2024 split on Sunday, March 3rd
+        assert_eq!(by_period, vec![(1, 3.0), (2, 4.0)]);
+    }
+}
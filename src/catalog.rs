@@ -0,0 +1,337 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Deref,
+    sync::Arc,
+};
+
+use ean13::Ean13;
+use rust_decimal::Decimal;
+
+use crate::{
+    AbcParseError, AbcProduct, AbcProductsBySku, Sku,
+    diff::{self, CatalogDiff},
+    search::SkuTrie,
+};
+
+/// A parsed catalog that can look products up by sku, alternate sku, UPC, or group without
+/// every caller building their own indexes.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{catalog::AbcCatalog, testing};
+///
+/// let catalog = AbcCatalog::new(testing::sample_export());
+/// assert_eq!(catalog.get_by_alt_sku("ALT").unwrap().sku(), "123456");
+/// assert_eq!(catalog.iter_by_group("A").count(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AbcCatalog {
+    products: AbcProductsBySku,
+    by_alt_sku: HashMap<String, String>,
+    by_upc: HashMap<String, String>,
+    by_group: BTreeMap<String, Vec<String>>,
+}
+
+impl AbcCatalog {
+    /// Take ownership of `products` and index them. When an alternate sku or UPC is shared by
+    /// more than one product, lookups return the one with the lowest sku
+    pub fn new(products: AbcProductsBySku) -> Self {
+        let mut skus: Vec<&Sku> = products.keys().collect();
+        skus.sort();
+
+        let mut by_alt_sku = HashMap::new();
+        let mut by_upc = HashMap::new();
+        let mut by_group: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for sku in skus {
+            let product = &products[sku];
+            for alt in product.alt_skus() {
+                by_alt_sku.entry(alt).or_insert(sku.to_string());
+            }
+            for upc in product.upcs() {
+                by_upc.entry(upc.to_string()).or_insert(sku.to_string());
+            }
+            if let Some(group) = product.group() {
+                by_group
+                    .entry(group.to_string())
+                    .or_default()
+                    .push(sku.to_string());
+            }
+        }
+        AbcCatalog {
+            products,
+            by_alt_sku,
+            by_upc,
+            by_group,
+        }
+    }
+
+    /// Parse and index an ABC database export. See [`AbcProduct::from_db_export`]
+    ///
+    /// # Errors
+    /// See [`AbcProduct::from_db_export`]
+    pub fn from_db_export(item_path: &str, item_posted_path: &str) -> Result<Self, AbcParseError> {
+        Ok(Self::new(AbcProduct::from_db_export(
+            item_path,
+            item_posted_path,
+        )?))
+    }
+
+    /// The product with the sku `sku`, which is normalized with [`Sku::new`] first
+    pub fn get_by_sku(&self, sku: &str) -> Option<&AbcProduct> {
+        self.products.get(&Sku::new(sku))
+    }
+
+    /// The product listing `alt` as one of its [`AbcProduct::alt_skus`], which is normalized
+    /// with [`Sku::new`] first
+    pub fn get_by_alt_sku(&self, alt: &str) -> Option<&AbcProduct> {
+        self.by_alt_sku
+            .get(Sku::new(alt).as_str())
+            .and_then(|sku| self.products.get(sku.as_str()))
+    }
+
+    /// The product with the UPC `upc`
+    pub fn get_by_upc(&self, upc: &Ean13) -> Option<&AbcProduct> {
+        self.by_upc
+            .get(&upc.to_string())
+            .and_then(|sku| self.products.get(sku.as_str()))
+    }
+
+    /// Every product in the group `group`, sorted by sku. Products without a group are never
+    /// returned
+    pub fn iter_by_group(&self, group: &str) -> impl Iterator<Item = &AbcProduct> {
+        self.by_group
+            .get(group)
+            .into_iter()
+            .flatten()
+            .filter_map(|sku| self.products.get(sku.as_str()))
+    }
+
+    /// The name of every group with at least one product, sorted
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        self.by_group.keys().map(|g| g.as_str())
+    }
+
+    /// Every product in the catalog, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &AbcProduct> {
+        self.products.values()
+    }
+
+    /// How many products are in the catalog
+    pub fn len(&self) -> usize {
+        self.products.len()
+    }
+
+    /// Whether the catalog has no products
+    pub fn is_empty(&self) -> bool {
+        self.products.is_empty()
+    }
+
+    /// The products of the catalog, for the functions of this crate that take an
+    /// [`AbcProductsBySku`]
+    pub fn products(&self) -> &AbcProductsBySku {
+        &self.products
+    }
+
+    /// Give up the indexes and return the products
+    pub fn into_products(self) -> AbcProductsBySku {
+        self.products
+    }
+
+    /// The products whose [`AbcProduct::margin_percent`] is below `threshold` percent, such as
+    /// `30` for every product making less than a 30% margin. Products with a list price of zero
+    /// have no margin percent and are always included, since a free product is almost always a
+    /// mistake. Products with a cost of zero are left out, since their margin is not known
+    ///
+    /// # Returns
+    /// The products, lowest margin first with zero priced products before all others, and by
+    /// sku when margins tie
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{catalog::AbcCatalog, testing};
+    /// use rust_decimal::Decimal;
+    ///
+    /// let catalog = AbcCatalog::new(testing::sample_export());
+    /// let low = catalog.products_below_margin(Decimal::from(50));
+    /// assert_eq!(low.len(), 1);
+    /// assert_eq!(low[0].sku(), "ABC123");
+    /// ```
+    pub fn products_below_margin(&self, threshold: Decimal) -> Vec<&AbcProduct> {
+        let mut below: Vec<(Option<Decimal>, &AbcProduct)> = self
+            .iter()
+            .map(|p| (p.margin_percent(), p))
+            .filter(|(margin, p)| match margin {
+                Some(margin) => *margin < threshold,
+                None => p.list().is_zero(),
+            })
+            .collect();
+        below.sort_by(|(a, p), (b, q)| a.cmp(b).then_with(|| p.sku().cmp(&q.sku())));
+        below.into_iter().map(|(_, p)| p).collect()
+    }
+
+    /// Compare two exports of the same catalog, such as last night's and tonight's, to find the
+    /// products that were added, removed, or changed so that only the changes need to be pushed
+    /// to other systems
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{ProductField, catalog::AbcCatalog, testing};
+    ///
+    /// let old = AbcCatalog::new(testing::sample_export());
+    /// let mut products = testing::sample_export();
+    /// products.remove("ABC123");
+    /// let new = AbcCatalog::new(products);
+    ///
+    /// let diff = AbcCatalog::diff(&old, &new);
+    /// assert_eq!(diff.removed[0].sku(), "ABC123");
+    /// assert_eq!(diff.changed_in(ProductField::List).count(), 0);
+    /// ```
+    pub fn diff(old: &AbcCatalog, new: &AbcCatalog) -> CatalogDiff {
+        diff::diff(&old.products, &new.products)
+    }
+}
+
+impl From<AbcProductsBySku> for AbcCatalog {
+    fn from(value: AbcProductsBySku) -> Self {
+        Self::new(value)
+    }
+}
+
+/// The indexes shared by every clone of an [`AbcCatalogView`]
+#[derive(Debug)]
+struct ViewIndexes {
+    catalog: AbcCatalog,
+    skus: SkuTrie,
+}
+
+/// A read-only catalog with every index built once up front, for services that answer many
+/// lookups from the same export. Cloning only bumps a reference count, so one view can be
+/// handed to every request handler and thread.
+///
+/// Every lookup of [`AbcCatalog`] is available through the view, along with sku completion.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{catalog::AbcCatalogView, testing};
+///
+/// let view = AbcCatalogView::build(testing::sample_export());
+/// let shared = view.clone();
+/// assert!(AbcCatalogView::ptr_eq(&view, &shared));
+/// assert_eq!(shared.get_by_alt_sku("ALT").unwrap().sku(), "123456");
+/// assert_eq!(shared.complete("abc", 10), vec!["ABC123".to_string()]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AbcCatalogView {
+    indexes: Arc<ViewIndexes>,
+}
+
+impl AbcCatalogView {
+    /// Take ownership of `products` and build every index
+    pub fn build(products: AbcProductsBySku) -> Self {
+        Self::from(AbcCatalog::new(products))
+    }
+
+    /// Up to `limit` skus starting with `prefix`, in sorted order. See [`SkuTrie::complete`]
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<String> {
+        self.indexes.skus.complete(prefix, limit)
+    }
+
+    /// Whether `a` and `b` are clones of the same view, and so share their indexes
+    pub fn ptr_eq(a: &AbcCatalogView, b: &AbcCatalogView) -> bool {
+        Arc::ptr_eq(&a.indexes, &b.indexes)
+    }
+}
+
+impl Deref for AbcCatalogView {
+    type Target = AbcCatalog;
+
+    fn deref(&self) -> &Self::Target {
+        &self.indexes.catalog
+    }
+}
+
+impl From<AbcCatalog> for AbcCatalogView {
+    fn from(value: AbcCatalog) -> Self {
+        let skus = SkuTrie::from_products(value.products());
+        AbcCatalogView {
+            indexes: Arc::new(ViewIndexes {
+                catalog: value,
+                skus,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_catalog() {
+        let catalog = AbcCatalog::from(sample_export());
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(catalog.get_by_sku("ABC123").unwrap().desc(), "PRODUCT B");
+        assert_eq!(catalog.get_by_alt_sku("ALT SKU").unwrap().sku(), "ABC123");
+        let upc = Ean13::from_str_nonstrict("0085875500015").unwrap();
+        assert_eq!(catalog.get_by_upc(&upc).unwrap().sku(), "123456");
+        assert_eq!(catalog.groups().collect::<Vec<&str>>(), vec!["A"]);
+        assert!(catalog.iter_by_group("B").next().is_none());
+        assert_eq!(catalog.into_products(), sample_export());
+    }
+
+    #[test]
+    fn test_margins() {
+        let products = sample_export();
+        let b = &products["ABC123"];
+        assert_eq!(b.margin(), Some(Decimal::new(289, 2)));
+        assert_eq!(b.margin_percent(), Some(Decimal::new(3559, 2)));
+        assert_eq!(b.markup_percent(), Some(Decimal::new(5526, 2)));
+        assert_eq!(
+            products["123456"].margin_percent(),
+            Some(Decimal::new(7947, 2))
+        );
+
+        let mut products = sample_export();
+        let free = crate::AbcProductBuilder::from(products["123456"].clone())
+            .with_sku("FREE")
+            .with_list(Decimal::ZERO)
+            .build()
+            .unwrap();
+        assert_eq!(free.margin_percent(), None);
+        products.insert(Sku::new("FREE"), free);
+        let uncosted = crate::AbcProductBuilder::from(products["123456"].clone())
+            .with_sku("UNCOSTED")
+            .with_cost(Decimal::ZERO)
+            .build()
+            .unwrap();
+        assert_eq!(uncosted.margin(), None);
+        assert_eq!(uncosted.margin_percent(), None);
+        products.insert(Sku::new("UNCOSTED"), uncosted);
+        let catalog = AbcCatalog::new(products);
+        let below: Vec<String> = catalog
+            .products_below_margin(Decimal::from(80))
+            .iter()
+            .map(|p| p.sku())
+            .collect();
+        assert_eq!(below, vec!["FREE", "ABC123", "123456"]);
+        assert_eq!(catalog.products_below_margin(Decimal::ZERO).len(), 1);
+    }
+
+    #[test]
+    fn test_catalog_view() {
+        let view = AbcCatalogView::build(sample_export());
+        let shared = view.clone();
+        let handle = std::thread::spawn(move || {
+            let upc = Ean13::from_str_nonstrict("0085875500015").unwrap();
+            shared.get_by_upc(&upc).map(|p| p.sku())
+        });
+        assert_eq!(handle.join().unwrap().as_deref(), Some("123456"));
+        assert_eq!(view.iter_by_group("A").count(), 1);
+        assert_eq!(view.complete("1", 10), vec!["123456".to_string()]);
+        assert!(!AbcCatalogView::ptr_eq(
+            &view,
+            &AbcCatalogView::build(sample_export())
+        ));
+    }
+}
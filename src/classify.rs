@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crate::{AbcProduct, AbcProductsBySku};
+
+/// What kind of item a product represents. Only [`ItemKind::Inventory`] items have meaningful
+/// stock levels and should be counted in stock or valuation math
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    /// A physical product that is stocked and sold
+    Inventory,
+    /// Billable labor or services
+    Labor,
+    /// Gift cards or store credit
+    GiftCard,
+    /// Fees and surcharges such as delivery or environmental fees
+    Fee,
+}
+
+/// Decides the [`ItemKind`] of products using the conventions a store uses to mark
+/// non-inventory items in ABC.
+///
+/// Rules are checked in order of group, then sku prefix, then description keyword. The first
+/// rule that matches wins. Products matching no rule are [`ItemKind::Inventory`].
+///
+/// # Example
+/// ```rust
+/// use abc_product::classify::{Classifier, ItemKind};
+///
+/// let classifier = Classifier::default()
+///     .with_group('L', ItemKind::Labor)
+///     .with_sku_prefix("GC-", ItemKind::GiftCard);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classifier {
+    groups: HashMap<String, ItemKind>,
+    sku_prefixes: Vec<(String, ItemKind)>,
+    desc_keywords: Vec<(String, ItemKind)>,
+}
+
+impl Classifier {
+    /// Create a [`Classifier`] with no rules at all. Every product will be classified as
+    /// [`ItemKind::Inventory`]
+    pub fn new() -> Self {
+        Classifier {
+            groups: HashMap::new(),
+            sku_prefixes: Vec::new(),
+            desc_keywords: Vec::new(),
+        }
+    }
+
+    /// Classify every product in `group` as `kind`. Groups are matched case insensitively
+    pub fn with_group(self, group: char, kind: ItemKind) -> Self {
+        let mut groups = self.groups;
+        groups.insert(group.to_uppercase().to_string(), kind);
+        Classifier { groups, ..self }
+    }
+
+    /// Classify every product whose sku starts with `prefix` as `kind`. Prefixes are matched
+    /// case insensitively
+    pub fn with_sku_prefix(self, prefix: &str, kind: ItemKind) -> Self {
+        let mut sku_prefixes = self.sku_prefixes;
+        sku_prefixes.push((prefix.to_uppercase(), kind));
+        Classifier {
+            sku_prefixes,
+            ..self
+        }
+    }
+
+    /// Classify every product whose description contains `keyword` as a whole word (or run of
+    /// whole words) as `kind`. Keywords are matched case insensitively
+    pub fn with_desc_keyword(self, keyword: &str, kind: ItemKind) -> Self {
+        let mut desc_keywords = self.desc_keywords;
+        desc_keywords.push((keyword.to_uppercase(), kind));
+        Classifier {
+            desc_keywords,
+            ..self
+        }
+    }
+
+    /// Determine what kind of item `product` is
+    pub fn classify(&self, product: &AbcProduct) -> ItemKind {
+        if let Some(kind) = product
+            .group()
+            .and_then(|g| self.groups.get(&g.to_string()))
+        {
+            return *kind;
+        }
+        let sku = product.sku().to_uppercase();
+        if let Some((_, kind)) = self
+            .sku_prefixes
+            .iter()
+            .find(|(prefix, _)| sku.starts_with(prefix))
+        {
+            return *kind;
+        }
+        let words: Vec<String> = product
+            .desc()
+            .to_uppercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_string())
+            .collect();
+        for (keyword, kind) in &self.desc_keywords {
+            let keyword: Vec<&str> = keyword.split_whitespace().collect();
+            if !keyword.is_empty() && words.windows(keyword.len()).any(|w| w == keyword) {
+                return *kind;
+            }
+        }
+        ItemKind::Inventory
+    }
+
+    /// Whether `product` is a stocked [`ItemKind::Inventory`] item
+    pub fn is_inventory(&self, product: &AbcProduct) -> bool {
+        self.classify(product) == ItemKind::Inventory
+    }
+
+    /// Copy just the [`ItemKind::Inventory`] products out of `products`, leaving labor, gift
+    /// cards, and fees behind
+    pub fn inventory_only(&self, products: &AbcProductsBySku) -> AbcProductsBySku {
+        products
+            .iter()
+            .filter(|(_, p)| self.is_inventory(p))
+            .map(|(sku, p)| (sku.clone(), p.clone()))
+            .collect()
+    }
+}
+
+impl Default for Classifier {
+    /// A [`Classifier`] that recognizes the descriptions ABC users most commonly give
+    /// non-inventory items: "LABOR", "GIFT CARD", and "FEE"
+    fn default() -> Self {
+        Classifier::new()
+            .with_desc_keyword("LABOR", ItemKind::Labor)
+            .with_desc_keyword("GIFT CARD", ItemKind::GiftCard)
+            .with_desc_keyword("FEE", ItemKind::Fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn product(sku: &str, desc: &str) -> AbcProduct {
+        AbcProduct::new()
+            .with_sku(sku)
+            .with_desc(desc)
+            .with_list(Decimal::new(1000, 2))
+            .with_cost(Decimal::ZERO)
+            .with_stock(0.0)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_classify() {
+        let classifier = Classifier::default().with_sku_prefix("gc", ItemKind::GiftCard);
+        assert_eq!(
+            classifier.classify(&product("100", "SMALL ENGINE LABOR")),
+            ItemKind::Labor
+        );
+        assert_eq!(
+            classifier.classify(&product("101", "$25 GIFT CARD")),
+            ItemKind::GiftCard
+        );
+        assert_eq!(
+            classifier.classify(&product("GC50", "STORE CREDIT")),
+            ItemKind::GiftCard
+        );
+        assert_eq!(
+            classifier.classify(&product("102", "COFFEE MAKER")),
+            ItemKind::Inventory
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{AbcParseError, AbcProduct, Encoding, ParseOptions, catalog::AbcCatalog};
+
+/// The `Database Export` directory of an ABC install, which holds one `CompanyNNN` directory for
+/// each company. Each company's data files are written to the `Data` directory inside of its
+/// own.
+///
+/// # Example
+/// ```rust,no_run
+/// use abc_product::company::AbcExportRoot;
+///
+/// let root = AbcExportRoot::discover("C:/ABC Software/Database Export").unwrap();
+/// for (company, catalog) in root.catalogs() {
+///     match catalog {
+///         Ok(catalog) => println!("Company {}: {} products", company, catalog.len()),
+///         Err(e) => eprintln!("Company {}: {}", company, e),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbcExportRoot {
+    path: PathBuf,
+    companies: BTreeMap<u32, PathBuf>,
+    encoding: Encoding,
+}
+
+impl AbcExportRoot {
+    /// Find every company exported under `path`
+    ///
+    /// # Arguments
+    /// * `path` - The export directory. This will probably be
+    ///   C:\ABC Software\Database Export
+    ///
+    /// # Returns
+    /// The root with every directory named `Company` followed by a company number, such as
+    /// `Company001`. Anything else in the directory is ignored
+    ///
+    /// # Errors
+    /// Any error listing the contents of `path`
+    pub fn discover(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut companies = BTreeMap::new();
+        for entry in fs::read_dir(&path)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let name = entry.file_name();
+            if let Some(number) = name.to_str().and_then(company_number) {
+                companies.insert(number, entry.path().join("Data"));
+            }
+        }
+        Ok(AbcExportRoot {
+            path,
+            companies,
+            encoding: Encoding::default(),
+        })
+    }
+
+    /// Read every company's files as text in `encoding` instead of detecting it
+    pub fn with_encoding(self, encoding: Encoding) -> Self {
+        AbcExportRoot { encoding, ..self }
+    }
+
+    /// The export directory the companies were found in
+    pub fn path(&self) -> PathBuf {
+        self.path.to_path_buf()
+    }
+
+    /// The number of every company found, in ascending order
+    pub fn companies(&self) -> Vec<u32> {
+        self.companies.keys().copied().collect()
+    }
+
+    /// The directory the data files of `company` are written to, such as
+    /// C:\ABC Software\Database Export\Company001\Data. [`None`] if no such company was found
+    pub fn data_dir(&self, company: u32) -> Option<PathBuf> {
+        self.companies.get(&company).cloned()
+    }
+
+    /// Parse the `item.data` and `item_posted.data` files of `company`
+    ///
+    /// # Returns
+    /// [`None`] if no such company was found
+    ///
+    /// # Errors
+    /// See [`AbcProduct::from_db_export_with_options`]
+    pub fn catalog(&self, company: u32) -> Option<Result<AbcCatalog, AbcParseError>> {
+        let dir = self.companies.get(&company)?;
+        Some(
+            AbcProduct::from_db_export_with_options(
+                &dir.join("item.data").to_string_lossy(),
+                &dir.join("item_posted.data").to_string_lossy(),
+                &ParseOptions::default().with_encoding(self.encoding),
+            )
+            .map(|(products, _, _)| AbcCatalog::new(products)),
+        )
+    }
+
+    /// Parse the catalog of every company found. See [`AbcExportRoot::catalog`]
+    ///
+    /// # Returns
+    /// Each company's catalog, or why it could not be parsed, keyed by company number. One
+    /// company failing to parse does not stop the others from being parsed
+    pub fn catalogs(&self) -> BTreeMap<u32, Result<AbcCatalog, AbcParseError>> {
+        self.companies
+            .keys()
+            .filter_map(|&company| Some((company, self.catalog(company)?)))
+            .collect()
+    }
+}
+
+/// The company number in the name of a company's export directory, such as 1 for `Company001`
+fn company_number(name: &str) -> Option<u32> {
+    let prefix = name.get(..7)?;
+    let digits = &name[7..];
+    if !prefix.eq_ignore_ascii_case("company")
+        || digits.is_empty()
+        || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SAMPLE_ITEM_DATA, SAMPLE_ITEM_POSTED_DATA, sample_export};
+
+    #[test]
+    fn test_discover() {
+        let dir = std::env::temp_dir().join(format!("abc-product-company-{}", std::process::id()));
+        let data = dir.join("Company001").join("Data");
+        fs::create_dir_all(&data).unwrap();
+        fs::write(data.join("item.data"), SAMPLE_ITEM_DATA).unwrap();
+        fs::write(data.join("item_posted.data"), SAMPLE_ITEM_POSTED_DATA).unwrap();
+        fs::create_dir_all(dir.join("Company012").join("Data")).unwrap();
+        fs::create_dir_all(dir.join("Company")).unwrap();
+        fs::create_dir_all(dir.join("Backups")).unwrap();
+        fs::write(dir.join("Company002"), b"").unwrap();
+
+        let root = AbcExportRoot::discover(&dir).unwrap();
+        assert_eq!(root.companies(), vec![1, 12]);
+        assert_eq!(root.data_dir(1), Some(data.clone()));
+        assert_eq!(root.data_dir(2), None);
+
+        let catalogs = root.catalogs();
+        assert_eq!(catalogs.len(), 2);
+        assert_eq!(catalogs[&1].as_ref().unwrap().products(), &sample_export());
+        assert!(catalogs[&12].is_err());
+        assert!(root.catalog(3).is_none());
+
+        let mut item = SAMPLE_ITEM_DATA.to_vec();
+        let at = item.windows(9).position(|w| w == b"PRODUCT A").unwrap() + 7;
+        item.splice(at..at + 1, *b"\x96");
+        fs::write(data.join("item.data"), item).unwrap();
+        assert!(root.catalog(1).unwrap().is_ok());
+        let root = root.with_encoding(Encoding::Utf8);
+        assert!(root.catalog(1).unwrap().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(company_number("COMPANY007"), Some(7));
+        assert_eq!(company_number("Company-1"), None);
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::Path,
+};
+
+use flate2::{read::MultiGzDecoder, write::GzEncoder};
+
+/// The first bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The first bytes of every zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// How a file is compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Not compressed
+    #[default]
+    None,
+    /// Compressed with gzip, usually named `*.gz`
+    Gzip,
+    /// Compressed with zstd, usually named `*.zst`
+    Zstd,
+}
+
+impl Compression {
+    /// The compression a file should be written with, going by the extension of `path`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) if e.eq_ignore_ascii_case("gz") => Self::Gzip,
+            Some(e) if e.eq_ignore_ascii_case("zst") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+
+    /// The compression of a file that starts with `header`, going by its magic bytes
+    pub fn detect(header: &[u8]) -> Self {
+        if header.starts_with(&GZIP_MAGIC) {
+            Self::Gzip
+        } else if header.starts_with(&ZSTD_MAGIC) {
+            Self::Zstd
+        } else {
+            Self::None
+        }
+    }
+}
+
+/// Wrap `inner` so that it reads decompressed data, detecting the compression from the magic
+/// bytes at the start of the stream. Uncompressed streams are read as is
+///
+/// # Errors
+/// Any [`std::io::Error`] raised while reading the start of `inner`
+pub fn reader<'a>(inner: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+    let mut inner = BufReader::new(inner);
+    let compression = Compression::detect(inner.fill_buf()?);
+    Ok(match compression {
+        Compression::None => Box::new(inner),
+        Compression::Gzip => Box::new(MultiGzDecoder::new(inner)),
+        Compression::Zstd => Box::new(zstd::Decoder::with_buffer(inner)?),
+    })
+}
+
+/// Open the file at `path` for reading, transparently decompressing it. See [`reader`]
+///
+/// # Errors
+/// Any [`std::io::Error`] raised while opening the file or reading its start
+pub fn open(path: &Path) -> io::Result<Box<dyn Read>> {
+    reader(fs::File::open(path)?)
+}
+
+/// A writer that compresses everything written through it. Unlike the encoders it wraps, it
+/// must be closed with [`Encoder::finish`] so that errors writing the end of the stream are not
+/// lost
+pub(crate) enum Encoder<W: Write> {
+    None(W),
+    Gzip(GzEncoder<W>),
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+impl<W: Write> Encoder<W> {
+    /// Start compressing into `inner` with `compression`
+    pub(crate) fn new(inner: W, compression: Compression) -> io::Result<Self> {
+        Ok(match compression {
+            Compression::None => Self::None(inner),
+            Compression::Gzip => Self::Gzip(GzEncoder::new(inner, flate2::Compression::default())),
+            Compression::Zstd => Self::Zstd(zstd::Encoder::new(inner, 0)?),
+        })
+    }
+
+    /// Write the end of the compressed stream and return the inner writer
+    pub(crate) fn finish(self) -> io::Result<W> {
+        match self {
+            Self::None(w) => Ok(w),
+            Self::Gzip(e) => e.finish(),
+            Self::Zstd(e) => e.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::None(w) => w.write(buf),
+            Self::Gzip(e) => e.write(buf),
+            Self::Zstd(e) => e.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::None(w) => w.flush(),
+            Self::Gzip(e) => e.flush(),
+            Self::Zstd(e) => e.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for compression in [Compression::None, Compression::Gzip, Compression::Zstd] {
+            let mut encoder = Encoder::new(Vec::new(), compression).unwrap();
+            encoder.write_all(b"123456\tPRODUCT A\n").unwrap();
+            let bytes = encoder.finish().unwrap();
+            assert_eq!(Compression::detect(&bytes), compression);
+
+            let mut out = String::new();
+            reader(bytes.as_slice())
+                .unwrap()
+                .read_to_string(&mut out)
+                .unwrap();
+            assert_eq!(out, "123456\tPRODUCT A\n");
+        }
+        assert_eq!(
+            Compression::from_path(Path::new("item.data.ZST")),
+            Compression::Zstd
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use std::{collections::HashMap, io::Read};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::{
+    AbcParseError, Encoding, open_data_file,
+    records::{amount, date, rows, text},
+};
+
+/// Column numbers of the fields this crate knows about in `customer.data`, counting from zero
+pub mod customer_columns {
+    pub const ACCOUNT: usize = 0;
+    pub const NAME: usize = 1;
+    pub const ADDRESS: [usize; 2] = [2, 3];
+    pub const CITY: usize = 4;
+    pub const STATE: usize = 5;
+    pub const ZIP: usize = 6;
+    pub const PHONE: usize = 7;
+    pub const PRICE_LEVEL: usize = 8;
+}
+
+/// Column numbers of the fields this crate knows about in `customer_posted.data`, counting from
+/// zero
+pub mod customer_posted_columns {
+    pub const ACCOUNT: usize = 0;
+    pub const BALANCE: usize = 1;
+    pub const LAST_SALE: usize = 2;
+}
+
+/// A map of account numbers to the [`AbcCustomer`] they belong to
+pub type AbcCustomersByAccount = HashMap<String, AbcCustomer>;
+
+/// A customer account, as exported from ABC's customer files
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbcCustomer {
+    account: String,
+    name: String,
+    address: Vec<String>,
+    city: Option<String>,
+    state: Option<String>,
+    zip: Option<String>,
+    phone: Option<String>,
+    price_level: Option<String>,
+    balance: Decimal,
+    last_sale: Option<NaiveDate>,
+}
+
+impl AbcCustomer {
+    /// Create a map of account numbers to [`AbcCustomer`]s by parsing the customer files of an
+    /// ABC database export.
+    ///
+    /// Run report 7-10 as described in [`crate::AbcProduct::from_db_export`], but select "C"
+    /// (Customer) as the file to export. Two files are written next to `item.data`, at
+    /// C:\ABC Software\Database Export\Company001\Data\customer.data and
+    /// C:\ABC Software\Database Export\Company001\Data\customer_posted.data.
+    ///
+    /// # Arguments
+    /// * `customer_path` - The path to the customer.data file generated by the db export
+    /// * `customer_posted_path` - The path to the customer_posted.data file generated by the db
+    ///   export
+    ///
+    /// # Returns
+    /// A [`HashMap`] of account numbers to the [`AbcCustomer`] they belong to
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file cannot be read, if a row is missing its account
+    /// number or name, if a balance cannot be parsed, or if the two files do not list the same
+    /// accounts
+    pub fn from_db_export(
+        customer_path: &str,
+        customer_posted_path: &str,
+    ) -> Result<AbcCustomersByAccount, AbcParseError> {
+        let customer_file = open_data_file(customer_path).map_err(csv::Error::from)?;
+        let customer_posted_file =
+            open_data_file(customer_posted_path).map_err(csv::Error::from)?;
+        Self::from_readers(customer_file, customer_posted_file)
+    }
+
+    /// Like [`AbcCustomer::from_db_export`], reading from any readers rather than from paths on
+    /// disk
+    pub(crate) fn from_readers(
+        customer: impl Read,
+        customer_posted: impl Read,
+    ) -> Result<AbcCustomersByAccount, AbcParseError> {
+        let mut posted: HashMap<String, (Decimal, Option<NaiveDate>)> = HashMap::new();
+        for row in rows(customer_posted, "customer_posted.data", Encoding::Auto)? {
+            let (row, record) = row?;
+            let account = text(&record, customer_posted_columns::ACCOUNT).ok_or_else(|| {
+                AbcParseError::missing(
+                    Some("customer_posted.data"),
+                    row,
+                    Some(customer_posted_columns::ACCOUNT),
+                    "account",
+                )
+            })?;
+            let balance = amount(
+                &record,
+                "customer_posted.data",
+                customer_posted_columns::BALANCE,
+                "balance",
+                row,
+            )?;
+            let last_sale = date(&record, customer_posted_columns::LAST_SALE);
+            posted.insert(account, (balance, last_sale));
+        }
+
+        let mut customers = AbcCustomersByAccount::new();
+        for row in rows(customer, "customer.data", Encoding::Auto)? {
+            let (row, record) = row?;
+            let account = text(&record, customer_columns::ACCOUNT).ok_or_else(|| {
+                AbcParseError::missing(
+                    Some("customer.data"),
+                    row,
+                    Some(customer_columns::ACCOUNT),
+                    "account",
+                )
+            })?;
+            let name = text(&record, customer_columns::NAME).ok_or_else(|| {
+                AbcParseError::missing(
+                    Some("customer.data"),
+                    row,
+                    Some(customer_columns::NAME),
+                    "name",
+                )
+            })?;
+            let (balance, last_sale) =
+                posted
+                    .get(&account)
+                    .copied()
+                    .ok_or(AbcParseError::MissingRecord {
+                        file: "customer_posted.data".to_string(),
+                        key: account.clone(),
+                        row,
+                    })?;
+            let customer = AbcCustomer {
+                account: account.clone(),
+                name,
+                address: customer_columns::ADDRESS
+                    .iter()
+                    .filter_map(|c| text(&record, *c))
+                    .collect(),
+                city: text(&record, customer_columns::CITY),
+                state: text(&record, customer_columns::STATE),
+                zip: text(&record, customer_columns::ZIP),
+                phone: text(&record, customer_columns::PHONE),
+                price_level: text(&record, customer_columns::PRICE_LEVEL),
+                balance,
+                last_sale,
+            };
+            customers.insert(account, customer);
+        }
+        if customers.len() != posted.len() {
+            return Err(AbcParseError::MismatchedRowCounts {
+                first: "customer.data".to_string(),
+                second: "customer_posted.data".to_string(),
+            });
+        }
+        Ok(customers)
+    }
+
+    /// The customer's account number
+    pub fn account(&self) -> String {
+        self.account.to_string()
+    }
+
+    /// The customer's name
+    pub fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    /// The street lines of the customer's address, skipping empty lines
+    pub fn address(&self) -> Vec<String> {
+        self.address.clone()
+    }
+
+    /// The city of the customer's address
+    pub fn city(&self) -> Option<String> {
+        self.city.clone()
+    }
+
+    /// The state of the customer's address
+    pub fn state(&self) -> Option<String> {
+        self.state.clone()
+    }
+
+    /// The zip code of the customer's address
+    pub fn zip(&self) -> Option<String> {
+        self.zip.clone()
+    }
+
+    /// The customer's phone number
+    pub fn phone(&self) -> Option<String> {
+        self.phone.clone()
+    }
+
+    /// The price level the customer buys at, if they do not pay list
+    pub fn price_level(&self) -> Option<String> {
+        self.price_level.clone()
+    }
+
+    /// What the customer owes the store. Negative when the customer has a credit
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+
+    /// The date of the customer's most recent sale, if they have bought anything
+    pub fn last_sale(&self) -> Option<NaiveDate> {
+        self.last_sale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_customer_parser() {
+        let customer = "1001\tJANE DOE\t4 ELM ST\t\tSPRINGFIELD\tIL\t62701\t555-0101\t2\n\
+                        1002\tCASH SALE\t\t\t\t\t\t\t\n";
+        let posted = "1002\t0.00\tnull\n1001\t-12.50\t2024-11-16\n";
+        let customers = AbcCustomer::from_readers(customer.as_bytes(), posted.as_bytes()).unwrap();
+        let jane = &customers["1001"];
+        assert_eq!(jane.name(), "JANE DOE");
+        assert_eq!(jane.price_level(), Some("2".to_string()));
+        assert_eq!(jane.balance(), Decimal::new(-1250, 2));
+        assert_eq!(jane.last_sale(), NaiveDate::from_ymd_opt(2024, 11, 16));
+        assert_eq!(customers["1002"].last_sale(), None);
+
+        assert!(AbcCustomer::from_readers(customer.as_bytes(), "1001\t0\t\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{AbcProduct, AbcProductsBySku, ProductField, export::canonical_value};
+
+/// One field of a product that changed between two exports. Values are rendered the same way
+/// as in [`crate::export::canonical`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldDiff {
+    /// The field that changed
+    pub field: ProductField,
+    /// The value in the old export
+    pub old: String,
+    /// The value in the new export
+    pub new: String,
+}
+
+/// A product that is in both exports but has at least one field that changed
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProductDiff {
+    /// The sku of the product
+    pub sku: String,
+    /// Every field that changed, in the order of [`ProductField::ALL`]
+    pub changes: Vec<FieldDiff>,
+}
+
+impl ProductDiff {
+    /// The change to `field`, if it changed
+    pub fn get(&self, field: ProductField) -> Option<&FieldDiff> {
+        self.changes.iter().find(|c| c.field == field)
+    }
+}
+
+/// Everything that changed between two exports of the same catalog, as returned by
+/// [`crate::catalog::AbcCatalog::diff`]. Each list is sorted by sku.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatalogDiff {
+    /// Products only in the new export
+    pub added: Vec<AbcProduct>,
+    /// Products only in the old export
+    pub removed: Vec<AbcProduct>,
+    /// Products in both exports whose fields changed
+    pub changed: Vec<ProductDiff>,
+}
+
+impl CatalogDiff {
+    /// Whether the two exports held the same products with the same values
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// The changed products where `field` is one of the fields that changed, such as every
+    /// price change when `field` is [`ProductField::List`]
+    pub fn changed_in(&self, field: ProductField) -> impl Iterator<Item = &ProductDiff> {
+        self.changed.iter().filter(move |p| p.get(field).is_some())
+    }
+}
+
+/// Compare two exports of the same catalog. See [`crate::catalog::AbcCatalog::diff`]
+pub fn diff(old: &AbcProductsBySku, new: &AbcProductsBySku) -> CatalogDiff {
+    let mut diff = CatalogDiff::default();
+    for (sku, product) in new {
+        match old.get(sku) {
+            None => diff.added.push(product.clone()),
+            Some(before) => {
+                let changes: Vec<FieldDiff> = ProductField::ALL
+                    .into_iter()
+                    .filter(|f| *f != ProductField::Sku)
+                    .filter_map(|field| {
+                        let (old, new) = (
+                            canonical_value(before, field),
+                            canonical_value(product, field),
+                        );
+                        (old != new).then_some(FieldDiff { field, old, new })
+                    })
+                    .collect();
+                if !changes.is_empty() {
+                    diff.changed.push(ProductDiff {
+                        sku: sku.to_string(),
+                        changes,
+                    });
+                }
+            }
+        }
+    }
+    diff.removed = old
+        .iter()
+        .filter(|(sku, _)| !new.contains_key(*sku))
+        .map(|(_, p)| p.clone())
+        .collect();
+    diff.added.sort_by_key(|p| p.sku());
+    diff.removed.sort_by_key(|p| p.sku());
+    diff.changed.sort_by(|a, b| a.sku.cmp(&b.sku));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, Sku, testing::sample_export};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_diff() {
+        let old = sample_export();
+        let mut new = sample_export();
+        new.remove("ABC123");
+        let repriced = AbcProductBuilder::from(new["123456"].clone())
+            .with_list(Decimal::new(649, 2))
+            .with_stock(4.0)
+            .with_attr("color", "RED")
+            .with_related(&["ABC123".to_string(), "X9".to_string()])
+            .build()
+            .unwrap();
+        new.insert(Sku::new("123456"), repriced);
+        let added = AbcProductBuilder::new()
+            .with_sku("NEW1")
+            .with_desc("NEW PRODUCT")
+            .with_list(Decimal::ONE)
+            .with_cost(Decimal::ONE)
+            .with_stock(1.0)
+            .build()
+            .unwrap();
+        new.insert(Sku::new("NEW1"), added);
+
+        let changes = diff(&old, &new);
+        assert_eq!(changes.added[0].sku(), "NEW1");
+        assert_eq!(changes.removed[0].sku(), "ABC123");
+        assert_eq!(changes.changed.len(), 1);
+        let fields: Vec<ProductField> =
+            changes.changed[0].changes.iter().map(|c| c.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                ProductField::List,
+                ProductField::Stock,
+                ProductField::Related,
+                ProductField::Attributes
+            ]
+        );
+        assert_eq!(
+            changes.changed[0].get(ProductField::Related).unwrap().new,
+            "ABC123,X9"
+        );
+        assert_eq!(
+            changes.changed[0]
+                .get(ProductField::Attributes)
+                .unwrap()
+                .new,
+            "color=RED"
+        );
+        assert_eq!(
+            changes.changed[0].get(ProductField::List),
+            Some(&FieldDiff {
+                field: ProductField::List,
+                old: "5.99".to_string(),
+                new: "6.49".to_string(),
+            })
+        );
+        assert_eq!(changes.changed_in(ProductField::Desc).count(), 0);
+
+        assert!(diff(&old, &old).is_empty());
+    }
+}
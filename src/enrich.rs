@@ -0,0 +1,160 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+use crate::{AbcGroup, AbcProduct, catalog::AbcCatalog, upc_from_str};
+
+/// A line rung up at the register, as exported by the point of sale before it is matched to
+/// the catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct PosLine {
+    /// What was scanned or keyed: a sku, an alternate sku, or a UPC
+    pub code: String,
+    /// How many units were sold. Negative for returns
+    pub qty: Decimal,
+    /// The unit price the customer was charged
+    pub price: Decimal,
+}
+
+/// A [`PosLine`] joined with the catalog, as returned by [`receipt_lines`]. Lines whose code
+/// is not in the catalog keep their quantity and price with every catalog field left empty,
+/// so that sales totals still add up
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnrichedLine {
+    /// The code as rung up
+    pub code: String,
+    /// The sku the code belongs to
+    pub sku: Option<String>,
+    /// The description of the product
+    pub desc: Option<String>,
+    /// The discount group of the product
+    pub group: Option<AbcGroup>,
+    /// How many units were sold
+    pub qty: Decimal,
+    /// The unit price the customer was charged
+    pub price: Decimal,
+    /// The current unit cost of the product. [`None`] if the product's cost is zero, which
+    /// means it is not known, such as when it was hidden by an [`crate::export::Redaction`]
+    pub cost: Option<Decimal>,
+    /// The gross margin of the whole line, `qty` times price less cost, rounded to the cent
+    pub margin: Option<Decimal>,
+    /// The gross margin as a percent of the price charged, rounded to two decimal places.
+    /// [`None`] if the line was given away
+    pub margin_percent: Option<Decimal>,
+}
+
+/// Find the product a code rung up at the register belongs to, trying it as a sku, then as an
+/// alternate sku, then as a UPC
+fn resolve<'a>(catalog: &'a AbcCatalog, code: &str) -> Option<&'a AbcProduct> {
+    let code = code.trim();
+    catalog
+        .get_by_sku(code)
+        .or_else(|| catalog.get_by_alt_sku(code))
+        .or_else(|| upc_from_str(code).and_then(|upc| catalog.get_by_upc(&upc)))
+}
+
+/// Join raw point of sale lines with the catalog, adding the description, cost, margin, and
+/// discount group that sales analysis needs.
+///
+/// Margins use the product's current cost, since the export does not keep the cost at the time
+/// of the sale.
+///
+/// # Arguments
+/// * `lines` - The lines to enrich
+/// * `catalog` - The products to look the lines up in
+///
+/// # Returns
+/// One [`EnrichedLine`] for each of `lines`, in the same order
+///
+/// # Example
+/// ```rust
+/// use abc_product::{
+///     catalog::AbcCatalog,
+///     enrich::{self, PosLine},
+///     testing,
+/// };
+/// use rust_decimal::Decimal;
+///
+/// let catalog = AbcCatalog::new(testing::sample_export());
+/// let lines = [PosLine {
+///     code: "ABC123".to_string(),
+///     qty: Decimal::from(2),
+///     price: Decimal::new(800, 2),
+/// }];
+/// let enriched = enrich::receipt_lines(&lines, &catalog);
+/// assert_eq!(enriched[0].desc.as_deref(), Some("PRODUCT B"));
+/// // 2 x (8.00 - 5.23)
+/// assert_eq!(enriched[0].margin, Some(Decimal::new(554, 2)));
+/// ```
+pub fn receipt_lines(lines: &[PosLine], catalog: &AbcCatalog) -> Vec<EnrichedLine> {
+    lines
+        .iter()
+        .map(|line| {
+            let product = resolve(catalog, &line.code);
+            // A cost of zero is not known, so no margin can be worked out from it
+            let cost = product.map(|p| p.cost()).filter(|c| !c.is_zero());
+            EnrichedLine {
+                code: line.code.to_string(),
+                sku: product.map(|p| p.sku()),
+                desc: product.map(|p| p.desc()),
+                group: product.and_then(|p| p.group()),
+                qty: line.qty,
+                price: line.price,
+                cost,
+                margin: cost.map(|c| {
+                    (line.qty * (line.price - c))
+                        .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+                }),
+                margin_percent: cost.filter(|_| !line.price.is_zero()).map(|c| {
+                    ((line.price - c) / line.price * Decimal::ONE_HUNDRED)
+                        .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+                }),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_receipt_lines() {
+        let catalog = AbcCatalog::new(sample_export());
+        let line = |code: &str, qty: i64, price: i64| PosLine {
+            code: code.to_string(),
+            qty: Decimal::from(qty),
+            price: Decimal::new(price, 2),
+        };
+        let lines = [
+            line(" 0085875500015", 1, 599),
+            line("ALT SKU", -1, 812),
+            line("NOPE", 3, 100),
+            line("123456", 1, 0),
+        ];
+
+        let enriched = receipt_lines(&lines, &catalog);
+        assert_eq!(enriched.len(), 4);
+        assert_eq!(enriched[0].sku.as_deref(), Some("123456"));
+        assert_eq!(enriched[0].margin_percent, Some(Decimal::new(7947, 2)));
+        assert_eq!(enriched[1].group, AbcGroup::new('A'));
+        // A return takes the margin back out
+        assert_eq!(enriched[1].margin, Some(Decimal::new(-289, 2)));
+        assert_eq!(
+            enriched[2],
+            EnrichedLine {
+                code: "NOPE".to_string(),
+                sku: None,
+                desc: None,
+                group: None,
+                qty: Decimal::from(3),
+                price: Decimal::ONE,
+                cost: None,
+                margin: None,
+                margin_percent: None,
+            }
+        );
+        assert_eq!(enriched[3].margin, Some(Decimal::new(-123, 2)));
+        assert_eq!(enriched[3].margin_percent, None);
+    }
+}
@@ -0,0 +1,762 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+
+use rust_decimal::{Decimal, RoundingStrategy};
+
+#[cfg(feature = "compression")]
+use crate::compress::{Compression, Encoder};
+use crate::{
+    AbcProduct, AbcProductsBySku, ProductField,
+    mode::Mode,
+    records::{ColumnLayout, ItemRecord},
+};
+
+/// Controls how exporters write their output files
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Write to a temporary file next to the destination and rename it into place only after
+    /// everything was written successfully. A crash or error part way through then leaves the
+    /// previous file untouched instead of a truncated one
+    pub atomic: bool,
+    /// Whether to actually write anything. In [`Mode::DryRun`] the contents are still generated
+    /// so their size can be recorded, but no files are touched
+    pub mode: Mode,
+}
+
+/// Something a [`Redaction`] can hide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedactedField {
+    /// A field stored on [`AbcProduct`]
+    Product(ProductField),
+    /// The gross margin. Margins can be worked out from the list price and cost, so hiding them
+    /// hides cost and average cost as well
+    Margin,
+    /// The primary vendor code. Only [`ItemRecord`]s carry it, so it is hidden by
+    /// [`Redaction::apply_record`]
+    Vendor,
+}
+
+impl From<ProductField> for RedactedField {
+    fn from(value: ProductField) -> Self {
+        RedactedField::Product(value)
+    }
+}
+
+/// Which fields to hide before products are handed to an exporter, so that sensitive data such
+/// as cost never reaches a customer facing feed.
+///
+/// Redaction works on the products themselves rather than on any one output format, so the
+/// redacted products from [`Redaction::apply_all`] can be passed to any exporter in this crate.
+/// Hidden costs are zeroed, and a product with a cost of zero has no
+/// [`AbcProduct::margin`] or [`AbcProduct::margin_percent`], so a hidden cost cannot turn into
+/// a margin of 100%. Raw rows from [`crate::records`] are redacted with
+/// [`Redaction::apply_record`].
+///
+/// # Example
+/// ```rust
+/// use abc_product::{export::Redaction, testing};
+/// use rust_decimal::Decimal;
+///
+/// let public = Redaction::customer_facing().apply_all(&testing::sample_export());
+/// assert_eq!(public["123456"].cost(), Decimal::ZERO);
+/// assert_eq!(public["123456"].margin_percent(), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Redaction {
+    /// The fields to hide
+    pub fields: Vec<RedactedField>,
+    /// Text to show in place of hidden text fields such as the description. [`None`] to leave
+    /// them empty. Hidden numbers are always zeroed, hidden lists emptied, and a hidden group
+    /// removed
+    pub mask: Option<String>,
+}
+
+impl Redaction {
+    /// Hide cost, average cost, margin, and vendor, the fields that must never appear in
+    /// customer facing output
+    pub fn customer_facing() -> Self {
+        Redaction {
+            fields: vec![
+                ProductField::Cost.into(),
+                ProductField::AvgCost.into(),
+                RedactedField::Margin,
+                RedactedField::Vendor,
+            ],
+            mask: None,
+        }
+    }
+
+    /// Whether `field` is hidden, either directly or because [`RedactedField::Margin`] is
+    pub fn redacts(&self, field: impl Into<RedactedField>) -> bool {
+        let field = field.into();
+        self.fields.contains(&field)
+            || (self.fields.contains(&RedactedField::Margin)
+                && matches!(
+                    field,
+                    RedactedField::Product(ProductField::Cost | ProductField::AvgCost)
+                ))
+    }
+
+    /// Copy `product` with every hidden field cleared or masked. The sku is kept even if it is
+    /// listed, since products cannot be exported without one
+    pub fn apply(&self, product: &AbcProduct) -> AbcProduct {
+        let mut product = product.clone();
+        let mask = self.mask.clone().unwrap_or_default();
+        for field in ProductField::ALL.into_iter().filter(|f| self.redacts(*f)) {
+            match field {
+                ProductField::Sku => {}
+                ProductField::Desc => product.desc = mask.to_string(),
+                ProductField::Upcs => product.upcs = Vec::new(),
+                ProductField::RawUpcs => product.raw_upcs = Vec::new(),
+                ProductField::List => product.list = Decimal::ZERO,
+                ProductField::Cost => product.cost = Decimal::ZERO,
+                ProductField::AvgCost => product.avg_cost = None,
+                ProductField::Stock => product.stock = 0.0,
+                ProductField::Group => product.group = None,
+                ProductField::Weight => product.weight = None,
+                ProductField::LastSold => product.last_sold = None,
+                ProductField::AltSkus => product.alt_skus = Vec::new(),
+                ProductField::CoreSku => product.core_sku = None,
+                ProductField::WarrantyMonths => product.warranty_months = None,
+                ProductField::NetContents => product.net_contents = None,
+                ProductField::CountryOfOrigin => product.country_of_origin = self.mask.clone(),
+                ProductField::MinStock => product.min_stock = None,
+                ProductField::MaxStock => product.max_stock = None,
+                ProductField::Related => product.related = Vec::new(),
+                ProductField::Attributes => product.attributes = BTreeMap::new(),
+            }
+        }
+        product
+    }
+
+    /// Copy every product in `products` through [`Redaction::apply`]
+    pub fn apply_all(&self, products: &AbcProductsBySku) -> AbcProductsBySku {
+        products
+            .iter()
+            .map(|(sku, p)| (sku.clone(), self.apply(p)))
+            .collect()
+    }
+
+    /// Copy a raw row of `item.data` with every hidden column cleared or masked, the same way
+    /// as [`Redaction::apply`]. Prices are written as `0` and average cost as `null`, so the
+    /// row still parses. Fields that are not in `item.data`, such as stock, are left alone
+    pub fn apply_record(&self, record: &ItemRecord) -> ItemRecord {
+        let layout = record.layout();
+        let mask = self.mask.clone().unwrap_or_default();
+        let mut record = record.clone();
+        if self.fields.contains(&RedactedField::Vendor) {
+            record = record.with_column(layout.vendor, "");
+        }
+        for field in ProductField::ALL.into_iter().filter(|f| self.redacts(*f)) {
+            let value = match field {
+                ProductField::Desc | ProductField::CountryOfOrigin => mask.as_str(),
+                ProductField::List | ProductField::Cost => "0",
+                ProductField::AvgCost => "null",
+                _ => "",
+            };
+            for column in item_columns(field, &layout) {
+                record = record.with_column(column, value);
+            }
+        }
+        record
+    }
+}
+
+/// The columns of `item.data` that hold `field` in `layout`. Empty for fields that are not in
+/// `item.data`, or have no column in `layout`
+fn item_columns(field: ProductField, layout: &ColumnLayout) -> Vec<usize> {
+    match field {
+        ProductField::Sku => Vec::new(),
+        ProductField::Desc => vec![layout.desc],
+        ProductField::Upcs | ProductField::RawUpcs => vec![layout.upcs],
+        ProductField::List => vec![layout.list],
+        ProductField::Cost => vec![layout.cost],
+        ProductField::AvgCost => vec![layout.avg_cost],
+        ProductField::Group => vec![layout.group],
+        ProductField::Weight => vec![layout.weight],
+        ProductField::AltSkus => layout.alt_skus.to_vec(),
+        ProductField::CoreSku => layout.core_sku.into_iter().collect(),
+        ProductField::WarrantyMonths => layout.warranty_months.into_iter().collect(),
+        ProductField::NetContents => layout.net_contents.into_iter().collect(),
+        ProductField::CountryOfOrigin => layout.country_of_origin.into_iter().collect(),
+        ProductField::MinStock => layout.min_stock.into_iter().collect(),
+        ProductField::MaxStock => layout.max_stock.into_iter().collect(),
+        ProductField::Stock
+        | ProductField::LastSold
+        | ProductField::Related
+        | ProductField::Attributes => Vec::new(),
+    }
+}
+
+/// The temporary file used while atomically writing `path`. It lives in the same directory so
+/// that the final rename never crosses file systems
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+/// Fill `file`, which will end up at `path`, using `write`. Files named `*.gz` or `*.zst` are
+/// compressed to match
+#[cfg(feature = "compression")]
+fn fill_file<F>(file: fs::File, path: &Path, write: F) -> io::Result<fs::File>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let mut encoder = Encoder::new(BufWriter::new(file), Compression::from_path(path))?;
+    write(&mut encoder)?;
+    encoder.finish()?.into_inner().map_err(|e| e.into_error())
+}
+
+/// Fill `file`, which will end up at `path`, using `write`
+#[cfg(not(feature = "compression"))]
+fn fill_file<F>(file: fs::File, _path: &Path, write: F) -> io::Result<fs::File>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let mut writer = BufWriter::new(file);
+    write(&mut writer)?;
+    writer.into_inner().map_err(|e| e.into_error())
+}
+
+/// Create the file at `path` and fill it using `write`, honoring `options`. With the
+/// `compression` feature, files named `*.gz` or `*.zst` are compressed to match.
+///
+/// # Arguments
+/// * `path` - The file to create or replace
+/// * `options` - How to write the file. See [`WriteOptions`]
+/// * `write` - Writes the contents of the file to the writer it is given, such as
+///   `|w| canonical(&products, w)`
+///
+/// # Errors
+/// Any [`std::io::Error`] raised while creating the file or by `write`. When writing
+/// atomically, the temporary file is removed and `path` is left as it was
+pub fn write_file<F>(path: &Path, options: &WriteOptions, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    if let Mode::DryRun(_) = options.mode {
+        let mut contents = Vec::new();
+        write(&mut contents)?;
+        options.mode.proceed(
+            "write",
+            &path.display().to_string(),
+            Some(format!("{} bytes", contents.len())),
+        );
+        return Ok(());
+    }
+    if !options.atomic {
+        fill_file(fs::File::create(path)?, path, write)?;
+        return Ok(());
+    }
+
+    let temp = temp_path(path);
+    let result = (|| {
+        let file = fill_file(fs::File::create(&temp)?, path, write)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// The previous copies of `path` kept by [`with_rotation`], oldest first
+fn rotated_backups(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let prefix = format!(
+        "{}.",
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    );
+    // Backups are sorted by their timestamp, then by the counter added when several copies were
+    // made within the same second
+    let mut backups: Vec<((String, u32), PathBuf)> = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(suffix) = name.strip_prefix(&prefix) else {
+            continue;
+        };
+        let (stamp, counter) = match suffix.get(15..) {
+            Some("") => (&suffix[..15], Some(0)),
+            Some(rest) => (
+                &suffix[..15],
+                rest.strip_prefix('-').and_then(|n| n.parse().ok()),
+            ),
+            None => continue,
+        };
+        let valid_stamp = stamp
+            .chars()
+            .enumerate()
+            .all(|(i, c)| if i == 8 { c == '-' } else { c.is_ascii_digit() });
+        if let (true, Some(counter)) = (valid_stamp, counter) {
+            backups.push(((stamp.to_string(), counter), entry.path()));
+        }
+    }
+    backups.sort();
+    let backups = backups.into_iter().map(|(_, path)| path).collect();
+    Ok(backups)
+}
+
+/// Keep a timestamped copy of the file at `path` before it is regenerated, and delete the oldest
+/// copies so that at most `keep_n` remain.
+///
+/// Copies are named after the original with the time it was last modified appended, such as
+/// `products.json.20241116-020000`. The file at `path` is copied rather than moved, so it stays
+/// in place for any consumers until the new export replaces it. Call this just before writing
+/// a new export to `path`.
+///
+/// # Arguments
+/// * `path` - The generated file that is about to be replaced. Nothing is copied if it does not
+///   exist yet
+/// * `keep_n` - How many previous copies to keep
+/// * `mode` - Whether to actually copy and remove files. See [`Mode`]
+///
+/// # Errors
+/// Any [`std::io::Error`] raised while copying the file or removing old copies
+pub fn with_rotation(path: &Path, keep_n: usize, mode: &Mode) -> io::Result<()> {
+    if path.exists() {
+        let modified = DateTime::<Local>::from(fs::metadata(path)?.modified()?);
+        let stamp = modified.format("%Y%m%d-%H%M%S").to_string();
+        let mut backup = PathBuf::from(format!("{}.{}", path.display(), stamp));
+        let mut n = 1;
+        while backup.exists() {
+            backup = PathBuf::from(format!("{}.{}-{}", path.display(), stamp, n));
+            n += 1;
+        }
+        if mode.proceed(
+            "copy",
+            &path.display().to_string(),
+            Some(format!("to {}", backup.display())),
+        ) {
+            fs::copy(path, backup)?;
+        }
+    }
+
+    let backups = rotated_backups(path)?;
+    if backups.len() > keep_n {
+        for old in &backups[..backups.len() - keep_n] {
+            if mode.proceed("remove", &old.display().to_string(), None) {
+                fs::remove_file(old)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Render a single field of `product` as plain text, with lists joined by commas, prices
+/// normalized, and missing values left empty
+fn field_text(product: &AbcProduct, field: ProductField) -> String {
+    match field {
+        ProductField::Sku => product.sku(),
+        ProductField::Desc => product.desc(),
+        ProductField::Upcs => product
+            .upcs()
+            .iter()
+            .map(|u| u.to_string())
+            .collect::<Vec<String>>()
+            .join(","),
+        ProductField::RawUpcs => product.raw_upcs().join(","),
+        ProductField::List => product.list().normalize().to_string(),
+        ProductField::Cost => product.cost().normalize().to_string(),
+        ProductField::AvgCost => product
+            .avg_cost()
+            .map(|c| c.normalize().to_string())
+            .unwrap_or_default(),
+        ProductField::Stock => product.stock().to_string(),
+        ProductField::Group => product.group().map(|g| g.to_string()).unwrap_or_default(),
+        ProductField::Weight => product.weight().map(|w| w.to_string()).unwrap_or_default(),
+        ProductField::LastSold => product
+            .last_sold()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        ProductField::AltSkus => product.alt_skus().join(","),
+        ProductField::CoreSku => product.core_sku().unwrap_or_default(),
+        ProductField::WarrantyMonths => product
+            .warranty_months()
+            .map(|m| m.to_string())
+            .unwrap_or_default(),
+        ProductField::NetContents => product
+            .net_contents()
+            .map(|n| n.to_string())
+            .unwrap_or_default(),
+        ProductField::CountryOfOrigin => product.country_of_origin().unwrap_or_default(),
+        ProductField::MinStock => product
+            .min_stock()
+            .map(|q| q.to_string())
+            .unwrap_or_default(),
+        ProductField::MaxStock => product
+            .max_stock()
+            .map(|q| q.to_string())
+            .unwrap_or_default(),
+        ProductField::Related => product.related().join(","),
+        ProductField::Attributes => product
+            .attributes()
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<String>>()
+            .join(","),
+    }
+}
+
+/// Render a single field of `product` in the normalized form used by [`canonical`]
+pub(crate) fn canonical_value(product: &AbcProduct, field: ProductField) -> String {
+    field_text(product, field)
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// How prices are written by [`AbcProduct::to_csv`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PriceFormat {
+    /// Just the number, such as `5.99`, which spreadsheets read as a number
+    #[default]
+    Plain,
+    /// With a dollar sign, such as `$5.99`
+    Currency,
+}
+
+/// Settings for [`AbcProduct::to_csv`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The columns to write, in order. Every [`ProductField`] by default
+    pub columns: Vec<ProductField>,
+    /// How to write list, cost, and average cost
+    pub price_format: PriceFormat,
+    /// How many decimal places to round prices to. 2 by default
+    pub decimals: u32,
+    /// The byte that separates columns. A comma by default
+    pub delimiter: u8,
+    /// Supplemental attributes to write after the field columns, in order, with the attribute
+    /// names as their headers. None by default. See [`crate::attributes::Attributes`]
+    pub attributes: Vec<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            columns: ProductField::ALL.to_vec(),
+            price_format: PriceFormat::default(),
+            decimals: 2,
+            delimiter: b',',
+            attributes: Vec::new(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Set the columns to write, in order
+    pub fn with_columns(self, columns: &[ProductField]) -> Self {
+        CsvOptions {
+            columns: columns.to_vec(),
+            ..self
+        }
+    }
+
+    /// Set how prices are written
+    pub fn with_price_format(self, price_format: PriceFormat) -> Self {
+        CsvOptions {
+            price_format,
+            ..self
+        }
+    }
+
+    /// Set how many decimal places prices are rounded to
+    pub fn with_decimals(self, decimals: u32) -> Self {
+        CsvOptions { decimals, ..self }
+    }
+
+    /// Set the byte that separates columns, such as `b'\t'` for tab separated output
+    pub fn with_delimiter(self, delimiter: u8) -> Self {
+        CsvOptions { delimiter, ..self }
+    }
+
+    /// Set the supplemental attributes to write after the field columns, in order
+    pub fn with_attribute_columns(self, attributes: &[&str]) -> Self {
+        CsvOptions {
+            attributes: attributes.iter().map(|a| a.to_string()).collect(),
+            ..self
+        }
+    }
+
+    /// Render `price` as set by these options
+    fn price(&self, price: Decimal) -> String {
+        let rounded =
+            price.round_dp_with_strategy(self.decimals, RoundingStrategy::MidpointAwayFromZero);
+        let number = format!("{:.*}", self.decimals as usize, rounded);
+        match self.price_format {
+            PriceFormat::Plain => number,
+            PriceFormat::Currency => format!("${}", number),
+        }
+    }
+}
+
+/// Write `products` as csv. See [`AbcProduct::to_csv`]
+pub(crate) fn write_csv(
+    writer: impl Write,
+    products: &[AbcProduct],
+    options: &CsvOptions,
+) -> io::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .from_writer(writer);
+    writer.write_record(
+        options
+            .columns
+            .iter()
+            .map(|c| c.name())
+            .chain(options.attributes.iter().map(|a| a.as_str())),
+    )?;
+    for product in products {
+        let fields = options.columns.iter().map(|field| match field {
+            ProductField::List => options.price(product.list()),
+            ProductField::Cost => options.price(product.cost()),
+            ProductField::AvgCost => product
+                .avg_cost()
+                .map(|c| options.price(c))
+                .unwrap_or_default(),
+            _ => field_text(product, *field),
+        });
+        let attributes = options
+            .attributes
+            .iter()
+            .map(|a| product.attr(a).unwrap_or_default());
+        writer.write_record(fields.chain(attributes))?;
+    }
+    writer.flush()
+}
+
+/// Write `products` in a deterministic, line oriented text form meant to be committed and
+/// diffed between versions of this crate to catch changes in parser behavior.
+///
+/// Products are sorted by sku and written as a `[sku]` header followed by one `field = value`
+/// line per [`ProductField`], with a blank line between products. Prices are normalized so
+/// that `1.230` and `1.23` render the same, missing values are left empty, and control
+/// characters are escaped so each value stays on one line.
+///
+/// # Arguments
+/// * `products` - The products to write
+/// * `writer` - Where to write the text
+///
+/// # Errors
+/// Any [`std::io::Error`] raised by `writer`
+pub fn canonical(products: &AbcProductsBySku, mut writer: impl Write) -> io::Result<()> {
+    let mut sorted: Vec<&AbcProduct> = products.values().collect();
+    sorted.sort_by_key(|p| p.sku());
+    for (i, product) in sorted.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer)?;
+        }
+        writeln!(writer, "[{}]", canonical_value(product, ProductField::Sku))?;
+        for field in ProductField::ALL
+            .iter()
+            .filter(|f| **f != ProductField::Sku)
+        {
+            writeln!(
+                writer,
+                "{} = {}",
+                field.name(),
+                canonical_value(product, *field)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_csv() {
+        let products = sample_export();
+        let options = CsvOptions::default()
+            .with_columns(&[ProductField::Sku, ProductField::Desc, ProductField::List])
+            .with_price_format(PriceFormat::Currency);
+        let mut out = Vec::new();
+        AbcProduct::to_csv(&mut out, &[products["123456"].clone()], &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "sku,desc,list\n123456,PRODUCT A,$5.99\n"
+        );
+
+        let options = options
+            .with_columns(&[ProductField::Cost, ProductField::AltSkus])
+            .with_price_format(PriceFormat::Plain)
+            .with_decimals(3);
+        let mut out = Vec::new();
+        AbcProduct::to_csv(&mut out, &[products["ABC123"].clone()], &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "cost,alt_skus\n5.230,ALT SKU\n"
+        );
+
+        let finished = crate::AbcProductBuilder::from(products["ABC123"].clone())
+            .with_attr("finish", "Brushed nickel")
+            .build()
+            .unwrap();
+        let options = CsvOptions::default()
+            .with_columns(&[ProductField::Sku])
+            .with_attribute_columns(&["finish", "color"]);
+        let mut out = Vec::new();
+        AbcProduct::to_csv(&mut out, &[finished], &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "sku,finish,color\nABC123,Brushed nickel,\n"
+        );
+    }
+
+    #[test]
+    fn test_atomic_write_file() {
+        let dir = std::env::temp_dir().join(format!("abc-product-export-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("products.txt");
+        let options = WriteOptions {
+            atomic: true,
+            ..Default::default()
+        };
+        write_file(&path, &options, |w| w.write_all(b"old")).unwrap();
+
+        let failed = write_file(&path, &options, |w| {
+            w.write_all(b"half written")?;
+            Err(io::Error::other("crashed"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dry_run_write_file() {
+        let dry_run = crate::mode::DryRun::new();
+        let options = WriteOptions {
+            atomic: true,
+            mode: Mode::DryRun(dry_run.clone()),
+        };
+        let path = std::env::temp_dir().join(format!("abc-product-dry-{}", std::process::id()));
+        write_file(&path, &options, |w| canonical(&sample_export(), w)).unwrap();
+        assert!(!path.exists());
+        assert_eq!(dry_run.planned().len(), 1);
+        assert_eq!(dry_run.planned()[0].action, "write");
+    }
+
+    #[test]
+    fn test_with_rotation() {
+        let dir = std::env::temp_dir().join(format!("abc-product-rotate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("products.txt");
+        for i in 0..4 {
+            with_rotation(&path, 2, &Mode::Live).unwrap();
+            fs::write(&path, i.to_string()).unwrap();
+        }
+        let backups = rotated_backups(&path).unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[1]).unwrap(), "2");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_canonical() {
+        let mut out = Vec::new();
+        canonical(&sample_export(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("[123456]\ndesc = PRODUCT A\n"));
+        assert!(out.contains("\n\n[ABC123]\ndesc = PRODUCT B\n"));
+        assert!(out.contains("list = 8.12\ncost = 5.23\navg_cost = \nstock = -6\ngroup = A\n"));
+
+        let mut redacted = Vec::new();
+        canonical(
+            &Redaction::customer_facing().apply_all(&sample_export()),
+            &mut redacted,
+        )
+        .unwrap();
+        let redacted = String::from_utf8(redacted).unwrap();
+        assert!(!redacted.contains("5.23"));
+        assert!(redacted.contains("list = 8.12\ncost = 0\navg_cost = \n"));
+
+        let mut products = sample_export();
+        let finished = crate::AbcProductBuilder::from(products["ABC123"].clone())
+            .with_attr("finish", "Brushed nickel")
+            .with_attr("color", "Silver")
+            .with_related(&["123456".to_string()])
+            .build()
+            .unwrap();
+        products.insert(crate::Sku::new("ABC123"), finished);
+        let mut out = Vec::new();
+        canonical(&products, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.ends_with("related = 123456\nattributes = color=Silver,finish=Brushed nickel\n")
+        );
+        let hidden = Redaction {
+            fields: vec![
+                ProductField::Related.into(),
+                ProductField::Attributes.into(),
+            ],
+            mask: None,
+        };
+        let hidden = hidden.apply(&products["ABC123"]);
+        assert!(hidden.attributes().is_empty());
+        assert!(hidden.related().is_empty());
+    }
+
+    #[test]
+    fn test_redaction() {
+        let margin_only = Redaction {
+            fields: vec![RedactedField::Margin],
+            mask: None,
+        };
+        assert!(margin_only.redacts(ProductField::Cost));
+        assert!(!margin_only.redacts(ProductField::List));
+        let product = margin_only.apply(&sample_export()["ABC123"]);
+        assert_eq!(product.cost(), Decimal::ZERO);
+        assert_eq!(product.margin(), None);
+        assert_eq!(product.margin_percent(), None);
+        assert_eq!(product.list(), Decimal::new(812, 2));
+
+        let item = crate::records::read_item_data("./item.data")
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(item.vendor().is_some());
+        let public = Redaction {
+            mask: Some("HIDDEN".to_string()),
+            ..Redaction::customer_facing()
+        }
+        .apply_record(&item);
+        assert_eq!(public.vendor(), None);
+        assert_eq!(public.cost().unwrap(), Decimal::ZERO);
+        assert_eq!(public.avg_cost(), None);
+        assert_eq!(public.list().unwrap(), item.list().unwrap());
+        assert_eq!(public.desc().unwrap(), item.desc().unwrap());
+        let hidden = Redaction {
+            fields: vec![ProductField::Desc.into(), ProductField::AltSkus.into()],
+            mask: Some("HIDDEN".to_string()),
+        }
+        .apply_record(&item);
+        assert_eq!(hidden.desc().unwrap(), "HIDDEN");
+        assert!(hidden.alt_skus().is_empty());
+        assert_eq!(hidden.len(), item.len());
+    }
+}
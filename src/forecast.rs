@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::{Sku, invoice::AbcInvoice, po::AbcPurchaseOrder};
+
+/// The average and standard deviation of a set of samples
+fn mean_std(samples: &[f64]) -> (f64, f64) {
+    if samples.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    if samples.len() < 2 {
+        return (mean, 0.0);
+    }
+    let var = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, var.sqrt())
+}
+
+/// How many standard deviations above the mean cover `service_level` of a normal distribution,
+/// using the rational approximation from Abramowitz and Stegun 26.2.23
+fn z_score(service_level: f64) -> f64 {
+    let p = service_level.clamp(0.5, 0.9999);
+    let t = (-2.0 * (1.0 - p).ln()).sqrt();
+    let z = t
+        - (2.515517 + 0.802853 * t + 0.010328 * t * t)
+            / (1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t);
+    z.max(0.0)
+}
+
+/// Sales and lead time history, used to size safety stock so that reorder points cover swings
+/// in demand and late deliveries rather than relying on a fixed min and max.
+///
+/// Demand is measured in whole weeks, so that the busy and slow days of a normal week do not
+/// count as volatility. Lead times are measured from the order date to the expected date of
+/// each purchase order line.
+///
+/// # Example
+/// ```rust,no_run
+/// use chrono::NaiveDate;
+/// use abc_product::{forecast::Forecast, invoice::AbcInvoice, po::AbcPurchaseOrder};
+///
+/// let invoices = AbcInvoice::from_db_export("./invoice.data", "./invoice_line.data").unwrap();
+/// let orders = AbcPurchaseOrder::from_db_export("./po.data", "./po_line.data").unwrap();
+/// let forecast = Forecast::new(
+///     invoices.values(),
+///     NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+///     NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(),
+/// )
+/// .with_lead_times(orders.values())
+/// .with_default_lead_time(7.0);
+/// let safety_stock = forecast.safety_stock("123456", 0.95);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Forecast {
+    weeks: usize,
+    weekly: HashMap<Sku, Vec<f64>>,
+    lead_times: HashMap<Sku, Vec<f64>>,
+    default_lead_time: f64,
+}
+
+impl Forecast {
+    /// Measure weekly demand for every sku sold on `invoices` dated from `from` up to and
+    /// including `to`, net of returns. A final partial week counts as a full week
+    pub fn new<'a>(
+        invoices: impl IntoIterator<Item = &'a AbcInvoice>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Self {
+        let days = (to - from).num_days().max(0) as usize + 1;
+        let weeks = days.div_ceil(7);
+        let mut weekly: HashMap<Sku, Vec<f64>> = HashMap::new();
+        for invoice in invoices {
+            let Some(date) = invoice.date().filter(|d| *d >= from && *d <= to) else {
+                continue;
+            };
+            let week = (date - from).num_days() as usize / 7;
+            for line in invoice.lines() {
+                if let Some(sku) = line.sku() {
+                    weekly
+                        .entry(Sku::new(&sku))
+                        .or_insert_with(|| vec![0.0; weeks])[week] +=
+                        line.qty().to_f64().unwrap_or_default();
+                }
+            }
+        }
+        Forecast {
+            weeks,
+            weekly,
+            ..Default::default()
+        }
+    }
+
+    /// Learn how many days each sku takes to arrive from the purchase orders in `orders`. Lines
+    /// of orders without both an order date and an expected date are skipped
+    pub fn with_lead_times<'a>(
+        self,
+        orders: impl IntoIterator<Item = &'a AbcPurchaseOrder>,
+    ) -> Self {
+        let mut lead_times = self.lead_times;
+        for order in orders {
+            let (Some(ordered), Some(expected)) = (order.order_date(), order.expected()) else {
+                continue;
+            };
+            let days = (expected - ordered).num_days().max(0) as f64;
+            for line in order.lines() {
+                lead_times
+                    .entry(Sku::new(&line.sku()))
+                    .or_default()
+                    .push(days);
+            }
+        }
+        Forecast { lead_times, ..self }
+    }
+
+    /// Set the lead time in days assumed for skus that have no purchase order history.
+    /// Defaults to 0
+    pub fn with_default_lead_time(self, days: f64) -> Self {
+        Forecast {
+            default_lead_time: days,
+            ..self
+        }
+    }
+
+    /// The average and standard deviation of units of `sku` sold per day
+    pub fn demand(&self, sku: &str) -> (f64, f64) {
+        match self.weekly.get(&Sku::new(sku)) {
+            Some(weekly) => {
+                let (mean, std) = mean_std(weekly);
+                (mean / 7.0, std / 7f64.sqrt())
+            }
+            None => (0.0, 0.0),
+        }
+    }
+
+    /// The average and standard deviation of the days `sku` takes to arrive once ordered
+    pub fn lead_time(&self, sku: &str) -> (f64, f64) {
+        match self.lead_times.get(&Sku::new(sku)) {
+            Some(days) => mean_std(days),
+            None => (self.default_lead_time, 0.0),
+        }
+    }
+
+    /// How many extra units of `sku` to keep on hand so that it is in stock through a
+    /// replenishment cycle `service_level` of the time, accounting for both demand and lead
+    /// time variance
+    ///
+    /// # Arguments
+    /// * `sku` - The product to size safety stock for
+    /// * `service_level` - The chance of not running out while waiting for an order, such as
+    ///   0.95. Clamped between 0.5, which needs no safety stock, and 0.9999
+    pub fn safety_stock(&self, sku: &str, service_level: f64) -> f64 {
+        let (demand, demand_std) = self.demand(sku);
+        let (lead, lead_std) = self.lead_time(sku);
+        z_score(service_level)
+            * (lead * demand_std.powi(2) + demand.powi(2) * lead_std.powi(2)).sqrt()
+    }
+
+    /// The stock level of `sku` at which to reorder, covering expected demand over the lead
+    /// time plus [`Forecast::safety_stock`]
+    pub fn reorder_point(&self, sku: &str, service_level: f64) -> f64 {
+        let (demand, _) = self.demand(sku);
+        let (lead, _) = self.lead_time(sku);
+        demand * lead + self.safety_stock(sku, service_level)
+    }
+
+    /// How many whole or partial weeks of sales history this forecast covers
+    pub fn weeks(&self) -> usize {
+        self.weeks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safety_stock() {
+        assert!((z_score(0.95) - 1.645).abs() < 0.01);
+        assert_eq!(z_score(0.2), 0.0);
+
+        let invoice = "1\t2024-01-01\n2\t2024-01-08\n3\t2024-01-09\n";
+        let lines = "1\tA\t\t7\t1\n2\tA\t\t14\t1\n3\tA\t\t7\t1\n3\tB\t\t7\t1\n";
+        let invoices = AbcInvoice::from_readers(invoice.as_bytes(), lines.as_bytes()).unwrap();
+        let po = "P1\tV\t2024-01-01\t2024-01-11\nP2\tV\t2024-02-01\t2024-02-07\n";
+        let po_lines = "P1\tA\t\t1\t0\t1\nP2\tA\t\t1\t0\t1\n";
+        let orders = AbcPurchaseOrder::from_readers(po.as_bytes(), po_lines.as_bytes()).unwrap();
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let forecast = Forecast::new(invoices.values(), day(1), day(21))
+            .with_lead_times(orders.values())
+            .with_default_lead_time(7.0);
+
+        assert_eq!(forecast.weeks(), 3);
+        // Weeks of 7, 21, and 0 units
+        let (demand, _) = forecast.demand("A");
+        assert!((demand - 4.0 / 3.0).abs() < 1e-9);
+        assert_eq!(forecast.demand(" a"), forecast.demand("A"));
+        assert_eq!(forecast.lead_time("a "), forecast.lead_time("A"));
+        assert_eq!(forecast.lead_time("A"), (8.0, 8f64.sqrt()));
+        assert_eq!(forecast.lead_time("B"), (7.0, 0.0));
+        assert!(forecast.safety_stock("A", 0.95) > forecast.safety_stock("A", 0.9));
+        assert_eq!(forecast.safety_stock("C", 0.95), 0.0);
+        assert!(forecast.reorder_point("A", 0.95) > demand * 8.0);
+    }
+}
@@ -0,0 +1,188 @@
+use rust_decimal::{Decimal, RoundingStrategy, prelude::FromPrimitive};
+
+/// How a sku should be displayed on receipts, labels, and web pages.
+///
+/// Every format can be reversed with [`SkuFormat::parse`], so a sku typed back in from a label or
+/// read out of a URL maps to the same canonical sku that ABC stores. Canonical skus are
+/// uppercase, have no surrounding whitespace, and are assumed to have no leading zeros on purely
+/// numeric skus.
+///
+/// # Example
+/// ```rust
+/// use abc_product::format::SkuFormat;
+///
+/// let format = SkuFormat::Segmented(vec![2, 4]);
+/// assert_eq!(format.format("120345"), "12-0345");
+/// assert_eq!(format.parse("12-0345"), "120345");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkuFormat {
+    /// Display the sku in uppercase, otherwise unchanged
+    Uppercase,
+    /// Left pad purely numeric skus with zeros to the given width. Skus containing any
+    /// non-digit characters are only uppercased
+    ZeroPadded(usize),
+    /// Split the sku into dash separated segments of the given lengths. Any characters left over
+    /// after the last segment form one final segment. Dashes in the sku itself are kept, and
+    /// count towards the length of their segment
+    Segmented(Vec<usize>),
+}
+
+impl SkuFormat {
+    /// Render the canonical `sku` in this format
+    pub fn format(&self, sku: &str) -> String {
+        let sku = sku.trim().to_uppercase();
+        match self {
+            Self::Uppercase => sku,
+            Self::ZeroPadded(width) => {
+                if !sku.is_empty() && sku.chars().all(|c| c.is_ascii_digit()) {
+                    format!("{:0>width$}", sku, width = width)
+                } else {
+                    sku
+                }
+            }
+            Self::Segmented(sizes) => {
+                let chars: Vec<char> = sku.chars().collect();
+                let mut segments: Vec<String> = Vec::new();
+                let mut start = 0;
+                for size in sizes {
+                    if start >= chars.len() {
+                        break;
+                    }
+                    let end = (start + size).min(chars.len());
+                    segments.push(chars[start..end].iter().collect());
+                    start = end;
+                }
+                if start < chars.len() {
+                    segments.push(chars[start..].iter().collect());
+                }
+                segments.join("-")
+            }
+        }
+    }
+
+    /// Convert a sku displayed in this format back to its canonical form
+    pub fn parse(&self, display: &str) -> String {
+        let display = display.trim().to_uppercase();
+        match self {
+            Self::Uppercase => display,
+            Self::ZeroPadded(_) => {
+                if !display.is_empty() && display.chars().all(|c| c.is_ascii_digit()) {
+                    let trimmed = display.trim_start_matches('0');
+                    if trimmed.is_empty() {
+                        "0".to_string()
+                    } else {
+                        trimmed.to_string()
+                    }
+                } else {
+                    display
+                }
+            }
+            Self::Segmented(sizes) => {
+                // Only drop the dashes that `format` put between segments, so dashes that are
+                // part of the sku survive
+                let chars: Vec<char> = display.chars().collect();
+                let mut sku = String::new();
+                let mut start = 0;
+                for size in sizes {
+                    if start >= chars.len() {
+                        break;
+                    }
+                    let end = (start + size).min(chars.len());
+                    sku.extend(&chars[start..end]);
+                    start = end;
+                    if chars.get(start) == Some(&'-') {
+                        start += 1;
+                    }
+                }
+                sku.extend(&chars[start..]);
+                sku
+            }
+        }
+    }
+}
+
+/// Units of measure that count whole items, and are rarely stocked in fractions
+const COUNT_UNITS: [&str; 12] = [
+    "EA", "PK", "PR", "BX", "CS", "DZ", "BG", "RL", "ST", "KT", "CT", "PC",
+];
+
+/// Format a quantity such as stock on hand with its unit of measure, like `3 EA`, `12.5 FT`, or
+/// `0.75 LB`.
+///
+/// Counted units such as `EA` and `BX` are shown with at most 2 decimal places, and measured
+/// units such as `FT`, `LB`, or `GAL` with at most 3. Trailing zeros are dropped, so floating
+/// point noise like `3.0000000000000004` shows as `3`.
+///
+/// # Arguments
+/// * `qty` - The quantity to format
+/// * `uom` - The unit of measure. It is uppercased and trimmed. If empty, only the number is
+///   returned
+///
+/// # Example
+/// ```rust
+/// use abc_product::format::format_qty;
+///
+/// assert_eq!(format_qty(0.1 + 0.2 + 2.7, "ea"), "3 EA");
+/// assert_eq!(format_qty(12.5, "FT"), "12.5 FT");
+/// ```
+pub fn format_qty(qty: f64, uom: &str) -> String {
+    let uom = uom.trim().to_uppercase();
+    let places = if COUNT_UNITS.contains(&uom.as_str()) {
+        2
+    } else {
+        3
+    };
+    let mut qty = Decimal::from_f64(qty)
+        .unwrap_or_default()
+        .round_dp_with_strategy(places, RoundingStrategy::MidpointAwayFromZero)
+        .normalize();
+    if qty.is_zero() {
+        // Avoid showing tiny negative quantities as -0
+        qty = Decimal::ZERO;
+    }
+    if uom.is_empty() {
+        qty.to_string()
+    } else {
+        format!("{} {}", qty, uom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sku_format_round_trip() {
+        let formats = [
+            SkuFormat::Uppercase,
+            SkuFormat::ZeroPadded(8),
+            SkuFormat::Segmented(vec![2, 2]),
+        ];
+        for sku in ["123456", "ABC123", "7", "ABC-123", "AB-CD", "A--1"] {
+            for format in &formats {
+                assert_eq!(format.parse(&format.format(sku)), sku);
+            }
+        }
+        assert_eq!(SkuFormat::ZeroPadded(8).format("1234"), "00001234");
+        assert_eq!(
+            SkuFormat::Segmented(vec![2, 2]).format("abc123"),
+            "AB-C1-23"
+        );
+        assert_eq!(
+            SkuFormat::Segmented(vec![2, 2]).format("abc-123"),
+            "AB-C--123"
+        );
+        assert_eq!(SkuFormat::Segmented(vec![2, 4]).parse("120345"), "120345");
+    }
+
+    #[test]
+    fn test_format_qty() {
+        assert_eq!(format_qty(3.0000000000000004, "EA"), "3 EA");
+        assert_eq!(format_qty(12.5, "ft"), "12.5 FT");
+        assert_eq!(format_qty(0.75, "LB"), "0.75 LB");
+        assert_eq!(format_qty(0.3333333, "GAL"), "0.333 GAL");
+        assert_eq!(format_qty(-0.0001, "EA"), "0 EA");
+        assert_eq!(format_qty(-2.0, ""), "-2");
+    }
+}
@@ -0,0 +1,250 @@
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+use crate::Encoding;
+
+/// The byte order mark that some Windows tools put at the start of UTF-8 files
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+/// The byte order mark at the start of little endian UTF-16 files
+const UTF16_LE_BOM: [u8; 2] = [0xff, 0xfe];
+/// The byte order mark at the start of big endian UTF-16 files
+const UTF16_BE_BOM: [u8; 2] = [0xfe, 0xff];
+
+/// Wrap `input` to clean up the quirks that files touched by Windows tooling pick up before
+/// they reach the csv parser.
+///
+/// A UTF-8 byte order mark is removed, and UTF-16 files (recognized by their byte order mark)
+/// are converted to UTF-8. Line endings are normalized to `\n`, whether they were `\r\n`, a
+/// lone `\r`, or a run of several `\r` before a `\n`, so that no stray `\r` is left on the end
+/// of the last column. Everything but UTF-16 is cleaned up as it is read, without holding the
+/// whole file in memory.
+///
+/// # Errors
+/// Any [`std::io::Error`] raised while reading the start of `input`, or an
+/// [`std::io::ErrorKind::InvalidData`] error if a UTF-16 file is malformed
+pub(crate) fn normalize<'a>(input: impl Read + 'a) -> io::Result<Box<dyn Read + 'a>> {
+    let mut input = BufReader::new(input);
+    // A short first read could split a byte order mark, so make sure enough is buffered
+    let mut head = Vec::new();
+    (&mut input).take(3).read_to_end(&mut head)?;
+
+    if head.starts_with(&UTF8_BOM) {
+        return Ok(Box::new(LineEndings::new(input)));
+    }
+    let utf16 = if head.starts_with(&UTF16_LE_BOM) {
+        Some(u16::from_le_bytes as fn([u8; 2]) -> u16)
+    } else if head.starts_with(&UTF16_BE_BOM) {
+        Some(u16::from_be_bytes as fn([u8; 2]) -> u16)
+    } else {
+        None
+    };
+    let rest = Cursor::new(head).chain(input);
+    match utf16 {
+        Some(from_bytes) => {
+            let mut bytes = Vec::new();
+            BufReader::new(rest).read_to_end(&mut bytes)?;
+            let text = decode_utf16(&bytes[2..], from_bytes)?;
+            Ok(Box::new(LineEndings::new(Cursor::new(text))))
+        }
+        None => Ok(Box::new(LineEndings::new(BufReader::new(rest)))),
+    }
+}
+
+/// Convert UTF-16 `bytes` to UTF-8, reading each code unit with `from_bytes`
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> io::Result<Vec<u8>> {
+    let units = bytes.chunks_exact(2).map(|c| from_bytes([c[0], c[1]]));
+    let text: String = char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(text.into_bytes())
+}
+
+/// A reader that turns every `\r\n`, lone `\r`, or run of `\r` before a `\n` into a single `\n`
+struct LineEndings<R: BufRead> {
+    inner: R,
+    /// Whether a `\r` has been read but not yet written out as a `\n`
+    pending_cr: bool,
+}
+
+impl<R: BufRead> LineEndings<R> {
+    fn new(inner: R) -> Self {
+        LineEndings {
+            inner,
+            pending_cr: false,
+        }
+    }
+}
+
+impl<R: BufRead> Read for LineEndings<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.len() < 2 {
+            // Every input byte can produce up to 2 output bytes, so read through a small buffer
+            let mut buf = [0; 2];
+            let n = self.read(&mut buf)?;
+            let n = n.min(out.len());
+            out[..n].copy_from_slice(&buf[..n]);
+            return Ok(n);
+        }
+
+        let input = self.inner.fill_buf()?;
+        if input.is_empty() {
+            if self.pending_cr {
+                self.pending_cr = false;
+                out[0] = b'\n';
+                return Ok(1);
+            }
+            return Ok(0);
+        }
+
+        let mut written = 0;
+        let mut consumed = 0;
+        for &b in input {
+            if out.len() - written < 2 {
+                break;
+            }
+            consumed += 1;
+            match b {
+                b'\r' => self.pending_cr = true,
+                b'\n' => {
+                    self.pending_cr = false;
+                    out[written] = b'\n';
+                    written += 1;
+                }
+                _ => {
+                    if self.pending_cr {
+                        self.pending_cr = false;
+                        out[written] = b'\n';
+                        written += 1;
+                    }
+                    out[written] = b;
+                    written += 1;
+                }
+            }
+        }
+        self.inner.consume(consumed);
+        if written == 0 {
+            // Everything consumed was a `\r`, so keep going rather than signal the end of input
+            return self.read(out);
+        }
+        Ok(written)
+    }
+}
+
+/// The characters Windows-1252 puts at bytes 0x80 to 0x9F. The five bytes it leaves undefined
+/// map to the control characters of the same value, as browsers do. Every other byte matches
+/// the Unicode code point of the same value
+const CP1252_HIGH: [char; 32] = [
+    '\u{20ac}', '\u{81}', '\u{201a}', '\u{192}', '\u{201e}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2c6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8d}', '\u{17d}', '\u{8f}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201c}', '\u{201d}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2dc}', '\u{2122}', '\u{161}', '\u{203a}', '\u{153}', '\u{9d}', '\u{17e}', '\u{178}',
+];
+
+/// Wrap `input`, which should already be [`normalize`]d, so that it reads as UTF-8 whatever
+/// `encoding` it was written in.
+///
+/// With [`Encoding::Auto`], each line that is valid UTF-8 is passed through unchanged and any
+/// other line is decoded as Windows-1252. Since Windows-1252 text with any byte above 0x7F is
+/// almost never valid UTF-8, this handles files that mix the two, such as an export where only
+/// a few descriptions were pasted in from another program.
+pub(crate) fn decode<'a>(input: impl Read + 'a, encoding: Encoding) -> Box<dyn Read + 'a> {
+    match encoding {
+        Encoding::Utf8 => Box::new(input),
+        Encoding::Auto | Encoding::Windows1252 => Box::new(Cp1252Lines {
+            inner: BufReader::new(input),
+            encoding,
+            line: Vec::new(),
+            pos: 0,
+        }),
+    }
+}
+
+/// Decode a line of Windows-1252 text as UTF-8
+fn decode_cp1252(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9f => CP1252_HIGH[(b - 0x80) as usize],
+            _ => b as char,
+        })
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// A reader that converts Windows-1252 text to UTF-8 a line at a time. See [`decode`]
+struct Cp1252Lines<R: BufRead> {
+    inner: R,
+    encoding: Encoding,
+    /// The current line, already converted
+    line: Vec<u8>,
+    /// How much of `line` has been read
+    pos: usize,
+}
+
+impl<R: BufRead> Read for Cp1252Lines<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.line.len() {
+            let mut raw = Vec::new();
+            if self.inner.read_until(b'\n', &mut raw)? == 0 {
+                return Ok(0);
+            }
+            self.line = if self.encoding == Encoding::Auto && std::str::from_utf8(&raw).is_ok() {
+                raw
+            } else {
+                decode_cp1252(&raw)
+            };
+            self.pos = 0;
+        }
+        let n = out.len().min(self.line.len() - self.pos);
+        out[..n].copy_from_slice(&self.line[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(input: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        normalize(input).unwrap().read_to_end(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut utf8 = UTF8_BOM.to_vec();
+        utf8.extend_from_slice(b"A\t1.5\r\nB\t2\r\r\nC\t3\rD\t4\r");
+        assert_eq!(read_all(&utf8), b"A\t1.5\nB\t2\nC\t3\nD\t4\n");
+        assert_eq!(read_all(b"A"), b"A");
+
+        let mut utf16 = UTF16_LE_BOM.to_vec();
+        for unit in "A\t1.5\r\n".encode_utf16() {
+            utf16.extend_from_slice(&unit.to_le_bytes());
+        }
+        assert_eq!(read_all(&utf16), b"A\t1.5\n");
+    }
+
+    #[test]
+    fn test_decode() {
+        let decoded = |input: &[u8], encoding: Encoding| {
+            let mut out = String::new();
+            decode(input, encoding).read_to_string(&mut out).unwrap();
+            out
+        };
+        let mixed = b"3/8\" WASHER \x96 ZINC\nCAF\xc3\xa9\n";
+        assert_eq!(
+            decoded(mixed, Encoding::Auto),
+            "3/8\" WASHER \u{2013} ZINC\nCAF\u{e9}\n"
+        );
+        assert_eq!(
+            decoded(b"CAF\xc3\xa9", Encoding::Windows1252),
+            "CAF\u{c3}\u{a9}"
+        );
+        let mut out = Vec::new();
+        decode(&mixed[..], Encoding::Utf8)
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, mixed);
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use crate::AbcProduct;
+
+/// What one electronic shelf label should show for a product
+#[derive(Debug, Clone, PartialEq)]
+pub struct EslItem {
+    /// The sku of the product, used by ESL systems to link the item to its labels
+    pub sku: String,
+    /// The description of the product
+    pub desc: String,
+    /// The regular shelf price
+    pub price: Decimal,
+    /// A temporary promotional price to show alongside the regular price, if any
+    pub promo_price: Option<Decimal>,
+    /// The first UPC of the product, if any
+    pub barcode: Option<String>,
+}
+
+impl EslItem {
+    /// Build the label data for `product`, showing `promo_price` if one is running
+    pub fn from_product(product: &AbcProduct, promo_price: Option<Decimal>) -> Self {
+        EslItem {
+            sku: product.sku(),
+            desc: product.desc(),
+            price: product.list().round_dp(2),
+            promo_price: promo_price.map(|p| p.round_dp(2)),
+            barcode: product.upcs().first().map(|u| u.to_string()),
+        }
+    }
+
+    /// This item as a JSON object. Prices are strings with two decimal places so that no
+    /// precision is lost to floating point on the receiving end, and missing values are `null`
+    pub fn to_json(&self) -> Value {
+        json!({
+            "sku": self.sku,
+            "description": self.desc,
+            "price": format!("{:.2}", self.price),
+            "promoPrice": self.promo_price.map(|p| format!("{:.2}", p)),
+            "barcode": self.barcode,
+        })
+    }
+}
+
+/// Build the JSON body of an item update for an ESL system, in the flat `{"items": [...]}`
+/// shape that ESL middleware such as Pricer and SES-imagotag VUSION can map from.
+///
+/// Pass only the products that changed to send an incremental update, or the whole catalog to
+/// send a full refresh.
+///
+/// # Arguments
+/// * `products` - The products whose labels should be updated. Items are sorted by sku
+/// * `promo_prices` - Promotional prices by sku for any products currently on promotion
+///
+/// # Example
+/// ```rust
+/// use std::collections::HashMap;
+/// use abc_product::{integrations::esl, testing};
+///
+/// let products = testing::sample_export();
+/// let body = esl::payload(products.values(), &HashMap::new());
+/// ```
+pub fn payload<'a>(
+    products: impl IntoIterator<Item = &'a AbcProduct>,
+    promo_prices: &HashMap<String, Decimal>,
+) -> String {
+    let mut items: Vec<EslItem> = products
+        .into_iter()
+        .map(|p| EslItem::from_product(p, promo_prices.get(&p.sku()).copied()))
+        .collect();
+    items.sort_by(|a, b| a.sku.cmp(&b.sku));
+    json!({ "items": items.iter().map(EslItem::to_json).collect::<Vec<Value>>() }).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_payload() {
+        let products = sample_export();
+        let promos = HashMap::from([("123456".to_string(), Decimal::new(499, 2))]);
+        let value: Value = serde_json::from_str(&payload(products.values(), &promos)).unwrap();
+        let items = value["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["sku"], "123456");
+        assert_eq!(items[0]["price"], "5.99");
+        assert_eq!(items[0]["promoPrice"], "4.99");
+        assert_eq!(items[0]["barcode"], "0085875500015");
+        assert_eq!(items[1]["promoPrice"], Value::Null);
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use crate::{AbcProduct, AbcProductsBySku, price::AbcPrice};
+
+/// The columns of the feed, in the order Google's local inventory feed specification lists
+/// them
+const HEADERS: [&str; 7] = [
+    "store_code",
+    "id",
+    "availability",
+    "price",
+    "quantity",
+    "pickup_method",
+    "pickup_sla",
+];
+
+/// Products with at least one unit but fewer than this many on hand are listed as having
+/// limited availability, so shoppers are not sent across town for the last unit
+const LIMITED_BELOW: f64 = 3.0;
+
+/// What Google needs to know about one store, beyond what is in its catalog
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StoreInfo {
+    /// The store code of the store's Google Business Profile
+    pub store_code: String,
+    /// How shoppers can pick up orders at the store, such as `buy` or `reserve`. [`None`] if
+    /// the store does not offer pickup
+    pub pickup_method: Option<String>,
+    /// How soon orders are ready for pickup, such as `same day`
+    pub pickup_sla: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalInventoryError {
+    /// A catalog was given for a store code that has no [`StoreInfo`]
+    UnknownStore(String),
+}
+
+impl std::fmt::Display for LocalInventoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownStore(code) => {
+                write!(f, "No store info was given for store code `{}`", code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LocalInventoryError {}
+
+/// How Google should describe the availability of `product` at a store
+fn availability(product: &AbcProduct) -> &'static str {
+    match product.stock() {
+        s if s >= LIMITED_BELOW => "in_stock",
+        s if s >= 1.0 => "limited_availability",
+        _ => "out_of_stock",
+    }
+}
+
+/// Build a Google local inventory feed telling shoppers which stores have each product on the
+/// shelf and at what price.
+///
+/// Each store's catalog comes from that store's own ABC company, so stores can carry
+/// different products at different prices. Core charge items are left out, since they are
+/// only sold alongside the products they belong to.
+///
+/// # Arguments
+/// * `per_store_catalogs` - Each store's products, keyed by the store code of its Google
+///   Business Profile
+/// * `store_info` - Every store that has a catalog
+///
+/// # Returns
+/// A tab delimited feed with a header row, sorted by store code and then by sku
+///
+/// # Errors
+/// A [`LocalInventoryError::UnknownStore`] if a store has a catalog but no [`StoreInfo`]
+///
+/// # Example
+/// ```rust
+/// use std::collections::BTreeMap;
+/// use abc_product::{integrations::local_inventory::{self, StoreInfo}, testing};
+///
+/// let stores = [StoreInfo {
+///     store_code: "MAIN".to_string(),
+///     ..Default::default()
+/// }];
+/// let catalogs = BTreeMap::from([("MAIN".to_string(), testing::sample_export())]);
+/// let feed = local_inventory::to_feed(&catalogs, &stores).unwrap();
+/// assert_eq!(feed.lines().nth(1).unwrap(), "MAIN\t123456\tout_of_stock\t5.99 USD\t0\t\t");
+/// ```
+pub fn to_feed(
+    per_store_catalogs: &BTreeMap<String, AbcProductsBySku>,
+    store_info: &[StoreInfo],
+) -> Result<String, LocalInventoryError> {
+    let mut feed = HEADERS.join("\t");
+    feed.push('\n');
+    for (code, products) in per_store_catalogs {
+        let store = store_info
+            .iter()
+            .find(|s| s.store_code == *code)
+            .ok_or(LocalInventoryError::UnknownStore(code.to_string()))?;
+        let cores = AbcProduct::core_skus(products);
+        let mut listed: Vec<&AbcProduct> = products
+            .values()
+            .filter(|p| !cores.contains(&p.sku()))
+            .collect();
+        listed.sort_by_key(|p| p.sku());
+        for product in listed {
+            let price = AbcPrice::new(product.list());
+            let row = [
+                code.to_string(),
+                product.sku(),
+                availability(product).to_string(),
+                format!("{} {}", price.amount(), price.currency().code()),
+                (product.stock().floor().max(0.0) as i64).to_string(),
+                store.pickup_method.clone().unwrap_or_default(),
+                store.pickup_sla.clone().unwrap_or_default(),
+            ];
+            feed.push_str(&row.join("\t"));
+            feed.push('\n');
+        }
+    }
+    Ok(feed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, Sku, testing::sample_export};
+
+    #[test]
+    fn test_to_feed() {
+        let main = sample_export();
+        let mut branch = sample_export();
+        let stocked = AbcProductBuilder::from(branch["ABC123"].clone())
+            .with_stock(2.0)
+            .build()
+            .unwrap();
+        branch.insert(Sku::new("ABC123"), stocked);
+        let catalogs = BTreeMap::from([("MAIN".to_string(), main), ("BRANCH".to_string(), branch)]);
+        let stores = [
+            StoreInfo {
+                store_code: "MAIN".to_string(),
+                ..Default::default()
+            },
+            StoreInfo {
+                store_code: "BRANCH".to_string(),
+                pickup_method: Some("buy".to_string()),
+                pickup_sla: Some("same day".to_string()),
+            },
+        ];
+
+        let feed = to_feed(&catalogs, &stores).unwrap();
+        let lines: Vec<&str> = feed.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("store_code\tid\t"));
+        assert_eq!(
+            lines[2],
+            "BRANCH\tABC123\tlimited_availability\t8.12 USD\t2\tbuy\tsame day"
+        );
+        assert_eq!(lines[4], "MAIN\tABC123\tout_of_stock\t8.12 USD\t0\t\t");
+
+        assert_eq!(
+            to_feed(&catalogs, &stores[..1]),
+            Err(LocalInventoryError::UnknownStore("BRANCH".to_string()))
+        );
+    }
+}
@@ -0,0 +1,15 @@
+/// Item update payloads for electronic shelf label systems
+pub mod esl;
+/// Google local inventory feeds of which stores have each product in stock
+pub mod local_inventory;
+/// Product import files for Shopify stores
+#[cfg(feature = "shopify")]
+pub mod shopify;
+/// Catalog objects for the Square Catalog API
+pub mod square;
+/// Mapping products to the category taxonomies of marketplace feeds
+pub mod taxonomy;
+/// Structured data and sitemaps for storefronts built from exports
+pub mod web;
+/// Product payloads for the WooCommerce REST API
+pub mod woocommerce;
@@ -0,0 +1,133 @@
+use crate::{AbcProduct, catalog::AbcCatalog};
+
+/// Grams in one pound, since Shopify always takes variant weights in grams
+const GRAMS_PER_LB: f64 = 453.59237;
+
+/// The columns of Shopify's product import CSV that are filled in, in the order Shopify's
+/// template lists them
+const HEADERS: [&str; 21] = [
+    "Handle",
+    "Title",
+    "Body (HTML)",
+    "Vendor",
+    "Type",
+    "Tags",
+    "Published",
+    "Option1 Name",
+    "Option1 Value",
+    "Variant SKU",
+    "Variant Grams",
+    "Variant Inventory Tracker",
+    "Variant Inventory Qty",
+    "Variant Inventory Policy",
+    "Variant Fulfillment Service",
+    "Variant Price",
+    "Variant Requires Shipping",
+    "Variant Taxable",
+    "Variant Barcode",
+    "Variant Weight Unit",
+    "Status",
+];
+
+/// The row of the import CSV for `product`, matching [`HEADERS`]
+fn row(product: &AbcProduct) -> [String; 21] {
+    let grams = product
+        .weight()
+        .map(|lb| (lb * GRAMS_PER_LB).round() as i64)
+        .unwrap_or_default();
+    // Shopify tracks whole units, and negative stock in ABC means a count is off rather than
+    // that units are owed
+    let qty = product.stock().floor().max(0.0) as i64;
+    [
+        product.slug(),
+        product.desc(),
+        String::new(),
+        String::new(),
+        product.group().map(|g| g.to_string()).unwrap_or_default(),
+        String::new(),
+        "TRUE".to_string(),
+        "Title".to_string(),
+        "Default Title".to_string(),
+        product.sku(),
+        grams.to_string(),
+        "shopify".to_string(),
+        qty.to_string(),
+        "deny".to_string(),
+        "manual".to_string(),
+        format!("{:.2}", product.list()),
+        "TRUE".to_string(),
+        "TRUE".to_string(),
+        product
+            .upcs()
+            .first()
+            .map(|u| u.to_string())
+            .unwrap_or_default(),
+        "lb".to_string(),
+        "active".to_string(),
+    ]
+}
+
+/// Render `catalog` as a Shopify product import CSV, with one single variant product per sku,
+/// sorted by sku.
+///
+/// Weights are converted from pounds to the grams Shopify expects, while still displaying in
+/// pounds on the storefront. The first UPC becomes the variant barcode, the discount group
+/// becomes the product type, and the [`AbcProduct::slug`] becomes the handle so product URLs
+/// match [`crate::integrations::web::product_url`]. Core charge items are left out, since they
+/// are only sold alongside the products they belong to.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{catalog::AbcCatalog, integrations::shopify, testing};
+///
+/// let csv = shopify::to_shopify_csv(&AbcCatalog::new(testing::sample_export()));
+/// assert!(csv.starts_with("Handle,Title,"));
+/// assert_eq!(csv.lines().count(), 3);
+/// ```
+pub fn to_shopify_csv(catalog: &AbcCatalog) -> String {
+    let cores = AbcProduct::core_skus(catalog.products());
+    let mut products: Vec<&AbcProduct> = catalog
+        .iter()
+        .filter(|p| !cores.contains(&p.sku()))
+        .collect();
+    products.sort_by_key(|p| p.sku());
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    // Writing to a Vec cannot fail
+    writer.write_record(HEADERS).unwrap();
+    for product in products {
+        writer.write_record(row(product)).unwrap();
+    }
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, Sku, testing::sample_export};
+
+    #[test]
+    fn test_shopify_csv() {
+        let mut products = sample_export();
+        let weighed = AbcProductBuilder::from(products["123456"].clone())
+            .with_weight(2.5)
+            .build()
+            .unwrap();
+        products.insert(Sku::new("123456"), weighed);
+        let csv = to_shopify_csv(&AbcCatalog::new(products));
+
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        let column = |name: &str| HEADERS.iter().position(|h| *h == name).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][column("Handle")], "product-a-123456");
+        assert_eq!(&rows[0][column("Variant Grams")], "1134");
+        assert_eq!(&rows[0][column("Variant Price")], "5.99");
+        assert_eq!(&rows[0][column("Variant Barcode")], "0085875500015");
+        assert_eq!(&rows[0][column("Variant Inventory Qty")], "0");
+        assert_eq!(&rows[1][column("Variant SKU")], "ABC123");
+        assert_eq!(&rows[1][column("Type")], "A");
+        assert_eq!(&rows[1][column("Variant Grams")], "0");
+        assert_eq!(&rows[1][column("Variant Inventory Qty")], "0");
+    }
+}
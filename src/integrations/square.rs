@@ -0,0 +1,158 @@
+use rust_decimal::{Decimal, RoundingStrategy, prelude::ToPrimitive};
+use serde_json::{Value, json};
+
+use crate::{AbcProduct, plan::fnv1a};
+
+/// The most objects, counting variations, Square accepts in one batch of a
+/// `BatchUpsertCatalogObjects` request
+pub const BATCH_LIMIT: usize = 1000;
+
+/// The currency list prices are sent in
+const CURRENCY: &str = "USD";
+
+/// The temporary ids Square uses to link a new item to its variation before it assigns
+/// permanent ones. Derived from the sku alone, so the same product always gets the same ids and
+/// retried requests never create duplicates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientIds {
+    /// The id of the `ITEM` object
+    pub item: String,
+    /// The id of the item's single `ITEM_VARIATION` object
+    pub variation: String,
+}
+
+impl ClientIds {
+    /// The client ids for the product with `sku`. Skus are hashed rather than copied in, since
+    /// Square only allows some characters in ids
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::integrations::square::ClientIds;
+    ///
+    /// let ids = ClientIds::for_sku("123456");
+    /// assert!(ids.item.starts_with("#item-"));
+    /// assert_eq!(ids, ClientIds::for_sku("123456"));
+    /// ```
+    pub fn for_sku(sku: &str) -> Self {
+        let hash = fnv1a(&["square", sku]);
+        ClientIds {
+            item: format!("#item-{:016x}", hash),
+            variation: format!("#variation-{:016x}", hash),
+        }
+    }
+}
+
+/// An amount of money as Square takes it, in whole cents
+fn money(amount: Decimal) -> Value {
+    let cents = (amount * Decimal::ONE_HUNDRED)
+        .round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+        .to_i64()
+        .unwrap_or_default();
+    json!({ "amount": cents, "currency": CURRENCY })
+}
+
+/// The Square `CatalogObject` for `product`: an `ITEM` named for the description holding one
+/// `ITEM_VARIATION` with the sku, first UPC, and list price. Both objects use
+/// [`ClientIds::for_sku`] as their ids, so the object can be sent to create a new item. To
+/// update an item already in Square, replace the ids with the ones Square assigned along with
+/// the current `version`.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{integrations::square, testing};
+///
+/// let object = square::catalog_object(&testing::sample_export()["123456"]);
+/// assert_eq!(object["type"], "ITEM");
+/// let variation = &object["item_data"]["variations"][0]["item_variation_data"];
+/// assert_eq!(variation["price_money"]["amount"], 599);
+/// ```
+pub fn catalog_object(product: &AbcProduct) -> Value {
+    let ids = ClientIds::for_sku(&product.sku());
+    let mut variation_data = json!({
+        "item_id": ids.item,
+        "name": "Regular",
+        "sku": product.sku(),
+        "pricing_type": "FIXED_PRICING",
+        "price_money": money(product.list()),
+        "track_inventory": true,
+    });
+    if let Some(upc) = product.upcs().first() {
+        variation_data["upc"] = json!(upc.to_string());
+    }
+    json!({
+        "type": "ITEM",
+        "id": ids.item,
+        "present_at_all_locations": true,
+        "item_data": {
+            "name": product.desc(),
+            "variations": [{
+                "type": "ITEM_VARIATION",
+                "id": ids.variation,
+                "present_at_all_locations": true,
+                "item_variation_data": variation_data,
+            }],
+        },
+    })
+}
+
+/// Build the body of a request to Square's `/v2/catalog/batch-upsert` endpoint for `products`,
+/// split into batches of at most [`BATCH_LIMIT`] objects.
+///
+/// The idempotency key is a hash of the objects being sent, so retrying the same request is
+/// safe while a request with changed products is treated as new.
+///
+/// # Arguments
+/// * `products` - The products to create in Square
+///
+/// # Returns
+/// The JSON body of the request
+pub fn batch_upsert_body(products: &[&AbcProduct]) -> Value {
+    let objects: Vec<Value> = products.iter().map(|p| catalog_object(p)).collect();
+    // Each item counts once for itself and once for its variation
+    let batches: Vec<Value> = objects
+        .chunks(BATCH_LIMIT / 2)
+        .map(|chunk| json!({ "objects": chunk }))
+        .collect();
+    let key = fnv1a(&[&serde_json::to_string(&batches).unwrap_or_default()]);
+    json!({
+        "idempotency_key": format!("{:016x}", key),
+        "batches": batches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_catalog_object() {
+        let products = sample_export();
+        let object = catalog_object(&products["ABC123"]);
+        let ids = ClientIds::for_sku("ABC123");
+        assert_eq!(object["id"], ids.item.as_str());
+        assert_eq!(object["item_data"]["name"], "PRODUCT B");
+        let variation = &object["item_data"]["variations"][0];
+        assert_eq!(variation["id"], ids.variation.as_str());
+        assert_eq!(
+            variation["item_variation_data"]["item_id"],
+            ids.item.as_str()
+        );
+        assert_eq!(
+            variation["item_variation_data"]["price_money"]["amount"],
+            812
+        );
+        assert!(variation["item_variation_data"].get("upc").is_none());
+        assert_ne!(ids, ClientIds::for_sku("123456"));
+
+        let many: Vec<&AbcProduct> = std::iter::repeat_n(&products["123456"], 600).collect();
+        let body = batch_upsert_body(&many);
+        assert_eq!(body["batches"].as_array().unwrap().len(), 2);
+        assert_eq!(body["batches"][1]["objects"].as_array().unwrap().len(), 100);
+        assert_eq!(body, batch_upsert_body(&many));
+        assert_ne!(
+            body["idempotency_key"],
+            batch_upsert_body(&many[..1])["idempotency_key"]
+        );
+    }
+}
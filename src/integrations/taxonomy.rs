@@ -0,0 +1,265 @@
+use std::{collections::BTreeMap, io::Read};
+
+use crate::{AbcGroup, AbcParseError, AbcProduct, AbcProductsBySku, ValueError, input};
+
+/// The supplemental attribute that holds a product's category, for stores that categorize
+/// more finely than discount groups. See [`crate::attributes::Attributes`]
+pub const CATEGORY_ATTRIBUTE: &str = "category";
+
+/// A marketplace whose feeds require a category from its own taxonomy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Marketplace {
+    /// Google Merchant Center, which takes a Google product category
+    Google,
+    /// Amazon, which takes a browse node
+    Amazon,
+}
+
+impl Marketplace {
+    /// Every marketplace, in order
+    pub const ALL: [Marketplace; 2] = [Marketplace::Google, Marketplace::Amazon];
+}
+
+/// The categories of a product in each marketplace's taxonomy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Categories {
+    /// The Google product category, as its numeric id or full path such as
+    /// `Hardware > Plumbing > Plumbing Fixtures > Faucets`
+    pub google: Option<String>,
+    /// The Amazon browse node id
+    pub amazon: Option<String>,
+}
+
+impl Categories {
+    /// The category for `marketplace`
+    pub fn get(&self, marketplace: Marketplace) -> Option<&str> {
+        match marketplace {
+            Marketplace::Google => self.google.as_deref(),
+            Marketplace::Amazon => self.amazon.as_deref(),
+        }
+    }
+
+    /// These categories, with any that are missing taken from `fallback`
+    fn or(self, fallback: Option<&Categories>) -> Self {
+        let Some(fallback) = fallback else {
+            return self;
+        };
+        Categories {
+            google: self.google.or_else(|| fallback.google.clone()),
+            amazon: self.amazon.or_else(|| fallback.amazon.clone()),
+        }
+    }
+}
+
+/// A product that has no category in at least one marketplace, as found by
+/// [`TaxonomyMap::unmapped`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unmapped {
+    /// The sku of the product
+    pub sku: String,
+    /// The discount group of the product, so whole groups can be mapped at once
+    pub group: Option<AbcGroup>,
+    /// Every marketplace the product has no category in
+    pub missing: Vec<Marketplace>,
+}
+
+/// A table, maintained by the store, that maps skus, categories, and discount groups to the
+/// categories of marketplace taxonomies.
+///
+/// When looking up a product, a mapping for its sku wins over one for its
+/// [`CATEGORY_ATTRIBUTE`], which wins over one for its discount group. Each marketplace is
+/// looked up separately, so a group can set the Amazon browse node while a sku overrides just
+/// the Google category.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{
+///     integrations::taxonomy::{Categories, Marketplace, TaxonomyMap},
+///     testing,
+/// };
+///
+/// let map = TaxonomyMap::new().with_group(
+///     "A",
+///     Categories {
+///         google: Some("1810".to_string()),
+///         amazon: None,
+///     },
+/// );
+/// let products = testing::sample_export();
+/// assert_eq!(map.lookup(&products["ABC123"]).google.unwrap(), "1810");
+/// let unmapped = map.unmapped(&products);
+/// assert_eq!(unmapped[0].sku, "123456");
+/// assert_eq!(unmapped[1].missing, vec![Marketplace::Amazon]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaxonomyMap {
+    by_sku: BTreeMap<String, Categories>,
+    by_category: BTreeMap<String, Categories>,
+    by_group: BTreeMap<String, Categories>,
+}
+
+impl TaxonomyMap {
+    /// Create an empty [`TaxonomyMap`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map a single sku
+    pub fn with_sku(self, sku: &str, categories: Categories) -> Self {
+        let mut by_sku = self.by_sku;
+        by_sku.insert(sku.trim().to_string(), categories);
+        TaxonomyMap { by_sku, ..self }
+    }
+
+    /// Map every product whose [`CATEGORY_ATTRIBUTE`] is `category`
+    pub fn with_category(self, category: &str, categories: Categories) -> Self {
+        let mut by_category = self.by_category;
+        by_category.insert(category.trim().to_string(), categories);
+        TaxonomyMap {
+            by_category,
+            ..self
+        }
+    }
+
+    /// Map every product in a discount group
+    pub fn with_group(self, group: &str, categories: Categories) -> Self {
+        let mut by_group = self.by_group;
+        by_group.insert(group.trim().to_string(), categories);
+        TaxonomyMap { by_group, ..self }
+    }
+
+    /// Load a map from a comma separated file with the columns `match`,
+    /// `google_product_category`, and `amazon_browse_node`, such as
+    /// `group:A,1810,3754161`. The match is `sku:`, `category:`, or `group:` followed by what
+    /// to match. Empty cells leave that marketplace unmapped, and a header row starting with
+    /// "match" is skipped.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the mapping table
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if the file cannot be read, or a match does not start with `sku:`,
+    /// `category:`, or `group:`
+    pub fn from_csv(path: &str) -> Result<Self, AbcParseError> {
+        Self::from_csv_reader(std::fs::File::open(path).map_err(csv::Error::from)?)
+    }
+
+    /// Like [`TaxonomyMap::from_csv`], reading from any reader rather than a path on disk
+    pub(crate) fn from_csv_reader(data: impl Read) -> Result<Self, AbcParseError> {
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(input::normalize(data).map_err(csv::Error::from)?);
+        let mut map = TaxonomyMap::new();
+        for (i, record) in reader.into_records().enumerate() {
+            let record = record?;
+            let Some(matcher) = record.get(0).map(str::trim).filter(|s| !s.is_empty()) else {
+                continue;
+            };
+            if matcher.eq_ignore_ascii_case("match") {
+                continue;
+            }
+            let cell = |n: usize| {
+                record
+                    .get(n)
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+            };
+            let categories = Categories {
+                google: cell(1),
+                amazon: cell(2),
+            };
+            map = match matcher.split_once(':') {
+                Some(("sku", sku)) => map.with_sku(sku, categories),
+                Some(("category", category)) => map.with_category(category, categories),
+                Some(("group", group)) => map.with_group(group, categories),
+                _ => {
+                    return Err(AbcParseError::invalid(
+                        None,
+                        i + 1,
+                        0,
+                        "match",
+                        matcher,
+                        ValueError::Unrecognized,
+                    ));
+                }
+            };
+        }
+        Ok(map)
+    }
+
+    /// The categories of `product` in every marketplace it is mapped in
+    pub fn lookup(&self, product: &AbcProduct) -> Categories {
+        Categories::default()
+            .or(self.by_sku.get(&product.sku()))
+            .or(product
+                .attr(CATEGORY_ATTRIBUTE)
+                .and_then(|c| self.by_category.get(c.trim())))
+            .or(product
+                .group()
+                .and_then(|g| self.by_group.get(&g.to_string())))
+    }
+
+    /// Every product in `products` that is missing a category in at least one marketplace,
+    /// since feeds for those marketplaces reject products without one
+    ///
+    /// # Returns
+    /// The unmapped products, sorted by sku
+    pub fn unmapped(&self, products: &AbcProductsBySku) -> Vec<Unmapped> {
+        let mut unmapped: Vec<Unmapped> = products
+            .values()
+            .filter_map(|product| {
+                let categories = self.lookup(product);
+                let missing: Vec<Marketplace> = Marketplace::ALL
+                    .into_iter()
+                    .filter(|m| categories.get(*m).is_none())
+                    .collect();
+                (!missing.is_empty()).then(|| Unmapped {
+                    sku: product.sku(),
+                    group: product.group(),
+                    missing,
+                })
+            })
+            .collect();
+        unmapped.sort_by(|a, b| a.sku.cmp(&b.sku));
+        unmapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attributes::Attributes, testing::sample_export};
+
+    #[test]
+    fn test_taxonomy_map() {
+        let table = "match,google_product_category,amazon_browse_node\n\
+                     group:A,1810,3754161\n\
+                     category:Faucets,,680350011\n\
+                     sku:ABC123,2206,\n";
+        let map = TaxonomyMap::from_csv_reader(table.as_bytes()).unwrap();
+        let mut products = sample_export();
+        Attributes::new()
+            .with_attr("123456", CATEGORY_ATTRIBUTE, "Faucets")
+            .apply(&mut products);
+
+        assert_eq!(
+            map.lookup(&products["ABC123"]),
+            Categories {
+                google: Some("2206".to_string()),
+                amazon: Some("3754161".to_string()),
+            }
+        );
+        assert_eq!(
+            map.unmapped(&products),
+            vec![Unmapped {
+                sku: "123456".to_string(),
+                group: None,
+                missing: vec![Marketplace::Google],
+            }]
+        );
+
+        assert!(TaxonomyMap::from_csv_reader("vendor:X,1,2\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use std::io::Write;
+
+use serde_json::json;
+
+use crate::{AbcProduct, AbcProductsBySku};
+
+/// The page for `product` under a storefront hosted at `base_url`
+///
+/// # Arguments
+/// * `product` - The product to link to
+/// * `base_url` - The root of the storefront, such as `https://example.com`. A trailing slash
+///   is ignored
+pub fn product_url(product: &AbcProduct, base_url: &str) -> String {
+    format!(
+        "{}/products/{}",
+        base_url.trim_end_matches('/'),
+        product.slug()
+    )
+}
+
+/// Escape the characters that are not allowed to appear as-is in XML text
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Build the schema.org `Product` JSON-LD for `product`, ready to be placed in a
+/// `<script type="application/ld+json">` tag on its page.
+///
+/// The first UPC, if any, is used as the `gtin13`. The offer is priced at list in USD and is
+/// `InStock` when stock is above zero, otherwise `OutOfStock`. Any [`AbcProduct::related`] skus
+/// are listed under `isRelatedTo`.
+///
+/// # Arguments
+/// * `product` - The product to describe
+/// * `base_url` - The root of the storefront, used to build the product's URL with
+///   [`product_url`]
+pub fn jsonld(product: &AbcProduct, base_url: &str) -> String {
+    let availability = if product.stock() > 0.0 {
+        "https://schema.org/InStock"
+    } else {
+        "https://schema.org/OutOfStock"
+    };
+    let mut value = json!({
+        "@context": "https://schema.org",
+        "@type": "Product",
+        "name": product.desc(),
+        "sku": product.sku(),
+        "url": product_url(product, base_url),
+        "offers": {
+            "@type": "Offer",
+            "price": product.list().round_dp(2).to_string(),
+            "priceCurrency": "USD",
+            "availability": availability,
+        },
+    });
+    if let Some(upc) = product.upcs().first() {
+        value["gtin13"] = json!(upc.to_string());
+    }
+    let related = product.related();
+    if !related.is_empty() {
+        value["isRelatedTo"] = related
+            .iter()
+            .map(|sku| json!({ "@type": "Product", "sku": sku }))
+            .collect();
+    }
+    value.to_string()
+}
+
+/// Write an XML sitemap listing the page of every product in `products`, sorted by sku. Core
+/// charge items are left out, since they are only sold alongside the products they belong to
+///
+/// # Arguments
+/// * `products` - The products to list
+/// * `base_url` - The root of the storefront, used to build each URL with [`product_url`]
+/// * `writer` - Where to write the sitemap
+///
+/// # Errors
+/// Any [`std::io::Error`] raised by `writer`
+pub fn sitemap(
+    products: &AbcProductsBySku,
+    base_url: &str,
+    mut writer: impl Write,
+) -> std::io::Result<()> {
+    let cores = AbcProduct::core_skus(products);
+    let mut sorted: Vec<&AbcProduct> = products
+        .values()
+        .filter(|p| !cores.contains(&p.sku()))
+        .collect();
+    sorted.sort_by_key(|p| p.sku());
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#
+    )?;
+    for product in sorted {
+        writeln!(
+            writer,
+            "  <url><loc>{}</loc></url>",
+            xml_escape(&product_url(product, base_url))
+        )?;
+    }
+    writeln!(writer, "</urlset>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_jsonld() {
+        let products = sample_export();
+        let value: serde_json::Value =
+            serde_json::from_str(&jsonld(&products["ABC123"], "https://example.com/")).unwrap();
+        assert_eq!(value["sku"], "ABC123");
+        assert_eq!(
+            value["url"],
+            "https://example.com/products/product-b-abc123"
+        );
+        assert_eq!(value["offers"]["price"], "8.12");
+        assert_eq!(
+            value["offers"]["availability"],
+            "https://schema.org/OutOfStock"
+        );
+        assert!(value.get("gtin13").is_none());
+        assert!(value.get("isRelatedTo").is_none());
+    }
+}
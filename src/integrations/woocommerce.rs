@@ -0,0 +1,128 @@
+use serde_json::{Value, json};
+
+use crate::AbcProduct;
+
+/// The most products WooCommerce accepts in one request to its batch endpoint
+pub const BATCH_LIMIT: usize = 100;
+
+/// The body of a request to WooCommerce's `/wp-json/wc/v3/products` endpoint for one product
+///
+/// # Example
+/// ```rust
+/// use abc_product::{integrations::woocommerce::ProductPayload, testing};
+///
+/// let payload = ProductPayload::from(&testing::sample_export()["123456"]);
+/// assert_eq!(payload.to_json()["regular_price"], "5.99");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductPayload {
+    /// The product name shown on the store, taken from the description
+    pub name: String,
+    /// The sku, which WooCommerce requires to be unique
+    pub sku: String,
+    /// The list price with two decimal places
+    pub regular_price: String,
+    /// The whole units on hand. Negative stock counts as none
+    pub stock_quantity: i64,
+    /// The weight in the store's weight unit, which should be set to pounds. [`None`] if the
+    /// weight is not known
+    pub weight: Option<String>,
+    /// The first UPC of the product, sent as WooCommerce's GTIN field
+    pub global_unique_id: Option<String>,
+}
+
+impl From<&AbcProduct> for ProductPayload {
+    fn from(product: &AbcProduct) -> Self {
+        ProductPayload {
+            name: product.desc(),
+            sku: product.sku(),
+            regular_price: format!("{:.2}", product.list()),
+            stock_quantity: product.stock().floor().max(0.0) as i64,
+            weight: product.weight().map(|w| w.to_string()),
+            global_unique_id: product.upcs().first().map(|u| u.to_string()),
+        }
+    }
+}
+
+impl ProductPayload {
+    /// This payload as a JSON object. WooCommerce takes prices and weights as strings, and
+    /// fields that are not known are left out so that they are not cleared on update
+    pub fn to_json(&self) -> Value {
+        let mut body = json!({
+            "name": self.name,
+            "type": "simple",
+            "sku": self.sku,
+            "regular_price": self.regular_price,
+            "manage_stock": true,
+            "stock_quantity": self.stock_quantity,
+        });
+        if let Some(weight) = &self.weight {
+            body["weight"] = json!(weight);
+        }
+        if let Some(gtin) = &self.global_unique_id {
+            body["global_unique_id"] = json!(gtin);
+        }
+        body
+    }
+}
+
+/// Build the bodies of requests to WooCommerce's `/wp-json/wc/v3/products/batch` endpoint that
+/// create new products and update existing ones, split so that no request has more than
+/// [`BATCH_LIMIT`] products
+///
+/// # Arguments
+/// * `create` - Products that are not on the store yet
+/// * `update` - Products already on the store, with their WooCommerce product ids
+///
+/// # Returns
+/// One JSON body per request, in the order they should be sent
+pub fn batches(create: &[ProductPayload], update: &[(u64, ProductPayload)]) -> Vec<Value> {
+    let mut operations: Vec<(&str, Value)> =
+        create.iter().map(|p| ("create", p.to_json())).collect();
+    operations.extend(update.iter().map(|(id, p)| {
+        let mut body = p.to_json();
+        body["id"] = json!(id);
+        ("update", body)
+    }));
+    operations
+        .chunks(BATCH_LIMIT)
+        .map(|chunk| {
+            let mut request = json!({});
+            for (operation, body) in chunk {
+                match request.get_mut(*operation).and_then(Value::as_array_mut) {
+                    Some(list) => list.push(body.clone()),
+                    None => request[*operation] = json!([body]),
+                }
+            }
+            request
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_payload() {
+        let products = sample_export();
+        let body = ProductPayload::from(&products["ABC123"]).to_json();
+        assert_eq!(body["sku"], "ABC123");
+        assert_eq!(body["regular_price"], "8.12");
+        assert_eq!(body["stock_quantity"], 0);
+        assert!(body.get("weight").is_none());
+        assert_eq!(
+            ProductPayload::from(&products["123456"]).to_json()["global_unique_id"],
+            "0085875500015"
+        );
+
+        let payload = ProductPayload::from(&products["123456"]);
+        let create = vec![payload.clone(); 150];
+        let requests = batches(&create, &[(42, payload)]);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["create"].as_array().unwrap().len(), 100);
+        assert_eq!(requests[1]["create"].as_array().unwrap().len(), 50);
+        assert_eq!(requests[1]["update"][0]["id"], 42);
+    }
+}
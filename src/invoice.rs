@@ -0,0 +1,286 @@
+use std::{collections::HashMap, io::Read};
+
+use chrono::NaiveDate;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+use crate::{
+    AbcParseError, Encoding, Sku, open_data_file,
+    records::{amount, date, rows, text},
+};
+
+/// Column numbers of the fields this crate knows about in `invoice.data`, counting from zero
+pub mod invoice_columns {
+    pub const NUMBER: usize = 0;
+    pub const DATE: usize = 1;
+    pub const CUSTOMER: usize = 2;
+    pub const CLERK: usize = 3;
+}
+
+/// Column numbers of the fields this crate knows about in `invoice_line.data`, counting from zero
+pub mod invoice_line_columns {
+    pub const NUMBER: usize = 0;
+    pub const SKU: usize = 1;
+    pub const DESC: usize = 2;
+    pub const QTY: usize = 3;
+    pub const PRICE: usize = 4;
+}
+
+/// A map of invoice numbers to the [`AbcInvoice`] they belong to
+pub type AbcInvoicesByNumber = HashMap<String, AbcInvoice>;
+
+/// A single line of an invoice
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbcInvoiceLine {
+    sku: Option<Sku>,
+    desc: String,
+    qty: Decimal,
+    price: Decimal,
+}
+
+impl AbcInvoiceLine {
+    /// The sku sold on this line. [`None`] for lines that are not for a product, such as
+    /// comments or miscellaneous charges
+    pub fn sku(&self) -> Option<String> {
+        self.sku.as_ref().map(|s| s.to_string())
+    }
+
+    /// The description printed on this line
+    pub fn desc(&self) -> String {
+        self.desc.to_string()
+    }
+
+    /// How many units were sold. Negative for returns
+    pub fn qty(&self) -> Decimal {
+        self.qty
+    }
+
+    /// The price charged for each unit
+    pub fn price(&self) -> Decimal {
+        self.price
+    }
+
+    /// The total charged for this line, `qty` times `price`
+    pub fn extended(&self) -> Decimal {
+        self.qty * self.price
+    }
+}
+
+/// A sale, as exported from ABC's invoice history files
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbcInvoice {
+    number: String,
+    date: Option<NaiveDate>,
+    customer: Option<String>,
+    clerk: Option<String>,
+    lines: Vec<AbcInvoiceLine>,
+}
+
+impl AbcInvoice {
+    /// Create a map of invoice numbers to [`AbcInvoice`]s by parsing the invoice history files of
+    /// an ABC database export.
+    ///
+    /// Run report 7-10 as described in [`crate::AbcProduct::from_db_export`], but select the
+    /// invoice history file to export. Two files are written next to `item.data`, at
+    /// C:\ABC Software\Database Export\Company001\Data\invoice.data and
+    /// C:\ABC Software\Database Export\Company001\Data\invoice_line.data.
+    ///
+    /// # Arguments
+    /// * `invoice_path` - The path to the invoice.data file generated by the db export
+    /// * `invoice_line_path` - The path to the invoice_line.data file generated by the db export
+    ///
+    /// # Returns
+    /// A [`HashMap`] of invoice numbers to the [`AbcInvoice`] they belong to. Lines keep the order
+    /// they have in the export
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file cannot be read, if a row is missing its invoice number,
+    /// if a quantity or price cannot be parsed, or if a line belongs to an invoice that is not in
+    /// `invoice.data`
+    pub fn from_db_export(
+        invoice_path: &str,
+        invoice_line_path: &str,
+    ) -> Result<AbcInvoicesByNumber, AbcParseError> {
+        let invoice_file = open_data_file(invoice_path).map_err(csv::Error::from)?;
+        let invoice_line_file = open_data_file(invoice_line_path).map_err(csv::Error::from)?;
+        Self::from_readers(invoice_file, invoice_line_file)
+    }
+
+    /// Like [`AbcInvoice::from_db_export`], reading from any readers rather than from paths on
+    /// disk
+    pub(crate) fn from_readers(
+        invoice: impl Read,
+        invoice_line: impl Read,
+    ) -> Result<AbcInvoicesByNumber, AbcParseError> {
+        let mut invoices = AbcInvoicesByNumber::new();
+        for row in rows(invoice, "invoice.data", Encoding::Auto)? {
+            let (row, record) = row?;
+            let number = text(&record, invoice_columns::NUMBER).ok_or_else(|| {
+                AbcParseError::missing(
+                    Some("invoice.data"),
+                    row,
+                    Some(invoice_columns::NUMBER),
+                    "number",
+                )
+            })?;
+            let invoice = AbcInvoice {
+                number: number.clone(),
+                date: date(&record, invoice_columns::DATE),
+                customer: text(&record, invoice_columns::CUSTOMER),
+                clerk: text(&record, invoice_columns::CLERK),
+                lines: Vec::new(),
+            };
+            invoices.insert(number, invoice);
+        }
+
+        for row in rows(invoice_line, "invoice_line.data", Encoding::Auto)? {
+            let (row, record) = row?;
+            let number = text(&record, invoice_line_columns::NUMBER).ok_or_else(|| {
+                AbcParseError::missing(
+                    Some("invoice_line.data"),
+                    row,
+                    Some(invoice_line_columns::NUMBER),
+                    "number",
+                )
+            })?;
+            let line = AbcInvoiceLine {
+                sku: text(&record, invoice_line_columns::SKU).map(|s| Sku::new(&s)),
+                desc: text(&record, invoice_line_columns::DESC).unwrap_or_default(),
+                qty: amount(
+                    &record,
+                    "invoice_line.data",
+                    invoice_line_columns::QTY,
+                    "qty",
+                    row,
+                )?,
+                price: amount(
+                    &record,
+                    "invoice_line.data",
+                    invoice_line_columns::PRICE,
+                    "price",
+                    row,
+                )?,
+            };
+            invoices
+                .get_mut(&number)
+                .ok_or_else(|| AbcParseError::MissingRecord {
+                    file: "invoice.data".to_string(),
+                    key: number.to_string(),
+                    row,
+                })?
+                .lines
+                .push(line);
+        }
+        Ok(invoices)
+    }
+
+    /// The invoice number
+    pub fn number(&self) -> String {
+        self.number.to_string()
+    }
+
+    /// The date of the sale
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.date
+    }
+
+    /// The account number of the customer, matching [`crate::customer::AbcCustomer::account`].
+    /// [`None`] for cash sales
+    pub fn customer(&self) -> Option<String> {
+        self.customer.clone()
+    }
+
+    /// The clerk who rang up the sale
+    pub fn clerk(&self) -> Option<String> {
+        self.clerk.clone()
+    }
+
+    /// The lines of the invoice, in the order they were entered
+    pub fn lines(&self) -> &[AbcInvoiceLine] {
+        &self.lines
+    }
+
+    /// The total of every line on the invoice, before tax
+    pub fn total(&self) -> Decimal {
+        self.lines.iter().map(|l| l.extended()).sum()
+    }
+}
+
+/// Count the units of each sku sold on `invoices` dated from `from` up to and including `to`,
+/// net of returns. Invoices without a date are skipped.
+///
+/// The result can be passed as the weights of [`crate::pricing::simulate`].
+///
+/// # Arguments
+/// * `invoices` - The invoices to count
+/// * `from` - The first day to count
+/// * `to` - The last day to count
+///
+/// # Returns
+/// A [`HashMap`] of skus to units sold. Skus that were never sold are left out
+pub fn units_sold<'a>(
+    invoices: impl IntoIterator<Item = &'a AbcInvoice>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> HashMap<String, f64> {
+    let mut units: HashMap<String, Decimal> = HashMap::new();
+    for invoice in invoices {
+        if !invoice.date.is_some_and(|d| d >= from && d <= to) {
+            continue;
+        }
+        for line in &invoice.lines {
+            if let Some(sku) = &line.sku {
+                *units.entry(sku.to_string()).or_default() += line.qty;
+            }
+        }
+    }
+    units
+        .into_iter()
+        .map(|(sku, qty)| (sku, qty.to_f64().unwrap_or_default()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invoice_parser() {
+        let invoice = "5001\t2024-11-16\t1001\tBOB\n5002\t2024-12-01\tnull\tAMY\n";
+        let lines = "5001\t123456\tPRODUCT A\t2\t5.99\n\
+                     5001\t\tDELIVERY\t1\t10.00\n\
+                     5002\t123456\tPRODUCT A\t-1\t5.99\n\
+                     5002\tabc123 \tPRODUCT B\t1\t8.12\n";
+        let invoices = AbcInvoice::from_readers(invoice.as_bytes(), lines.as_bytes()).unwrap();
+        let first = &invoices["5001"];
+        assert_eq!(first.customer(), Some("1001".to_string()));
+        assert_eq!(first.clerk(), Some("BOB".to_string()));
+        assert_eq!(first.lines().len(), 2);
+        assert_eq!(first.lines()[1].sku(), None);
+        assert_eq!(first.total(), Decimal::new(2198, 2));
+        assert_eq!(invoices["5002"].customer(), None);
+        assert_eq!(
+            invoices["5002"].lines()[1].sku(),
+            Some("ABC123".to_string())
+        );
+
+        let day = |d: u32, m: u32| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert_eq!(
+            units_sold(invoices.values(), day(1, 1), day(31, 12))["123456"],
+            1.0
+        );
+        assert_eq!(
+            units_sold(invoices.values(), day(1, 1), day(30, 11))["123456"],
+            2.0
+        );
+        assert_eq!(
+            units_sold(invoices.values(), day(1, 1), day(31, 12))["ABC123"],
+            1.0
+        );
+
+        assert!(
+            AbcInvoice::from_readers(invoice.as_bytes(), "9999\t1\tX\t1\t1\n".as_bytes()).is_err()
+        );
+    }
+}
@@ -0,0 +1,357 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{AbcProduct, ProductField, format::format_qty, pdf, upc::barcode_modules};
+
+/// What a text element on a label displays
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Binding {
+    /// The value of a product field. Prices are shown with a dollar sign and two decimal places
+    Field(ProductField),
+    /// The same fixed text on every label
+    Text(String),
+    /// The list price per unit of the product's net contents, such as `$0.25 / OZ`. Blank if
+    /// the net contents are not known
+    UnitPrice,
+}
+
+/// What kind of thing an [`Element`] draws
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ElementKind {
+    /// A line of text
+    Text {
+        /// What the text shows
+        binding: Binding,
+        /// The height of the text in points
+        size_pt: f64,
+    },
+    /// An EAN-13 barcode of the product's first UPC. Nothing is drawn if the product has no UPC
+    Barcode {
+        /// The height of the bars in millimeters
+        height_mm: f64,
+        /// The width of the narrowest bar in millimeters. 0.33 is the nominal EAN-13 size
+        module_mm: f64,
+    },
+}
+
+/// One piece of a label layout, positioned from the top left corner of the label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Element {
+    /// The distance from the left edge of the label in millimeters
+    pub x_mm: f64,
+    /// The distance from the top edge of the label in millimeters
+    pub y_mm: f64,
+    /// What to draw
+    #[serde(flatten)]
+    pub kind: ElementKind,
+}
+
+/// A label layout that stores can edit as TOML without any code changes.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{labels::Template, testing};
+///
+/// let template = Template::from_toml(r#"
+///     name = "shelf"
+///     width_mm = 50.0
+///     height_mm = 25.0
+///
+///     [[elements]]
+///     x_mm = 2.0
+///     y_mm = 2.0
+///     type = "text"
+///     binding = { field = "desc" }
+///     size_pt = 8.0
+///
+///     [[elements]]
+///     x_mm = 2.0
+///     y_mm = 8.0
+///     type = "text"
+///     binding = { field = "list" }
+///     size_pt = 14.0
+/// "#).unwrap();
+/// let zpl = template.render_zpl(&testing::sample_export()["123456"], 203);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    /// A name to pick the template by, such as "shelf" or "peg hook"
+    pub name: String,
+    /// The width of the label in millimeters
+    pub width_mm: f64,
+    /// The height of the label in millimeters
+    pub height_mm: f64,
+    /// Everything drawn on the label
+    pub elements: Vec<Element>,
+}
+
+/// The text a [`Binding`] shows for `product`
+fn bound_text(binding: &Binding, product: &AbcProduct) -> String {
+    let field = match binding {
+        Binding::Text(text) => return text.to_string(),
+        Binding::UnitPrice => {
+            return match (product.net_contents(), product.unit_price()) {
+                (Some(contents), Some(unit_price)) => {
+                    format!("${:.2} / {}", unit_price, contents.unit())
+                }
+                _ => String::new(),
+            };
+        }
+        Binding::Field(field) => field,
+    };
+    let price = |p: rust_decimal::Decimal| format!("${:.2}", p);
+    match field {
+        ProductField::Sku => product.sku(),
+        ProductField::Desc => product.desc(),
+        ProductField::Upcs => product
+            .upcs()
+            .first()
+            .map(|u| u.to_string())
+            .unwrap_or_default(),
+        ProductField::RawUpcs => product.raw_upcs().join(", "),
+        ProductField::List => price(product.list()),
+        ProductField::Cost => price(product.cost()),
+        ProductField::AvgCost => product.avg_cost().map(price).unwrap_or_default(),
+        ProductField::Stock => format_qty(product.stock(), "EA"),
+        ProductField::Group => product.group().map(|g| g.to_string()).unwrap_or_default(),
+        ProductField::Weight => product
+            .weight()
+            .map(|w| format!("{} LB", w))
+            .unwrap_or_default(),
+        ProductField::LastSold => product
+            .last_sold()
+            .map(|d| d.format("%m/%d/%Y").to_string())
+            .unwrap_or_default(),
+        ProductField::AltSkus => product.alt_skus().join(", "),
+        ProductField::CoreSku => product.core_sku().unwrap_or_default(),
+        ProductField::WarrantyMonths => product
+            .warranty_months()
+            .map(|m| format!("{} months", m))
+            .unwrap_or_default(),
+        ProductField::NetContents => product
+            .net_contents()
+            .map(|n| format!("NET {}", n))
+            .unwrap_or_default(),
+        ProductField::CountryOfOrigin => product
+            .country_of_origin()
+            .map(|c| format!("Made in {}", c))
+            .unwrap_or_default(),
+        ProductField::MinStock => product
+            .min_stock()
+            .map(|q| format_qty(q, "EA"))
+            .unwrap_or_default(),
+        ProductField::MaxStock => product
+            .max_stock()
+            .map(|q| format_qty(q, "EA"))
+            .unwrap_or_default(),
+        ProductField::Related => product.related().join(", "),
+        ProductField::Attributes => product
+            .attributes()
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v))
+            .collect::<Vec<String>>()
+            .join(", "),
+    }
+}
+
+impl Template {
+    /// Parse a template from TOML
+    ///
+    /// # Errors
+    /// A [`toml::de::Error`] if the TOML is malformed or does not describe a template
+    pub fn from_toml(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(s)
+    }
+
+    /// Write this template as TOML
+    ///
+    /// # Errors
+    /// A [`toml::ser::Error`] if the template cannot be represented in TOML
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string(self)
+    }
+
+    /// Render a label for `product` as ZPL for a Zebra printer
+    ///
+    /// # Arguments
+    /// * `product` - The product the label is for
+    /// * `dpi` - The resolution of the printer in dots per inch, usually 203 or 300
+    pub fn render_zpl(&self, product: &AbcProduct, dpi: u32) -> String {
+        let dots = |mm: f64| (mm / 25.4 * dpi as f64).round() as i64;
+        let mut zpl = format!(
+            "^XA\n^PW{}\n^LL{}\n",
+            dots(self.width_mm),
+            dots(self.height_mm)
+        );
+        for element in &self.elements {
+            let (x, y) = (dots(element.x_mm), dots(element.y_mm));
+            match &element.kind {
+                ElementKind::Text { binding, size_pt } => {
+                    let height = (size_pt / 72.0 * dpi as f64).round() as i64;
+                    // ^ and ~ start ZPL commands, so they cannot appear in field data
+                    let text = bound_text(binding, product).replace(['^', '~'], " ");
+                    zpl.push_str(&format!(
+                        "^FO{},{}^A0N,{},{}^FD{}^FS\n",
+                        x, y, height, height, text
+                    ));
+                }
+                ElementKind::Barcode {
+                    height_mm,
+                    module_mm,
+                } => {
+                    if let Some(upc) = product.upcs().first() {
+                        // ^BE takes the first 12 digits and calculates the check digit itself
+                        let digits: String = upc.to_string().chars().take(12).collect();
+                        zpl.push_str(&format!(
+                            "^FO{},{}^BY{}^BEN,{},Y,N^FD{}^FS\n",
+                            x,
+                            y,
+                            dots(*module_mm).max(1),
+                            dots(*height_mm),
+                            digits
+                        ));
+                    }
+                }
+            }
+        }
+        zpl.push_str("^XZ\n");
+        zpl
+    }
+
+    /// Render one label per product as pages of a PDF, sized to the label
+    ///
+    /// # Returns
+    /// The bytes of the PDF file
+    pub fn render_pdf(&self, products: &[&AbcProduct]) -> Vec<u8> {
+        let width = self.width_mm * pdf::PT_PER_MM;
+        let height = self.height_mm * pdf::PT_PER_MM;
+        let mut doc = pdf::PdfDocument::new();
+        for product in products {
+            let mut content = String::new();
+            for element in &self.elements {
+                let x = element.x_mm * pdf::PT_PER_MM;
+                let top = height - element.y_mm * pdf::PT_PER_MM;
+                match &element.kind {
+                    ElementKind::Text { binding, size_pt } => {
+                        content.push_str(&pdf::text(
+                            x,
+                            top - size_pt,
+                            *size_pt,
+                            &bound_text(binding, product),
+                        ));
+                    }
+                    ElementKind::Barcode {
+                        height_mm,
+                        module_mm,
+                    } => {
+                        let Some(modules) = product.upcs().first().and_then(barcode_modules) else {
+                            continue;
+                        };
+                        let module = module_mm * pdf::PT_PER_MM;
+                        let bar_height = height_mm * pdf::PT_PER_MM;
+                        for (i, m) in modules.chars().enumerate() {
+                            if m == '1' {
+                                content.push_str(&pdf::rect(
+                                    x + i as f64 * module,
+                                    top - bar_height,
+                                    module,
+                                    bar_height,
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+            doc.add_page(width, height, content);
+        }
+        doc.to_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_template() {
+        let template = Template {
+            name: "shelf".to_string(),
+            width_mm: 50.0,
+            height_mm: 25.0,
+            elements: vec![
+                Element {
+                    x_mm: 2.0,
+                    y_mm: 2.0,
+                    kind: ElementKind::Text {
+                        binding: Binding::Field(ProductField::List),
+                        size_pt: 14.0,
+                    },
+                },
+                Element {
+                    x_mm: 2.0,
+                    y_mm: 10.0,
+                    kind: ElementKind::Barcode {
+                        height_mm: 10.0,
+                        module_mm: 0.33,
+                    },
+                },
+            ],
+        };
+        assert_eq!(
+            Template::from_toml(&template.to_toml().unwrap()).unwrap(),
+            template
+        );
+
+        let products = sample_export();
+        let zpl = template.render_zpl(&products["123456"], 203);
+        assert!(zpl.contains("^FD$5.99^FS"));
+        assert!(zpl.contains("^FD008587550001^FS"));
+
+        let pdf = template.render_pdf(&[&products["123456"], &products["ABC123"]]);
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(String::from_utf8_lossy(&pdf).contains("/Count 2"));
+    }
+
+    #[test]
+    fn test_compliance_bindings() {
+        let product = crate::AbcProductBuilder::from(sample_export()["123456"].clone())
+            .with_net_contents("12 oz".parse().unwrap())
+            .with_country_of_origin("USA")
+            .build()
+            .unwrap();
+        let text = |binding: Binding| bound_text(&binding, &product);
+        assert_eq!(text(Binding::Field(ProductField::NetContents)), "NET 12 OZ");
+        assert_eq!(text(Binding::UnitPrice), "$0.50 / OZ");
+        assert_eq!(
+            text(Binding::Field(ProductField::CountryOfOrigin)),
+            "Made in USA"
+        );
+        assert_eq!(
+            bound_text(&Binding::UnitPrice, &sample_export()["123456"]),
+            ""
+        );
+
+        let template = Template::from_toml(
+            r#"
+            name = "unit price"
+            width_mm = 50.0
+            height_mm = 25.0
+
+            [[elements]]
+            x_mm = 2.0
+            y_mm = 2.0
+            type = "text"
+            binding = "unit_price"
+            size_pt = 8.0
+            "#,
+        )
+        .unwrap();
+        assert!(
+            template
+                .render_zpl(&product, 203)
+                .contains("^FD$0.50 / OZ^FS")
+        );
+    }
+}
@@ -1,9 +1,135 @@
-use std::{char, collections::HashMap};
+use std::{
+    char,
+    collections::{BTreeMap, HashMap, HashSet},
+};
 
 use chrono::NaiveDate;
 use ean13::Ean13;
 use rust_decimal::Decimal;
 
+use records::ColumnLayout;
+
+/// Patterns in sales and stock history, such as products that are bought together and stock-outs
+pub mod analytics;
+/// Combining partial products from several sources into one catalog
+pub mod assemble;
+/// Supplemental product attributes that ABC cannot store, loaded from sidecar files and checked
+/// against a schema
+pub mod attributes;
+/// 4-5-4 retail calendar periods for lining reports up with the merchandising calendar
+pub mod calendar;
+/// An owned catalog of products indexed by sku, alternate sku, UPC, and group, and a shared
+/// read-only view of one
+pub mod catalog;
+/// Separating stocked inventory from labor, gift cards, and fees
+pub mod classify;
+/// Finding every company in an ABC database export and parsing each one's catalog
+pub mod company;
+/// Transparent gzip and zstd compression of data files and exports
+#[cfg(feature = "compression")]
+pub mod compress;
+/// Customer accounts parsed from the customer files of an ABC database export
+pub mod customer;
+/// Field by field comparison of two exports of the same catalog
+pub mod diff;
+/// Joining point of sale lines with the catalog for sales analysis
+pub mod enrich;
+/// Writers that turn parsed products back into files
+pub mod export;
+/// Demand and lead time forecasting for safety stock and reorder points
+pub mod forecast;
+/// Consistent display formatting for skus and quantities
+pub mod format;
+mod input;
+/// Feeds and payloads for storefronts and other outside systems
+pub mod integrations;
+/// Sales history parsed from the invoice files of an ABC database export
+pub mod invoice;
+/// Label layouts and their ZPL and PDF renderers
+#[cfg(feature = "labels")]
+pub mod labels;
+/// Net contents and unit pricing for weights and measures labeling
+pub mod measure;
+/// Dry runs that record side effects instead of performing them
+pub mod mode;
+/// Streaming products to and from JSON lines files for data pipelines
+#[cfg(feature = "serde")]
+pub mod ndjson;
+/// Chat notification payloads for pipeline results
+pub mod notify;
+#[cfg(feature = "pdf")]
+mod pdf;
+/// Reviewable change plans and applying them to remote platforms
+pub mod plan;
+/// Purchase orders parsed from an ABC database export, and checking deliveries against them
+pub mod po;
+/// Currency aware prices that are always rounded to the cent
+pub mod price;
+/// Previewing the effect of price changes
+pub mod pricing;
+/// Reports on data that should be cleaned up inside ABC
+pub mod quality;
+/// Customer quotes with contract pricing, tax, and freight estimates
+pub mod quotes;
+/// Comparing remote platforms against the parsed catalog
+pub mod reconcile;
+/// Low level, row by row access to the ABC data files
+pub mod records;
+/// Links between products that go together
+pub mod relations;
+/// Parsing exports in time boxed steps that can be resumed after an interruption
+pub mod resume;
+/// Return merchandise authorizations and their credit memo import lines
+pub mod rma;
+/// Representative subsets of a catalog for staging and test feeds
+pub mod sample;
+/// Indexes for looking up products by partial input
+pub mod search;
+/// Signing exports so consumers can detect tampered or truncated copies
+#[cfg(feature = "signing")]
+pub mod signing;
+/// A static HTML catalog for in-store kiosks
+#[cfg(feature = "sitegen")]
+pub mod sitegen;
+/// Tracking customer orders for items the store does not stock
+pub mod special_orders;
+/// Caching parsed catalogs in a SQLite database so later runs can skip parsing the export
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+/// Deciding whether catalog changes are safe to push to other systems without review
+pub mod sync;
+/// Small sample exports for examples and tests
+pub mod testing;
+/// Paired import files for moving stock between companies
+pub mod transfers;
+/// Barcode lookup, GS1 prefix analysis of UPCs, and in-house barcode assignment
+pub mod upc;
+/// The redesigned API, with borrowing accessors and typed prices, quantities, and errors, usable
+/// alongside the current API while downstream crates migrate
+pub mod v2;
+/// What the stock on hand is worth at cost and at list price
+pub mod valuation;
+/// Suppliers parsed from the vendor file of an ABC database export
+pub mod vendor;
+/// Warranty lengths from outside of ABC, for products whose export does not carry one
+pub mod warranty;
+/// Reloading the catalog whenever ABC writes a new database export
+#[cfg(feature = "watch")]
+pub mod watch;
+
+/// Open an ABC data file for parsing. With the `compression` feature, gzip and zstd files are
+/// decompressed transparently
+#[cfg(feature = "compression")]
+fn open_data_file(path: &str) -> std::io::Result<Box<dyn std::io::Read>> {
+    compress::open(std::path::Path::new(path))
+}
+
+/// Open an ABC data file for parsing
+#[cfg(not(feature = "compression"))]
+fn open_data_file(path: &str) -> std::io::Result<std::fs::File> {
+    std::fs::File::open(path)
+}
+
 /// Attempt to convert a string into a [`Decimal`] by stripping out any characters that are not
 /// digits or the decimal point. Used primarily to parse pricing from the csv ABC database export
 ///
@@ -24,11 +150,208 @@ fn price_from_str(price_str: &str) -> Result<Decimal, rust_decimal::Error> {
     price_str.parse()
 }
 
+/// Attempt to convert the digits of a UPC from the ABC database export into an [`Ean13`],
+/// correcting the check digit if necessary
+///
+/// # Arguments
+/// * `s` - The digits of a single UPC
+///
+/// # Returns
+/// The corrected [`Ean13`], or [`None`] if `s` is too short to be a usable UPC or cannot be
+/// parsed at all
+pub(crate) fn upc_from_str(s: &str) -> Option<Ean13> {
+    if s.len() == 11 {
+        // Some ABC UPCs leave out the check digit, so make one up and let [`Ean13::from_str_nonstrict`] fix it
+        Ean13::from_str_nonstrict(&format!("{}0", s)).ok()
+    } else if s.len() < 11 {
+        // Anything less than 11 characters long is probably a dead upc
+        None
+    } else {
+        // Anything 12 characters and up has a chance of being a good upc
+        Ean13::from_str_nonstrict(s).ok()
+    }
+}
+
+/// Lowercase `s` and replace every run of characters that are not ASCII letters or digits with a
+/// single dash, trimming dashes from either end. Used to build URL slugs
+fn slugify(s: &str) -> String {
+    let mut slug = String::new();
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// A discount group of ABC, which is always a single letter from A to Z.
+///
+/// # Example
+/// ```rust
+/// use abc_product::AbcGroup;
+///
+/// let group: AbcGroup = " b".parse().unwrap();
+/// assert_eq!(group, AbcGroup::new('B').unwrap());
+/// assert_eq!(group.to_string(), "B");
+/// assert!("AB".parse::<AbcGroup>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct AbcGroup(char);
+
+impl AbcGroup {
+    /// The group `letter`, in either case
+    ///
+    /// # Returns
+    /// [`None`] if `letter` is not an ASCII letter
+    pub fn new(letter: char) -> Option<Self> {
+        letter
+            .is_ascii_alphabetic()
+            .then(|| AbcGroup(letter.to_ascii_uppercase()))
+    }
+
+    /// The uppercase letter of the group
+    pub fn letter(&self) -> char {
+        self.0
+    }
+}
+
+/// The error returned when text cannot be read as an [`AbcGroup`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbcGroupParseError(pub String);
+
+impl std::fmt::Display for AbcGroupParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` is not a single letter from A to Z", self.0)
+    }
+}
+
+impl std::error::Error for AbcGroupParseError {}
+
+impl std::str::FromStr for AbcGroup {
+    type Err = AbcGroupParseError;
+
+    /// Read a single letter, ignoring surrounding whitespace and case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
+        match (chars.next(), chars.next()) {
+            (Some(letter), None) => AbcGroup::new(letter),
+            _ => None,
+        }
+        .ok_or(AbcGroupParseError(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for AbcGroup {
+    type Error = AbcGroupParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<AbcGroup> for String {
+    fn from(value: AbcGroup) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for AbcGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A product's sku, trimmed and uppercased so that the same sku always compares equal no matter
+/// which file or system it was read from. ABC pads some skus with trailing spaces in
+/// `item.data` but not in `item_posted.data`, and storefronts often lowercase them.
+///
+/// Maps keyed by [`Sku`] can be looked up with a `&str`, which must already be normalized. Pass
+/// text through [`Sku::new`] first when it may not be.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{Sku, testing};
+///
+/// let sku = Sku::new(" abc123  ");
+/// assert_eq!(sku.as_str(), "ABC123");
+/// let products = testing::sample_export();
+/// assert!(products.contains_key(&sku));
+/// assert!(products.contains_key("ABC123"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "String", into = "String")
+)]
+pub struct Sku(String);
+
+impl Sku {
+    /// Normalize `raw` into a [`Sku`]
+    pub fn new(raw: &str) -> Self {
+        Sku(raw.trim().to_uppercase())
+    }
+
+    /// The normalized sku
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Sku {
+    fn from(value: &str) -> Self {
+        Sku::new(value)
+    }
+}
+
+impl From<String> for Sku {
+    fn from(value: String) -> Self {
+        Sku::new(&value)
+    }
+}
+
+impl From<Sku> for String {
+    fn from(value: Sku) -> Self {
+        value.0
+    }
+}
+
+impl std::borrow::Borrow<str> for Sku {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Sku {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Sku {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl std::fmt::Display for Sku {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 /// Represents a product or inventory item in ABC accounting software.
 ///
 /// # Example
 /// ```rust
-/// use abc_product::{AbcProduct, AbcProductsBySku, AbcParseError};
+/// use abc_product::{AbcProduct, AbcProductsBySku, testing};
 /// use rust_decimal::Decimal;
 ///
 /// // Manually creating an [`AbcProduct`]
@@ -41,59 +364,570 @@ fn price_from_str(price_str: &str) -> Result<Decimal, rust_decimal::Error> {
 ///     .build()
 ///     .unwrap();
 ///
-/// // Creating a map of skus to their products
-/// let products_by_sku: Result<AbcProductsBySku, AbcParseError> = AbcProduct::from_db_export("./item.data", "./item_posted.data");
+/// // Creating a map of skus to their products. Real exports are parsed with
+/// // `AbcProduct::from_db_export("item.data", "item_posted.data")`
+/// let products_by_sku: AbcProductsBySku = testing::sample_export();
+/// assert_eq!(products_by_sku.len(), 2);
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AbcProduct {
-    sku: String,
+    sku: Sku,
     desc: String,
+    #[cfg_attr(feature = "serde", serde(with = "upc_strings"))]
     upcs: Vec<Ean13>,
+    raw_upcs: Vec<String>,
     list: Decimal,
     cost: Decimal,
+    avg_cost: Option<Decimal>,
     stock: f64,
-    group: Option<String>,
+    group: Option<AbcGroup>,
     weight: Option<f64>,
     last_sold: Option<chrono::NaiveDate>,
-    alt_skus: Vec<String>,
+    alt_skus: Vec<Sku>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    core_sku: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    warranty_months: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    net_contents: Option<measure::NetContents>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    country_of_origin: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    min_stock: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_stock: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    related: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    attributes: BTreeMap<String, String>,
 }
 
 /// Used to safely construct an [`AbcProduct`]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AbcProductBuilder {
-    sku: Option<String>,
+    sku: Option<Sku>,
     desc: Option<String>,
+    #[cfg_attr(feature = "serde", serde(with = "upc_strings"))]
     upcs: Vec<Ean13>,
+    raw_upcs: Vec<String>,
     list: Option<Decimal>,
     cost: Option<Decimal>,
+    avg_cost: Option<Decimal>,
     stock: Option<f64>,
     weight: Option<f64>,
-    group: Option<String>,
+    group: Option<AbcGroup>,
     last_sold: Option<chrono::NaiveDate>,
-    alt_skus: Vec<String>,
+    alt_skus: Vec<Sku>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    core_sku: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    warranty_months: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    net_contents: Option<measure::NetContents>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    country_of_origin: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    min_stock: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_stock: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    related: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    attributes: BTreeMap<String, String>,
+}
+
+/// Serializes UPCs as their 13 digit strings, so that serialized products do not depend on how
+/// [`Ean13`] represents itself
+#[cfg(feature = "serde")]
+mod upc_strings {
+    use ean13::Ean13;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(upcs: &[Ean13], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(upcs.iter().map(|u| u.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Ean13>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| {
+                Ean13::from_str_nonstrict(s)
+                    .map_err(|_| D::Error::custom(format!("`{}` is not a valid UPC", s)))
+            })
+            .collect()
+    }
 }
 
 /// A map where the key is a product's sku, and the value is the referenced [`AbcProduct`]
-pub type AbcProductsBySku = HashMap<String, AbcProduct>;
+pub type AbcProductsBySku = HashMap<Sku, AbcProduct>;
+
+/// Names each piece of data stored on an [`AbcProduct`], for code that works with products one
+/// field at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ProductField {
+    Sku,
+    Desc,
+    Upcs,
+    RawUpcs,
+    List,
+    Cost,
+    AvgCost,
+    Stock,
+    Group,
+    Weight,
+    LastSold,
+    AltSkus,
+    CoreSku,
+    WarrantyMonths,
+    NetContents,
+    CountryOfOrigin,
+    MinStock,
+    MaxStock,
+    Related,
+    Attributes,
+}
+
+impl ProductField {
+    /// Every [`ProductField`] in the order they are declared on [`AbcProduct`]
+    pub const ALL: [ProductField; 20] = [
+        ProductField::Sku,
+        ProductField::Desc,
+        ProductField::Upcs,
+        ProductField::RawUpcs,
+        ProductField::List,
+        ProductField::Cost,
+        ProductField::AvgCost,
+        ProductField::Stock,
+        ProductField::Group,
+        ProductField::Weight,
+        ProductField::LastSold,
+        ProductField::AltSkus,
+        ProductField::CoreSku,
+        ProductField::WarrantyMonths,
+        ProductField::NetContents,
+        ProductField::CountryOfOrigin,
+        ProductField::MinStock,
+        ProductField::MaxStock,
+        ProductField::Related,
+        ProductField::Attributes,
+    ];
+
+    /// The snake case name of this field, matching the name of its accessor on [`AbcProduct`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sku => "sku",
+            Self::Desc => "desc",
+            Self::Upcs => "upcs",
+            Self::RawUpcs => "raw_upcs",
+            Self::List => "list",
+            Self::Cost => "cost",
+            Self::AvgCost => "avg_cost",
+            Self::Stock => "stock",
+            Self::Group => "group",
+            Self::Weight => "weight",
+            Self::LastSold => "last_sold",
+            Self::AltSkus => "alt_skus",
+            Self::CoreSku => "core_sku",
+            Self::WarrantyMonths => "warranty_months",
+            Self::NetContents => "net_contents",
+            Self::CountryOfOrigin => "country_of_origin",
+            Self::MinStock => "min_stock",
+            Self::MaxStock => "max_stock",
+            Self::Related => "related",
+            Self::Attributes => "attributes",
+        }
+    }
+}
+
+/// Which cost figure to use when valuing a product
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CostBasis {
+    /// The weighted average cost that ABC maintains as stock is received
+    Average,
+    /// The cost of the most recent receipt. This is the `cost` column of `item.data`
+    Last,
+}
 
 #[derive(Debug)]
 pub enum AbcParseError {
-    /// An error caused by the csv parser.
-    CsvError(csv::Error),
-    /// A field required by [`AbcProduct`] is missing from the csv file. Value 0 is the name of the
-    /// field that is missing. Value 1 is the row of the file that failed
-    MissingField(String, usize),
+    /// A file could not be read or split into rows
+    CsvError(Box<CsvError>),
+    /// A required field is missing from a row
+    MissingField(Box<MissingField>),
     /// Attempted to combine data from the `item.data` and `item_posted.data` file under one
     /// [`AbcProduct`], but skus do not match
     MisMatchedSkus,
-    /// Covers any additional errors that arise while parsing. Value 0 should be used to provide
-    /// context to the error such as the row that the error occurred on
-    Custom(String),
+    /// A field is present but its value cannot be parsed
+    InvalidValue(Box<InvalidValue>),
+    /// A row of one file refers to a record that is not in the file it is joined with
+    MissingRecord {
+        /// The file that should have the record, such as `item_posted.data`
+        file: String,
+        /// The sku, account, or number that was looked up
+        key: String,
+        /// The row of the other file that refers to the record, counting from one
+        row: usize,
+    },
+    /// Two files that are read side by side have a different number of rows
+    MismatchedRowCounts {
+        /// One of the files, such as `item.data`
+        first: String,
+        /// The other file, such as `item_posted.data`
+        second: String,
+    },
+    /// None of the known column layouts match the export. Value 0 is the path of the file, if
+    /// it was read from disk
+    UnknownLayout(Option<String>),
+    /// The file at the path in value 0 has changed since a checkpoint of it was taken
+    FileChanged(String),
+    /// A checkpoint could not be read. Value 0 describes what is wrong with it
+    InvalidCheckpoint(String),
+    /// A line of a JSON lines file is not a product. Value 0 is the line, counting from one
+    InvalidJson(usize, serde_json::Error),
+}
+
+/// A value that could not be parsed, and where in the export it was found
+#[derive(Debug)]
+pub struct InvalidValue {
+    /// The name of the export file, such as `item.data`. [`None`] for files that are not part
+    /// of an ABC export, such as a warranty table
+    pub file: Option<String>,
+    /// The row of the file, counting from one
+    pub row: usize,
+    /// The index of the column, counting from zero
+    pub column: usize,
+    /// The name of the field
+    pub field: String,
+    /// The value exactly as it appears in the file
+    pub raw: String,
+    /// What kind of value was expected and why it could not be parsed
+    pub source: ValueError,
+}
+
+impl std::fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cannot parse {} `{}` in row {}, column {}",
+            self.field, self.raw, self.row, self.column
+        )?;
+        if let Some(file) = &self.file {
+            write!(f, " of {}", file)?;
+        }
+        write!(f, ": {}", self.source)
+    }
+}
+
+impl std::error::Error for InvalidValue {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// A required field that is missing, and where in the export it should have been
+#[derive(Debug)]
+pub struct MissingField {
+    /// The name of the export file, such as `item.data`. [`None`] for files that are not part
+    /// of an ABC export, and for products built with an [`AbcProductBuilder`]
+    pub file: Option<String>,
+    /// The row of the file, counting from one. 0 for products built with an
+    /// [`AbcProductBuilder`]
+    pub row: usize,
+    /// The index of the column the field should be in, counting from zero. [`None`] for
+    /// products built with an [`AbcProductBuilder`]
+    pub column: Option<usize>,
+    /// The name of the field
+    pub field: String,
+}
+
+impl std::fmt::Display for MissingField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Missing field `{}` in row {}", self.field, self.row)?;
+        if let Some(column) = self.column {
+            write!(f, ", column {}", column)?;
+        }
+        match &self.file {
+            Some(file) => write!(f, " of {}", file),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An error reading or splitting a file into rows, and where in the file it happened
+#[derive(Debug)]
+pub struct CsvError {
+    /// The name of the export file, such as `item.data`. [`None`] if the file is not known,
+    /// such as for a file that could not be opened
+    pub file: Option<String>,
+    /// The row of the file, counting from one. [`None`] if the error is not in a row, such as
+    /// for a file that could not be opened
+    pub row: Option<usize>,
+    /// The index of the column, counting from zero. [`None`] unless a single column could not
+    /// be read, such as text that is not valid UTF-8
+    pub column: Option<usize>,
+    /// The error raised by the csv parser
+    pub source: csv::Error,
+}
+
+impl std::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "Cannot read {}: ", file)?;
+        }
+        write!(f, "{}", self.source)
+    }
+}
+
+impl std::error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Why the value of a field could not be parsed, as carried by [`InvalidValue`]
+#[derive(Debug)]
+pub enum ValueError {
+    /// The value is not a price or dollar amount
+    Price(rust_decimal::Error),
+    /// The value is not a number
+    Number(std::num::ParseFloatError),
+    /// The value is not a whole number
+    Integer(std::num::ParseIntError),
+    /// The value is not a date
+    Date(chrono::ParseError),
+    /// The value is not one of the forms the field accepts
+    Unrecognized,
+}
+
+impl std::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Price(_) => write!(f, "not a price"),
+            Self::Number(_) => write!(f, "not a number"),
+            Self::Integer(_) => write!(f, "not a whole number"),
+            Self::Date(_) => write!(f, "not a date"),
+            Self::Unrecognized => write!(f, "not a recognized value"),
+        }
+    }
+}
+
+impl std::error::Error for ValueError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Price(e) => Some(e),
+            Self::Number(e) => Some(e),
+            Self::Integer(e) => Some(e),
+            Self::Date(e) => Some(e),
+            Self::Unrecognized => None,
+        }
+    }
+}
+
+impl From<rust_decimal::Error> for ValueError {
+    fn from(value: rust_decimal::Error) -> Self {
+        Self::Price(value)
+    }
+}
+
+impl From<std::num::ParseFloatError> for ValueError {
+    fn from(value: std::num::ParseFloatError) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<std::num::ParseIntError> for ValueError {
+    fn from(value: std::num::ParseIntError) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<chrono::ParseError> for ValueError {
+    fn from(value: chrono::ParseError) -> Self {
+        Self::Date(value)
+    }
+}
+
+/// A data quality problem found while parsing an export that did not stop the parse
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum ParseWarning {
+    /// A UPC listed for the sku was too short or otherwise not a valid UPC, and was left out of
+    /// [`AbcProduct::upcs`]. It is still available from [`AbcProduct::raw_upcs`]
+    DroppedUpc { sku: String, raw: String },
+    /// Non-digit characters were removed from the UPC column of the sku
+    TrimmedUpcs { sku: String, raw: String },
+    /// A text field of the sku has leading or trailing whitespace
+    UntrimmedField { sku: String, field: ProductField },
+    /// The weight of the sku is not a number, so it was treated as unknown
+    UnparsedWeight { sku: String, raw: String },
+    /// The last sold date of the sku is not a valid date, so it was treated as never sold
+    UnparsedLastSold { sku: String, raw: String },
+    /// The discount group of the sku is not a single letter, so it was treated as having no
+    /// group
+    UnparsedGroup { sku: String, raw: String },
+    /// The sku only appears in the named file, so it was left out
+    OrphanSku { sku: String, file: String },
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DroppedUpc { sku, raw } => {
+                write!(f, "Dropped invalid UPC `{}` from sku `{}`", raw, sku)
+            }
+            Self::TrimmedUpcs { sku, raw } => {
+                write!(
+                    f,
+                    "Removed stray characters from UPCs `{}` of sku `{}`",
+                    raw, sku
+                )
+            }
+            Self::UntrimmedField { sku, field } => write!(
+                f,
+                "The {} of sku `{}` has surrounding whitespace",
+                field.name(),
+                sku
+            ),
+            Self::UnparsedWeight { sku, raw } => {
+                write!(f, "Ignored unreadable weight `{}` of sku `{}`", raw, sku)
+            }
+            Self::UnparsedLastSold { sku, raw } => {
+                write!(
+                    f,
+                    "Ignored unreadable last sold date `{}` of sku `{}`",
+                    raw, sku
+                )
+            }
+            Self::UnparsedGroup { sku, raw } => {
+                write!(f, "Ignored unreadable group `{}` of sku `{}`", raw, sku)
+            }
+            Self::OrphanSku { sku, file } => {
+                write!(f, "Sku `{}` only appears in {}", sku, file)
+            }
+        }
+    }
+}
+
+/// Every [`ParseWarning`] raised while parsing an export, in the order they were found
+pub type ParseWarnings = Vec<ParseWarning>;
+
+/// Rows that could not be parsed, as their row number in the file, counting from one, alongside
+/// what was wrong with them. Rows of `item.data` come first, followed by rows of
+/// `item_posted.data`.
+///
+/// Row 0 marks a product whose rows both parsed but could not be combined into one
+/// [`AbcProduct`]. Those errors come last, since they belong to neither file alone
+pub type RowErrors = Vec<(usize, AbcParseError)>;
+
+/// What to do when a row of an export cannot be parsed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop and return the error, failing the whole import
+    #[default]
+    Fail,
+    /// Leave the row out and carry on
+    Skip,
+    /// Leave the row out, but keep the error in the returned [`RowErrors`]
+    Collect,
+}
+
+impl ErrorPolicy {
+    /// Deal with the error `e` raised by row `row` according to this policy. Errors reading the
+    /// file itself always fail, since no later row can be read either
+    fn handle(
+        self,
+        row: usize,
+        e: AbcParseError,
+        errors: &mut RowErrors,
+    ) -> Result<(), AbcParseError> {
+        if let AbcParseError::CsvError(csv_error) = &e
+            && matches!(csv_error.source.kind(), csv::ErrorKind::Io(_))
+        {
+            return Err(e);
+        }
+        match self {
+            Self::Fail => Err(e),
+            Self::Skip => Ok(()),
+            Self::Collect => {
+                errors.push((row, e));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The text encoding of an export. ABC writes Windows-1252, but files that have been through
+/// other tools are often UTF-8
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Read each line as UTF-8 if it is valid UTF-8, otherwise as Windows-1252
+    #[default]
+    Auto,
+    /// Only accept UTF-8. Rows that are not valid UTF-8 fail to parse
+    Utf8,
+    /// Read every line as Windows-1252
+    Windows1252,
+}
+
+/// Settings for [`AbcProduct::from_db_export_with_options`]
+///
+/// # Example
+/// ```rust
+/// use abc_product::{AbcProduct, ErrorPolicy, ParseOptions};
+///
+/// let options = ParseOptions::default().with_on_error(ErrorPolicy::Collect);
+/// let (products, bad_rows, _warnings) =
+///     AbcProduct::from_db_export_with_options("./item.data", "./item_posted.data", &options)
+///         .unwrap();
+/// assert!(bad_rows.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseOptions {
+    /// What to do with rows that cannot be parsed
+    pub on_error: ErrorPolicy,
+    /// Which column holds each field
+    pub layout: ColumnLayout,
+    /// The text encoding of both files
+    pub encoding: Encoding,
+}
+
+impl ParseOptions {
+    /// Set what to do with rows that cannot be parsed
+    pub fn with_on_error(self, on_error: ErrorPolicy) -> Self {
+        ParseOptions { on_error, ..self }
+    }
+
+    /// Set which column holds each field, for versions of ABC that export a different layout
+    pub fn with_layout(self, layout: ColumnLayout) -> Self {
+        ParseOptions { layout, ..self }
+    }
+
+    /// Set the text encoding of both files
+    pub fn with_encoding(self, encoding: Encoding) -> Self {
+        ParseOptions { encoding, ..self }
+    }
 }
 
 /// Just the fields that can be parsed from the `item_posted.data` file. Intended to be combined
 /// with [`IntermediateProduct`] to create a full [`AbcProduct`]
 struct IntermediatePostedProduct {
-    sku: String,
+    sku: Sku,
     stock: f64,
     last_sold: Option<chrono::NaiveDate>,
 }
@@ -101,14 +935,24 @@ struct IntermediatePostedProduct {
 /// Just the fields that can be parsed from the `item.data` file. Intended to be combined with
 /// [`IntermediatePostedProduct`] to create a full [`AbcProduct`]
 struct IntermediateBaseProduct {
-    sku: String,
+    /// The row of `item.data` the product was parsed from, counting from one
+    row: usize,
+    sku: Sku,
     desc: String,
     upcs: Vec<Ean13>,
+    raw_upcs: Vec<String>,
     list: Decimal,
     cost: Decimal,
-    group: Option<String>,
+    avg_cost: Option<Decimal>,
+    group: Option<AbcGroup>,
     weight: Option<f64>,
-    alt_skus: Vec<String>,
+    alt_skus: Vec<Sku>,
+    core_sku: Option<String>,
+    warranty_months: Option<u32>,
+    net_contents: Option<measure::NetContents>,
+    country_of_origin: Option<String>,
+    min_stock: Option<f64>,
+    max_stock: Option<f64>,
 }
 
 impl AbcProduct {
@@ -119,7 +963,7 @@ impl AbcProduct {
 
     /// Fetch this product's sku
     pub fn sku(&self) -> String {
-        self.sku.clone()
+        self.sku.to_string()
     }
 
     /// Fetch this product's description
@@ -132,6 +976,12 @@ impl AbcProduct {
         self.upcs.to_vec()
     }
 
+    /// Fetch the digits of each UPC as they appeared in the export, before check digits were
+    /// corrected and unusable codes were dropped to produce [`AbcProduct::upcs`]
+    pub fn raw_upcs(&self) -> Vec<String> {
+        self.raw_upcs.to_vec()
+    }
+
     /// Fetch this product's list price as a [`Decimal`]
     pub fn list(&self) -> Decimal {
         self.list
@@ -142,6 +992,36 @@ impl AbcProduct {
         self.cost
     }
 
+    /// This product's list price as an [`price::AbcPrice`], rounded to the cent
+    pub fn list_price(&self) -> price::AbcPrice {
+        price::AbcPrice::new(self.list)
+    }
+
+    /// This product's cost as an [`price::AbcPrice`], rounded to the cent
+    pub fn cost_price(&self) -> price::AbcPrice {
+        price::AbcPrice::new(self.cost)
+    }
+
+    /// Fetch this product's weighted average cost. [`None`] if the export did not provide one
+    pub fn avg_cost(&self) -> Option<Decimal> {
+        self.avg_cost
+    }
+
+    /// Fetch this product's cost on the given [`CostBasis`]
+    ///
+    /// # Arguments
+    /// * `basis` - Whether to use the average cost or the last cost
+    ///
+    /// # Returns
+    /// The cost on the requested basis. If [`CostBasis::Average`] is requested but no average
+    /// cost is known for this product, then the last cost is returned instead
+    pub fn cost_basis(&self, basis: CostBasis) -> Decimal {
+        match basis {
+            CostBasis::Average => self.avg_cost.unwrap_or(self.cost),
+            CostBasis::Last => self.cost,
+        }
+    }
+
     /// Fetch this product's current inventory level or stock
     pub fn stock(&self) -> f64 {
         self.stock
@@ -152,10 +1032,16 @@ impl AbcProduct {
         self.weight
     }
 
-    /// What product group does this product belong to? Should be a single character from A-Z or
-    /// [`None`]
-    pub fn group(&self) -> Option<String> {
-        self.group.to_owned()
+    /// The discount group this product belongs to, or [`None`] if it has no group
+    pub fn group(&self) -> Option<AbcGroup> {
+        self.group
+    }
+
+    /// The discount group this product belongs to as text, the way [`AbcProduct::group`] used to
+    /// return it
+    #[deprecated(note = "use `group`, which returns a typed `AbcGroup`")]
+    pub fn group_str(&self) -> Option<String> {
+        self.group.map(|g| g.to_string())
     }
 
     /// The date that this product was last sold. [`None`] if the product has not been sold
@@ -165,7 +1051,217 @@ impl AbcProduct {
 
     /// The list of alternative skus for this product
     pub fn alt_skus(&self) -> Vec<String> {
-        self.alt_skus.to_owned()
+        self.alt_skus.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// The sku of the core charge item that must be sold alongside this product, such as the
+    /// refundable deposit on a battery or alternator. [`None`] if the product has no core
+    pub fn core_sku(&self) -> Option<String> {
+        self.core_sku.to_owned()
+    }
+
+    /// How many months the product is under warranty from the day it is sold. [`None`] if the
+    /// warranty is not known
+    pub fn warranty_months(&self) -> Option<u32> {
+        self.warranty_months
+    }
+
+    /// The last day a unit sold on `sale_date` is under warranty
+    ///
+    /// # Returns
+    /// [`None`] if the product's warranty is not known
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{AbcProductBuilder, testing};
+    /// use chrono::NaiveDate;
+    ///
+    /// let drill = AbcProductBuilder::from(testing::sample_export()["123456"].clone())
+    ///     .with_warranty_months(12)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     drill.warranty_expiry(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()),
+    ///     NaiveDate::from_ymd_opt(2025, 2, 28)
+    /// );
+    /// ```
+    pub fn warranty_expiry(&self, sale_date: NaiveDate) -> Option<NaiveDate> {
+        sale_date.checked_add_months(chrono::Months::new(self.warranty_months?))
+    }
+
+    /// The declared net quantity of the package, such as `12 OZ`. [`None`] if it is not known
+    pub fn net_contents(&self) -> Option<measure::NetContents> {
+        self.net_contents.to_owned()
+    }
+
+    /// The list price per unit of [`AbcProduct::net_contents`], for unit price labels
+    pub fn unit_price(&self) -> Option<Decimal> {
+        self.net_contents.as_ref()?.unit_price(self.list)
+    }
+
+    /// The country the product was made in. [`None`] if it is not known
+    pub fn country_of_origin(&self) -> Option<String> {
+        self.country_of_origin.to_owned()
+    }
+
+    /// The stock level set in ABC at which the product should be reordered. [`None`] if it is
+    /// not known
+    pub fn min_stock(&self) -> Option<f64> {
+        self.min_stock
+    }
+
+    /// The stock level set in ABC that reorders should bring the product back up to. [`None`]
+    /// if it is not known
+    pub fn max_stock(&self) -> Option<f64> {
+        self.max_stock
+    }
+
+    /// The list price of this product's core charge, looked up in `products`. [`None`] if the
+    /// product has no core or its core sku is not in `products`
+    pub fn core_charge(&self, products: &AbcProductsBySku) -> Option<Decimal> {
+        self.core_sku
+            .as_ref()
+            .and_then(|c| products.get(&Sku::new(c)))
+            .map(|c| c.list())
+    }
+
+    /// The list price plus any core charge, which is what a customer pays at the counter. The
+    /// core is refunded when the old part is returned, so margins should still use
+    /// [`AbcProduct::list`]
+    pub fn list_with_core(&self, products: &AbcProductsBySku) -> Decimal {
+        self.list + self.core_charge(products).unwrap_or_default()
+    }
+
+    /// The gross margin in dollars: the list price less the cost
+    ///
+    /// # Returns
+    /// [`None`] if the cost is zero, which means the cost is not known, such as when it was
+    /// hidden by an [`export::Redaction`]
+    pub fn margin(&self) -> Option<Decimal> {
+        (!self.cost.is_zero()).then(|| self.list - self.cost)
+    }
+
+    /// The gross margin as a percent of the list price, rounded to two decimal places, such as
+    /// `35.59` for a 35.59% margin
+    ///
+    /// # Returns
+    /// [`None`] if the list price is zero, or the cost is zero and so not known. See
+    /// [`AbcProduct::margin`]
+    pub fn margin_percent(&self) -> Option<Decimal> {
+        if self.cost.is_zero() {
+            return None;
+        }
+        self.list_price().margin_over(self.cost_price())
+    }
+
+    /// The markup as a percent of cost, rounded to two decimal places, such as `55.26` for a
+    /// list price 55.26% over cost
+    ///
+    /// # Returns
+    /// [`None`] if the cost is zero
+    pub fn markup_percent(&self) -> Option<Decimal> {
+        self.list_price().markup_over(self.cost_price())
+    }
+
+    /// The skus in `products` that are the core charge of some other product. Cores are
+    /// deposits rather than merchandise, so storefronts and sales reports usually leave them out
+    pub fn core_skus(products: &AbcProductsBySku) -> HashSet<String> {
+        products
+            .values()
+            .filter_map(|p| p.core_sku())
+            .filter(|c| products.contains_key(&Sku::new(c)))
+            .collect()
+    }
+
+    /// The skus of products that go with this one, such as accessories or parts that are often
+    /// bought together. Empty unless filled in with [`relations::Relations::apply`]
+    pub fn related(&self) -> Vec<String> {
+        self.related.to_owned()
+    }
+
+    /// The value of the supplemental attribute `key`, such as `attr("finish")`. [`None`] unless
+    /// filled in with [`attributes::Attributes::apply`]
+    pub fn attr(&self, key: &str) -> Option<String> {
+        self.attributes.get(key).cloned()
+    }
+
+    /// Every supplemental attribute of this product, keyed by name
+    pub fn attributes(&self) -> BTreeMap<String, String> {
+        self.attributes.to_owned()
+    }
+
+    /// Write `products` as csv with a header row, such as for buyers to open in a spreadsheet
+    ///
+    /// # Arguments
+    /// * `writer` - Where to write the csv
+    /// * `products` - The products to write, one row each in the order given
+    /// * `options` - The columns to write and how to format prices. See [`export::CsvOptions`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{AbcProduct, ProductField, export::CsvOptions, testing};
+    ///
+    /// let products = testing::sample_export();
+    /// let options = CsvOptions::default().with_columns(&[ProductField::Sku, ProductField::List]);
+    /// let mut out = Vec::new();
+    /// AbcProduct::to_csv(&mut out, &[products["123456"].clone()], &options).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "sku,list\n123456,5.99\n");
+    /// ```
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised by `writer`
+    pub fn to_csv(
+        writer: impl std::io::Write,
+        products: &[AbcProduct],
+        options: &export::CsvOptions,
+    ) -> std::io::Result<()> {
+        export::write_csv(writer, products, options)
+    }
+
+    /// A URL slug for this product made from its description followed by its sku, such as
+    /// `product-a-123456`. Long descriptions are cut down to at most 60 characters at a word
+    /// boundary. Because the sku is always included, two products can only share a slug when
+    /// their skus differ just in case or punctuation. Use [`AbcProduct::duplicate_slugs`] to
+    /// check a whole catalog
+    pub fn slug(&self) -> String {
+        let mut desc = slugify(&self.desc);
+        if desc.len() > 60 {
+            desc.truncate(60);
+            if let Some(i) = desc.rfind('-') {
+                desc.truncate(i);
+            }
+        }
+        let sku = slugify(self.sku.as_str());
+        if desc.is_empty() {
+            sku
+        } else {
+            format!("{}-{}", desc, sku)
+        }
+    }
+
+    /// Find every slug in `products` that is shared by more than one sku
+    ///
+    /// # Returns
+    /// Each duplicated slug alongside the skus that share it. Both the slugs and the skus are
+    /// sorted. Empty if every slug is unique
+    pub fn duplicate_slugs(products: &AbcProductsBySku) -> Vec<(String, Vec<String>)> {
+        let mut by_slug: HashMap<String, Vec<String>> = HashMap::new();
+        for product in products.values() {
+            by_slug
+                .entry(product.slug())
+                .or_default()
+                .push(product.sku());
+        }
+        let mut dups: Vec<(String, Vec<String>)> = by_slug
+            .into_iter()
+            .filter(|(_, skus)| skus.len() > 1)
+            .map(|(slug, mut skus)| {
+                skus.sort();
+                (slug, skus)
+            })
+            .collect();
+        dups.sort();
+        dups
     }
 
     /// Create a map of skus to [`AbcProduct`]s by parsing ABC database export files.
@@ -193,27 +1289,337 @@ impl AbcProduct {
         item_path: &str,
         item_posted_path: &str,
     ) -> Result<AbcProductsBySku, AbcParseError> {
-        let base_products = IntermediateBaseProduct::parse_item_data(item_path)?;
-        let posted_products = IntermediatePostedProduct::parse_item_posted_data(item_posted_path)?;
-        if base_products.len() != posted_products.len() {
-            return Err(AbcParseError::Custom(
-                "The item_posted.data and item.data files have a different nember of items"
-                    .to_string(),
-            ));
+        let item_file = open_data_file(item_path).map_err(csv::Error::from)?;
+        let item_posted_file = open_data_file(item_posted_path).map_err(csv::Error::from)?;
+        Self::from_readers(item_file, item_posted_file)
+    }
+
+    /// Create a map of skus to [`AbcProduct`]s by parsing the contents of an ABC database export
+    /// from any readers rather than from paths on disk, such as an export downloaded from remote
+    /// storage or held in memory. Unlike the path based functions, compressed data is not
+    /// decompressed, so wrap the reader in a decoder first if needed
+    ///
+    /// # Arguments
+    /// * `item` - The contents of the `item.data` file
+    /// * `item_posted` - The contents of the `item_posted.data` file
+    ///
+    /// # Returns
+    /// A [`HashMap`] of ABC SKUs to the [`AbcProduct`] they belong to
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{AbcProduct, testing};
+    ///
+    /// let products = AbcProduct::from_readers(
+    ///     testing::SAMPLE_ITEM_DATA,
+    ///     testing::SAMPLE_ITEM_POSTED_DATA,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(products.len(), 2);
+    /// ```
+    ///
+    /// # Errors
+    /// See [`AbcProduct::from_db_export`]
+    pub fn from_readers(
+        item: impl std::io::Read,
+        item_posted: impl std::io::Read,
+    ) -> Result<AbcProductsBySku, AbcParseError> {
+        Self::from_readers_with_options(item, item_posted, &ParseOptions::default())
+            .map(|(products, _, _)| products)
+    }
+
+    /// Like [`AbcProduct::from_db_export`], but rows that cannot be parsed are handled as set by
+    /// `options` rather than always failing the whole import.
+    ///
+    /// When rows are skipped or collected, a product whose row in one file could not be parsed
+    /// is also missing from the other file, so products found in only one of the files are left
+    /// out and reported as [`ParseWarning::OrphanSku`] instead of failing the parse.
+    ///
+    /// # Arguments
+    /// * `item_path` - The path to the item.data file generated by the db export
+    /// * `item_posted_path` - The path to the item_posted.data file generated by the db export
+    /// * `options` - How to parse the files. See [`ParseOptions`]
+    ///
+    /// # Returns
+    /// The products that were parsed, the rows that could not be when [`ErrorPolicy::Collect`]
+    /// is used, and every warning raised while parsing. See
+    /// [`AbcProduct::from_db_export_with_warnings`]
+    ///
+    /// # Errors
+    /// See [`AbcProduct::from_db_export`]. With [`ErrorPolicy::Skip`] or
+    /// [`ErrorPolicy::Collect`], only errors opening or reading the files are returned
+    pub fn from_db_export_with_options(
+        item_path: &str,
+        item_posted_path: &str,
+        options: &ParseOptions,
+    ) -> Result<(AbcProductsBySku, RowErrors, ParseWarnings), AbcParseError> {
+        let item_file = open_data_file(item_path).map_err(csv::Error::from)?;
+        let item_posted_file = open_data_file(item_posted_path).map_err(csv::Error::from)?;
+        Self::from_readers_with_options(item_file, item_posted_file, options)
+    }
+
+    /// Like [`AbcProduct::from_db_export_with_options`], reading from any readers rather than
+    /// from paths on disk. See [`AbcProduct::from_readers`]
+    ///
+    /// # Errors
+    /// See [`AbcProduct::from_db_export_with_options`]
+    pub fn from_readers_with_options(
+        item: impl std::io::Read,
+        item_posted: impl std::io::Read,
+        options: &ParseOptions,
+    ) -> Result<(AbcProductsBySku, RowErrors, ParseWarnings), AbcParseError> {
+        let strict = options.on_error == ErrorPolicy::Fail;
+        Self::parse_readers(item, item_posted, options, strict)
+    }
+
+    /// Like [`AbcProduct::from_db_export`], but data quality problems that the parser can work
+    /// around are returned as [`ParseWarnings`] instead of being silently corrected.
+    ///
+    /// Products that appear in only one of the two files are also reported as
+    /// [`ParseWarning::OrphanSku`] and left out, rather than failing the whole parse.
+    ///
+    /// # Arguments
+    /// * `item_path` - The path to the item.data file generated by the db export
+    /// * `item_posted_path` - The path to the item_posted.data file generated by the db export
+    ///
+    /// # Returns
+    /// The parsed products along with every warning raised while parsing them
+    ///
+    /// # Errors
+    /// See [`AbcProduct::from_db_export`]
+    pub fn from_db_export_with_warnings(
+        item_path: &str,
+        item_posted_path: &str,
+    ) -> Result<(AbcProductsBySku, ParseWarnings), AbcParseError> {
+        let item_file = open_data_file(item_path).map_err(csv::Error::from)?;
+        let item_posted_file = open_data_file(item_posted_path).map_err(csv::Error::from)?;
+        Self::from_readers_with_warnings(item_file, item_posted_file)
+    }
+
+    /// Like [`AbcProduct::from_db_export_with_warnings`], reading from any readers rather than
+    /// from paths on disk. See [`AbcProduct::from_readers`]
+    ///
+    /// # Errors
+    /// See [`AbcProduct::from_db_export_with_warnings`]
+    pub fn from_readers_with_warnings(
+        item: impl std::io::Read,
+        item_posted: impl std::io::Read,
+    ) -> Result<(AbcProductsBySku, ParseWarnings), AbcParseError> {
+        let (products, _, warnings) =
+            Self::parse_readers(item, item_posted, &ParseOptions::default(), false)?;
+        Ok((products, warnings))
+    }
+
+    /// Parse both files and join their rows by sku, the work shared by
+    /// [`AbcProduct::from_readers_with_options`] and [`AbcProduct::from_readers_with_warnings`].
+    /// With `strict`, files with different numbers of rows or a sku missing from
+    /// `item_posted.data` fail the parse. Otherwise skus in only one file are left out and
+    /// reported as [`ParseWarning::OrphanSku`]
+    fn parse_readers(
+        item: impl std::io::Read,
+        item_posted: impl std::io::Read,
+        options: &ParseOptions,
+        strict: bool,
+    ) -> Result<(AbcProductsBySku, RowErrors, ParseWarnings), AbcParseError> {
+        let mut warnings = ParseWarnings::new();
+        let mut errors = RowErrors::new();
+        let base_products =
+            IntermediateBaseProduct::parse_item_data(item, &mut warnings, options, &mut errors)?;
+        let posted_products = IntermediatePostedProduct::parse_item_posted_data(
+            item_posted,
+            &mut warnings,
+            options,
+            &mut errors,
+        )?;
+        if strict && base_products.len() != posted_products.len() {
+            return Err(AbcParseError::MismatchedRowCounts {
+                first: "item.data".to_string(),
+                second: "item_posted.data".to_string(),
+            });
         }
 
+        let mut skus: Vec<&Sku> = base_products.keys().collect();
+        skus.sort();
         let mut products = AbcProductsBySku::new();
-        for (sku, base_product) in base_products {
-            let posted_product =
-                posted_products
-                    .get(&sku)
-                    .ok_or(AbcParseError::Custom(format!(
-                        "item_posted.data file has no product with sku '{}'",
-                        sku
-                    )))?;
-            products.insert(sku, AbcProduct::try_from((&base_product, posted_product))?);
+        for sku in skus {
+            let posted_product = match posted_products.get(sku) {
+                Some(posted_product) => posted_product,
+                None if strict => {
+                    return Err(AbcParseError::MissingRecord {
+                        file: "item_posted.data".to_string(),
+                        key: sku.to_string(),
+                        row: base_products[sku].row,
+                    });
+                }
+                None => {
+                    warnings.push(ParseWarning::OrphanSku {
+                        sku: sku.to_string(),
+                        file: "item.data".to_string(),
+                    });
+                    continue;
+                }
+            };
+            match AbcProduct::try_from((&base_products[sku], posted_product)) {
+                Ok(product) => {
+                    products.insert(sku.clone(), product);
+                }
+                // Row 0 marks a failure to combine the two files. See [`RowErrors`]
+                Err(e) => options.on_error.handle(0, e, &mut errors)?,
+            }
         }
-        Ok(products)
+        let mut posted_only: Vec<&Sku> = posted_products
+            .keys()
+            .filter(|sku| !base_products.contains_key(*sku))
+            .collect();
+        posted_only.sort();
+        for sku in posted_only {
+            warnings.push(ParseWarning::OrphanSku {
+                sku: sku.to_string(),
+                file: "item_posted.data".to_string(),
+            });
+        }
+        Ok((products, errors, warnings))
+    }
+}
+
+/// Lazily parses products from an ABC database export one row at a time, for catalogs too large
+/// to hold in an [`AbcProductsBySku`].
+///
+/// Rows of `item.data` and `item_posted.data` are joined in lockstep, which relies on ABC
+/// writing both files in the same order. If the skus of two rows do not match, an
+/// [`AbcParseError::MisMatchedSkus`] is yielded and iteration stops, since every following row
+/// would be misaligned too. Other problems with a row are handled by the [`ErrorPolicy`] of the
+/// [`ParseOptions`]: under [`ErrorPolicy::Fail`] they are yielded as errors without stopping, so
+/// callers can skip bad rows, while rows left out under [`ErrorPolicy::Collect`] are kept in
+/// [`AbcProductIter::errors`]. Rows are parsed by the same code as [`AbcProduct::from_readers`],
+/// and anything unusual about them is kept in [`AbcProductIter::warnings`].
+///
+/// # Example
+/// ```rust
+/// use abc_product::AbcProductIter;
+///
+/// let in_stock = AbcProductIter::from_db_export("./item.data", "./item_posted.data")
+///     .unwrap()
+///     .filter_map(|p| p.ok())
+///     .filter(|p| p.stock() > 0.0)
+///     .count();
+/// ```
+pub struct AbcProductIter<'a> {
+    items: Box<dyn Iterator<Item = Result<records::ItemRecord, AbcParseError>> + 'a>,
+    posted: Box<dyn Iterator<Item = Result<records::PostedRecord, AbcParseError>> + 'a>,
+    on_error: ErrorPolicy,
+    row: usize,
+    errors: RowErrors,
+    warnings: ParseWarnings,
+    done: bool,
+}
+
+impl AbcProductIter<'static> {
+    /// Start parsing the export files at the given paths. See [`AbcProduct::from_db_export`]
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file cannot be opened
+    pub fn from_db_export(item_path: &str, item_posted_path: &str) -> Result<Self, AbcParseError> {
+        Self::from_db_export_with_options(item_path, item_posted_path, &ParseOptions::default())
+    }
+
+    /// Like [`AbcProductIter::from_db_export`], reading the files as set by `options`. See
+    /// [`AbcProduct::from_db_export_with_options`]
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file cannot be opened
+    pub fn from_db_export_with_options(
+        item_path: &str,
+        item_posted_path: &str,
+        options: &ParseOptions,
+    ) -> Result<Self, AbcParseError> {
+        let item_file = open_data_file(item_path).map_err(csv::Error::from)?;
+        let item_posted_file = open_data_file(item_posted_path).map_err(csv::Error::from)?;
+        AbcProductIter::from_readers_with_options(item_file, item_posted_file, options)
+    }
+}
+
+impl<'a> AbcProductIter<'a> {
+    /// Start parsing an export from any readers rather than from paths on disk. See
+    /// [`AbcProduct::from_readers`]
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if the start of either reader cannot be read
+    pub fn from_readers(
+        item: impl std::io::Read + 'a,
+        item_posted: impl std::io::Read + 'a,
+    ) -> Result<Self, AbcParseError> {
+        Self::from_readers_with_options(item, item_posted, &ParseOptions::default())
+    }
+
+    /// Like [`AbcProductIter::from_readers`], reading the files as set by `options`
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if the start of either reader cannot be read
+    pub fn from_readers_with_options(
+        item: impl std::io::Read + 'a,
+        item_posted: impl std::io::Read + 'a,
+        options: &ParseOptions,
+    ) -> Result<Self, AbcParseError> {
+        Ok(AbcProductIter {
+            items: Box::new(records::item_records(item, options)?),
+            posted: Box::new(records::posted_records(item_posted, options)?),
+            on_error: options.on_error,
+            row: 0,
+            errors: RowErrors::new(),
+            warnings: ParseWarnings::new(),
+            done: false,
+        })
+    }
+
+    /// The rows left out so far under [`ErrorPolicy::Collect`]
+    pub fn errors(&self) -> &RowErrors {
+        &self.errors
+    }
+
+    /// Anything unusual noticed in the rows parsed so far
+    pub fn warnings(&self) -> &ParseWarnings {
+        &self.warnings
+    }
+}
+
+impl Iterator for AbcProductIter<'_> {
+    type Item = Result<AbcProduct, AbcParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.row += 1;
+            let product = match (self.items.next(), self.posted.next()) {
+                (None, None) => {
+                    self.done = true;
+                    return None;
+                }
+                (Some(Ok(item)), Some(Ok(posted))) => {
+                    records::join(&item, &posted, &mut self.warnings)
+                }
+                (Some(Err(e)), _) | (_, Some(Err(e))) => Err(e),
+                (Some(_), None) | (None, Some(_)) => {
+                    self.done = true;
+                    return Some(Err(AbcParseError::MismatchedRowCounts {
+                        first: "item.data".to_string(),
+                        second: "item_posted.data".to_string(),
+                    }));
+                }
+            };
+            match product {
+                Ok(product) => return Some(Ok(product)),
+                Err(AbcParseError::MisMatchedSkus) => {
+                    self.done = true;
+                    return Some(Err(AbcParseError::MisMatchedSkus));
+                }
+                Err(e) => {
+                    if let Err(e) = self.on_error.handle(self.row, e, &mut self.errors) {
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+        None
     }
 }
 
@@ -227,16 +1633,26 @@ impl TryFrom<(&IntermediateBaseProduct, &IntermediatePostedProduct)> for AbcProd
             return Err(AbcParseError::MisMatchedSkus);
         }
         Ok(AbcProduct {
-            sku: inter.sku.to_string(),
+            sku: inter.sku.clone(),
             desc: inter.desc.to_string(),
             alt_skus: inter.alt_skus.to_vec(),
             upcs: inter.upcs.to_vec(),
+            raw_upcs: inter.raw_upcs.to_vec(),
             cost: inter.cost,
+            avg_cost: inter.avg_cost,
             list: inter.list,
-            group: inter.group.clone(),
+            group: inter.group,
             weight: inter.weight,
             stock: posted.stock,
             last_sold: posted.last_sold,
+            core_sku: inter.core_sku.clone(),
+            warranty_months: inter.warranty_months,
+            net_contents: inter.net_contents.clone(),
+            country_of_origin: inter.country_of_origin.clone(),
+            min_stock: inter.min_stock,
+            max_stock: inter.max_stock,
+            related: Vec::new(),
+            attributes: BTreeMap::new(),
         })
     }
 }
@@ -248,20 +1664,30 @@ impl AbcProductBuilder {
             sku: None,
             desc: None,
             upcs: Vec::new(),
+            raw_upcs: Vec::new(),
             list: None,
             cost: None,
+            avg_cost: None,
             stock: None,
             weight: None,
             group: None,
             last_sold: None,
             alt_skus: Vec::new(),
+            core_sku: None,
+            warranty_months: None,
+            net_contents: None,
+            country_of_origin: None,
+            min_stock: None,
+            max_stock: None,
+            related: Vec::new(),
+            attributes: BTreeMap::new(),
         }
     }
 
     /// Set the sku for this product
     pub fn with_sku(self, sku: &str) -> Self {
         AbcProductBuilder {
-            sku: Some(sku.to_string()),
+            sku: Some(Sku::new(sku)),
             ..self
         }
     }
@@ -289,6 +1715,14 @@ impl AbcProductBuilder {
         }
     }
 
+    /// Set the UPCs for this product as they appeared in the export
+    pub fn with_raw_upcs(self, raw_upcs: &[String]) -> Self {
+        AbcProductBuilder {
+            raw_upcs: raw_upcs.to_vec(),
+            ..self
+        }
+    }
+
     /// Set this product's list price
     pub fn with_list(self, list: Decimal) -> Self {
         AbcProductBuilder {
@@ -305,6 +1739,14 @@ impl AbcProductBuilder {
         }
     }
 
+    /// Set this product's weighted average cost
+    pub fn with_avg_cost(self, avg_cost: Decimal) -> Self {
+        AbcProductBuilder {
+            avg_cost: Some(avg_cost),
+            ..self
+        }
+    }
+
     /// Set the stock level (inventory) of this product
     pub fn with_stock(self, stock: f64) -> Self {
         AbcProductBuilder {
@@ -330,13 +1772,15 @@ impl AbcProductBuilder {
     /// If `group` is a character between 'A' and 'Z' inclusive, return Some([`AbcProductBuilder`])
     /// with a group of `group`. If `group` is outside of the range 'A' to 'Z', return [`None`]
     pub fn with_group(self, group: char) -> Option<Self> {
-        if (group < 'A' || group > 'Z') && (group < 'a' || group > 'z') {
-            return None;
-        }
-        Some(AbcProductBuilder {
-            group: Some(group.to_string().to_uppercase()),
+        AbcGroup::new(group).map(|g| self.with_abc_group(g))
+    }
+
+    /// This product's group, already checked to be a letter from A-Z
+    pub fn with_abc_group(self, group: AbcGroup) -> Self {
+        AbcProductBuilder {
+            group: Some(group),
             ..self
-        })
+        }
     }
 
     /// Sets the date that this product was last sold
@@ -345,23 +1789,176 @@ impl AbcProductBuilder {
             last_sold: Some(last_sold),
             ..self
         }
-    }
-
-    /// Sets the value of all alternative skus for this builder
-    pub fn with_alt_skus(self, alt_skus: &[String]) -> Self {
-        AbcProductBuilder {
-            alt_skus: alt_skus.to_vec(),
-            ..self
+    }
+
+    /// Sets the value of all alternative skus for this builder
+    pub fn with_alt_skus(self, alt_skus: &[String]) -> Self {
+        AbcProductBuilder {
+            alt_skus: alt_skus.iter().map(|s| Sku::new(s)).collect(),
+            ..self
+        }
+    }
+
+    /// Add a single alternative sku to the list of alternative skus for this builder
+    pub fn add_alt_sku(self, alt: impl ToString) -> Self {
+        let mut new_skus = self.alt_skus;
+        new_skus.push(Sku::new(&alt.to_string()));
+        Self {
+            alt_skus: new_skus,
+            ..self
+        }
+    }
+
+    /// Sets the sku of the core charge item sold alongside this product
+    pub fn with_core_sku(self, core_sku: &str) -> Self {
+        AbcProductBuilder {
+            core_sku: Some(core_sku.to_string()),
+            ..self
+        }
+    }
+
+    /// Sets how many months the product is under warranty
+    pub fn with_warranty_months(self, warranty_months: u32) -> Self {
+        AbcProductBuilder {
+            warranty_months: Some(warranty_months),
+            ..self
+        }
+    }
+
+    /// Sets the declared net quantity of the package
+    pub fn with_net_contents(self, net_contents: measure::NetContents) -> Self {
+        AbcProductBuilder {
+            net_contents: Some(net_contents),
+            ..self
+        }
+    }
+
+    /// Sets the country the product was made in
+    pub fn with_country_of_origin(self, country: &str) -> Self {
+        AbcProductBuilder {
+            country_of_origin: Some(country.to_string()),
+            ..self
+        }
+    }
+
+    /// Sets the stock level at which the product should be reordered
+    pub fn with_min_stock(self, min_stock: f64) -> Self {
+        AbcProductBuilder {
+            min_stock: Some(min_stock),
+            ..self
+        }
+    }
+
+    /// Sets the stock level that reorders should bring the product back up to
+    pub fn with_max_stock(self, max_stock: f64) -> Self {
+        AbcProductBuilder {
+            max_stock: Some(max_stock),
+            ..self
+        }
+    }
+
+    /// Sets the skus of products related to this one
+    pub fn with_related(self, related: &[String]) -> Self {
+        AbcProductBuilder {
+            related: related.to_vec(),
+            ..self
+        }
+    }
+
+    /// Sets the supplemental attribute `key` to `value`
+    pub fn with_attr(self, key: &str, value: &str) -> Self {
+        let mut attributes = self.attributes;
+        attributes.insert(key.to_string(), value.to_string());
+        AbcProductBuilder { attributes, ..self }
+    }
+
+    /// Check every field of this builder at once, rather than stopping at the first missing
+    /// field the way [`AbcProductBuilder::build`] does
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{AbcProduct, ProductField, ValidationIssue};
+    /// use rust_decimal::Decimal;
+    ///
+    /// let report = AbcProduct::new()
+    ///     .with_sku("A1")
+    ///     .with_desc(" ")
+    ///     .with_list(Decimal::new(-100, 2))
+    ///     .validate();
+    /// assert_eq!(
+    ///     report.errors,
+    ///     vec![
+    ///         ValidationIssue::EmptyDesc,
+    ///         ValidationIssue::NegativeList(Decimal::new(-100, 2)),
+    ///         ValidationIssue::Missing(ProductField::Cost),
+    ///         ValidationIssue::Missing(ProductField::Stock),
+    ///     ]
+    /// );
+    /// ```
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        if self.sku.as_ref().is_none_or(|s| s.as_str().is_empty()) {
+            report
+                .errors
+                .push(ValidationIssue::Missing(ProductField::Sku));
+        }
+        match &self.desc {
+            None => report
+                .errors
+                .push(ValidationIssue::Missing(ProductField::Desc)),
+            Some(desc) if desc.trim().is_empty() => report.errors.push(ValidationIssue::EmptyDesc),
+            Some(_) => {}
+        }
+        match self.list {
+            None => report
+                .errors
+                .push(ValidationIssue::Missing(ProductField::List)),
+            Some(list) if list.is_sign_negative() && !list.is_zero() => {
+                report.errors.push(ValidationIssue::NegativeList(list))
+            }
+            Some(_) => {}
+        }
+        match self.cost {
+            None => report
+                .errors
+                .push(ValidationIssue::Missing(ProductField::Cost)),
+            Some(cost) if cost.is_sign_negative() && !cost.is_zero() => {
+                report.errors.push(ValidationIssue::NegativeCost(cost))
+            }
+            Some(_) => {}
+        }
+        if self.stock.is_none() {
+            report
+                .errors
+                .push(ValidationIssue::Missing(ProductField::Stock));
+        }
+        if let (Some(cost), Some(list)) = (self.cost, self.list)
+            && cost > list
+        {
+            report
+                .warnings
+                .push(ValidationIssue::CostAboveList { cost, list });
         }
+        report
     }
 
-    /// Add a single alternative sku to the list of alternative skus for this builder
-    pub fn add_alt_sku(self, alt: impl ToString) -> Self {
-        let mut new_skus = self.alt_skus;
-        new_skus.push(alt.to_string());
-        Self {
-            alt_skus: new_skus,
-            ..self
+    /// Like [`AbcProductBuilder::build`], but only builds the product if
+    /// [`AbcProductBuilder::validate`] finds no errors, and reports every problem at once
+    ///
+    /// # Returns
+    /// The product along with any warnings
+    ///
+    /// # Errors
+    /// The [`ValidationReport`] if it has any errors
+    pub fn build_validated(self) -> Result<(AbcProduct, Vec<ValidationIssue>), ValidationReport> {
+        let report = self.validate();
+        if !report.is_valid() {
+            return Err(report);
+        }
+        match self.build() {
+            Ok(product) => Ok((product, report.warnings)),
+            // Every field build requires was just checked
+            Err(_) => Err(report),
         }
     }
 
@@ -381,42 +1978,273 @@ impl AbcProductBuilder {
             sku: self
                 .sku
                 .clone()
-                .ok_or(AbcParseError::MissingField("sku".to_string(), 0))?,
+                .ok_or_else(|| AbcParseError::missing(None, 0, None, "sku"))?,
             desc: self
                 .desc
                 .clone()
-                .ok_or(AbcParseError::MissingField("desc".to_string(), 0))?,
+                .ok_or_else(|| AbcParseError::missing(None, 0, None, "desc"))?,
             upcs: self.upcs,
+            raw_upcs: self.raw_upcs,
             list: self
                 .list
-                .ok_or(AbcParseError::MissingField("list".to_string(), 0))?,
+                .ok_or_else(|| AbcParseError::missing(None, 0, None, "list"))?,
             cost: self
                 .cost
-                .ok_or(AbcParseError::MissingField("cost".to_string(), 0))?,
+                .ok_or_else(|| AbcParseError::missing(None, 0, None, "cost"))?,
+            avg_cost: self.avg_cost,
             stock: self
                 .stock
-                .ok_or(AbcParseError::MissingField("stock".to_string(), 0))?,
+                .ok_or_else(|| AbcParseError::missing(None, 0, None, "stock"))?,
             weight: self.weight,
             group: self.group,
             last_sold: self.last_sold,
             alt_skus: self.alt_skus,
+            core_sku: self.core_sku,
+            warranty_months: self.warranty_months,
+            net_contents: self.net_contents,
+            country_of_origin: self.country_of_origin,
+            min_stock: self.min_stock,
+            max_stock: self.max_stock,
+            related: self.related,
+            attributes: self.attributes,
         })
     }
 }
 
+/// Setters that change an [`AbcProductBuilder`] in place rather than consuming it, for
+/// assembling a product a piece at a time from more than one source
+///
+/// # Example
+/// ```rust
+/// use abc_product::{AbcGroup, AbcProduct};
+/// use rust_decimal::Decimal;
+///
+/// let mut builder = AbcProduct::new();
+/// builder.set_sku("a1").set_desc("WIDGET").set_stock(4.0);
+/// for (list, cost) in [(Decimal::new(999, 2), Decimal::new(450, 2))] {
+///     builder.set_list(list).set_cost(cost);
+/// }
+/// if let Some(group) = AbcGroup::new('C') {
+///     builder.set_group(group);
+/// }
+/// let product = builder.build().unwrap();
+/// assert_eq!(product.sku(), "A1");
+/// assert_eq!(product.group(), AbcGroup::new('C'));
+/// ```
+impl AbcProductBuilder {
+    /// Set the sku for this product
+    pub fn set_sku(&mut self, sku: &str) -> &mut Self {
+        self.sku = Some(Sku::new(sku));
+        self
+    }
+
+    /// Set the description for this product
+    pub fn set_desc(&mut self, desc: &str) -> &mut Self {
+        self.desc = Some(desc.to_string());
+        self
+    }
+
+    /// Set the value of the list of UPCs for this product
+    pub fn set_upcs(&mut self, upcs: Vec<Ean13>) -> &mut Self {
+        self.upcs = upcs;
+        self
+    }
+
+    /// Add a UPC to the list of UPCs for this product
+    pub fn push_upc(&mut self, upc: Ean13) -> &mut Self {
+        self.upcs.push(upc);
+        self
+    }
+
+    /// Set the UPCs for this product as they appeared in the export
+    pub fn set_raw_upcs(&mut self, raw_upcs: &[String]) -> &mut Self {
+        self.raw_upcs = raw_upcs.to_vec();
+        self
+    }
+
+    /// Set this product's list price
+    pub fn set_list(&mut self, list: Decimal) -> &mut Self {
+        self.list = Some(list);
+        self
+    }
+
+    /// Set this product's cost
+    pub fn set_cost(&mut self, cost: Decimal) -> &mut Self {
+        self.cost = Some(cost);
+        self
+    }
+
+    /// Set this product's weighted average cost
+    pub fn set_avg_cost(&mut self, avg_cost: Decimal) -> &mut Self {
+        self.avg_cost = Some(avg_cost);
+        self
+    }
+
+    /// Set the stock level (inventory) of this product
+    pub fn set_stock(&mut self, stock: f64) -> &mut Self {
+        self.stock = Some(stock);
+        self
+    }
+
+    /// Set this product's weight in pounds
+    pub fn set_weight(&mut self, weight: f64) -> &mut Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Set this product's discount group. Takes an [`AbcGroup`] rather than a [`char`] so that
+    /// a chain of setters cannot be broken by an invalid group
+    pub fn set_group(&mut self, group: AbcGroup) -> &mut Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// Sets the date that this product was last sold
+    pub fn set_last_sold(&mut self, last_sold: NaiveDate) -> &mut Self {
+        self.last_sold = Some(last_sold);
+        self
+    }
+
+    /// Sets the value of all alternative skus for this builder
+    pub fn set_alt_skus(&mut self, alt_skus: &[String]) -> &mut Self {
+        self.alt_skus = alt_skus.iter().map(|s| Sku::new(s)).collect();
+        self
+    }
+
+    /// Add a single alternative sku to the list of alternative skus for this builder
+    pub fn push_alt_sku(&mut self, alt: impl ToString) -> &mut Self {
+        self.alt_skus.push(Sku::new(&alt.to_string()));
+        self
+    }
+
+    /// Sets the sku of the core charge item sold alongside this product
+    pub fn set_core_sku(&mut self, core_sku: &str) -> &mut Self {
+        self.core_sku = Some(core_sku.to_string());
+        self
+    }
+
+    /// Sets how many months the product is under warranty
+    pub fn set_warranty_months(&mut self, warranty_months: u32) -> &mut Self {
+        self.warranty_months = Some(warranty_months);
+        self
+    }
+
+    /// Sets the declared net quantity of the package
+    pub fn set_net_contents(&mut self, net_contents: measure::NetContents) -> &mut Self {
+        self.net_contents = Some(net_contents);
+        self
+    }
+
+    /// Sets the country the product was made in
+    pub fn set_country_of_origin(&mut self, country: &str) -> &mut Self {
+        self.country_of_origin = Some(country.to_string());
+        self
+    }
+
+    /// Sets the stock level at which the product should be reordered
+    pub fn set_min_stock(&mut self, min_stock: f64) -> &mut Self {
+        self.min_stock = Some(min_stock);
+        self
+    }
+
+    /// Sets the stock level that reorders should bring the product back up to
+    pub fn set_max_stock(&mut self, max_stock: f64) -> &mut Self {
+        self.max_stock = Some(max_stock);
+        self
+    }
+
+    /// Sets the skus of products related to this one
+    pub fn set_related(&mut self, related: &[String]) -> &mut Self {
+        self.related = related.to_vec();
+        self
+    }
+
+    /// Sets the supplemental attribute `key` to `value`
+    pub fn set_attr(&mut self, key: &str, value: &str) -> &mut Self {
+        self.attributes.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// A problem with an [`AbcProductBuilder`], as found by [`AbcProductBuilder::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// A required field was never set
+    Missing(ProductField),
+    /// The description is blank
+    EmptyDesc,
+    /// The list price is below zero
+    NegativeList(Decimal),
+    /// The cost is below zero
+    NegativeCost(Decimal),
+    /// The product costs more than it sells for. Allowed, since loss leaders and clearance items
+    /// are sold this way on purpose, but usually a typo
+    CostAboveList { cost: Decimal, list: Decimal },
+}
+
+impl std::fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(field) => write!(f, "The {} is missing", field.name()),
+            Self::EmptyDesc => write!(f, "The description is blank"),
+            Self::NegativeList(list) => write!(f, "The list price {} is negative", list),
+            Self::NegativeCost(cost) => write!(f, "The cost {} is negative", cost),
+            Self::CostAboveList { cost, list } => {
+                write!(f, "The cost {} is more than the list price {}", cost, list)
+            }
+        }
+    }
+}
+
+/// Everything wrong with an [`AbcProductBuilder`], as returned by
+/// [`AbcProductBuilder::validate`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// Problems that keep the product from being built, in field order
+    pub errors: Vec<ValidationIssue>,
+    /// Problems worth a second look that do not keep the product from being built
+    pub warnings: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether the product can be built
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let issues: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", issues.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
 impl From<AbcProduct> for AbcProductBuilder {
     fn from(value: AbcProduct) -> Self {
         AbcProductBuilder {
-            sku: Some(value.sku()),
+            sku: Some(value.sku.clone()),
             desc: Some(value.desc()),
             upcs: value.upcs(),
+            raw_upcs: value.raw_upcs,
             list: Some(value.list),
             cost: Some(value.cost),
+            avg_cost: value.avg_cost,
             stock: Some(value.stock),
             weight: value.weight,
             group: value.group,
             last_sold: value.last_sold,
             alt_skus: value.alt_skus,
+            core_sku: value.core_sku,
+            warranty_months: value.warranty_months,
+            net_contents: value.net_contents,
+            country_of_origin: value.country_of_origin,
+            min_stock: value.min_stock,
+            max_stock: value.max_stock,
+            related: value.related,
+            attributes: value.attributes,
         }
     }
 }
@@ -424,26 +2252,170 @@ impl From<AbcProduct> for AbcProductBuilder {
 impl std::fmt::Display for AbcParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::MissingField(field, row) => {
-                write!(f, "Missing field `{}` in row {}", field, row)
-            }
+            Self::MissingField(e) => write!(f, "{}", e),
             Self::MisMatchedSkus => {
                 write!(
                     f,
                     "Attempted to combine data from `item.data` and `item_posted.data` into a single [`AbcProduct`], but the skus do not match"
                 )
             }
-            _ => write!(f, "{:?}", self),
+            Self::InvalidValue(e) => write!(f, "{}", e),
+            Self::MissingRecord { file, key, row } => write!(
+                f,
+                "{} file has no record for `{}`, referred to in row {}",
+                file, key, row
+            ),
+            Self::MismatchedRowCounts { first, second } => write!(
+                f,
+                "The {} and {} files have a different number of rows",
+                first, second
+            ),
+            Self::UnknownLayout(path) => {
+                write!(f, "No known column layout matches the export")?;
+                match path {
+                    Some(path) => write!(f, " in {}", path),
+                    None => Ok(()),
+                }
+            }
+            Self::FileChanged(path) => {
+                write!(f, "{} has changed since the checkpoint was taken", path)
+            }
+            Self::InvalidCheckpoint(what) => write!(f, "Invalid checkpoint: {}", what),
+            Self::InvalidJson(line, e) => write!(f, "Invalid product in line {}: {}", line, e),
+            Self::CsvError(e) => write!(f, "{}", e),
         }
     }
 }
 
-impl std::error::Error for AbcParseError {}
+impl std::error::Error for AbcParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CsvError(e) => Some(&e.source),
+            Self::InvalidValue(e) => e.source(),
+            Self::InvalidJson(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl AbcParseError {
+    /// An [`AbcParseError::InvalidValue`] for the value `raw` of `field`
+    pub(crate) fn invalid(
+        file: Option<&str>,
+        row: usize,
+        column: usize,
+        field: &str,
+        raw: &str,
+        source: impl Into<ValueError>,
+    ) -> Self {
+        Self::InvalidValue(Box::new(InvalidValue {
+            file: file.map(|f| f.to_string()),
+            row,
+            column,
+            field: field.to_string(),
+            raw: raw.to_string(),
+            source: source.into(),
+        }))
+    }
+
+    /// An [`AbcParseError::MissingField`] for `field`, which should be in `column` of `row`
+    pub(crate) fn missing(
+        file: Option<&str>,
+        row: usize,
+        column: Option<usize>,
+        field: &str,
+    ) -> Self {
+        Self::MissingField(Box::new(MissingField {
+            file: file.map(|f| f.to_string()),
+            row,
+            column,
+            field: field.to_string(),
+        }))
+    }
+
+    /// An [`AbcParseError::CsvError`] for `source`, raised while reading `file`. The row and
+    /// column are taken from `source` where it knows them
+    pub(crate) fn csv(file: Option<&str>, source: csv::Error) -> Self {
+        let row = source.position().map(|p| p.record() as usize + 1);
+        let column = match source.kind() {
+            csv::ErrorKind::Utf8 { err, .. } => Some(err.field()),
+            _ => None,
+        };
+        Self::CsvError(Box::new(CsvError {
+            file: file.map(|f| f.to_string()),
+            row,
+            column,
+            source,
+        }))
+    }
+}
 
 impl From<csv::Error> for AbcParseError {
     fn from(value: csv::Error) -> Self {
-        Self::CsvError(value)
+        Self::csv(None, value)
+    }
+}
+
+/// Parse every row of a tab delimited export file with `from_row`, keyed by the sku `sku` reads
+/// from each parsed row. Warnings and errors are reported in row order.
+///
+/// With the `rayon` feature, the whole file is split into rows first and the rows are parsed
+/// across threads, since parsing prices, UPCs, and dates takes far longer than splitting the
+/// rows. Without it, rows are parsed one at a time and parsing stops at the first error when
+/// `options` says to fail.
+///
+/// # Errors
+/// Any error reading `input`, or the first row that cannot be parsed when `options` says to fail
+fn parse_rows<T: Send>(
+    input: impl std::io::Read,
+    file: &str,
+    warnings: &mut ParseWarnings,
+    options: &ParseOptions,
+    errors: &mut RowErrors,
+    from_row: impl Fn(
+        &csv::StringRecord,
+        usize,
+        &ColumnLayout,
+        &mut ParseWarnings,
+    ) -> Result<T, AbcParseError>
+    + Sync,
+    sku: impl Fn(&T) -> Sku,
+) -> Result<HashMap<Sku, T>, AbcParseError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .from_reader(input::decode(
+            input::normalize(input).map_err(csv::Error::from)?,
+            options.encoding,
+        ));
+    let parse = |(i, row): (usize, Result<csv::StringRecord, csv::Error>)| {
+        let mut row_warnings = ParseWarnings::new();
+        let parsed = row
+            .map_err(|e| AbcParseError::csv(Some(file), e))
+            .and_then(|row| from_row(&row, i + 1, &options.layout, &mut row_warnings));
+        (parsed, row_warnings)
+    };
+
+    #[cfg(not(feature = "rayon"))]
+    let parsed = reader.records().enumerate().map(parse);
+    #[cfg(feature = "rayon")]
+    let parsed: Vec<(Result<T, AbcParseError>, ParseWarnings)> = {
+        use rayon::prelude::*;
+        let rows: Vec<Result<csv::StringRecord, csv::Error>> = reader.records().collect();
+        rows.into_par_iter().enumerate().map(parse).collect()
+    };
+
+    let mut products = HashMap::new();
+    for (i, (parsed, row_warnings)) in parsed.into_iter().enumerate() {
+        warnings.extend(row_warnings);
+        match parsed {
+            Ok(product) => {
+                products.insert(sku(&product), product);
+            }
+            Err(e) => options.on_error.handle(i + 1, e, errors)?,
+        }
     }
+    Ok(products)
 }
 
 impl IntermediatePostedProduct {
@@ -451,8 +2423,8 @@ impl IntermediatePostedProduct {
     /// file
     ///
     /// # Arguments
-    /// * `item_posted_path` - The path to the `item_posted.data` file that contains posted data
-    /// fields for ABC inventory items
+    /// * `item_posted` - The contents of the `item_posted.data` file that contains posted data
+    ///   fields for ABC inventory items
     ///
     /// # Returns
     /// A map from skus to [`IntermediatePostedProduct`]. Each [`IntermediatePostedProduct`]
@@ -463,45 +2435,72 @@ impl IntermediatePostedProduct {
     /// [`AbcParseError`]s to be raised if there are missing fields or other problems
     /// deserializing the data
     fn parse_item_posted_data(
-        item_posted_path: &str,
-    ) -> Result<HashMap<String, IntermediatePostedProduct>, AbcParseError> {
-        let mut posted_data = csv::ReaderBuilder::new()
-            .delimiter(b'\t')
-            .has_headers(false)
-            .from_path(item_posted_path)?;
-
-        let mut products = HashMap::new();
-        let mut i = 0;
-        while let Some(row) = posted_data.records().next() {
-            i += 1;
-            let row = row?;
-            let sku = row
-                .get(0)
-                .ok_or(AbcParseError::MissingField("sku".to_string(), i))?
-                .to_string();
-            let stock_str = row
-                .get(19)
-                .ok_or(AbcParseError::MissingField("stock".to_string(), i))?
-                .to_string();
-            let stock: f64 = stock_str.parse().or(Err(AbcParseError::Custom(format!(
-                "Cannot parse f64 from stock_str in row {} of posted items",
-                i
-            ))))?;
-            let last_sold_str: String = row
-                .get(1)
-                .ok_or(AbcParseError::MissingField("last_sold".to_string(), i))?
-                .to_string();
-            let last_sold = chrono::NaiveDate::parse_from_str(&last_sold_str, "%Y-%m-%d").ok();
-            products.insert(
-                sku.clone(),
-                IntermediatePostedProduct {
-                    sku,
-                    stock,
-                    last_sold,
-                },
-            );
+        item_posted: impl std::io::Read,
+        warnings: &mut ParseWarnings,
+        options: &ParseOptions,
+        errors: &mut RowErrors,
+    ) -> Result<HashMap<Sku, IntermediatePostedProduct>, AbcParseError> {
+        parse_rows(
+            item_posted,
+            "item_posted.data",
+            warnings,
+            options,
+            errors,
+            Self::from_row,
+            |p| p.sku.clone(),
+        )
+    }
+
+    /// Parse row number `i` of the `item_posted.data` file. Every parser of `item_posted.data`
+    /// rows goes through here, so that they all agree
+    fn from_row(
+        row: &csv::StringRecord,
+        i: usize,
+        layout: &ColumnLayout,
+        warnings: &mut ParseWarnings,
+    ) -> Result<Self, AbcParseError> {
+        let sku = Sku::new(row.get(layout.posted_sku).ok_or_else(|| {
+            AbcParseError::missing(Some("item_posted.data"), i, Some(layout.posted_sku), "sku")
+        })?);
+        let stock_str = row
+            .get(layout.stock)
+            .ok_or_else(|| {
+                AbcParseError::missing(Some("item_posted.data"), i, Some(layout.stock), "stock")
+            })?
+            .to_string();
+        let stock: f64 = stock_str.parse().map_err(|e| {
+            AbcParseError::invalid(
+                Some("item_posted.data"),
+                i,
+                layout.stock,
+                "stock",
+                &stock_str,
+                e,
+            )
+        })?;
+        let last_sold_str: String = row
+            .get(layout.last_sold)
+            .ok_or_else(|| {
+                AbcParseError::missing(
+                    Some("item_posted.data"),
+                    i,
+                    Some(layout.last_sold),
+                    "last_sold",
+                )
+            })?
+            .to_string();
+        let last_sold = chrono::NaiveDate::parse_from_str(&last_sold_str, "%Y-%m-%d").ok();
+        if last_sold.is_none() && !last_sold_str.is_empty() && last_sold_str != "null" {
+            warnings.push(ParseWarning::UnparsedLastSold {
+                sku: sku.to_string(),
+                raw: last_sold_str.to_string(),
+            });
         }
-        Ok(products)
+        Ok(IntermediatePostedProduct {
+            sku,
+            stock,
+            last_sold,
+        })
     }
 }
 
@@ -511,8 +2510,8 @@ impl IntermediateBaseProduct {
     /// with [`IntermediatePostedProduct`]s that share a sku.
     ///
     /// # Arguments
-    /// * `item_path` - The path to the ABC db export file usually called `item.data`. This file
-    /// contains most of the information for each inventory item
+    /// * `item` - The contents of the ABC db export file usually called `item.data`. This file
+    ///   contains most of the information for each inventory item
     ///
     /// # Returns
     /// A map from skus to [`IntermediateBaseProduct`]. Each [`IntermediateBaseProduct`]
@@ -523,102 +2522,178 @@ impl IntermediateBaseProduct {
     /// [`AbcParseError`]s to be raised if there are missing fields or other problems
     /// deserializing the data
     fn parse_item_data(
-        item_path: &str,
-    ) -> Result<HashMap<String, IntermediateBaseProduct>, AbcParseError> {
-        let mut item_data = csv::ReaderBuilder::new()
-            .delimiter(b'\t')
-            .has_headers(false)
-            .from_path(item_path)?;
-
-        let mut i = 0;
-        let mut products = HashMap::new();
-        while let Some(row) = item_data.records().next() {
-            i += 1;
-            let row = row?;
-            let sku = row
-                .get(0)
-                .ok_or(AbcParseError::MissingField("sku".to_string(), i))?
-                .to_string();
-            let desc = row
-                .get(1)
-                .ok_or(AbcParseError::MissingField("desc".to_string(), i))?
-                .to_string();
-            let upc_str: String = row
-                .get(43)
-                .ok_or(AbcParseError::MissingField("upcs".to_string(), i))?
-                .chars()
-                .filter(|c| c.is_digit(10) || *c == ',')
-                .collect();
-            let upcs: Vec<Ean13> = upc_str
-                .split(",")
-                .filter_map(|s| {
-                    if s.len() == 11 {
-                        // Some ABC UPCs leave out the check digit, so make one up and let [`Ean13::from_str_nonstrict`] fix it
-                        Ean13::from_str_nonstrict(&format!("{}0", s)).ok()
-                    } else if s.len() < 11 {
-                        // Anything less than 11 characters long is probably a dead upc
-                        None
-                    } else {
-                        // Anything 12 characters and up has a chance of being a good upc
-                        Ean13::from_str_nonstrict(s).ok()
-                    }
-                })
-                .collect();
-            let list = row
-                .get(6)
-                .ok_or(AbcParseError::MissingField("list".to_string(), i))?;
-            let list = price_from_str(list).or(Err(AbcParseError::Custom(format!(
-                "Cannot parse a price for list in row {}",
-                i
-            ))))?;
-            let cost = row
-                .get(8)
-                .ok_or(AbcParseError::MissingField("cost".to_string(), i))?;
-            let cost = price_from_str(cost).or(Err(AbcParseError::Custom(format!(
-                "Cannot parse a price for cost in row {}",
-                i
-            ))))?;
-            let weight_str = row
-                .get(45)
-                .ok_or(AbcParseError::MissingField("weight".to_string(), i))?;
-            let weight = match weight_str.parse::<f64>() {
-                Ok(f) => Some(f),
-                Err(_) => None,
-            };
-            let group = row.get(18);
-            let group = match group {
-                Some(g) => {
-                    if g.is_empty() {
-                        None
-                    } else {
-                        Some(g.to_owned())
-                    }
-                }
-                None => None,
-            };
-            let alt_skus = [row.get(40), row.get(41), row.get(42)]
-                .iter()
-                .filter_map(|o| match o {
-                    Some("") => None,
-                    Some(s) => Some(s.to_string()),
-                    None => None,
-                })
-                .collect();
-            products.insert(
-                sku.clone(),
-                IntermediateBaseProduct {
-                    sku,
-                    desc,
-                    upcs,
-                    list,
-                    cost,
-                    weight,
-                    group,
-                    alt_skus,
-                },
-            );
+        item: impl std::io::Read,
+        warnings: &mut ParseWarnings,
+        options: &ParseOptions,
+        errors: &mut RowErrors,
+    ) -> Result<HashMap<Sku, IntermediateBaseProduct>, AbcParseError> {
+        parse_rows(
+            item,
+            "item.data",
+            warnings,
+            options,
+            errors,
+            Self::from_row,
+            |p| p.sku.clone(),
+        )
+    }
+
+    /// Parse row number `i` of the `item.data` file. Every parser of `item.data` rows goes
+    /// through here, so that they all agree
+    fn from_row(
+        row: &csv::StringRecord,
+        i: usize,
+        layout: &ColumnLayout,
+        warnings: &mut ParseWarnings,
+    ) -> Result<Self, AbcParseError> {
+        let raw_sku = row
+            .get(layout.sku)
+            .ok_or_else(|| AbcParseError::missing(Some("item.data"), i, Some(layout.sku), "sku"))?;
+        let sku = Sku::new(raw_sku);
+        let desc = row
+            .get(layout.desc)
+            .ok_or_else(|| AbcParseError::missing(Some("item.data"), i, Some(layout.desc), "desc"))?
+            .to_string();
+        for (field, value) in [(ProductField::Sku, raw_sku), (ProductField::Desc, &desc)] {
+            if value.trim() != value {
+                warnings.push(ParseWarning::UntrimmedField {
+                    sku: sku.to_string(),
+                    field,
+                });
+            }
+        }
+        let upc_column = row.get(layout.upcs).ok_or_else(|| {
+            AbcParseError::missing(Some("item.data"), i, Some(layout.upcs), "upcs")
+        })?;
+        // ABC writes UPCs as a quoted list such as ["123","456"], so only characters other
+        // than those are worth reporting
+        if upc_column
+            .chars()
+            .any(|c| !c.is_ascii_digit() && !",[]\"\\ ".contains(c))
+        {
+            warnings.push(ParseWarning::TrimmedUpcs {
+                sku: sku.to_string(),
+                raw: upc_column.to_string(),
+            });
+        }
+        let upc_str: String = upc_column
+            .chars()
+            .filter(|c| c.is_digit(10) || *c == ',')
+            .collect();
+        let raw_upcs: Vec<String> = upc_str
+            .split(",")
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+        let mut upcs: Vec<Ean13> = Vec::new();
+        for raw in &raw_upcs {
+            match upc_from_str(raw) {
+                Some(upc) => upcs.push(upc),
+                None => warnings.push(ParseWarning::DroppedUpc {
+                    sku: sku.to_string(),
+                    raw: raw.to_string(),
+                }),
+            }
+        }
+        let list = row.get(layout.list).ok_or_else(|| {
+            AbcParseError::missing(Some("item.data"), i, Some(layout.list), "list")
+        })?;
+        let list = price_from_str(list).map_err(|e| {
+            AbcParseError::invalid(Some("item.data"), i, layout.list, "list", list, e)
+        })?;
+        let cost = row.get(layout.cost).ok_or_else(|| {
+            AbcParseError::missing(Some("item.data"), i, Some(layout.cost), "cost")
+        })?;
+        let cost = price_from_str(cost).map_err(|e| {
+            AbcParseError::invalid(Some("item.data"), i, layout.cost, "cost", cost, e)
+        })?;
+        // Average cost is frequently exported as "null", in which case only last cost is known
+        let avg_cost = row
+            .get(layout.avg_cost)
+            .and_then(|c| price_from_str(c).ok());
+        let weight_str = row.get(layout.weight).ok_or_else(|| {
+            AbcParseError::missing(Some("item.data"), i, Some(layout.weight), "weight")
+        })?;
+        let weight = match weight_str.parse::<f64>() {
+            Ok(f) => Some(f),
+            Err(_) => None,
+        };
+        if weight.is_none() && !weight_str.trim().is_empty() {
+            warnings.push(ParseWarning::UnparsedWeight {
+                sku: sku.to_string(),
+                raw: weight_str.to_string(),
+            });
         }
-        Ok(products)
+        let group = match row.get(layout.group).filter(|g| !g.trim().is_empty()) {
+            Some(g) => {
+                let group = g.parse::<AbcGroup>().ok();
+                if group.is_none() {
+                    warnings.push(ParseWarning::UnparsedGroup {
+                        sku: sku.to_string(),
+                        raw: g.to_string(),
+                    });
+                }
+                group
+            }
+            None => None,
+        };
+        let alt_skus = [
+            row.get(layout.alt_skus[0]),
+            row.get(layout.alt_skus[1]),
+            row.get(layout.alt_skus[2]),
+        ]
+        .iter()
+        .filter_map(|o| match o {
+            Some(s) if s.trim().is_empty() => None,
+            Some(s) => Some(Sku::new(s)),
+            None => None,
+        })
+        .collect();
+        let core_sku = layout
+            .core_sku
+            .and_then(|c| row.get(c))
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string());
+        let warranty_months = layout
+            .warranty_months
+            .and_then(|c| row.get(c))
+            .and_then(|m| m.trim().parse().ok());
+        let net_contents = layout
+            .net_contents
+            .and_then(|c| row.get(c))
+            .and_then(|n| n.parse().ok());
+        let country_of_origin = layout
+            .country_of_origin
+            .and_then(|c| row.get(c))
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string());
+        let stock_level = |column: Option<usize>| {
+            column
+                .and_then(|c| row.get(c))
+                .and_then(|q| q.trim().parse::<f64>().ok())
+        };
+        let min_stock = stock_level(layout.min_stock);
+        let max_stock = stock_level(layout.max_stock);
+        Ok(IntermediateBaseProduct {
+            row: i,
+            sku,
+            desc,
+            upcs,
+            raw_upcs,
+            list,
+            cost,
+            avg_cost,
+            weight,
+            group,
+            alt_skus,
+            core_sku,
+            warranty_months,
+            net_contents,
+            country_of_origin,
+            min_stock,
+            max_stock,
+        })
     }
 }
 
@@ -637,11 +2712,12 @@ mod tests {
             products,
             AbcProductsBySku::from([
                 (
-                    "123456".to_string(),
+                    Sku::new("123456"),
                     AbcProduct::new()
                         .with_sku("123456")
                         .with_desc("PRODUCT A")
                         .add_upc(Ean13::from_str_nonstrict("85875500014").unwrap())
+                        .with_raw_upcs(&["085875500014".to_string()])
                         .with_cost(Decimal::new(123, 2))
                         .with_stock(0.00)
                         .with_list(Decimal::new(599, 2))
@@ -651,7 +2727,7 @@ mod tests {
                         .unwrap()
                 ),
                 (
-                    "ABC123".to_string(),
+                    Sku::new("ABC123"),
                     AbcProduct::new()
                         .with_sku("ABC123")
                         .with_desc("PRODUCT B")
@@ -668,4 +2744,488 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_parser_windows_line_endings() {
+        let crlf = |data: &[u8]| {
+            let mut out = vec![0xef, 0xbb, 0xbf];
+            for b in data {
+                if *b == b'\n' {
+                    out.extend_from_slice(b"\r\r\n");
+                } else {
+                    out.push(*b);
+                }
+            }
+            out
+        };
+        let products = AbcProduct::from_readers(
+            crlf(testing::SAMPLE_ITEM_DATA).as_slice(),
+            crlf(testing::SAMPLE_ITEM_POSTED_DATA).as_slice(),
+        )
+        .unwrap();
+        assert_eq!(products, testing::sample_export());
+    }
+
+    #[test]
+    fn test_parse_warnings() {
+        let item = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("[\\\"085875500014\\\"]", "[\\\"123\\\"]");
+        let posted = "123456\t2024-13-45\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t0\n";
+        let (products, warnings) =
+            AbcProduct::from_readers_with_warnings(item.as_bytes(), posted.as_bytes()).unwrap();
+        assert_eq!(products.len(), 1);
+        assert_eq!(
+            warnings,
+            vec![
+                ParseWarning::DroppedUpc {
+                    sku: "123456".to_string(),
+                    raw: "123".to_string()
+                },
+                ParseWarning::UnparsedLastSold {
+                    sku: "123456".to_string(),
+                    raw: "2024-13-45".to_string()
+                },
+                ParseWarning::OrphanSku {
+                    sku: "ABC123".to_string(),
+                    file: "item.data".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_validated() {
+        let builder = AbcProduct::new()
+            .with_sku("A1")
+            .with_desc("WIDGET")
+            .with_list(Decimal::new(500, 2))
+            .with_cost(Decimal::new(650, 2))
+            .with_stock(1.0);
+        let (product, warnings) = builder.clone().build_validated().unwrap();
+        assert_eq!(product.sku(), "A1");
+        assert_eq!(
+            warnings,
+            vec![ValidationIssue::CostAboveList {
+                cost: Decimal::new(650, 2),
+                list: Decimal::new(500, 2)
+            }]
+        );
+
+        let report = AbcProductBuilder::new()
+            .with_cost(Decimal::NEGATIVE_ONE)
+            .build_validated()
+            .unwrap_err();
+        assert_eq!(report.errors.len(), 5);
+        assert_eq!(
+            report.to_string(),
+            "The sku is missing; The desc is missing; The list is missing; \
+             The cost -1 is negative; The stock is missing"
+        );
+    }
+
+    #[test]
+    fn test_mut_builder() {
+        let sample = &testing::sample_export()["ABC123"];
+        let mut builder = AbcProduct::new();
+        builder.set_sku("abc123 ").set_desc("PRODUCT B");
+        for alt in sample.alt_skus() {
+            builder.push_alt_sku(alt);
+        }
+        builder
+            .set_list(Decimal::new(812, 2))
+            .set_cost(Decimal::new(523, 2))
+            .set_stock(-6.0);
+        if let Some(group) = sample.group() {
+            builder.set_group(group);
+        }
+        if let Some(last_sold) = sample.last_sold() {
+            builder.set_last_sold(last_sold);
+        }
+        assert_eq!(&builder.build().unwrap(), sample);
+    }
+
+    #[test]
+    fn test_sku_normalization() {
+        let item = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("ABC123\t", "abc123  \t");
+        let products =
+            AbcProduct::from_readers(item.as_bytes(), testing::SAMPLE_ITEM_POSTED_DATA).unwrap();
+        assert_eq!(products, testing::sample_export());
+        assert_eq!(products[&Sku::new(" Abc123")].alt_skus(), vec!["ALT SKU"]);
+        assert_eq!(Sku::from("alt sku"), "ALT SKU");
+    }
+
+    #[test]
+    fn test_unparsed_group() {
+        let item = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("\tA\t", "\t12\t");
+        let (products, warnings) = AbcProduct::from_readers_with_warnings(
+            item.as_bytes(),
+            testing::SAMPLE_ITEM_POSTED_DATA,
+        )
+        .unwrap();
+        assert_eq!(products["ABC123"].group(), None);
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::UnparsedGroup {
+                sku: "ABC123".to_string(),
+                raw: "12".to_string()
+            }]
+        );
+        assert_eq!(
+            testing::sample_export()["ABC123"].group(),
+            AbcGroup::new('a')
+        );
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        use std::error::Error;
+
+        let posted = String::from_utf8(testing::SAMPLE_ITEM_POSTED_DATA.to_vec())
+            .unwrap()
+            .replace("\t-6.00\t", "\tlots\t");
+        let err =
+            AbcProduct::from_readers(testing::SAMPLE_ITEM_DATA, posted.as_bytes()).unwrap_err();
+        let AbcParseError::InvalidValue(invalid) = &err else {
+            panic!("expected an invalid value, got {:?}", err);
+        };
+        assert_eq!(invalid.file.as_deref(), Some("item_posted.data"));
+        assert_eq!((invalid.row, invalid.column), (2, 19));
+        assert_eq!(invalid.raw, "lots");
+        assert!(matches!(invalid.source, ValueError::Number(_)));
+        assert_eq!(
+            err.to_string(),
+            "Cannot parse stock `lots` in row 2, column 19 of item_posted.data: not a number"
+        );
+        let cause = err.source().and_then(|e| e.source()).unwrap();
+        assert_eq!(cause.to_string(), "invalid float literal");
+
+        let item = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("\t8.12\t", "\t8.1.2\t");
+        let err = AbcProduct::from_readers(item.as_bytes(), testing::SAMPLE_ITEM_POSTED_DATA)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AbcParseError::InvalidValue(e) if e.field == "list" && matches!(e.source, ValueError::Price(_))
+        ));
+
+        let posted: String = String::from_utf8(testing::SAMPLE_ITEM_POSTED_DATA.to_vec())
+            .unwrap()
+            .lines()
+            .enumerate()
+            .map(|(i, line)| match i {
+                1 => line.split('\t').take(5).collect::<Vec<_>>().join("\t") + "\n",
+                _ => line.to_string() + "\n",
+            })
+            .collect();
+        let err =
+            AbcProduct::from_readers(testing::SAMPLE_ITEM_DATA, posted.as_bytes()).unwrap_err();
+        let AbcParseError::CsvError(csv) = &err else {
+            panic!("expected a csv error, got {:?}", err);
+        };
+        assert_eq!(csv.file.as_deref(), Some("item_posted.data"));
+        assert_eq!((csv.row, csv.column), (Some(2), None));
+
+        let first_row = |data: &[u8]| {
+            String::from_utf8(data.to_vec())
+                .unwrap()
+                .lines()
+                .next()
+                .unwrap()
+                .to_string()
+        };
+        let posted = first_row(testing::SAMPLE_ITEM_POSTED_DATA)
+            .split('\t')
+            .take(5)
+            .collect::<Vec<_>>()
+            .join("\t");
+        let item = first_row(testing::SAMPLE_ITEM_DATA);
+        let err = AbcProduct::from_readers(item.as_bytes(), posted.as_bytes()).unwrap_err();
+        let AbcParseError::MissingField(missing) = &err else {
+            panic!("expected a missing field, got {:?}", err);
+        };
+        assert_eq!(missing.file.as_deref(), Some("item_posted.data"));
+        assert_eq!((missing.row, missing.column), (1, Some(19)));
+        assert_eq!(
+            err.to_string(),
+            "Missing field `stock` in row 1, column 19 of item_posted.data"
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let item = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("\t8.12\t", "\tabc\t");
+        let parse = |policy: ErrorPolicy| {
+            AbcProduct::from_readers_with_options(
+                item.as_bytes(),
+                testing::SAMPLE_ITEM_POSTED_DATA,
+                &ParseOptions::default().with_on_error(policy),
+            )
+        };
+        assert!(parse(ErrorPolicy::Fail).is_err());
+
+        let (products, errors, warnings) = parse(ErrorPolicy::Skip).unwrap();
+        assert_eq!(products.keys().collect::<Vec<&Sku>>(), vec!["123456"]);
+        assert!(errors.is_empty());
+        // The posted row of the product that failed to parse has nothing to join with
+        assert_eq!(
+            warnings,
+            vec![ParseWarning::OrphanSku {
+                sku: "ABC123".to_string(),
+                file: "item_posted.data".to_string(),
+            }]
+        );
+
+        let (_, errors, _) = parse(ErrorPolicy::Collect).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 2);
+
+        let layout = ColumnLayout {
+            list: 8,
+            ..Default::default()
+        };
+        let (products, _, _) = AbcProduct::from_readers_with_options(
+            testing::SAMPLE_ITEM_DATA,
+            testing::SAMPLE_ITEM_POSTED_DATA,
+            &ParseOptions::default().with_layout(layout),
+        )
+        .unwrap();
+        assert_eq!(products["123456"].list(), Decimal::new(123, 2));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut item = testing::SAMPLE_ITEM_DATA.to_vec();
+        let at = item.windows(9).position(|w| w == b"PRODUCT A").unwrap() + 7;
+        item.splice(at..at + 1, *b"\x96");
+        let parse = |encoding: Encoding| {
+            AbcProduct::from_readers_with_options(
+                &item[..],
+                testing::SAMPLE_ITEM_POSTED_DATA,
+                &ParseOptions::default().with_encoding(encoding),
+            )
+        };
+        let (products, _, _) = parse(Encoding::Auto).unwrap();
+        assert_eq!(products["123456"].desc(), "PRODUCT\u{2013}A");
+        let Err(AbcParseError::CsvError(err)) = parse(Encoding::Utf8) else {
+            panic!("expected a csv error");
+        };
+        assert_eq!(err.file.as_deref(), Some("item.data"));
+        assert_eq!((err.row, err.column), (Some(1), Some(1)));
+
+        let stream = |encoding: Encoding| {
+            AbcProductIter::from_readers_with_options(
+                &item[..],
+                testing::SAMPLE_ITEM_POSTED_DATA,
+                &ParseOptions::default().with_encoding(encoding),
+            )
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+        };
+        assert!(stream(Encoding::Auto).is_ok());
+        assert!(stream(Encoding::Utf8).is_err());
+    }
+
+    #[test]
+    fn test_core_charge() {
+        let layout = ColumnLayout {
+            core_sku: Some(records::item_columns::ALT_SKUS[2]),
+            ..Default::default()
+        };
+        let (parsed, _, _) = AbcProduct::from_readers_with_options(
+            testing::SAMPLE_ITEM_DATA,
+            testing::SAMPLE_ITEM_POSTED_DATA,
+            &ParseOptions::default().with_layout(layout),
+        )
+        .unwrap();
+        assert_eq!(parsed["123456"].core_sku(), Some("ALT".to_string()));
+
+        let mut products = testing::sample_export();
+        assert_eq!(products["123456"].core_sku(), None);
+        let battery = AbcProductBuilder::from(products["123456"].clone())
+            .with_core_sku("ABC123")
+            .build()
+            .unwrap();
+        products.insert(Sku::new("123456"), battery);
+        assert_eq!(
+            products["123456"].core_charge(&products),
+            Some(Decimal::new(812, 2))
+        );
+        assert_eq!(
+            products["123456"].list_with_core(&products),
+            Decimal::new(1411, 2)
+        );
+        assert_eq!(products["ABC123"].core_charge(&products), None);
+        assert_eq!(
+            AbcProduct::core_skus(&products),
+            HashSet::from(["ABC123".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_avg_cost() {
+        let item = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("\t1.23\tnull\t", "\t1.23\t1.10\t");
+        let products =
+            AbcProduct::from_readers(item.as_bytes(), testing::SAMPLE_ITEM_POSTED_DATA).unwrap();
+        assert_eq!(products["123456"].avg_cost(), Some(Decimal::new(110, 2)));
+        assert_eq!(products["ABC123"].avg_cost(), None);
+
+        assert_eq!(
+            products["123456"].cost_basis(CostBasis::Average),
+            Decimal::new(110, 2)
+        );
+        assert_eq!(
+            products["123456"].cost_basis(CostBasis::Last),
+            Decimal::new(123, 2)
+        );
+        // Without an average cost, the last cost is used instead
+        assert_eq!(
+            products["ABC123"].cost_basis(CostBasis::Average),
+            Decimal::new(523, 2)
+        );
+        assert_eq!(
+            products["ABC123"].cost_basis(CostBasis::Last),
+            Decimal::new(523, 2)
+        );
+    }
+
+    #[test]
+    fn test_product_iter() {
+        let products: AbcProductsBySku = AbcProductIter::from_readers(
+            testing::SAMPLE_ITEM_DATA,
+            testing::SAMPLE_ITEM_POSTED_DATA,
+        )
+        .unwrap()
+        .map(|p| p.map(|p| (p.sku.clone(), p)))
+        .collect::<Result<_, _>>()
+        .unwrap();
+        assert_eq!(products, testing::sample_export());
+
+        let mut short = AbcProductIter::from_readers(
+            testing::SAMPLE_ITEM_DATA,
+            &testing::SAMPLE_ITEM_POSTED_DATA[..1],
+        )
+        .unwrap();
+        assert!(matches!(
+            short.next(),
+            Some(Err(AbcParseError::MisMatchedSkus))
+        ));
+        assert!(short.next().is_none());
+
+        let item = testing::moved_item_data();
+        let options = ParseOptions::default()
+            .with_layout(records::KnownLayout::moved_list_and_weight().layout);
+        let moved: AbcProductsBySku = AbcProductIter::from_readers_with_options(
+            item.as_bytes(),
+            testing::SAMPLE_ITEM_POSTED_DATA,
+            &options,
+        )
+        .unwrap()
+        .map(|p| p.map(|p| (p.sku.clone(), p)))
+        .collect::<Result<_, _>>()
+        .unwrap();
+        assert_eq!(moved["123456"].list(), Decimal::new(599, 2));
+        assert_eq!(moved["ABC123"].list(), Decimal::new(812, 2));
+        assert_eq!(moved["ABC123"].weight(), Some(2.5));
+    }
+
+    #[test]
+    fn test_product_iter_matches_from_readers() {
+        let item: String = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let mut fields: Vec<&str> = line.split('\t').collect();
+                fields[records::item_columns::ALT_SKUS[0]] = " ";
+                fields[records::item_columns::GROUP] = "??";
+                if i == 1 {
+                    fields[records::item_columns::LIST] = "abc";
+                }
+                fields.join("\t") + "\n"
+            })
+            .collect();
+        let options = ParseOptions::default().with_on_error(ErrorPolicy::Collect);
+        let (products, errors, warnings) = AbcProduct::from_readers_with_options(
+            item.as_bytes(),
+            testing::SAMPLE_ITEM_POSTED_DATA,
+            &options,
+        )
+        .unwrap();
+
+        let mut iter = AbcProductIter::from_readers_with_options(
+            item.as_bytes(),
+            testing::SAMPLE_ITEM_POSTED_DATA,
+            &options,
+        )
+        .unwrap();
+        let streamed: AbcProductsBySku = iter
+            .by_ref()
+            .map(|p| p.map(|p| (p.sku.clone(), p)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(streamed, products);
+        assert!(
+            streamed
+                .values()
+                .all(|p| p.alt_skus.iter().all(|s| !s.to_string().is_empty()))
+        );
+        let rows = |errors: &RowErrors| errors.iter().map(|(row, _)| *row).collect::<Vec<_>>();
+        assert_eq!(rows(iter.errors()), rows(&errors));
+        assert_eq!(rows(&errors), vec![2]);
+        assert!(
+            iter.warnings()
+                .iter()
+                .any(|w| matches!(w, ParseWarning::UnparsedGroup { .. }))
+        );
+        // Rows are dropped in pairs when streaming, so no orphan sku is left behind
+        let warnings: ParseWarnings = warnings
+            .into_iter()
+            .filter(|w| !matches!(w, ParseWarning::OrphanSku { .. }))
+            .collect();
+        assert_eq!(iter.warnings(), &warnings);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let products = testing::sample_export();
+        let json = serde_json::to_string(&products).unwrap();
+        assert!(json.contains(r#""upcs":["0085875500015"]"#));
+        assert!(json.contains(r#""list":"5.99""#));
+        let parsed: AbcProductsBySku = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, products);
+    }
+
+    #[test]
+    fn test_slug() {
+        let products = crate::testing::sample_export();
+        assert_eq!(products["123456"].slug(), "product-a-123456");
+        assert!(AbcProduct::duplicate_slugs(&products).is_empty());
+
+        let mut products = products;
+        for sku in ["ABC-123", "ABC.123"] {
+            let dup = AbcProductBuilder::from(products["ABC123"].clone())
+                .with_sku(sku)
+                .build()
+                .unwrap();
+            products.insert(dup.sku().into(), dup);
+        }
+        assert_eq!(
+            AbcProduct::duplicate_slugs(&products),
+            vec![(
+                "product-b-abc-123".to_string(),
+                vec!["ABC-123".to_string(), "ABC.123".to_string()]
+            )]
+        );
+    }
 }
@@ -0,0 +1,113 @@
+use rust_decimal::{Decimal, RoundingStrategy, prelude::FromPrimitive};
+
+/// The declared net quantity of a packaged product, such as `12 OZ` or `1.5 LB`, as printed on
+/// shelf labels where weights and measures rules require it.
+///
+/// # Example
+/// ```rust
+/// use abc_product::measure::NetContents;
+/// use rust_decimal::Decimal;
+///
+/// let contents: NetContents = "16 fl oz".parse().unwrap();
+/// assert_eq!(contents.to_string(), "16 FL OZ");
+/// assert_eq!(contents.unit_price(Decimal::new(399, 2)), Some(Decimal::new(25, 2)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetContents {
+    quantity: f64,
+    unit: String,
+}
+
+impl NetContents {
+    /// Create a [`NetContents`] of `quantity` of `unit`, such as `12.0` and `"oz"`
+    ///
+    /// # Returns
+    /// [`None`] if `quantity` is not a positive number or `unit` is blank
+    pub fn new(quantity: f64, unit: &str) -> Option<Self> {
+        let unit = unit.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if !quantity.is_finite() || quantity <= 0.0 || unit.is_empty() {
+            return None;
+        }
+        Some(NetContents {
+            quantity,
+            unit: unit.to_uppercase(),
+        })
+    }
+
+    /// How many units the package holds
+    pub fn quantity(&self) -> f64 {
+        self.quantity
+    }
+
+    /// The unit of measure, in uppercase
+    pub fn unit(&self) -> String {
+        self.unit.to_owned()
+    }
+
+    /// The price of one unit when the package sells for `price`, rounded to the cent
+    ///
+    /// # Returns
+    /// [`None`] if the quantity cannot be represented as a [`Decimal`]
+    pub fn unit_price(&self, price: Decimal) -> Option<Decimal> {
+        let quantity = Decimal::from_f64(self.quantity)?;
+        Some((price / quantity).round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero))
+    }
+}
+
+impl std::fmt::Display for NetContents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.quantity, self.unit)
+    }
+}
+
+/// The error returned when text cannot be read as [`NetContents`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetContentsParseError(pub String);
+
+impl std::fmt::Display for NetContentsParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot read `{}` as a quantity and unit", self.0)
+    }
+}
+
+impl std::error::Error for NetContentsParseError {}
+
+impl std::str::FromStr for NetContents {
+    type Err = NetContentsParseError;
+
+    /// Read text such as `12 OZ`, `1.5LB`, or `16 fl oz`: a number followed by a unit
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (quantity, unit) = s.split_at(split);
+        quantity
+            .parse::<f64>()
+            .ok()
+            .and_then(|q| NetContents::new(q, unit))
+            .ok_or(NetContentsParseError(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_net_contents() {
+        let contents: NetContents = "1.5LB".parse().unwrap();
+        assert_eq!(contents.quantity(), 1.5);
+        assert_eq!(contents.unit(), "LB");
+        assert_eq!(contents.to_string(), "1.5 LB");
+        assert_eq!(
+            contents.unit_price(Decimal::new(500, 2)),
+            Some(Decimal::new(333, 2))
+        );
+
+        assert!("OZ".parse::<NetContents>().is_err());
+        assert!("0 OZ".parse::<NetContents>().is_err());
+        assert!("12".parse::<NetContents>().is_err());
+    }
+}
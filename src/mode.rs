@@ -0,0 +1,120 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// One side effect that a dry run skipped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedOp {
+    /// What would have been done, such as "write" or "remove"
+    pub action: String,
+    /// What it would have been done to, such as a file path or API endpoint
+    pub target: String,
+    /// Extra detail worth reviewing, such as the number of bytes that would have been written
+    pub detail: Option<String>,
+}
+
+impl fmt::Display for PlannedOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.action, self.target)?;
+        if let Some(detail) = &self.detail {
+            write!(f, " ({})", detail)?;
+        }
+        Ok(())
+    }
+}
+
+/// A shared record of the [`PlannedOp`]s skipped during a dry run. Clones share the same record,
+/// so one [`DryRun`] can be handed to every writer and integration taking part in a sync
+#[derive(Debug, Clone, Default)]
+pub struct DryRun {
+    planned: Arc<Mutex<Vec<PlannedOp>>>,
+}
+
+impl DryRun {
+    /// Create an empty record
+    pub fn new() -> Self {
+        DryRun::default()
+    }
+
+    /// Record an operation that was skipped
+    pub fn record(&self, action: &str, target: &str, detail: Option<String>) {
+        let op = PlannedOp {
+            action: action.to_string(),
+            target: target.to_string(),
+            detail,
+        };
+        // A panic while holding the lock cannot leave the Vec half modified, so recover it
+        self.planned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(op);
+    }
+
+    /// Every operation recorded so far, in the order they were skipped
+    pub fn planned(&self) -> Vec<PlannedOp> {
+        self.planned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+impl PartialEq for DryRun {
+    /// Two [`DryRun`]s are equal when they share the same record
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.planned, &other.planned)
+    }
+}
+
+impl Eq for DryRun {}
+
+/// Whether writers and integrations should actually perform their side effects.
+///
+/// Everything in this crate that writes files or pushes data takes a [`Mode`], directly or
+/// through its options. In [`Mode::DryRun`], nothing is written. Each skipped side effect is
+/// recorded instead, giving a reviewable plan of what a live run would do.
+///
+/// # Example
+/// ```rust
+/// use std::path::Path;
+/// use abc_product::{export::{self, WriteOptions}, mode::{DryRun, Mode}};
+///
+/// let dry_run = DryRun::new();
+/// let options = WriteOptions {
+///     mode: Mode::DryRun(dry_run.clone()),
+///     ..Default::default()
+/// };
+/// export::write_file(Path::new("products.txt"), &options, |w| w.write_all(b"sku")).unwrap();
+/// assert_eq!(dry_run.planned()[0].to_string(), "write products.txt (3 bytes)");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Perform side effects as normal
+    #[default]
+    Live,
+    /// Skip side effects and record them in the given [`DryRun`]
+    DryRun(DryRun),
+}
+
+impl Mode {
+    /// Whether side effects are being skipped
+    pub fn is_dry_run(&self) -> bool {
+        matches!(self, Mode::DryRun(_))
+    }
+
+    /// Check whether an operation should be performed, recording it if this is a dry run.
+    ///
+    /// # Returns
+    /// `true` if the caller should go ahead with the operation, `false` if it was recorded
+    /// instead
+    pub fn proceed(&self, action: &str, target: &str, detail: Option<String>) -> bool {
+        match self {
+            Mode::Live => true,
+            Mode::DryRun(dry_run) => {
+                dry_run.record(action, target, detail);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{AbcParseError, AbcProduct, AbcProductsBySku, catalog::AbcCatalog};
+
+/// Write each of `products` as one line of JSON.
+///
+/// Fields are named as in [`crate::ProductField::name`], prices are written as strings so that
+/// no precision is lost, and dates are written as YYYY-MM-DD. Products are written as they are
+/// read from `products`, so nothing has to be collected first.
+///
+/// # Arguments
+/// * `products` - The products to write
+/// * `writer` - Where to write the lines
+///
+/// # Errors
+/// Any [`std::io::Error`] raised by `writer`
+pub fn write<'a>(
+    products: impl IntoIterator<Item = &'a AbcProduct>,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    for product in products {
+        serde_json::to_writer(&mut writer, product)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Read products written by [`write`] one line at a time. Blank lines are skipped
+///
+/// # Returns
+/// Each product in the order it was written, or an [`AbcParseError::InvalidJson`] for a line
+/// that is not a product. Reading continues after a bad line
+///
+/// # Example
+/// ```rust
+/// use abc_product::{ndjson, testing};
+///
+/// let products = testing::sample_export();
+/// let mut lines = Vec::new();
+/// ndjson::write(products.values(), &mut lines).unwrap();
+/// let read: Vec<_> = ndjson::read(lines.as_slice()).map(|p| p.unwrap()).collect();
+/// assert_eq!(read.len(), 2);
+/// ```
+pub fn read(reader: impl BufRead) -> impl Iterator<Item = Result<AbcProduct, AbcParseError>> {
+    reader.lines().enumerate().filter_map(|(i, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return Some(Err(csv::Error::from(e).into())),
+        };
+        if line.trim().is_empty() {
+            return None;
+        }
+        Some(serde_json::from_str(&line).map_err(|e| AbcParseError::InvalidJson(i + 1, e)))
+    })
+}
+
+impl AbcCatalog {
+    /// Write every product as one line of JSON, sorted by sku. See [`write`]
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised by `writer`
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{catalog::AbcCatalog, testing};
+    ///
+    /// let catalog = AbcCatalog::new(testing::sample_export());
+    /// let mut out = Vec::new();
+    /// catalog.to_ndjson(&mut out).unwrap();
+    /// let out = String::from_utf8(out).unwrap();
+    /// assert!(out.lines().next().unwrap().starts_with(r#"{"sku":"123456","desc":"PRODUCT A""#));
+    /// assert_eq!(AbcCatalog::from_ndjson(out.as_bytes()).unwrap(), catalog);
+    /// ```
+    pub fn to_ndjson(&self, writer: impl Write) -> io::Result<()> {
+        let mut sorted: Vec<&AbcProduct> = self.iter().collect();
+        sorted.sort_by_key(|p| p.sku());
+        write(sorted, writer)
+    }
+
+    /// Read a catalog written by [`AbcCatalog::to_ndjson`]. Use [`read`] instead to handle each
+    /// product as it is read rather than holding them all
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] for the first line that cannot be read or is not a product
+    pub fn from_ndjson(reader: impl BufRead) -> Result<Self, AbcParseError> {
+        let mut products = AbcProductsBySku::new();
+        for product in read(reader) {
+            let product = product?;
+            products.insert(product.sku.clone(), product);
+        }
+        Ok(AbcCatalog::new(products))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_ndjson() {
+        let products = sample_export();
+        let mut out = Vec::new();
+        AbcCatalog::new(products.clone())
+            .to_ndjson(&mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(r#""upcs":["0085875500015"]"#));
+        assert!(lines[1].contains(r#""list":"8.12""#));
+        assert!(lines[1].contains(r#""last_sold":"2019-05-28""#));
+        assert!(lines[1].contains(r#""group":"A""#));
+
+        let input = format!("{}\n\nnot json\n{}\n", lines[0], lines[1]);
+        let read: Vec<_> = read(input.as_bytes()).collect();
+        assert_eq!(read.len(), 3);
+        assert_eq!(read[0].as_ref().unwrap(), &products["123456"]);
+        assert!(matches!(read[1], Err(AbcParseError::InvalidJson(3, _))));
+        assert_eq!(read[2].as_ref().unwrap(), &products["ABC123"]);
+        assert!(AbcCatalog::from_ndjson(input.as_bytes()).is_err());
+    }
+}
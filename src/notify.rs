@@ -0,0 +1,164 @@
+use serde_json::{Value, json};
+
+use crate::{AbcParseError, AbcProductsBySku};
+
+/// A short report of what a pipeline run did, ready to be posted to chat with [`slack_blocks`]
+/// or [`teams_card`].
+///
+/// # Example
+/// ```rust
+/// use abc_product::{notify::{self, Summary}, testing};
+///
+/// let summary = Summary::from_products(&testing::sample_export())
+///     .with_fact("Pushed", "2");
+/// let body = notify::slack_blocks(&summary);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// A one line headline such as "Nightly sync finished"
+    pub title: String,
+    /// Labelled values such as ("Products", "1520")
+    pub facts: Vec<(String, String)>,
+    /// Data quality problems or failures that someone should look at
+    pub alerts: Vec<String>,
+}
+
+impl Summary {
+    /// Create an empty [`Summary`] with the given `title`
+    pub fn new(title: &str) -> Self {
+        Summary {
+            title: title.to_string(),
+            facts: Vec::new(),
+            alerts: Vec::new(),
+        }
+    }
+
+    /// Add a labelled value to this summary
+    pub fn with_fact(self, name: &str, value: impl ToString) -> Self {
+        let mut facts = self.facts;
+        facts.push((name.to_string(), value.to_string()));
+        Summary { facts, ..self }
+    }
+
+    /// Add an alert to this summary
+    pub fn with_alert(self, alert: &str) -> Self {
+        let mut alerts = self.alerts;
+        alerts.push(alert.to_string());
+        Summary { alerts, ..self }
+    }
+
+    /// Summarize a freshly parsed export with product counts, and an alert when any products
+    /// have negative stock
+    pub fn from_products(products: &AbcProductsBySku) -> Self {
+        let in_stock = products.values().filter(|p| p.stock() > 0.0).count();
+        let negative = products.values().filter(|p| p.stock() < 0.0).count();
+        let summary = Summary::new("ABC export parsed")
+            .with_fact("Products", products.len())
+            .with_fact("In stock", in_stock)
+            .with_fact("Negative stock", negative);
+        if negative > 0 {
+            summary.with_alert(&format!("Products with negative stock: {}", negative))
+        } else {
+            summary
+        }
+    }
+
+    /// Summarize the result of parsing an export. A failed parse becomes an alert
+    pub fn from_parse_result(result: &Result<AbcProductsBySku, AbcParseError>) -> Self {
+        match result {
+            Ok(products) => Summary::from_products(products),
+            Err(e) => Summary::new("ABC export failed to parse").with_alert(&e.to_string()),
+        }
+    }
+}
+
+/// Format `summary` as a Slack Block Kit message body, suitable for posting to an incoming
+/// webhook
+pub fn slack_blocks(summary: &Summary) -> String {
+    let mut blocks: Vec<Value> = vec![json!({
+        "type": "header",
+        "text": { "type": "plain_text", "text": summary.title },
+    })];
+    // Slack allows at most 10 fields in a single section
+    for chunk in summary.facts.chunks(10) {
+        let fields: Vec<Value> = chunk
+            .iter()
+            .map(|(name, value)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", name, value) }))
+            .collect();
+        blocks.push(json!({ "type": "section", "fields": fields }));
+    }
+    if !summary.alerts.is_empty() {
+        let text = summary
+            .alerts
+            .iter()
+            .map(|a| format!(":warning: {}", a))
+            .collect::<Vec<String>>()
+            .join("\n");
+        blocks.push(json!({ "type": "divider" }));
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": text },
+        }));
+    }
+    json!({ "text": summary.title, "blocks": blocks }).to_string()
+}
+
+/// Format `summary` as a Microsoft Teams message carrying an Adaptive Card, suitable for
+/// posting to an incoming webhook
+pub fn teams_card(summary: &Summary) -> String {
+    let mut body: Vec<Value> = vec![json!({
+        "type": "TextBlock",
+        "text": summary.title,
+        "weight": "Bolder",
+        "size": "Medium",
+    })];
+    if !summary.facts.is_empty() {
+        let facts: Vec<Value> = summary
+            .facts
+            .iter()
+            .map(|(name, value)| json!({ "title": name, "value": value }))
+            .collect();
+        body.push(json!({ "type": "FactSet", "facts": facts }));
+    }
+    for alert in &summary.alerts {
+        body.push(json!({
+            "type": "TextBlock",
+            "text": alert,
+            "color": "Attention",
+            "wrap": true,
+        }));
+    }
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": body,
+            },
+        }],
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_slack_blocks() {
+        let summary = Summary::from_products(&sample_export());
+        assert_eq!(
+            summary.alerts,
+            vec!["Products with negative stock: 1".to_string()]
+        );
+        let value: Value = serde_json::from_str(&slack_blocks(&summary)).unwrap();
+        let blocks = value["blocks"].as_array().unwrap();
+        assert_eq!(blocks[0]["text"]["text"], "ABC export parsed");
+        assert_eq!(blocks[1]["fields"][0]["text"], "*Products*\n2");
+        assert_eq!(blocks.len(), 4);
+    }
+}
@@ -0,0 +1,114 @@
+//! A tiny PDF writer covering just what label and document renderers need: pages of Helvetica
+//! text and filled rectangles.
+
+/// Points per millimeter. PDF measures everything in points of 1/72 inch
+#[cfg(feature = "labels")]
+pub(crate) const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// Escape `s` for use inside a PDF string literal. Characters outside of Latin-1 cannot be drawn
+/// by the standard Helvetica font and are replaced with `?`
+fn escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '\\' | '(' | ')' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// Content stream operators that draw `s` with its baseline starting at (`x`, `y`) points from
+/// the bottom left of the page
+pub(crate) fn text(x: f64, y: f64, size: f64, s: &str) -> String {
+    format!(
+        "BT /F1 {:.2} Tf {:.2} {:.2} Td ({}) Tj ET\n",
+        size,
+        x,
+        y,
+        escape(s)
+    )
+}
+
+/// Content stream operators that fill a black rectangle whose bottom left corner is at
+/// (`x`, `y`) points from the bottom left of the page
+#[cfg(feature = "labels")]
+pub(crate) fn rect(x: f64, y: f64, width: f64, height: f64) -> String {
+    format!("{:.2} {:.2} {:.2} {:.2} re f\n", x, y, width, height)
+}
+
+/// A PDF being built one page at a time
+#[derive(Debug, Default)]
+pub(crate) struct PdfDocument {
+    /// The width and height in points and the content stream of each page
+    pages: Vec<(f64, f64, String)>,
+}
+
+impl PdfDocument {
+    pub(crate) fn new() -> Self {
+        PdfDocument::default()
+    }
+
+    /// Add a page of `width` by `height` points drawn by the `content` operators
+    pub(crate) fn add_page(&mut self, width: f64, height: f64, content: String) {
+        self.pages.push((width, height, content));
+    }
+
+    /// Serialize the document into the bytes of a PDF file
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        // Objects 1-3 are the catalog, page tree, and font. Each page then takes two objects:
+        // the page itself followed by its content stream
+        let mut objects: Vec<String> = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                (0..self.pages.len())
+                    .map(|i| format!("{} 0 R", 4 + i * 2))
+                    .collect::<Vec<String>>()
+                    .join(" "),
+                self.pages.len()
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_string(),
+        ];
+        for (i, (width, height, content)) in self.pages.iter().enumerate() {
+            objects.push(format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+                width,
+                height,
+                5 + i * 2
+            ));
+            objects.push(format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                content.len(),
+                content
+            ));
+        }
+
+        let mut out = String::from("%PDF-1.4\n");
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+        }
+        let xref = out.len();
+        out.push_str(&format!(
+            "xref\n0 {}\n0000000000 65535 f \n",
+            objects.len() + 1
+        ));
+        for offset in offsets {
+            out.push_str(&format!("{:010} 00000 n \n", offset));
+        }
+        out.push_str(&format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        ));
+        out.into_bytes()
+    }
+}
@@ -0,0 +1,360 @@
+use std::str::FromStr;
+
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use serde_json::{Value, json};
+
+use crate::{
+    ProductField,
+    mode::Mode,
+    reconcile::{Action, FieldChange, RemoteCatalogWriter, RemoteProduct},
+};
+
+/// The version of the plan file format written by [`Plan::to_json`]
+const PLAN_VERSION: u64 = 1;
+
+/// How export times are written in plan files
+const EXPORT_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Hash `parts` with 64 bit FNV-1a. A zero byte is hashed after each part so that moving
+/// characters from one part to the next changes the hash
+pub(crate) fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+#[derive(Debug)]
+pub enum PlanError {
+    /// The plan file is not valid JSON
+    Json(serde_json::Error),
+    /// The plan file is JSON, but not a plan this version of the crate understands. Contains a
+    /// description of the first problem found
+    Invalid(String),
+}
+
+impl std::fmt::Display for PlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "Plan is not valid JSON: {}", e),
+            Self::Invalid(reason) => write!(f, "Plan is not valid: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+impl From<serde_json::Error> for PlanError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+/// A reviewable set of changes to make to a remote platform, such as the output of
+/// [`crate::reconcile::reconcile`].
+///
+/// Plans are written to a JSON file with [`Plan::to_json`] so someone can review and approve
+/// them, then read back with [`Plan::from_json`] and carried out with [`apply`]. Nothing is
+/// recomputed at apply time, so exactly what was approved is what gets pushed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    /// The changes to make, in order
+    pub actions: Vec<Action>,
+    /// When the export the plan was computed from was taken. Part of every idempotency key, so
+    /// that the same change planned from a later export is treated as a new operation
+    pub export_time: Option<NaiveDateTime>,
+}
+
+/// Read a required string property of a plan action
+fn str_prop(value: &Value, key: &str) -> Result<String, PlanError> {
+    value[key]
+        .as_str()
+        .map(|s| s.to_string())
+        .ok_or(PlanError::Invalid(format!(
+            "missing `{}` in {}",
+            key, value
+        )))
+}
+
+impl Plan {
+    /// Wrap `actions` in a [`Plan`]
+    pub fn new(actions: Vec<Action>) -> Self {
+        Plan {
+            actions,
+            export_time: None,
+        }
+    }
+
+    /// Set when the export this plan was computed from was taken
+    pub fn with_export_time(self, export_time: NaiveDateTime) -> Self {
+        Plan {
+            export_time: Some(export_time),
+            ..self
+        }
+    }
+
+    /// A deterministic key identifying `action` as planned from this plan's export, for APIs
+    /// that accept an idempotency key so that a retried request is not applied twice.
+    ///
+    /// The key is a 16 character hex hash of the sku, the kind of action, every field and value
+    /// it sets, and [`Plan::export_time`]. Planning the same change from the same export always
+    /// gives the same key
+    pub fn idempotency_key(&self, action: &Action) -> String {
+        let export_time = self
+            .export_time
+            .map(|t| t.format(EXPORT_TIME_FORMAT).to_string())
+            .unwrap_or_default();
+        let mut parts: Vec<String> = vec![export_time];
+        match action {
+            Action::Create(product) => parts.extend([
+                "create".to_string(),
+                product.sku.to_string(),
+                product.desc.to_string(),
+                product.price.normalize().to_string(),
+                product.stock.to_string(),
+            ]),
+            Action::Update(sku, changes) => {
+                parts.extend(["update".to_string(), sku.to_string()]);
+                for change in changes {
+                    parts.extend([change.field.name().to_string(), change.local.to_string()]);
+                }
+            }
+            Action::Archive(sku) => parts.extend(["archive".to_string(), sku.to_string()]),
+        }
+        let parts: Vec<&str> = parts.iter().map(|p| p.as_str()).collect();
+        format!("{:016x}", fnv1a(&parts))
+    }
+
+    /// Write this plan as pretty printed JSON for review
+    pub fn to_json(&self) -> String {
+        let actions: Vec<Value> = self
+            .actions
+            .iter()
+            .map(|action| {
+                let mut value = match action {
+                    Action::Create(product) => json!({
+                        "action": "create",
+                        "sku": product.sku,
+                        "desc": product.desc,
+                        "price": product.price.to_string(),
+                        "stock": product.stock,
+                    }),
+                    Action::Update(sku, changes) => json!({
+                        "action": "update",
+                        "sku": sku,
+                        "changes": changes
+                            .iter()
+                            .map(|c| json!({ "field": c.field.name(), "remote": c.remote, "local": c.local }))
+                            .collect::<Vec<Value>>(),
+                    }),
+                    Action::Archive(sku) => json!({ "action": "archive", "sku": sku }),
+                };
+                // Written for reviewers and audit logs only. Keys are recomputed when applying
+                value["idempotency_key"] = json!(self.idempotency_key(action));
+                value
+            })
+            .collect();
+        serde_json::to_string_pretty(&json!({
+            "version": PLAN_VERSION,
+            "export_time": self.export_time.map(|t| t.format(EXPORT_TIME_FORMAT).to_string()),
+            "actions": actions,
+        }))
+        .unwrap_or_default()
+    }
+
+    /// Read a plan written by [`Plan::to_json`]
+    ///
+    /// # Errors
+    /// A [`PlanError`] if `s` is not JSON or does not describe a plan
+    pub fn from_json(s: &str) -> Result<Self, PlanError> {
+        let value: Value = serde_json::from_str(s)?;
+        if value["version"].as_u64() != Some(PLAN_VERSION) {
+            return Err(PlanError::Invalid(format!(
+                "unsupported version {}",
+                value["version"]
+            )));
+        }
+        let export_time = match value["export_time"].as_str() {
+            Some(t) => Some(
+                NaiveDateTime::parse_from_str(t, EXPORT_TIME_FORMAT)
+                    .map_err(|e| PlanError::Invalid(format!("bad export_time: {}", e)))?,
+            ),
+            None => None,
+        };
+        let Some(raw_actions) = value["actions"].as_array() else {
+            return Err(PlanError::Invalid("missing `actions`".to_string()));
+        };
+
+        let mut actions = Vec::new();
+        for raw in raw_actions {
+            let sku = str_prop(raw, "sku")?;
+            let action = match raw["action"].as_str() {
+                Some("create") => Action::Create(RemoteProduct {
+                    sku,
+                    desc: str_prop(raw, "desc")?,
+                    price: Decimal::from_str(&str_prop(raw, "price")?)
+                        .map_err(|e| PlanError::Invalid(format!("bad price: {}", e)))?,
+                    stock: raw["stock"]
+                        .as_f64()
+                        .ok_or(PlanError::Invalid(format!("missing `stock` in {}", raw)))?,
+                }),
+                Some("update") => {
+                    let mut changes = Vec::new();
+                    for change in raw["changes"].as_array().unwrap_or(&Vec::new()) {
+                        let name = str_prop(change, "field")?;
+                        let field = ProductField::ALL
+                            .into_iter()
+                            .find(|f| f.name() == name)
+                            .ok_or(PlanError::Invalid(format!("unknown field `{}`", name)))?;
+                        changes.push(FieldChange {
+                            field,
+                            remote: str_prop(change, "remote")?,
+                            local: str_prop(change, "local")?,
+                        });
+                    }
+                    Action::Update(sku, changes)
+                }
+                Some("archive") => Action::Archive(sku),
+                _ => {
+                    return Err(PlanError::Invalid(format!(
+                        "unknown action {}",
+                        raw["action"]
+                    )));
+                }
+            };
+            actions.push(action);
+        }
+        Ok(Plan {
+            actions,
+            export_time,
+        })
+    }
+}
+
+/// Carry out every action of an approved `plan` against `remote`, in order.
+///
+/// # Arguments
+/// * `plan` - The approved plan
+/// * `remote` - The platform to change
+/// * `mode` - In [`Mode::DryRun`], each action is recorded instead of sent to `remote`
+///
+/// # Returns
+/// How many actions were sent to `remote`. Always 0 in [`Mode::DryRun`]
+///
+/// # Errors
+/// The first error raised by `remote`. Actions before the failed one have already been applied
+/// and are not rolled back
+pub fn apply<R: RemoteCatalogWriter>(
+    plan: &Plan,
+    remote: &R,
+    mode: &Mode,
+) -> Result<usize, R::Error> {
+    let mut applied = 0;
+    for action in &plan.actions {
+        match action {
+            Action::Create(product) => {
+                if mode.proceed("create", &product.sku, None) {
+                    remote.create(product, &plan.idempotency_key(action))?;
+                    applied += 1;
+                }
+            }
+            Action::Update(sku, changes) => {
+                let fields: Vec<&str> = changes.iter().map(|c| c.field.name()).collect();
+                if mode.proceed("update", sku, Some(fields.join(", "))) {
+                    remote.update(sku, changes, &plan.idempotency_key(action))?;
+                    applied += 1;
+                }
+            }
+            Action::Archive(sku) => {
+                if mode.proceed("archive", sku, None) {
+                    remote.archive(sku, &plan.idempotency_key(action))?;
+                    applied += 1;
+                }
+            }
+        }
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reconcile::RemoteCatalog, testing::sample_export};
+    use std::{cell::RefCell, collections::HashMap};
+
+    #[derive(Default)]
+    struct MemoryCatalog(RefCell<HashMap<String, RemoteProduct>>);
+
+    impl RemoteCatalog for MemoryCatalog {
+        type Error = ();
+
+        fn fetch_skus(&self) -> Result<Vec<String>, ()> {
+            Ok(self.0.borrow().keys().cloned().collect())
+        }
+
+        fn fetch_product(&self, sku: &str) -> Result<Option<RemoteProduct>, ()> {
+            Ok(self.0.borrow().get(sku).cloned())
+        }
+    }
+
+    impl RemoteCatalogWriter for MemoryCatalog {
+        fn create(&self, product: &RemoteProduct, _key: &str) -> Result<(), ()> {
+            self.0
+                .borrow_mut()
+                .insert(product.sku.to_string(), product.clone());
+            Ok(())
+        }
+
+        fn update(&self, _sku: &str, _changes: &[FieldChange], _key: &str) -> Result<(), ()> {
+            Err(())
+        }
+
+        fn archive(&self, sku: &str, _key: &str) -> Result<(), ()> {
+            self.0.borrow_mut().remove(sku);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_plan_round_trip_and_apply() {
+        let local = sample_export();
+        let remote = MemoryCatalog::default();
+        let export_time = chrono::NaiveDate::from_ymd_opt(2024, 11, 16)
+            .unwrap()
+            .and_hms_opt(2, 0, 0)
+            .unwrap();
+        let plan = Plan::new(crate::reconcile::reconcile(&remote, &local).unwrap())
+            .with_export_time(export_time);
+        assert_eq!(plan.actions.len(), 2);
+        let key = plan.idempotency_key(&plan.actions[0]);
+        assert_eq!(key.len(), 16);
+        assert_eq!(key, plan.idempotency_key(&plan.actions[0].clone()));
+        assert_ne!(
+            key,
+            Plan::new(plan.actions.clone()).idempotency_key(&plan.actions[0])
+        );
+
+        let reviewed = Plan::from_json(&plan.to_json()).unwrap();
+        assert_eq!(reviewed, plan);
+
+        let dry_run = crate::mode::DryRun::new();
+        assert_eq!(
+            apply(&reviewed, &remote, &Mode::DryRun(dry_run.clone())),
+            Ok(0)
+        );
+        assert_eq!(dry_run.planned().len(), 2);
+        assert_eq!(remote.fetch_skus().unwrap().len(), 0);
+
+        assert_eq!(apply(&reviewed, &remote, &Mode::Live), Ok(2));
+        assert!(
+            crate::reconcile::reconcile(&remote, &local)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
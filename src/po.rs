@@ -0,0 +1,473 @@
+use std::{collections::HashMap, io::Read};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::{
+    AbcParseError, Encoding, open_data_file,
+    records::{amount, date, rows, text},
+};
+
+/// Column numbers of the fields this crate knows about in `po.data`, counting from zero
+pub mod po_columns {
+    pub const NUMBER: usize = 0;
+    pub const VENDOR: usize = 1;
+    pub const ORDER_DATE: usize = 2;
+    pub const EXPECTED: usize = 3;
+}
+
+/// Column numbers of the fields this crate knows about in `po_line.data`, counting from zero
+pub mod po_line_columns {
+    pub const NUMBER: usize = 0;
+    pub const SKU: usize = 1;
+    pub const DESC: usize = 2;
+    pub const ORDERED: usize = 3;
+    pub const RECEIVED: usize = 4;
+    pub const COST: usize = 5;
+}
+
+/// A map of purchase order numbers to the [`AbcPurchaseOrder`] they belong to
+pub type AbcPurchaseOrdersByNumber = HashMap<String, AbcPurchaseOrder>;
+
+/// A single line of a purchase order
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbcPoLine {
+    sku: String,
+    desc: String,
+    ordered: f64,
+    received: f64,
+    cost: Decimal,
+}
+
+impl AbcPoLine {
+    /// The sku being ordered
+    pub fn sku(&self) -> String {
+        self.sku.to_string()
+    }
+
+    /// The description printed on this line
+    pub fn desc(&self) -> String {
+        self.desc.to_string()
+    }
+
+    /// How many units were ordered
+    pub fn ordered(&self) -> f64 {
+        self.ordered
+    }
+
+    /// How many units have been received so far
+    pub fn received(&self) -> f64 {
+        self.received
+    }
+
+    /// How many units are still expected. Never less than zero, even if more were received than
+    /// ordered
+    pub fn outstanding(&self) -> f64 {
+        (self.ordered - self.received).max(0.0)
+    }
+
+    /// The cost of each unit agreed with the vendor
+    pub fn cost(&self) -> Decimal {
+        self.cost
+    }
+}
+
+/// An order placed with a vendor, as exported from ABC's purchase order files
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbcPurchaseOrder {
+    number: String,
+    vendor: Option<String>,
+    order_date: Option<NaiveDate>,
+    expected: Option<NaiveDate>,
+    lines: Vec<AbcPoLine>,
+}
+
+impl AbcPurchaseOrder {
+    /// Create a map of purchase order numbers to [`AbcPurchaseOrder`]s by parsing the purchase
+    /// order files of an ABC database export.
+    ///
+    /// Run report 7-10 as described in [`crate::AbcProduct::from_db_export`], but select the
+    /// purchase order file to export. Two files are written next to `item.data`, at
+    /// C:\ABC Software\Database Export\Company001\Data\po.data and
+    /// C:\ABC Software\Database Export\Company001\Data\po_line.data.
+    ///
+    /// # Arguments
+    /// * `po_path` - The path to the po.data file generated by the db export
+    /// * `po_line_path` - The path to the po_line.data file generated by the db export
+    ///
+    /// # Returns
+    /// A [`HashMap`] of purchase order numbers to the [`AbcPurchaseOrder`] they belong to. Lines
+    /// keep the order they have in the export
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file cannot be read, if a row is missing its order number
+    /// or sku, if a quantity or cost cannot be parsed, or if a line belongs to an order that is
+    /// not in `po.data`
+    pub fn from_db_export(
+        po_path: &str,
+        po_line_path: &str,
+    ) -> Result<AbcPurchaseOrdersByNumber, AbcParseError> {
+        let po_file = open_data_file(po_path).map_err(csv::Error::from)?;
+        let po_line_file = open_data_file(po_line_path).map_err(csv::Error::from)?;
+        Self::from_readers(po_file, po_line_file)
+    }
+
+    /// Like [`AbcPurchaseOrder::from_db_export`], reading from any readers rather than from
+    /// paths on disk
+    pub(crate) fn from_readers(
+        po: impl Read,
+        po_line: impl Read,
+    ) -> Result<AbcPurchaseOrdersByNumber, AbcParseError> {
+        let mut orders = AbcPurchaseOrdersByNumber::new();
+        for row in rows(po, "po.data", Encoding::Auto)? {
+            let (row, record) = row?;
+            let number = text(&record, po_columns::NUMBER).ok_or_else(|| {
+                AbcParseError::missing(Some("po.data"), row, Some(po_columns::NUMBER), "number")
+            })?;
+            let order = AbcPurchaseOrder {
+                number: number.clone(),
+                vendor: text(&record, po_columns::VENDOR),
+                order_date: date(&record, po_columns::ORDER_DATE),
+                expected: date(&record, po_columns::EXPECTED),
+                lines: Vec::new(),
+            };
+            orders.insert(number, order);
+        }
+
+        for row in rows(po_line, "po_line.data", Encoding::Auto)? {
+            let (row, record) = row?;
+            let number = text(&record, po_line_columns::NUMBER).ok_or_else(|| {
+                AbcParseError::missing(
+                    Some("po_line.data"),
+                    row,
+                    Some(po_line_columns::NUMBER),
+                    "number",
+                )
+            })?;
+            let qty = |column: usize, name: &str| match text(&record, column) {
+                Some(q) => q.parse::<f64>().map_err(|e| {
+                    AbcParseError::invalid(Some("po_line.data"), row, column, name, &q, e)
+                }),
+                None => Ok(0.0),
+            };
+            let line = AbcPoLine {
+                sku: text(&record, po_line_columns::SKU).ok_or_else(|| {
+                    AbcParseError::missing(
+                        Some("po_line.data"),
+                        row,
+                        Some(po_line_columns::SKU),
+                        "sku",
+                    )
+                })?,
+                desc: text(&record, po_line_columns::DESC).unwrap_or_default(),
+                ordered: qty(po_line_columns::ORDERED, "ordered")?,
+                received: qty(po_line_columns::RECEIVED, "received")?,
+                cost: amount(&record, "po_line.data", po_line_columns::COST, "cost", row)?,
+            };
+            orders
+                .get_mut(&number)
+                .ok_or_else(|| AbcParseError::MissingRecord {
+                    file: "po.data".to_string(),
+                    key: number.to_string(),
+                    row,
+                })?
+                .lines
+                .push(line);
+        }
+        Ok(orders)
+    }
+
+    /// The purchase order number
+    pub fn number(&self) -> String {
+        self.number.to_string()
+    }
+
+    /// The code of the vendor the order was placed with, matching
+    /// [`crate::vendor::AbcVendor::code`]
+    pub fn vendor(&self) -> Option<String> {
+        self.vendor.clone()
+    }
+
+    /// The date the order was placed
+    pub fn order_date(&self) -> Option<NaiveDate> {
+        self.order_date
+    }
+
+    /// The date the vendor is expected to deliver the order
+    pub fn expected(&self) -> Option<NaiveDate> {
+        self.expected
+    }
+
+    /// The lines of the order, in the order they were entered
+    pub fn lines(&self) -> &[AbcPoLine] {
+        &self.lines
+    }
+
+    /// Whether every line of the order has been received in full
+    pub fn is_received(&self) -> bool {
+        self.lines.iter().all(|l| l.outstanding() == 0.0)
+    }
+}
+
+/// Total the units of each sku still expected on `orders`, for counting stock that is already
+/// on its way when deciding what to reorder
+pub fn on_order<'a>(
+    orders: impl IntoIterator<Item = &'a AbcPurchaseOrder>,
+) -> HashMap<String, f64> {
+    let mut on_order: HashMap<String, f64> = HashMap::new();
+    for line in orders.into_iter().flat_map(|o| o.lines.iter()) {
+        if line.outstanding() > 0.0 {
+            *on_order.entry(line.sku.to_string()).or_default() += line.outstanding();
+        }
+    }
+    on_order
+}
+
+/// A quantity of a product counted in off the truck, as entered from the packing slip or
+/// vendor invoice
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiptLine {
+    /// The sku received
+    pub sku: String,
+    /// How many units were received
+    pub qty: f64,
+    /// The cost of each unit on the vendor's invoice, if known. [`None`] to accept the cost on
+    /// the purchase order
+    pub cost: Option<Decimal>,
+}
+
+/// A difference between what was ordered and what was received
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReceiptIssue {
+    /// Fewer units arrived than were still expected on the order
+    Short {
+        sku: String,
+        expected: f64,
+        received: f64,
+    },
+    /// More units arrived than were still expected on the order
+    Over {
+        sku: String,
+        expected: f64,
+        received: f64,
+    },
+    /// The vendor invoiced a different cost than the purchase order agreed
+    CostChanged {
+        sku: String,
+        ordered: Decimal,
+        invoiced: Decimal,
+    },
+    /// A sku arrived that is not on the purchase order at all
+    NotOrdered { sku: String, received: f64 },
+}
+
+impl std::fmt::Display for ReceiptIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Short {
+                sku,
+                expected,
+                received,
+            } => write!(
+                f,
+                "Sku `{}` is short: expected {}, received {}",
+                sku, expected, received
+            ),
+            Self::Over {
+                sku,
+                expected,
+                received,
+            } => write!(
+                f,
+                "Sku `{}` is over: expected {}, received {}",
+                sku, expected, received
+            ),
+            Self::CostChanged {
+                sku,
+                ordered,
+                invoiced,
+            } => write!(
+                f,
+                "Sku `{}` was ordered at {} but invoiced at {}",
+                sku, ordered, invoiced
+            ),
+            Self::NotOrdered { sku, received } => {
+                write!(
+                    f,
+                    "Sku `{}` was not ordered, but {} were received",
+                    sku, received
+                )
+            }
+        }
+    }
+}
+
+/// The result of checking a delivery against its purchase order, as produced by [`reconcile`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reconciliation {
+    /// Every short, over, cost change, and unexpected sku, in the order of the purchase order
+    /// followed by any skus that were not ordered
+    pub issues: Vec<ReceiptIssue>,
+    /// Tab delimited lines of sku, quantity, and cost to import as a receipt in ABC. Every sku
+    /// that was received is included, at the invoiced cost where one was given
+    pub import: String,
+}
+
+/// Check a delivery against the purchase order it fills, and build the receiving import for
+/// ABC.
+///
+/// Quantities are compared with what is still outstanding on `po`, so a second delivery against
+/// a partly received order is not reported as over. Receipt lines for the same sku are added
+/// together.
+///
+/// # Arguments
+/// * `po` - The purchase order being received
+/// * `receipt_lines` - What was counted in
+///
+/// # Returns
+/// A [`Reconciliation`] listing every discrepancy alongside the import for ABC
+pub fn reconcile(po: &AbcPurchaseOrder, receipt_lines: &[ReceiptLine]) -> Reconciliation {
+    let mut received: Vec<(String, f64, Option<Decimal>)> = Vec::new();
+    for line in receipt_lines {
+        match received.iter_mut().find(|(sku, _, _)| *sku == line.sku) {
+            Some((_, qty, cost)) => {
+                *qty += line.qty;
+                *cost = line.cost.or(*cost);
+            }
+            None => received.push((line.sku.to_string(), line.qty, line.cost)),
+        }
+    }
+
+    let mut ordered: Vec<(String, f64, Decimal)> = Vec::new();
+    for line in &po.lines {
+        match ordered.iter_mut().find(|(sku, _, _)| *sku == line.sku) {
+            Some((_, qty, _)) => *qty += line.outstanding(),
+            None => ordered.push((line.sku(), line.outstanding(), line.cost)),
+        }
+    }
+
+    let mut issues = Vec::new();
+    let mut import = String::new();
+    for (sku, expected, po_cost) in &ordered {
+        let (qty, cost) = received
+            .iter()
+            .find(|(s, _, _)| s == sku)
+            .map(|(_, qty, cost)| (*qty, *cost))
+            .unwrap_or((0.0, None));
+        if qty < *expected {
+            issues.push(ReceiptIssue::Short {
+                sku: sku.to_string(),
+                expected: *expected,
+                received: qty,
+            });
+        } else if qty > *expected {
+            issues.push(ReceiptIssue::Over {
+                sku: sku.to_string(),
+                expected: *expected,
+                received: qty,
+            });
+        }
+        if let Some(invoiced) = cost.filter(|c| c != po_cost) {
+            issues.push(ReceiptIssue::CostChanged {
+                sku: sku.to_string(),
+                ordered: *po_cost,
+                invoiced,
+            });
+        }
+        if qty > 0.0 {
+            import.push_str(&format!("{}\t{}\t{}\n", sku, qty, cost.unwrap_or(*po_cost)));
+        }
+    }
+    for (sku, qty, cost) in &received {
+        if ordered.iter().any(|(s, _, _)| s == sku) {
+            continue;
+        }
+        issues.push(ReceiptIssue::NotOrdered {
+            sku: sku.to_string(),
+            received: *qty,
+        });
+        if *qty > 0.0 {
+            import.push_str(&format!("{}\t{}\t{}\n", sku, qty, cost.unwrap_or_default()));
+        }
+    }
+    Reconciliation { issues, import }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_po_parser() {
+        let po = "PO1\tACME\t2024-11-01\t2024-11-15\nPO2\tBOLT\t2024-11-02\tnull\n";
+        let lines = "PO1\t123456\tPRODUCT A\t12\t12\t1.23\n\
+                     PO1\tABC123\tPRODUCT B\t6\t2\t5.23\n\
+                     PO2\t123456\tPRODUCT A\t24\t\t1.20\n";
+        let orders = AbcPurchaseOrder::from_readers(po.as_bytes(), lines.as_bytes()).unwrap();
+        let first = &orders["PO1"];
+        assert_eq!(first.vendor(), Some("ACME".to_string()));
+        assert_eq!(first.expected(), NaiveDate::from_ymd_opt(2024, 11, 15));
+        assert_eq!(first.lines()[1].outstanding(), 4.0);
+        assert!(!first.is_received());
+        assert_eq!(orders["PO2"].expected(), None);
+
+        let on_order = on_order(orders.values());
+        assert_eq!(on_order["123456"], 24.0);
+        assert_eq!(on_order["ABC123"], 4.0);
+
+        assert!(
+            AbcPurchaseOrder::from_readers(po.as_bytes(), "PO1\t\t\t1\t0\t1\n".as_bytes()).is_err()
+        );
+        let missing = "PO1\t123456\tPRODUCT A\t1\t0\t1\nPO3\t123456\tPRODUCT A\t1\t0\t1\n";
+        assert!(matches!(
+            AbcPurchaseOrder::from_readers(po.as_bytes(), missing.as_bytes()),
+            Err(AbcParseError::MissingRecord { row: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let po = "PO1\tACME\t2024-11-01\t2024-11-15\n";
+        let lines = "PO1\t123456\tPRODUCT A\t12\t0\t1.23\n\
+                     PO1\tABC123\tPRODUCT B\t6\t2\t5.23\n";
+        let orders = AbcPurchaseOrder::from_readers(po.as_bytes(), lines.as_bytes()).unwrap();
+        let receipt = |sku: &str, qty: f64, cost: Option<Decimal>| ReceiptLine {
+            sku: sku.to_string(),
+            qty,
+            cost,
+        };
+        let result = reconcile(
+            &orders["PO1"],
+            &[
+                receipt("123456", 10.0, None),
+                receipt("ABC123", 4.0, Some(Decimal::new(550, 2))),
+                receipt("EXTRA", 1.0, None),
+            ],
+        );
+        assert_eq!(
+            result.issues,
+            vec![
+                ReceiptIssue::Short {
+                    sku: "123456".to_string(),
+                    expected: 12.0,
+                    received: 10.0
+                },
+                ReceiptIssue::CostChanged {
+                    sku: "ABC123".to_string(),
+                    ordered: Decimal::new(523, 2),
+                    invoiced: Decimal::new(550, 2)
+                },
+                ReceiptIssue::NotOrdered {
+                    sku: "EXTRA".to_string(),
+                    received: 1.0
+                },
+            ]
+        );
+        assert_eq!(
+            result.import,
+            "123456\t10\t1.23\nABC123\t4\t5.50\nEXTRA\t1\t0\n"
+        );
+    }
+}
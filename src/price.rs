@@ -0,0 +1,259 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// The currency of an [`AbcPrice`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Currency {
+    /// United States dollars, which ABC exports are always priced in
+    #[default]
+    Usd,
+    /// Canadian dollars
+    Cad,
+    /// Euros
+    Eur,
+    /// British pounds
+    Gbp,
+}
+
+impl Currency {
+    /// The ISO 4217 code of the currency, such as `USD`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Usd => "USD",
+            Self::Cad => "CAD",
+            Self::Eur => "EUR",
+            Self::Gbp => "GBP",
+        }
+    }
+
+    /// The symbol written before amounts of the currency, such as `$`
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Usd => "$",
+            Self::Cad => "CA$",
+            Self::Eur => "€",
+            Self::Gbp => "£",
+        }
+    }
+}
+
+/// Round `amount` to the cent, with halves rounded away from zero as registers do, and give it
+/// exactly two decimal places
+fn to_cents(amount: Decimal) -> Decimal {
+    let mut cents = amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+    cents.rescale(2);
+    cents
+}
+
+/// An amount of money in a known currency, always rounded to the cent so that every price
+/// computed from it rounds the same way.
+///
+/// # Example
+/// ```rust
+/// use abc_product::price::AbcPrice;
+/// use rust_decimal::Decimal;
+///
+/// let cost = AbcPrice::new(Decimal::new(1000, 2));
+/// let list = cost.with_markup(Decimal::new(335, 1));
+/// assert_eq!(list.amount(), Decimal::new(1335, 2));
+/// assert_eq!(list.margin_over(cost), Some(Decimal::new(2509, 2)));
+/// assert_eq!(list.to_string(), "$13.35");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "PriceFields")
+)]
+pub struct AbcPrice {
+    amount: Decimal,
+    currency: Currency,
+}
+
+/// The fields of an [`AbcPrice`] as they are serialized, before the amount is rounded to the
+/// cent
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PriceFields {
+    amount: Decimal,
+    currency: Currency,
+}
+
+#[cfg(feature = "serde")]
+impl From<PriceFields> for AbcPrice {
+    fn from(value: PriceFields) -> Self {
+        AbcPrice::with_currency(value.amount, value.currency)
+    }
+}
+
+impl AbcPrice {
+    /// A price of `amount` US dollars, rounded to the cent
+    pub fn new(amount: Decimal) -> Self {
+        Self::with_currency(amount, Currency::Usd)
+    }
+
+    /// A price of `amount` in `currency`, rounded to the cent
+    pub fn with_currency(amount: Decimal, currency: Currency) -> Self {
+        AbcPrice {
+            amount: to_cents(amount),
+            currency,
+        }
+    }
+
+    /// The amount of the price, with exactly two decimal places
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    /// The currency of the price
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// The gross margin of this price over `cost`, as a percent of this price rounded to two
+    /// decimal places, such as `25.09` for a 25.09% margin
+    ///
+    /// # Returns
+    /// [`None`] if the prices are in different currencies or this price is zero
+    pub fn margin_over(&self, cost: AbcPrice) -> Option<Decimal> {
+        if self.currency != cost.currency || self.amount.is_zero() {
+            return None;
+        }
+        Some(to_cents(
+            (self.amount - cost.amount) / self.amount * Decimal::ONE_HUNDRED,
+        ))
+    }
+
+    /// The markup of this price over `cost`, as a percent of `cost` rounded to two decimal
+    /// places, such as `33.50` for a price 33.5% over cost
+    ///
+    /// # Returns
+    /// [`None`] if the prices are in different currencies or `cost` is zero
+    pub fn markup_over(&self, cost: AbcPrice) -> Option<Decimal> {
+        if self.currency != cost.currency || cost.amount.is_zero() {
+            return None;
+        }
+        Some(to_cents(
+            (self.amount - cost.amount) / cost.amount * Decimal::ONE_HUNDRED,
+        ))
+    }
+
+    /// This price marked up by `pct` percent of itself, such as `40` for a 40% markup, rounded
+    /// to the cent
+    pub fn with_markup(&self, pct: Decimal) -> Self {
+        Self::with_currency(
+            self.amount * (Decimal::ONE_HUNDRED + pct) / Decimal::ONE_HUNDRED,
+            self.currency,
+        )
+    }
+
+    /// This price times `qty`, such as the extended price of a line, rounded to the cent
+    pub fn times(&self, qty: Decimal) -> Self {
+        Self::with_currency(self.amount * qty, self.currency)
+    }
+
+    /// The sum of this price and `other`
+    ///
+    /// # Returns
+    /// [`None`] if the prices are in different currencies
+    pub fn checked_add(&self, other: AbcPrice) -> Option<Self> {
+        (self.currency == other.currency)
+            .then(|| Self::with_currency(self.amount + other.amount, self.currency))
+    }
+
+    /// This price less `other`
+    ///
+    /// # Returns
+    /// [`None`] if the prices are in different currencies
+    pub fn checked_sub(&self, other: AbcPrice) -> Option<Self> {
+        (self.currency == other.currency)
+            .then(|| Self::with_currency(self.amount - other.amount, self.currency))
+    }
+}
+
+impl PartialOrd for AbcPrice {
+    /// Prices are ordered by amount. Prices in different currencies cannot be compared
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (self.currency == other.currency).then(|| self.amount.cmp(&other.amount))
+    }
+}
+
+impl From<AbcPrice> for Decimal {
+    fn from(value: AbcPrice) -> Self {
+        value.amount
+    }
+}
+
+impl std::fmt::Display for AbcPrice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.amount.is_sign_negative() && !self.amount.is_zero() {
+            "-"
+        } else {
+            ""
+        };
+        write!(
+            f,
+            "{}{}{:.2}",
+            sign,
+            self.currency.symbol(),
+            self.amount.abs()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abc_price() {
+        // Halves round away from zero rather than to even
+        assert_eq!(
+            AbcPrice::new(Decimal::new(12345, 3)).amount(),
+            Decimal::new(1235, 2)
+        );
+        assert_eq!(
+            AbcPrice::new(Decimal::new(-5, 3)).amount(),
+            Decimal::new(-1, 2)
+        );
+        assert_eq!(AbcPrice::new(Decimal::from(6)).to_string(), "$6.00");
+        assert_eq!(AbcPrice::new(Decimal::from(6)).amount().to_string(), "6.00");
+        assert_eq!(AbcPrice::new(Decimal::new(-599, 2)).to_string(), "-$5.99");
+
+        let list = AbcPrice::new(Decimal::new(599, 2));
+        let cost = AbcPrice::new(Decimal::new(123, 2));
+        assert_eq!(list.margin_over(cost), Some(Decimal::new(7947, 2)));
+        assert_eq!(AbcPrice::default().margin_over(cost), None);
+        assert_eq!(list.markup_over(cost), Some(Decimal::new(38699, 2)));
+        assert_eq!(list.markup_over(AbcPrice::default()), None);
+        assert_eq!(list.times(Decimal::from(3)).amount(), Decimal::new(1797, 2));
+        assert_eq!(
+            list.checked_sub(cost).map(|m| m.amount()),
+            Some(Decimal::new(476, 2))
+        );
+
+        let cad = AbcPrice::with_currency(Decimal::ONE, Currency::Cad);
+        assert_eq!(cad.to_string(), "CA$1.00");
+        assert_eq!(list.checked_add(cad), None);
+        assert_eq!(list.margin_over(cad), None);
+        assert_eq!(list.markup_over(cad), None);
+
+        assert!(cost < list);
+        assert_eq!(list.partial_cmp(&cad), None);
+        assert!(!(list < cad) && !(list > cad));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_abc_price_serde() {
+        let price: AbcPrice =
+            serde_json::from_str(r#"{"amount":"1.005","currency":"Cad"}"#).unwrap();
+        assert_eq!(
+            price,
+            AbcPrice::with_currency(Decimal::new(101, 2), Currency::Cad)
+        );
+        assert_eq!(price.amount().to_string(), "1.01");
+        let json = serde_json::to_string(&price).unwrap();
+        assert_eq!(serde_json::from_str::<AbcPrice>(&json).unwrap(), price);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rust_decimal::{Decimal, prelude::FromPrimitive};
+
+use crate::{AbcProduct, AbcProductsBySku, price::AbcPrice};
+
+/// How a [`PricingRule`] changes the list price of the products it matches
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceAction {
+    /// Set list to cost plus the given percent of cost, such as `40` for a 40% markup
+    MarkupOnCost(Decimal),
+    /// Raise (or, if negative, lower) the current list by the given percent
+    AdjustList(Decimal),
+    /// Set list to exactly the given price
+    SetList(Decimal),
+}
+
+/// A proposed price change for some part of the catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingRule {
+    /// Only match products in this group. [`None`] matches every group
+    pub group: Option<String>,
+    /// Only match products whose sku starts with this prefix. [`None`] matches every sku
+    pub sku_prefix: Option<String>,
+    /// The change to make to matching products
+    pub action: PriceAction,
+}
+
+impl PricingRule {
+    /// Whether this rule applies to `product`
+    pub fn matches(&self, product: &AbcProduct) -> bool {
+        let group_matches = match &self.group {
+            Some(g) => product
+                .group()
+                .is_some_and(|pg| pg.to_string().eq_ignore_ascii_case(g)),
+            None => true,
+        };
+        let sku_matches = match &self.sku_prefix {
+            Some(prefix) => product.sku().starts_with(prefix.as_str()),
+            None => true,
+        };
+        group_matches && sku_matches
+    }
+
+    /// The list price `product` would have under this rule, rounded to the cent with halves
+    /// rounded away from zero. Markups are figured on the unrounded cost
+    pub fn apply(&self, product: &AbcProduct) -> Decimal {
+        let hundred = Decimal::ONE_HUNDRED;
+        let list = match self.action {
+            PriceAction::MarkupOnCost(pct) => product.cost() * (hundred + pct) / hundred,
+            PriceAction::AdjustList(pct) => product.list() * (hundred + pct) / hundred,
+            PriceAction::SetList(price) => price,
+        };
+        AbcPrice::new(list).amount()
+    }
+}
+
+/// Current and projected sales figures for one slice of the catalog, weighted by units sold
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarginImpact {
+    /// How many products are in this slice
+    pub products: usize,
+    /// How many of those products a rule would reprice
+    pub repriced: usize,
+    /// Sum of list times units sold at current prices
+    pub current_revenue: Decimal,
+    /// Sum of (list - cost) times units sold at current prices
+    pub current_margin: Decimal,
+    /// Sum of list times units sold at projected prices
+    pub projected_revenue: Decimal,
+    /// Sum of (list - cost) times units sold at projected prices
+    pub projected_margin: Decimal,
+}
+
+impl MarginImpact {
+    /// The change in margin dollars the rules would cause
+    pub fn margin_change(&self) -> Decimal {
+        self.projected_margin - self.current_margin
+    }
+}
+
+/// The result of [`simulate`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Simulation {
+    /// The sku, current list, and projected list of every product a rule would reprice, sorted
+    /// by sku
+    pub changes: Vec<(String, Decimal, Decimal)>,
+    /// The impact on each product group. Products without a group are under the empty string
+    pub by_group: BTreeMap<String, MarginImpact>,
+    /// The impact on the whole catalog
+    pub total: MarginImpact,
+}
+
+/// Preview what a set of pricing rules would do to margins before any prices are changed.
+///
+/// Each product is repriced by the first rule in `rules` that matches it. Products matching no
+/// rule keep their current list price. Revenue and margin are weighted by how many units of
+/// each product sell, so that a change to a fast mover counts for more than a change to an item
+/// that never sells.
+///
+/// # Arguments
+/// * `products` - The catalog to reprice
+/// * `rules` - The proposed rules, in priority order
+/// * `weights` - Units sold per sku over some period, such as the last year of sales history.
+///   Skus missing from the map are weighted 0. Pass [`None`] to weight every product as 1 unit
+///
+/// # Returns
+/// The projected price changes and their margin impact by group and in total
+pub fn simulate(
+    products: &AbcProductsBySku,
+    rules: &[PricingRule],
+    weights: Option<&HashMap<String, f64>>,
+) -> Simulation {
+    let mut sorted: Vec<&AbcProduct> = products.values().collect();
+    sorted.sort_by_key(|p| p.sku());
+
+    let mut simulation = Simulation::default();
+    for product in sorted {
+        let units = match weights {
+            Some(w) => w.get(&product.sku()).copied().unwrap_or(0.0),
+            None => 1.0,
+        };
+        let units = Decimal::from_f64(units).unwrap_or_default();
+        let projected = match rules.iter().find(|r| r.matches(product)) {
+            Some(rule) => rule.apply(product),
+            None => product.list(),
+        };
+        let repriced = projected != product.list();
+        if repriced {
+            simulation
+                .changes
+                .push((product.sku(), product.list(), projected));
+        }
+
+        let group = product.group().map(|g| g.to_string()).unwrap_or_default();
+        for impact in [
+            simulation.by_group.entry(group).or_default(),
+            &mut simulation.total,
+        ] {
+            impact.products += 1;
+            impact.repriced += repriced as usize;
+            impact.current_revenue += product.list() * units;
+            impact.current_margin += (product.list() - product.cost()) * units;
+            impact.projected_revenue += projected * units;
+            impact.projected_margin += (projected - product.cost()) * units;
+        }
+    }
+    simulation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_simulate() {
+        let rules = [PricingRule {
+            group: Some("a".to_string()),
+            sku_prefix: None,
+            action: PriceAction::MarkupOnCost(Decimal::new(100, 0)),
+        }];
+        let weights = HashMap::from([("ABC123".to_string(), 2.0)]);
+        let sim = simulate(&sample_export(), &rules, Some(&weights));
+        assert_eq!(
+            sim.changes,
+            vec![(
+                "ABC123".to_string(),
+                Decimal::new(812, 2),
+                Decimal::new(1046, 2)
+            )]
+        );
+        // Margin per unit goes from 8.12 - 5.23 = 2.89 to 10.46 - 5.23 = 5.23, on 2 units sold
+        assert_eq!(sim.by_group["A"].margin_change(), Decimal::new(468, 2));
+        assert_eq!(sim.total.products, 2);
+    }
+}
@@ -0,0 +1,641 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{Months, NaiveDate};
+use rust_decimal::{Decimal, prelude::FromPrimitive};
+
+use crate::{AbcProduct, AbcProductsBySku, Sku, invoice::AbcInvoice, upc_from_str};
+
+/// What went wrong with a UPC in the export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpcIssueKind {
+    /// The UPC was exported without its check digit, so one was calculated
+    MissingCheckDigit,
+    /// The UPC's check digit was wrong and was replaced with the correct one
+    CheckDigitCorrected,
+    /// The UPC could not be used at all and was left off of the product
+    Dropped,
+}
+
+/// A single UPC that the parser had to correct or drop
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpcIssue {
+    /// The sku of the product the UPC belongs to
+    pub sku: String,
+    /// The description of the product the UPC belongs to
+    pub desc: String,
+    /// The UPC as it appears in ABC
+    pub raw: String,
+    /// What is wrong with the UPC
+    pub kind: UpcIssueKind,
+    /// What the UPC should be changed to in ABC, if a fix is known. Written with the same number
+    /// of digits as `raw` where possible so it matches how the store enters codes
+    pub suggestion: Option<String>,
+}
+
+/// Every UPC in a catalog that needs to be fixed inside ABC, as produced by [`upc_report`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpcReport {
+    /// The problem UPCs, sorted by sku
+    pub issues: Vec<UpcIssue>,
+}
+
+impl UpcReport {
+    /// Render the report as tab separated text with a header row, ready to open in a
+    /// spreadsheet and work through
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::from("sku\tdesc\tupc in abc\tproblem\tchange to\n");
+        for issue in &self.issues {
+            let problem = match issue.kind {
+                UpcIssueKind::MissingCheckDigit => "missing check digit",
+                UpcIssueKind::CheckDigitCorrected => "wrong check digit",
+                UpcIssueKind::Dropped => "not a usable upc",
+            };
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                issue.sku,
+                issue.desc,
+                issue.raw,
+                problem,
+                issue.suggestion.as_deref().unwrap_or("")
+            ));
+        }
+        out
+    }
+}
+
+/// Work out what, if anything, is wrong with a single raw UPC of `product`
+fn upc_issue(product: &AbcProduct, raw: &str) -> Option<UpcIssue> {
+    let (kind, suggestion) = match upc_from_str(raw) {
+        None => (UpcIssueKind::Dropped, None),
+        Some(ean) => {
+            let digits = ean.to_string();
+            let suggestion = if raw.len() <= 12 && digits.starts_with('0') {
+                digits[1..].to_string()
+            } else {
+                digits.to_string()
+            };
+            if raw.len() == 11 {
+                (UpcIssueKind::MissingCheckDigit, Some(suggestion))
+            } else if format!("{:0>13}", raw) != digits {
+                (UpcIssueKind::CheckDigitCorrected, Some(suggestion))
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(UpcIssue {
+        sku: product.sku(),
+        desc: product.desc(),
+        raw: raw.to_string(),
+        kind,
+        suggestion,
+    })
+}
+
+/// List every UPC in `products` whose check digit had to be corrected or that had to be dropped
+/// entirely while parsing, along with the corrected code where one is known
+pub fn upc_report(products: &AbcProductsBySku) -> UpcReport {
+    let mut sorted: Vec<&AbcProduct> = products.values().collect();
+    sorted.sort_by_key(|p| p.sku());
+    let issues = sorted
+        .iter()
+        .flat_map(|p| {
+            p.raw_upcs()
+                .into_iter()
+                .filter_map(|raw| upc_issue(p, &raw))
+        })
+        .collect();
+    UpcReport { issues }
+}
+
+/// The words and abbreviations a store accepts in product descriptions, for [`desc_audit`]
+///
+/// # Example
+/// ```rust
+/// use abc_product::{quality::{self, Dictionary}, testing};
+///
+/// let dictionary = Dictionary::new()
+///     .with_words(["PRODUCT", "PIPE", "NIPPLE"])
+///     .with_abbreviation("GLV", "GALV");
+/// let audit = quality::desc_audit(&testing::sample_export(), &dictionary);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    abbreviations: HashMap<String, String>,
+}
+
+impl Dictionary {
+    /// Create an empty [`Dictionary`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept each of `words` in descriptions. Words are compared without regard to case
+    pub fn with_words<'a>(self, words: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut new_words = self.words;
+        new_words.extend(words.into_iter().map(|w| w.trim().to_uppercase()));
+        Dictionary {
+            words: new_words,
+            ..self
+        }
+    }
+
+    /// Flag `variant` wherever it is used instead of the store's `preferred` abbreviation, such
+    /// as "GLV" for "GALV". The preferred form is accepted as a word
+    pub fn with_abbreviation(self, variant: &str, preferred: &str) -> Self {
+        let mut words = self.words;
+        words.insert(preferred.trim().to_uppercase());
+        let mut abbreviations = self.abbreviations;
+        abbreviations.insert(
+            variant.trim().to_uppercase(),
+            preferred.trim().to_uppercase(),
+        );
+        Dictionary {
+            words,
+            abbreviations,
+        }
+    }
+}
+
+/// What is wrong with a product description
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DescIssueKind {
+    /// A word that is not in the [`Dictionary`], often a typo
+    UnknownWord(String),
+    /// An abbreviation used instead of the store's preferred one
+    InconsistentAbbreviation { found: String, preferred: String },
+    /// The same description is used by other skus, listed here
+    Duplicate(Vec<String>),
+}
+
+/// A single problem with a product description
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescIssue {
+    /// The sku of the product
+    pub sku: String,
+    /// The description as it appears in ABC
+    pub desc: String,
+    /// What is wrong with the description
+    pub kind: DescIssueKind,
+}
+
+/// Every description problem in a catalog, as produced by [`desc_audit`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DescAudit {
+    /// The problems found, sorted by sku
+    pub issues: Vec<DescIssue>,
+}
+
+impl DescAudit {
+    /// Render the audit as a tab separated cleanup worksheet with a header row
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::from("sku\tdesc\tproblem\tdetail\n");
+        for issue in &self.issues {
+            let (problem, detail) = match &issue.kind {
+                DescIssueKind::UnknownWord(word) => ("unknown word", word.to_string()),
+                DescIssueKind::InconsistentAbbreviation { found, preferred } => (
+                    "inconsistent abbreviation",
+                    format!("{} -> {}", found, preferred),
+                ),
+                DescIssueKind::Duplicate(skus) => ("duplicate description", skus.join(", ")),
+            };
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                issue.sku, issue.desc, problem, detail
+            ));
+        }
+        out
+    }
+}
+
+/// Split a description into uppercase words, skipping sizes and other tokens containing digits
+/// and single letters, which are rarely misspelled
+fn desc_words(desc: &str) -> Vec<String> {
+    desc.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.trim_matches('\'').to_uppercase())
+        .filter(|w| w.chars().count() > 1 && !w.chars().any(|c| c.is_ascii_digit()))
+        .collect()
+}
+
+/// Check every description in `products` against `dictionary`, flagging unknown words,
+/// abbreviations that differ from the store's preferred ones, and descriptions shared by more
+/// than one sku. Each unknown word or abbreviation is reported once per product.
+///
+/// # Arguments
+/// * `products` - The catalog to audit
+/// * `dictionary` - The words and abbreviations the store accepts
+///
+/// # Returns
+/// A [`DescAudit`] that can be saved as a cleanup worksheet with [`DescAudit::to_tsv`]
+pub fn desc_audit(products: &AbcProductsBySku, dictionary: &Dictionary) -> DescAudit {
+    let mut sorted: Vec<&AbcProduct> = products.values().collect();
+    sorted.sort_by_key(|p| p.sku());
+
+    let mut by_desc: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for product in &sorted {
+        let normalized = product
+            .desc()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_uppercase();
+        by_desc.entry(normalized).or_default().push(product.sku());
+    }
+    let shared: HashMap<&String, &Vec<String>> = by_desc
+        .iter()
+        .filter(|(_, skus)| skus.len() > 1)
+        .flat_map(|(_, skus)| skus.iter().map(move |sku| (sku, skus)))
+        .collect();
+
+    let mut issues = Vec::new();
+    for product in sorted {
+        let sku = product.sku();
+        let issue = |kind| DescIssue {
+            sku: sku.clone(),
+            desc: product.desc(),
+            kind,
+        };
+        let mut seen = HashSet::new();
+        for word in desc_words(&product.desc()) {
+            if !seen.insert(word.clone()) {
+                continue;
+            }
+            if let Some(preferred) = dictionary.abbreviations.get(&word) {
+                issues.push(issue(DescIssueKind::InconsistentAbbreviation {
+                    found: word,
+                    preferred: preferred.to_string(),
+                }));
+            } else if !dictionary.words.contains(&word) {
+                issues.push(issue(DescIssueKind::UnknownWord(word)));
+            }
+        }
+        if let Some(skus) = shared.get(&sku) {
+            let others = skus.iter().filter(|s| **s != sku).cloned().collect();
+            issues.push(issue(DescIssueKind::Duplicate(others)));
+        }
+    }
+    DescAudit { issues }
+}
+
+/// A product whose cost has not changed in a long time, as found by [`stale_costs`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleCost {
+    /// The sku of the product
+    pub sku: String,
+    /// The description of the product
+    pub desc: String,
+    /// The product's current cost
+    pub cost: Decimal,
+    /// The date of the earliest snapshot from which the cost has stayed the same
+    pub since: NaiveDate,
+    /// The value of the product's stock on hand at its current cost. Negative stock counts as 0
+    pub value: Decimal,
+}
+
+/// Find products whose cost has not changed in at least `years` years. Costs that are never
+/// updated make margins look better than they are, so the report is sorted by inventory value,
+/// largest first, to show where that matters most.
+///
+/// # Arguments
+/// * `history` - Dated snapshots of the catalog, such as exports kept from past syncs, in any
+///   order. The most recent snapshot is taken as the current catalog
+/// * `years` - How many years a cost must go unchanged to be reported
+///
+/// # Returns
+/// Every product in the most recent snapshot whose cost is the same in every snapshot going back
+/// at least `years` years from it. Products that first appear more recently than that are not
+/// reported, since how long their cost has been unchanged is not known
+pub fn stale_costs(history: &[(NaiveDate, AbcProductsBySku)], years: u32) -> Vec<StaleCost> {
+    let mut history: Vec<&(NaiveDate, AbcProductsBySku)> = history.iter().collect();
+    history.sort_by_key(|(date, _)| *date);
+    let Some((latest, current)) = history.last() else {
+        return Vec::new();
+    };
+    let cutoff = latest
+        .checked_sub_months(Months::new(years.saturating_mul(12)))
+        .unwrap_or(NaiveDate::MIN);
+
+    let mut stale: Vec<StaleCost> = current
+        .values()
+        .filter_map(|product| {
+            let since = history
+                .iter()
+                .rev()
+                .take_while(|(_, snapshot)| {
+                    snapshot
+                        .get(&product.sku)
+                        .is_some_and(|p| p.cost() == product.cost())
+                })
+                .last()
+                .map(|(date, _)| *date)?;
+            (since <= cutoff).then(|| StaleCost {
+                sku: product.sku(),
+                desc: product.desc(),
+                cost: product.cost(),
+                since,
+                value: product.cost()
+                    * Decimal::from_f64(product.stock().max(0.0)).unwrap_or_default(),
+            })
+        })
+        .collect();
+    stale.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.sku.cmp(&b.sku)));
+    stale
+}
+
+/// Why a line was flagged by [`price_overrides`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideKind {
+    /// The product was sold for less than it cost
+    BelowCost,
+    /// The product was sold for more than the tolerance away from its list price
+    OffList,
+}
+
+/// An invoice line whose price was changed at the register, as found by [`price_overrides`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceOverride {
+    /// The number of the invoice the line is on
+    pub invoice: String,
+    /// The date of the sale
+    pub date: Option<NaiveDate>,
+    /// The clerk who rang up the sale
+    pub clerk: Option<String>,
+    /// The account of the customer, or [`None`] for a cash sale
+    pub customer: Option<String>,
+    /// The sku sold
+    pub sku: String,
+    /// How many units were sold
+    pub qty: Decimal,
+    /// The unit price charged
+    pub price: Decimal,
+    /// The product's current list price
+    pub list: Decimal,
+    /// The product's current cost
+    pub cost: Decimal,
+    /// Why the line was flagged
+    pub kind: OverrideKind,
+}
+
+impl PriceOverride {
+    /// How much less was charged for the whole line than at list price. Negative when the
+    /// product was sold above list
+    pub fn given_away(&self) -> Decimal {
+        self.qty * (self.list - self.price)
+    }
+}
+
+/// The overrides of a single clerk or customer, as totalled by [`price_overrides`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OverrideSummary {
+    /// How many lines were flagged
+    pub lines: usize,
+    /// How many of those lines were sold below cost
+    pub below_cost: usize,
+    /// The total of [`PriceOverride::given_away`] over every flagged line
+    pub given_away: Decimal,
+}
+
+impl OverrideSummary {
+    /// Count `line` toward the summary
+    fn add(&mut self, line: &PriceOverride) {
+        self.lines += 1;
+        if line.kind == OverrideKind::BelowCost {
+            self.below_cost += 1;
+        }
+        self.given_away += line.given_away();
+    }
+}
+
+/// Every price override on a set of invoices, as produced by [`price_overrides`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OverrideReport {
+    /// The flagged lines, sorted by invoice number and then in the order they were entered
+    pub lines: Vec<PriceOverride>,
+    /// The flagged lines of each clerk, with sales that have no clerk under [`None`]
+    pub by_clerk: BTreeMap<Option<String>, OverrideSummary>,
+    /// The flagged lines of each customer, with cash sales under [`None`]
+    pub by_customer: BTreeMap<Option<String>, OverrideSummary>,
+}
+
+/// Find invoice lines sold below cost or far from list price, and total them by clerk and by
+/// customer. A clerk who regularly gives the same customers "friend prices" stands out in the
+/// summaries long before the shrinkage shows up in the margins.
+///
+/// Prices are compared with the product's current list and cost, since the export does not
+/// keep what they were on the day of the sale, so review lines from before a price change with
+/// that in mind. Returns, lines without a sku, and skus that are no longer in the catalog are
+/// skipped.
+///
+/// # Arguments
+/// * `invoices` - The sales to audit
+/// * `catalog` - The products to compare prices against
+/// * `tolerance` - How far from list, as a percent of list, a price may be before it is
+///   flagged, such as `10` to allow the usual contractor discount
+///
+/// # Returns
+/// An [`OverrideReport`] of every flagged line. A line sold below cost is always flagged as
+/// [`OverrideKind::BelowCost`], however close it is to list
+pub fn price_overrides<'a>(
+    invoices: impl IntoIterator<Item = &'a AbcInvoice>,
+    catalog: &AbcProductsBySku,
+    tolerance: Decimal,
+) -> OverrideReport {
+    let mut report = OverrideReport::default();
+    for invoice in invoices {
+        for line in invoice.lines() {
+            let Some(product) = line.sku().and_then(|sku| catalog.get(&Sku::new(&sku))) else {
+                continue;
+            };
+            if line.qty() <= Decimal::ZERO {
+                continue;
+            }
+            let (list, cost) = (product.list(), product.cost());
+            let kind = if line.price() < cost {
+                OverrideKind::BelowCost
+            } else if !list.is_zero()
+                && (line.price() - list).abs() / list * Decimal::ONE_HUNDRED > tolerance
+            {
+                OverrideKind::OffList
+            } else {
+                continue;
+            };
+            let flagged = PriceOverride {
+                invoice: invoice.number(),
+                date: invoice.date(),
+                clerk: invoice.clerk(),
+                customer: invoice.customer(),
+                sku: product.sku(),
+                qty: line.qty(),
+                price: line.price(),
+                list,
+                cost,
+                kind,
+            };
+            report
+                .by_clerk
+                .entry(flagged.clerk.clone())
+                .or_default()
+                .add(&flagged);
+            report
+                .by_customer
+                .entry(flagged.customer.clone())
+                .or_default()
+                .add(&flagged);
+            report.lines.push(flagged);
+        }
+    }
+    report.lines.sort_by(|a, b| a.invoice.cmp(&b.invoice));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, testing::sample_export};
+
+    #[test]
+    fn test_upc_report() {
+        let report = upc_report(&sample_export());
+        assert_eq!(
+            report.issues,
+            vec![UpcIssue {
+                sku: "123456".to_string(),
+                desc: "PRODUCT A".to_string(),
+                raw: "085875500014".to_string(),
+                kind: UpcIssueKind::CheckDigitCorrected,
+                suggestion: Some("085875500015".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_stale_costs() {
+        let current = sample_export();
+        let mut old = current.clone();
+        let repriced = AbcProductBuilder::from(old["123456"].clone())
+            .with_cost(Decimal::new(1, 0))
+            .build()
+            .unwrap();
+        old.insert(Sku::new("123456"), repriced);
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let history = vec![
+            (date("2025-06-01"), current),
+            (date("2022-01-01"), old.clone()),
+            (date("2023-01-01"), old),
+        ];
+
+        let stale = stale_costs(&history, 2);
+        let skus: Vec<&str> = stale.iter().map(|s| s.sku.as_str()).collect();
+        assert!(!skus.contains(&"123456"));
+        assert_eq!(skus.len(), sample_export().len() - 1);
+        assert!(stale.iter().all(|s| s.since == date("2022-01-01")));
+        assert!(stale_costs(&history, 4).is_empty());
+    }
+
+    #[test]
+    fn test_desc_audit() {
+        let mut products = sample_export();
+        let base = products["123456"].clone();
+        for (sku, desc) in [
+            ("1", "GLV PIPE 1/2\""),
+            ("2", "Galv  pipe 1/2\""),
+            ("3", "GALV PIEP"),
+        ] {
+            let product = AbcProductBuilder::from(base.clone())
+                .with_sku(sku)
+                .with_desc(desc)
+                .build()
+                .unwrap();
+            products.insert(Sku::new(sku), product);
+        }
+        let dictionary = Dictionary::new()
+            .with_words(["product", "pipe"])
+            .with_abbreviation("GLV", "GALV");
+
+        let kinds: Vec<(String, DescIssueKind)> = desc_audit(&products, &dictionary)
+            .issues
+            .into_iter()
+            .map(|i| (i.sku, i.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    "1".to_string(),
+                    DescIssueKind::InconsistentAbbreviation {
+                        found: "GLV".to_string(),
+                        preferred: "GALV".to_string()
+                    }
+                ),
+                (
+                    "3".to_string(),
+                    DescIssueKind::UnknownWord("PIEP".to_string())
+                ),
+            ]
+        );
+
+        let products: AbcProductsBySku = products
+            .into_iter()
+            .filter(|(sku, _)| sku != "3")
+            .map(|(sku, p)| {
+                let desc = if sku == "1" || sku == "2" {
+                    "GLV PIPE 1/2\""
+                } else {
+                    "PRODUCT"
+                };
+                (
+                    sku,
+                    AbcProductBuilder::from(p).with_desc(desc).build().unwrap(),
+                )
+            })
+            .collect();
+        let audit = desc_audit(&products, &dictionary);
+        assert!(audit.issues.contains(&DescIssue {
+            sku: "1".to_string(),
+            desc: "GLV PIPE 1/2\"".to_string(),
+            kind: DescIssueKind::Duplicate(vec!["2".to_string()]),
+        }));
+        assert!(audit.issues.contains(&DescIssue {
+            sku: "123456".to_string(),
+            desc: "PRODUCT".to_string(),
+            kind: DescIssueKind::Duplicate(vec!["ABC123".to_string()]),
+        }));
+    }
+
+    #[test]
+    fn test_price_overrides() {
+        let invoice = "5002\t2024-12-01\t1001\tBOB\n5001\t2024-11-16\tnull\tAMY\n";
+        let lines = "5001\t123456\tPRODUCT A\t2\t5.99\n\
+                     5001\tABC123\tPRODUCT B\t1\t7.50\n\
+                     5002\tABC123\tPRODUCT B\t3\t5.00\n\
+                     5002\t123456\tPRODUCT A\t1\t4.99\n\
+                     5002\t123456\tPRODUCT A\t-1\t0.01\n\
+                     5002\tGONE\tOLD PRODUCT\t1\t0.01\n";
+        let invoices = AbcInvoice::from_readers(invoice.as_bytes(), lines.as_bytes()).unwrap();
+        let report = price_overrides(invoices.values(), &sample_export(), Decimal::TEN);
+
+        let flagged: Vec<(&str, &str, OverrideKind)> = report
+            .lines
+            .iter()
+            .map(|l| (l.invoice.as_str(), l.sku.as_str(), l.kind))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                ("5002", "ABC123", OverrideKind::BelowCost),
+                ("5002", "123456", OverrideKind::OffList),
+            ]
+        );
+        let bob = report.by_clerk[&Some("BOB".to_string())];
+        assert_eq!(bob.lines, 2);
+        assert_eq!(bob.below_cost, 1);
+        // 3 x (8.12 - 5.00) + 1 x (5.99 - 4.99)
+        assert_eq!(bob.given_away, Decimal::new(1036, 2));
+        assert_eq!(report.by_customer.len(), 1);
+
+        // 7.50 is 7.6% under 8.12
+        let strict = price_overrides(invoices.values(), &sample_export(), Decimal::from(5));
+        assert_eq!(strict.by_customer[&None].lines, 1);
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use rust_decimal::{Decimal, RoundingStrategy, prelude::FromPrimitive};
+
+use crate::{AbcProduct, AbcProductsBySku, Sku, format::format_qty};
+
+/// How freight is estimated on a quote from the weight of its lines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FreightRate {
+    /// The charge per pound of goods
+    pub per_lb: Decimal,
+    /// The smallest freight charge on any quote with weight to ship
+    pub minimum: Decimal,
+    /// Merchandise subtotals at or above this ship free
+    pub free_over: Option<Decimal>,
+}
+
+/// The prices, tax, and freight a customer is quoted at
+///
+/// # Example
+/// ```rust
+/// use rust_decimal::Decimal;
+/// use abc_product::quotes::{FreightRate, QuoteTerms};
+///
+/// let terms = QuoteTerms::default()
+///     .with_contract_price("123456", Decimal::new(499, 2))
+///     .with_tax_rate(Decimal::new(6, 2))
+///     .with_freight(FreightRate {
+///         per_lb: Decimal::new(35, 2),
+///         minimum: Decimal::new(15, 0),
+///         free_over: None,
+///     });
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QuoteTerms {
+    contract_prices: HashMap<Sku, Decimal>,
+    tax_rate: Decimal,
+    freight: Option<FreightRate>,
+}
+
+impl QuoteTerms {
+    /// Quote `sku` at `price` instead of its list price
+    pub fn with_contract_price(self, sku: &str, price: Decimal) -> Self {
+        let mut contract_prices = self.contract_prices;
+        contract_prices.insert(Sku::new(sku), price);
+        QuoteTerms {
+            contract_prices,
+            ..self
+        }
+    }
+
+    /// Charge tax at `rate`, such as 0.06 for 6%, on the merchandise subtotal. Freight is not
+    /// taxed
+    pub fn with_tax_rate(self, rate: Decimal) -> Self {
+        QuoteTerms {
+            tax_rate: rate,
+            ..self
+        }
+    }
+
+    /// Estimate freight with `rate`. Without a rate, quotes have no freight charge
+    pub fn with_freight(self, rate: FreightRate) -> Self {
+        QuoteTerms {
+            freight: Some(rate),
+            ..self
+        }
+    }
+}
+
+/// One product on a quote
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteLine {
+    /// The sku quoted
+    pub sku: String,
+    /// The product's description
+    pub desc: String,
+    /// How many units are quoted
+    pub qty: f64,
+    /// The price of each unit, either the contract price or list
+    pub unit_price: Decimal,
+    /// The weight of each unit in pounds, if known
+    pub weight: Option<f64>,
+    /// The sku of the line this is the core charge for. Core lines are added by [`quote`] and
+    /// are left out of the freight estimate
+    pub core_for: Option<String>,
+}
+
+impl QuoteLine {
+    /// The quantity times the unit price, rounded to the cent
+    pub fn extended(&self) -> Decimal {
+        (Decimal::from_f64(self.qty).unwrap_or_default() * self.unit_price)
+            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+    }
+}
+
+/// A priced estimate for a customer, as built by [`quote`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    /// Who the quote is for, such as a customer's name or account number
+    pub customer: Option<String>,
+    /// The date the quote was made
+    pub date: NaiveDate,
+    /// The products quoted, in the order they were requested
+    pub lines: Vec<QuoteLine>,
+    /// The estimated freight charge
+    pub freight: Decimal,
+    /// The skus whose weight is unknown, so were left out of the freight estimate
+    pub missing_weights: Vec<String>,
+    /// The tax charged on the subtotal
+    pub tax: Decimal,
+}
+
+impl Quote {
+    /// The total of every line, before freight and tax
+    pub fn subtotal(&self) -> Decimal {
+        self.lines.iter().map(|l| l.extended()).sum()
+    }
+
+    /// The amount due: the subtotal, freight, and tax
+    pub fn total(&self) -> Decimal {
+        self.subtotal() + self.freight + self.tax
+    }
+
+    /// Render the quote as comma separated values, with a header row, one row per line, and
+    /// rows for the subtotal, freight, tax, and total
+    pub fn to_csv(&self) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        // Writing to a Vec cannot fail
+        let mut row = |fields: &[String]| writer.write_record(fields).unwrap();
+        row(&["sku", "desc", "qty", "unit price", "extended"].map(String::from));
+        for line in &self.lines {
+            row(&[
+                line.sku.to_string(),
+                line.desc.to_string(),
+                format_qty(line.qty, ""),
+                line.unit_price.round_dp(2).to_string(),
+                line.extended().to_string(),
+            ]);
+        }
+        for (name, amount) in [
+            ("subtotal", self.subtotal()),
+            ("freight", self.freight),
+            ("tax", self.tax),
+            ("total", self.total()),
+        ] {
+            row(&[
+                String::new(),
+                name.to_string(),
+                String::new(),
+                String::new(),
+                amount.to_string(),
+            ]);
+        }
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    /// Render the quote as a letter sized PDF, continuing onto more pages as needed
+    ///
+    /// # Arguments
+    /// * `store` - The name printed at the top of each page
+    ///
+    /// # Returns
+    /// The bytes of the PDF file
+    #[cfg(feature = "pdf")]
+    pub fn to_pdf(&self, store: &str) -> Vec<u8> {
+        use crate::pdf;
+
+        const WIDTH: f64 = 612.0;
+        const HEIGHT: f64 = 792.0;
+        const MARGIN: f64 = 54.0;
+        const LINE: f64 = 14.0;
+        let columns = [
+            MARGIN,
+            MARGIN + 90.0,
+            MARGIN + 330.0,
+            MARGIN + 390.0,
+            MARGIN + 450.0,
+        ];
+
+        let header = |content: &mut String| {
+            content.push_str(&pdf::text(MARGIN, HEIGHT - MARGIN, 16.0, store));
+            let mut y = HEIGHT - MARGIN - 24.0;
+            content.push_str(&pdf::text(MARGIN, y, 10.0, &format!("Quote {}", self.date)));
+            if let Some(customer) = &self.customer {
+                y -= LINE;
+                content.push_str(&pdf::text(MARGIN, y, 10.0, &format!("For {}", customer)));
+            }
+            y -= LINE * 2.0;
+            for (x, title) in columns
+                .iter()
+                .zip(["Sku", "Description", "Qty", "Price", "Total"])
+            {
+                content.push_str(&pdf::text(*x, y, 10.0, title));
+            }
+            y - LINE
+        };
+
+        let mut doc = pdf::PdfDocument::new();
+        let mut content = String::new();
+        let mut y = header(&mut content);
+        let mut rows: Vec<[String; 5]> = self
+            .lines
+            .iter()
+            .map(|l| {
+                [
+                    l.sku.to_string(),
+                    l.desc.chars().take(40).collect(),
+                    format_qty(l.qty, ""),
+                    format!("${}", l.unit_price.round_dp(2)),
+                    format!("${}", l.extended()),
+                ]
+            })
+            .collect();
+        rows.push(Default::default());
+        for (name, amount) in [
+            ("Subtotal", self.subtotal()),
+            ("Freight", self.freight),
+            ("Tax", self.tax),
+            ("Total", self.total()),
+        ] {
+            let mut row: [String; 5] = Default::default();
+            row[3] = name.to_string();
+            row[4] = format!("${}", amount);
+            rows.push(row);
+        }
+        for row in rows {
+            if y < MARGIN {
+                doc.add_page(WIDTH, HEIGHT, std::mem::take(&mut content));
+                y = header(&mut content);
+            }
+            for (x, field) in columns.iter().zip(row.iter()) {
+                content.push_str(&pdf::text(*x, y, 10.0, field));
+            }
+            y -= LINE;
+        }
+        doc.add_page(WIDTH, HEIGHT, content);
+        doc.to_bytes()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuoteError {
+    /// The sku is not in the catalog
+    UnknownSku(String),
+    /// The quantity requested for the sku was zero or negative
+    InvalidQuantity(String, f64),
+    /// The first sku has a core charge, but its core sku (the second) is not in the catalog
+    MissingCore(String, String),
+}
+
+impl std::fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSku(sku) => write!(f, "Sku `{}` does not exist in the catalog", sku),
+            Self::InvalidQuantity(sku, qty) => {
+                write!(f, "Cannot quote a quantity of {} for sku `{}`", qty, sku)
+            }
+            Self::MissingCore(sku, core) => {
+                write!(
+                    f,
+                    "Core sku `{}` of sku `{}` is not in the catalog",
+                    core, sku
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {}
+
+/// Price a list of skus and quantities for a customer.
+///
+/// Each line is priced at the customer's contract price where one is set in `terms`, otherwise
+/// at list. Freight is estimated from the total weight of the lines. Products without a weight
+/// are listed in [`Quote::missing_weights`] so the estimate can be checked. Products with a
+/// core charge are followed by a line for their core sku at the same quantity.
+///
+/// # Arguments
+/// * `catalog` - The products to quote from
+/// * `requests` - Each sku and quantity wanted, in the order they should appear
+/// * `terms` - The customer's prices, tax rate, and freight rate
+/// * `customer` - Who the quote is for, if anyone in particular
+/// * `date` - The date of the quote
+///
+/// # Errors
+/// A [`QuoteError`] if any sku or core sku is missing from `catalog` or if a quantity is not
+/// positive
+pub fn quote(
+    catalog: &AbcProductsBySku,
+    requests: &[(&str, f64)],
+    terms: &QuoteTerms,
+    customer: Option<&str>,
+    date: NaiveDate,
+) -> Result<Quote, QuoteError> {
+    let mut lines = Vec::new();
+    for (sku, qty) in requests {
+        if *qty <= 0.0 {
+            return Err(QuoteError::InvalidQuantity(sku.to_string(), *qty));
+        }
+        let product = catalog
+            .get(&Sku::new(sku))
+            .ok_or(QuoteError::UnknownSku(sku.to_string()))?;
+        let price = |p: &AbcProduct| {
+            terms
+                .contract_prices
+                .get(&p.sku)
+                .copied()
+                .unwrap_or(p.list())
+        };
+        lines.push(QuoteLine {
+            sku: product.sku(),
+            desc: product.desc(),
+            qty: *qty,
+            unit_price: price(product),
+            weight: product.weight(),
+            core_for: None,
+        });
+        if let Some(core_sku) = product.core_sku() {
+            let core = catalog
+                .get(&Sku::new(&core_sku))
+                .ok_or(QuoteError::MissingCore(product.sku(), core_sku.to_string()))?;
+            lines.push(QuoteLine {
+                sku: core.sku(),
+                desc: core.desc(),
+                qty: *qty,
+                unit_price: price(core),
+                weight: None,
+                core_for: Some(product.sku()),
+            });
+        }
+    }
+
+    let subtotal: Decimal = lines.iter().map(|l| l.extended()).sum();
+    let missing_weights = lines
+        .iter()
+        .filter(|l| l.weight.is_none() && l.core_for.is_none())
+        .map(|l| l.sku.to_string())
+        .collect();
+    let freight = match &terms.freight {
+        Some(rate) if rate.free_over.is_none_or(|over| subtotal < over) => {
+            let pounds: f64 = lines.iter().map(|l| l.qty * l.weight.unwrap_or(0.0)).sum();
+            if pounds > 0.0 {
+                (Decimal::from_f64(pounds).unwrap_or_default() * rate.per_lb)
+                    .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+                    .max(rate.minimum)
+            } else {
+                Decimal::ZERO
+            }
+        }
+        _ => Decimal::ZERO,
+    };
+    let tax = (subtotal * terms.tax_rate)
+        .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+    Ok(Quote {
+        customer: customer.map(|c| c.to_string()),
+        date,
+        lines,
+        freight,
+        missing_weights,
+        tax,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, testing::sample_export};
+
+    #[test]
+    fn test_quote() {
+        let terms = QuoteTerms::default()
+            .with_contract_price("abc123 ", Decimal::new(750, 2))
+            .with_tax_rate(Decimal::new(6, 2))
+            .with_freight(FreightRate {
+                per_lb: Decimal::new(50, 2),
+                minimum: Decimal::new(10, 0),
+                free_over: None,
+            });
+        let date = NaiveDate::from_ymd_opt(2024, 11, 16).unwrap();
+        let mut catalog = sample_export();
+        let weighed = AbcProductBuilder::from(catalog["123456"].clone())
+            .with_weight(3.0)
+            .build()
+            .unwrap();
+        catalog.insert(Sku::new("123456"), weighed);
+        let quote = quote(
+            &catalog,
+            &[("123456", 2.0), (" abc123", 1.0)],
+            &terms,
+            Some("1001"),
+            date,
+        )
+        .unwrap();
+        // 2 x 5.99 at list plus 1 x 7.50 under contract
+        assert_eq!(quote.subtotal(), Decimal::new(1948, 2));
+        assert_eq!(quote.tax, Decimal::new(117, 2));
+        assert_eq!(quote.missing_weights, vec!["ABC123".to_string()]);
+        // 6 pounds at 0.50 is under the minimum
+        assert_eq!(quote.freight, Decimal::new(10, 0));
+        assert_eq!(quote.total(), Decimal::new(3065, 2));
+        assert!(quote.to_csv().ends_with(",total,,,30.65\n"));
+
+        assert_eq!(
+            super::quote(&sample_export(), &[("NOPE", 1.0)], &terms, None, date),
+            Err(QuoteError::UnknownSku("NOPE".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_quote_cores() {
+        let date = NaiveDate::from_ymd_opt(2024, 11, 16).unwrap();
+        let mut catalog = sample_export();
+        let with_core = AbcProductBuilder::from(catalog["123456"].clone())
+            .with_core_sku("ABC123")
+            .build()
+            .unwrap();
+        catalog.insert(Sku::new("123456"), with_core);
+        let quote = quote(
+            &catalog,
+            &[("123456", 2.0)],
+            &QuoteTerms::default(),
+            None,
+            date,
+        )
+        .unwrap();
+        assert_eq!(quote.lines.len(), 2);
+        assert_eq!(quote.lines[1].sku, "ABC123");
+        assert_eq!(quote.lines[1].qty, 2.0);
+        assert_eq!(quote.lines[1].core_for, Some("123456".to_string()));
+        // 2 x 5.99 plus 2 x 8.12 in cores
+        assert_eq!(quote.subtotal(), Decimal::new(2822, 2));
+        assert_eq!(quote.missing_weights, vec!["123456".to_string()]);
+
+        catalog.remove("ABC123");
+        assert_eq!(
+            super::quote(
+                &catalog,
+                &[("123456", 1.0)],
+                &QuoteTerms::default(),
+                None,
+                date
+            ),
+            Err(QuoteError::MissingCore(
+                "123456".to_string(),
+                "ABC123".to_string()
+            ))
+        );
+    }
+
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_quote_pdf() {
+        let date = NaiveDate::from_ymd_opt(2024, 11, 16).unwrap();
+        let requests: Vec<(&str, f64)> = (0..60).map(|_| ("123456", 1.0)).collect();
+        let quote = quote(
+            &sample_export(),
+            &requests,
+            &QuoteTerms::default(),
+            None,
+            date,
+        )
+        .unwrap();
+        let pdf = String::from_utf8_lossy(&quote.to_pdf("Hardware Store")).to_string();
+        assert!(pdf.starts_with("%PDF"));
+        assert!(pdf.contains("/Count 2"));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeSet;
+
+use rust_decimal::Decimal;
+
+use crate::{AbcProduct, AbcProductsBySku, ProductField};
+
+/// A product as listed on a remote platform such as a web store
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteProduct {
+    /// The sku the remote platform lists the product under
+    pub sku: String,
+    /// The title or description shown on the remote platform
+    pub desc: String,
+    /// The price the remote platform charges
+    pub price: Decimal,
+    /// The quantity the remote platform believes is available
+    pub stock: f64,
+}
+
+/// The read side of a remote platform that mirrors the catalog. Integrations implement this to
+/// get [`reconcile`] for free.
+pub trait RemoteCatalog {
+    /// The error raised when the remote platform cannot be read
+    type Error;
+
+    /// Fetch the sku of every product listed on the remote platform
+    fn fetch_skus(&self) -> Result<Vec<String>, Self::Error>;
+
+    /// Fetch a single product by sku. [`None`] if the remote platform has no such product
+    fn fetch_product(&self, sku: &str) -> Result<Option<RemoteProduct>, Self::Error>;
+}
+
+/// The write side of a remote platform, used by [`crate::plan::apply`] to carry out the
+/// [`Action`]s found by [`reconcile`].
+///
+/// Every method is given the idempotency key of the operation from
+/// [`crate::plan::Plan::idempotency_key`]. Implementations should pass it along to APIs that
+/// support idempotency keys, or otherwise use it to skip operations they have already done, so
+/// that retrying a partly applied plan does not apply anything twice
+pub trait RemoteCatalogWriter: RemoteCatalog {
+    /// List a new product on the remote platform
+    fn create(&self, product: &RemoteProduct, idempotency_key: &str) -> Result<(), Self::Error>;
+
+    /// Set the given fields of an existing product to their [`FieldChange::local`] values
+    fn update(
+        &self,
+        sku: &str,
+        changes: &[FieldChange],
+        idempotency_key: &str,
+    ) -> Result<(), Self::Error>;
+
+    /// Archive or unlist a product that is no longer in the catalog
+    fn archive(&self, sku: &str, idempotency_key: &str) -> Result<(), Self::Error>;
+}
+
+/// One field that differs between the remote platform and the local catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// The field that differs
+    pub field: ProductField,
+    /// The value currently on the remote platform
+    pub remote: String,
+    /// The value in the local catalog, which the remote platform should be updated to
+    pub local: String,
+}
+
+/// What needs to happen on the remote platform to bring it in line with the local catalog
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// The product is missing from the remote platform and should be created as described
+    Create(RemoteProduct),
+    /// The product with this sku exists on the remote platform but some fields are out of date
+    Update(String, Vec<FieldChange>),
+    /// The product with this sku is on the remote platform but no longer in the local catalog,
+    /// and should be archived
+    Archive(String),
+}
+
+/// How `product` should be listed on a remote platform
+fn remote_product(product: &AbcProduct) -> RemoteProduct {
+    RemoteProduct {
+        sku: product.sku(),
+        desc: product.desc(),
+        price: product.list().round_dp(2),
+        stock: product.stock(),
+    }
+}
+
+/// The fields of `remote` that do not match `local`
+fn changes(remote: &RemoteProduct, local: &AbcProduct) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    if remote.desc != local.desc() {
+        changes.push(FieldChange {
+            field: ProductField::Desc,
+            remote: remote.desc.to_string(),
+            local: local.desc(),
+        });
+    }
+    if remote.price.round_dp(2) != local.list().round_dp(2) {
+        changes.push(FieldChange {
+            field: ProductField::List,
+            remote: remote.price.round_dp(2).to_string(),
+            local: local.list().round_dp(2).to_string(),
+        });
+    }
+    if remote.stock != local.stock() {
+        changes.push(FieldChange {
+            field: ProductField::Stock,
+            remote: remote.stock.to_string(),
+            local: local.stock().to_string(),
+        });
+    }
+    changes
+}
+
+/// Compare a remote platform against the local catalog, treating the local catalog as the
+/// source of truth.
+///
+/// # Arguments
+/// * `remote` - The platform to check
+/// * `local` - The parsed export
+///
+/// # Returns
+/// The actions needed to bring the remote platform in line, sorted by sku. Descriptions,
+/// prices (to the cent), and stock are compared
+///
+/// # Errors
+/// The first error raised while reading from `remote`
+pub fn reconcile<R: RemoteCatalog>(
+    remote: &R,
+    local: &AbcProductsBySku,
+) -> Result<Vec<Action>, R::Error> {
+    let remote_skus: BTreeSet<String> = remote.fetch_skus()?.into_iter().collect();
+    let skus: BTreeSet<&str> = remote_skus
+        .iter()
+        .map(|s| s.as_str())
+        .chain(local.keys().map(|s| s.as_str()))
+        .collect();
+
+    let mut actions = Vec::new();
+    for sku in skus {
+        let action = match (remote_skus.contains(sku), local.get(sku)) {
+            (false, Some(product)) => Action::Create(remote_product(product)),
+            (true, None) => Action::Archive(sku.to_string()),
+            (true, Some(product)) => match remote.fetch_product(sku)? {
+                None => Action::Create(remote_product(product)),
+                Some(remote_product) => {
+                    let changes = changes(&remote_product, product);
+                    if changes.is_empty() {
+                        continue;
+                    }
+                    Action::Update(sku.to_string(), changes)
+                }
+            },
+            (false, None) => continue,
+        };
+        actions.push(action);
+    }
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+    use std::collections::HashMap;
+
+    struct MemoryCatalog(HashMap<String, RemoteProduct>);
+
+    impl RemoteCatalog for MemoryCatalog {
+        type Error = ();
+
+        fn fetch_skus(&self) -> Result<Vec<String>, ()> {
+            Ok(self.0.keys().cloned().collect())
+        }
+
+        fn fetch_product(&self, sku: &str) -> Result<Option<RemoteProduct>, ()> {
+            Ok(self.0.get(sku).cloned())
+        }
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let local = sample_export();
+        let product = &local["123456"];
+        let remote = MemoryCatalog(HashMap::from([
+            (
+                "123456".to_string(),
+                RemoteProduct {
+                    sku: "123456".to_string(),
+                    desc: product.desc(),
+                    price: Decimal::new(499, 2),
+                    stock: product.stock(),
+                },
+            ),
+            (
+                "GONE".to_string(),
+                RemoteProduct {
+                    sku: "GONE".to_string(),
+                    desc: "OLD".to_string(),
+                    price: Decimal::ONE,
+                    stock: 0.0,
+                },
+            ),
+        ]));
+        assert_eq!(
+            reconcile(&remote, &local).unwrap(),
+            vec![
+                Action::Update(
+                    "123456".to_string(),
+                    vec![FieldChange {
+                        field: ProductField::List,
+                        remote: "4.99".to_string(),
+                        local: "5.99".to_string(),
+                    }]
+                ),
+                Action::Create(remote_product(&local["ABC123"])),
+                Action::Archive("GONE".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,882 @@
+use std::io::Read;
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::{
+    AbcParseError, AbcProduct, Encoding, IntermediateBaseProduct, IntermediatePostedProduct,
+    ParseOptions, ParseWarnings, Sku, input, measure::NetContents, open_data_file, price_from_str,
+};
+
+/// Column numbers of the fields this crate knows about in `item.data`, counting from zero
+pub mod item_columns {
+    pub const SKU: usize = 0;
+    pub const DESC: usize = 1;
+    pub const LIST: usize = 6;
+    pub const COST: usize = 8;
+    pub const AVG_COST: usize = 9;
+    pub const VENDOR: usize = 10;
+    pub const GROUP: usize = 18;
+    pub const ALT_SKUS: [usize; 3] = [40, 41, 42];
+    pub const UPCS: usize = 43;
+    pub const WEIGHT: usize = 45;
+}
+
+/// Column numbers of the fields this crate knows about in `item_posted.data`, counting from zero
+pub mod posted_columns {
+    pub const SKU: usize = 0;
+    pub const LAST_SOLD: usize = 1;
+    pub const STOCK: usize = 19;
+}
+
+/// Which column holds each field this crate reads from `item.data` and `item_posted.data`,
+/// counting from zero.
+///
+/// Some versions of ABC add or move columns in the export. The default layout matches
+/// [`item_columns`] and [`posted_columns`], and any field can be overridden.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{ParseOptions, records::ColumnLayout};
+///
+/// let layout = ColumnLayout {
+///     list: 7,
+///     weight: 46,
+///     ..Default::default()
+/// };
+/// let options = ParseOptions::default().with_layout(layout);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ColumnLayout {
+    /// The sku in `item.data`
+    pub sku: usize,
+    /// The description in `item.data`
+    pub desc: usize,
+    /// The list price in `item.data`
+    pub list: usize,
+    /// The last cost in `item.data`
+    pub cost: usize,
+    /// The average cost in `item.data`
+    pub avg_cost: usize,
+    /// The primary vendor code in `item.data`
+    pub vendor: usize,
+    /// The discount group in `item.data`
+    pub group: usize,
+    /// The alternate skus in `item.data`
+    pub alt_skus: [usize; 3],
+    /// The UPC list in `item.data`
+    pub upcs: usize,
+    /// The weight in `item.data`
+    pub weight: usize,
+    /// The sku in `item_posted.data`
+    pub posted_sku: usize,
+    /// The date last sold in `item_posted.data`
+    pub last_sold: usize,
+    /// The stock on hand in `item_posted.data`
+    pub stock: usize,
+    /// The sku of the item's core charge in `item.data`. [`None`] by default, because stock ABC
+    /// exports do not carry one; set it when the store keeps core skus in a spare column
+    pub core_sku: Option<usize>,
+    /// The warranty length in months in `item.data`. [`None`] by default, like
+    /// [`ColumnLayout::core_sku`]
+    pub warranty_months: Option<usize>,
+    /// The net contents, such as `12 OZ`, in `item.data`. [`None`] by default
+    pub net_contents: Option<usize>,
+    /// The country of origin in `item.data`. [`None`] by default
+    pub country_of_origin: Option<usize>,
+    /// The minimum stock level in `item.data`. [`None`] by default
+    pub min_stock: Option<usize>,
+    /// The maximum stock level in `item.data`. [`None`] by default
+    pub max_stock: Option<usize>,
+}
+
+impl Default for ColumnLayout {
+    fn default() -> Self {
+        ColumnLayout {
+            sku: item_columns::SKU,
+            desc: item_columns::DESC,
+            list: item_columns::LIST,
+            cost: item_columns::COST,
+            avg_cost: item_columns::AVG_COST,
+            vendor: item_columns::VENDOR,
+            group: item_columns::GROUP,
+            alt_skus: item_columns::ALT_SKUS,
+            upcs: item_columns::UPCS,
+            weight: item_columns::WEIGHT,
+            posted_sku: posted_columns::SKU,
+            last_sold: posted_columns::LAST_SOLD,
+            stock: posted_columns::STOCK,
+            core_sku: None,
+            warranty_months: None,
+            net_contents: None,
+            country_of_origin: None,
+            min_stock: None,
+            max_stock: None,
+        }
+    }
+}
+
+/// Read a required column of a row
+fn required<'a>(
+    record: &'a csv::StringRecord,
+    file: &str,
+    column: usize,
+    name: &str,
+    row: usize,
+) -> Result<&'a str, AbcParseError> {
+    record
+        .get(column)
+        .ok_or_else(|| AbcParseError::missing(Some(file), row, Some(column), name))
+}
+
+/// One row of `item.data`, with typed access to every column this crate understands and raw
+/// access to the rest
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemRecord {
+    row: usize,
+    record: csv::StringRecord,
+    layout: ColumnLayout,
+}
+
+impl ItemRecord {
+    /// Wrap a parsed csv row, numbered from one
+    pub(crate) fn new(row: usize, record: csv::StringRecord) -> Self {
+        ItemRecord {
+            row,
+            record,
+            layout: ColumnLayout::default(),
+        }
+    }
+
+    /// Read this row's fields from the columns given by `layout` instead of the defaults
+    pub fn with_layout(self, layout: ColumnLayout) -> Self {
+        ItemRecord { layout, ..self }
+    }
+
+    /// The columns this row's fields are read from
+    pub fn layout(&self) -> ColumnLayout {
+        self.layout
+    }
+
+    /// Copy this row with the text of `column` replaced by `value`. Rows too short to have the
+    /// column are copied unchanged
+    pub(crate) fn with_column(&self, column: usize, value: &str) -> Self {
+        let record = self
+            .record
+            .iter()
+            .enumerate()
+            .map(|(i, field)| if i == column { value } else { field })
+            .collect();
+        ItemRecord {
+            record,
+            ..self.clone()
+        }
+    }
+
+    /// The row of the file this record came from, counting from one
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// The raw text of any column, counting from zero. [`None`] if the row is too short
+    pub fn get(&self, column: usize) -> Option<&str> {
+        self.record.get(column)
+    }
+
+    /// How many columns the row has
+    pub fn len(&self) -> usize {
+        self.record.len()
+    }
+
+    /// Whether the row has no columns at all
+    pub fn is_empty(&self) -> bool {
+        self.record.is_empty()
+    }
+
+    /// The sku of the item
+    pub fn sku(&self) -> Result<&str, AbcParseError> {
+        required(&self.record, "item.data", self.layout.sku, "sku", self.row)
+    }
+
+    /// The description of the item
+    pub fn desc(&self) -> Result<&str, AbcParseError> {
+        required(
+            &self.record,
+            "item.data",
+            self.layout.desc,
+            "desc",
+            self.row,
+        )
+    }
+
+    /// The list price of the item
+    pub fn list(&self) -> Result<Decimal, AbcParseError> {
+        let list = required(
+            &self.record,
+            "item.data",
+            self.layout.list,
+            "list",
+            self.row,
+        )?;
+        price_from_str(list).map_err(|e| {
+            AbcParseError::invalid(
+                Some("item.data"),
+                self.row,
+                self.layout.list,
+                "list",
+                list,
+                e,
+            )
+        })
+    }
+
+    /// The last cost of the item
+    pub fn cost(&self) -> Result<Decimal, AbcParseError> {
+        let cost = required(
+            &self.record,
+            "item.data",
+            self.layout.cost,
+            "cost",
+            self.row,
+        )?;
+        price_from_str(cost).map_err(|e| {
+            AbcParseError::invalid(
+                Some("item.data"),
+                self.row,
+                self.layout.cost,
+                "cost",
+                cost,
+                e,
+            )
+        })
+    }
+
+    /// The average cost of the item. [`None`] if ABC exported it as "null"
+    pub fn avg_cost(&self) -> Option<Decimal> {
+        self.get(self.layout.avg_cost)
+            .and_then(|c| price_from_str(c).ok())
+    }
+
+    /// The code of the item's primary vendor, if any. Matches [`crate::vendor::AbcVendor::code`]
+    pub fn vendor(&self) -> Option<&str> {
+        self.get(self.layout.vendor).filter(|v| !v.is_empty())
+    }
+
+    /// The discount group of the item, if any
+    pub fn group(&self) -> Option<&str> {
+        self.get(self.layout.group).filter(|g| !g.is_empty())
+    }
+
+    /// The sku of the item's core charge, if the layout has a core column and it is filled in
+    pub fn core_sku(&self) -> Option<&str> {
+        self.layout
+            .core_sku
+            .and_then(|c| self.get(c))
+            .filter(|c| !c.is_empty())
+    }
+
+    /// The warranty length of the item in months, if the layout has a warranty column and it
+    /// holds a whole number
+    pub fn warranty_months(&self) -> Option<u32> {
+        self.layout
+            .warranty_months
+            .and_then(|c| self.get(c))
+            .and_then(|m| m.trim().parse().ok())
+    }
+
+    /// The net contents of the item, if the layout has a net contents column and it holds a
+    /// quantity and unit
+    pub fn net_contents(&self) -> Option<NetContents> {
+        self.layout
+            .net_contents
+            .and_then(|c| self.get(c))
+            .and_then(|n| n.parse().ok())
+    }
+
+    /// The country of origin of the item, if the layout has a country column and it is filled in
+    pub fn country_of_origin(&self) -> Option<&str> {
+        self.layout
+            .country_of_origin
+            .and_then(|c| self.get(c))
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+    }
+
+    /// The minimum stock level of the item, if the layout has a min column and it is a number
+    pub fn min_stock(&self) -> Option<f64> {
+        self.layout
+            .min_stock
+            .and_then(|c| self.get(c))
+            .and_then(|q| q.trim().parse().ok())
+    }
+
+    /// The maximum stock level of the item, if the layout has a max column and it is a number
+    pub fn max_stock(&self) -> Option<f64> {
+        self.layout
+            .max_stock
+            .and_then(|c| self.get(c))
+            .and_then(|q| q.trim().parse().ok())
+    }
+
+    /// The alternate skus of the item, skipping empty slots
+    pub fn alt_skus(&self) -> Vec<&str> {
+        self.layout
+            .alt_skus
+            .iter()
+            .filter_map(|c| self.get(*c))
+            .filter(|s| !s.trim().is_empty())
+            .collect()
+    }
+
+    /// The raw text of the UPC column, such as `["085875500014"]`
+    pub fn upc_column(&self) -> Result<&str, AbcParseError> {
+        required(
+            &self.record,
+            "item.data",
+            self.layout.upcs,
+            "upcs",
+            self.row,
+        )
+    }
+
+    /// Every UPC listed for the item as plain digits, without any validation
+    pub fn raw_upcs(&self) -> Result<Vec<String>, AbcParseError> {
+        let digits: String = self
+            .upc_column()?
+            .chars()
+            .filter(|c| c.is_ascii_digit() || *c == ',')
+            .collect();
+        Ok(digits
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect())
+    }
+
+    /// The raw text of the weight column
+    pub fn weight_column(&self) -> Result<&str, AbcParseError> {
+        required(
+            &self.record,
+            "item.data",
+            self.layout.weight,
+            "weight",
+            self.row,
+        )
+    }
+
+    /// The weight of the item. [`None`] if the column is missing or not a number
+    pub fn weight(&self) -> Option<f64> {
+        self.get(self.layout.weight)
+            .and_then(|w| w.parse::<f64>().ok())
+    }
+}
+
+/// One row of `item_posted.data`, with typed access to every column this crate understands and
+/// raw access to the rest
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostedRecord {
+    row: usize,
+    record: csv::StringRecord,
+    layout: ColumnLayout,
+}
+
+impl PostedRecord {
+    /// Wrap a parsed csv row, numbered from one
+    pub(crate) fn new(row: usize, record: csv::StringRecord) -> Self {
+        PostedRecord {
+            row,
+            record,
+            layout: ColumnLayout::default(),
+        }
+    }
+
+    /// Read this row's fields from the columns given by `layout` instead of the defaults
+    pub fn with_layout(self, layout: ColumnLayout) -> Self {
+        PostedRecord { layout, ..self }
+    }
+
+    /// The row of the file this record came from, counting from one
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// The raw text of any column, counting from zero. [`None`] if the row is too short
+    pub fn get(&self, column: usize) -> Option<&str> {
+        self.record.get(column)
+    }
+
+    /// How many columns the row has
+    pub fn len(&self) -> usize {
+        self.record.len()
+    }
+
+    /// Whether the row has no columns at all
+    pub fn is_empty(&self) -> bool {
+        self.record.is_empty()
+    }
+
+    /// The sku of the item
+    pub fn sku(&self) -> Result<&str, AbcParseError> {
+        required(
+            &self.record,
+            "item_posted.data",
+            self.layout.posted_sku,
+            "sku",
+            self.row,
+        )
+    }
+
+    /// The raw text of the last sold column
+    pub fn last_sold_column(&self) -> Result<&str, AbcParseError> {
+        required(
+            &self.record,
+            "item_posted.data",
+            self.layout.last_sold,
+            "last_sold",
+            self.row,
+        )
+    }
+
+    /// The date the item was last sold. [`None`] if it has never sold or the date is not valid
+    pub fn last_sold(&self) -> Option<NaiveDate> {
+        self.get(self.layout.last_sold)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    /// The quantity on hand
+    pub fn stock(&self) -> Result<f64, AbcParseError> {
+        let stock = required(
+            &self.record,
+            "item_posted.data",
+            self.layout.stock,
+            "stock",
+            self.row,
+        )?;
+        stock.parse().map_err(|e| {
+            AbcParseError::invalid(
+                Some("item_posted.data"),
+                self.row,
+                self.layout.stock,
+                "stock",
+                stock,
+                e,
+            )
+        })
+    }
+}
+
+impl TryFrom<(&ItemRecord, &PostedRecord)> for AbcProduct {
+    type Error = AbcParseError;
+
+    fn try_from((item, posted): (&ItemRecord, &PostedRecord)) -> Result<Self, Self::Error> {
+        join(item, posted, &mut ParseWarnings::new())
+    }
+}
+
+/// Join a row of `item.data` with the matching row of `item_posted.data`, parsing both with the
+/// same row parsers as [`AbcProduct::from_readers`] and adding anything unusual to `warnings`
+///
+/// # Errors
+/// [`AbcParseError::MisMatchedSkus`] if the rows are for different items, checked before
+/// anything else, or the first error parsing either row
+pub(crate) fn join(
+    item: &ItemRecord,
+    posted: &PostedRecord,
+    warnings: &mut ParseWarnings,
+) -> Result<AbcProduct, AbcParseError> {
+    if Sku::new(item.sku()?) != Sku::new(posted.sku()?) {
+        return Err(AbcParseError::MisMatchedSkus);
+    }
+    let base = IntermediateBaseProduct::from_row(&item.record, item.row, &item.layout, warnings)?;
+    let posted =
+        IntermediatePostedProduct::from_row(&posted.record, posted.row, &posted.layout, warnings)?;
+    AbcProduct::try_from((&base, &posted))
+}
+
+/// The text of a column, trimmed. [`None`] if the column is missing, empty, or "null"
+pub(crate) fn text(record: &csv::StringRecord, column: usize) -> Option<String> {
+    record
+        .get(column)
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty() && *v != "null")
+        .map(|v| v.to_string())
+}
+
+/// A signed dollar amount or quantity in column `column` of `file`, such as a balance or a
+/// returned line. Empty and "null" columns are zero
+pub(crate) fn amount(
+    record: &csv::StringRecord,
+    file: &str,
+    column: usize,
+    name: &str,
+    row: usize,
+) -> Result<Decimal, AbcParseError> {
+    match text(record, column) {
+        Some(a) => a
+            .replace(['$', ','], "")
+            .parse::<Decimal>()
+            .map_err(|e| AbcParseError::invalid(Some(file), row, column, name, &a, e)),
+        None => Ok(Decimal::ZERO),
+    }
+}
+
+/// A date in a column, written by ABC as YYYY-MM-DD. [`None`] if it cannot be parsed
+pub(crate) fn date(record: &csv::StringRecord, column: usize) -> Option<NaiveDate> {
+    text(record, column).and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok())
+}
+
+/// Iterate over the raw csv records of an ABC data file, numbering rows from one
+pub(crate) fn rows<'a>(
+    data: impl Read + 'a,
+    file: &'static str,
+    encoding: Encoding,
+) -> Result<
+    impl Iterator<Item = Result<(usize, csv::StringRecord), AbcParseError>> + 'a,
+    AbcParseError,
+> {
+    let reader = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(input::decode(
+            input::normalize(data).map_err(csv::Error::from)?,
+            encoding,
+        ));
+    Ok(reader
+        .into_records()
+        .enumerate()
+        .map(move |(i, r)| Ok((i + 1, r.map_err(|e| AbcParseError::csv(Some(file), e))?))))
+}
+
+/// Read the rows of `item.data` from any reader, with the columns given by `options`. See
+/// [`read_item_data`]
+pub(crate) fn item_records<'a, R: Read + 'a>(
+    data: R,
+    options: &ParseOptions,
+) -> Result<impl Iterator<Item = Result<ItemRecord, AbcParseError>> + use<'a, R>, AbcParseError> {
+    let layout = options.layout;
+    Ok(rows(data, "item.data", options.encoding)?
+        .map(move |r| r.map(|(row, record)| ItemRecord::new(row, record).with_layout(layout))))
+}
+
+/// Read the rows of `item_posted.data` from any reader, with the columns given by `options`.
+/// See [`read_item_posted_data`]
+pub(crate) fn posted_records<'a, R: Read + 'a>(
+    data: R,
+    options: &ParseOptions,
+) -> Result<impl Iterator<Item = Result<PostedRecord, AbcParseError>> + use<'a, R>, AbcParseError> {
+    let layout = options.layout;
+    Ok(rows(data, "item_posted.data", options.encoding)?
+        .map(move |r| r.map(|(row, record)| PostedRecord::new(row, record).with_layout(layout))))
+}
+
+/// Read `item.data` one row at a time, for building joins and aggregations that the
+/// [`crate::AbcProduct`] model does not cover.
+///
+/// Unlike [`crate::AbcProduct::from_db_export`], rows are not validated up front. Each typed
+/// accessor on [`ItemRecord`] reports its own problems, so a bad price in one row does not stop
+/// the sku of every row from being read.
+///
+/// # Example
+/// ```rust
+/// use abc_product::records;
+///
+/// let mut total_list = rust_decimal::Decimal::ZERO;
+/// for record in records::read_item_data("./item.data").unwrap() {
+///     total_list += record.unwrap().list().unwrap();
+/// }
+/// ```
+///
+/// # Errors
+/// An [`AbcParseError`] if the file cannot be opened. Each item of the iterator is an error if
+/// that row cannot be read as csv
+pub fn read_item_data(
+    path: &str,
+) -> Result<impl Iterator<Item = Result<ItemRecord, AbcParseError>>, AbcParseError> {
+    read_item_data_with_options(path, &ParseOptions::default())
+}
+
+/// Like [`read_item_data`], reading each field from the column given by
+/// [`ParseOptions::layout`]
+///
+/// # Errors
+/// See [`read_item_data`]
+pub fn read_item_data_with_options(
+    path: &str,
+    options: &ParseOptions,
+) -> Result<impl Iterator<Item = Result<ItemRecord, AbcParseError>> + use<>, AbcParseError> {
+    item_records(open_data_file(path).map_err(csv::Error::from)?, options)
+}
+
+/// Read `item_posted.data` one row at a time. See [`read_item_data`]
+///
+/// # Errors
+/// An [`AbcParseError`] if the file cannot be opened. Each item of the iterator is an error if
+/// that row cannot be read as csv
+pub fn read_item_posted_data(
+    path: &str,
+) -> Result<impl Iterator<Item = Result<PostedRecord, AbcParseError>>, AbcParseError> {
+    read_item_posted_data_with_options(path, &ParseOptions::default())
+}
+
+/// Like [`read_item_posted_data`], reading each field from the column given by
+/// [`ParseOptions::layout`]
+///
+/// # Errors
+/// See [`read_item_data`]
+pub fn read_item_posted_data_with_options(
+    path: &str,
+    options: &ParseOptions,
+) -> Result<impl Iterator<Item = Result<PostedRecord, AbcParseError>> + use<>, AbcParseError> {
+    posted_records(open_data_file(path).map_err(csv::Error::from)?, options)
+}
+
+/// A column layout written by a known version of ABC, as chosen by [`detect_layout`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnownLayout {
+    /// A short name for the export version, for logging which one was chosen
+    pub name: &'static str,
+    /// How many columns each row of `item.data` has in this version
+    pub item_width: usize,
+    /// How many columns each row of `item_posted.data` has in this version
+    pub posted_width: usize,
+    /// Where this version puts each field
+    pub layout: ColumnLayout,
+}
+
+impl KnownLayout {
+    /// The layout of the exports this crate was written against, matching [`item_columns`]
+    /// and [`posted_columns`]
+    pub fn standard() -> Self {
+        KnownLayout {
+            name: "standard",
+            item_width: 100,
+            posted_width: 52,
+            layout: ColumnLayout::default(),
+        }
+    }
+
+    /// The layout of exports from versions of ABC that moved the list price to column 7 and the
+    /// weight to column 46, leaving every other field where [`KnownLayout::standard`] has it
+    pub fn moved_list_and_weight() -> Self {
+        KnownLayout {
+            name: "moved list and weight",
+            layout: ColumnLayout {
+                list: 7,
+                weight: 46,
+                ..Default::default()
+            },
+            ..KnownLayout::standard()
+        }
+    }
+
+    /// Whether a row of `item.data` fits this layout
+    fn fits_item(&self, record: &ItemRecord) -> bool {
+        record.len() == self.item_width
+            && record.sku().is_ok_and(|s| !s.is_empty())
+            && record.list().is_ok()
+            && record.cost().is_ok()
+            && record.upc_column().is_ok_and(|u| {
+                u.chars()
+                    .all(|c| c.is_ascii_digit() || ",[]\" ".contains(c))
+            })
+            && record
+                .weight_column()
+                .is_ok_and(|w| w.trim().is_empty() || w.parse::<f64>().is_ok())
+    }
+
+    /// Whether a row of `item_posted.data` fits this layout
+    fn fits_posted(&self, record: &PostedRecord) -> bool {
+        record.len() == self.posted_width
+            && record.sku().is_ok_and(|s| !s.is_empty())
+            && record.stock().is_ok()
+            && record.last_sold_column().is_ok_and(|d| {
+                d.is_empty() || d == "null" || NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok()
+            })
+    }
+}
+
+/// The export versions [`detect_layout`] chooses between
+pub fn known_layouts() -> Vec<KnownLayout> {
+    vec![
+        KnownLayout::standard(),
+        KnownLayout::moved_list_and_weight(),
+    ]
+}
+
+/// How many rows at the start of a file are checked when detecting its layout
+const SNIFF_ROWS: usize = 100;
+
+/// Work out which version of ABC wrote an export file, so that stores running different
+/// versions can be parsed without configuring each one by hand.
+///
+/// The first rows of the file are checked against every layout from [`known_layouts`]: the
+/// number of columns, prices where list and cost should be, a UPC list where the UPCs should
+/// be, and a date where the last sold date should be. Either `item.data` or `item_posted.data`
+/// may be given, since both are written by the same version.
+///
+/// # Arguments
+/// * `path` - The path to `item.data` or `item_posted.data`
+///
+/// # Returns
+/// The layout that the most rows fit. Pass its [`KnownLayout::layout`] to
+/// [`crate::ParseOptions::with_layout`]
+///
+/// # Example
+/// ```rust
+/// use abc_product::{ParseOptions, records};
+///
+/// let known = records::detect_layout("./item.data").unwrap();
+/// assert_eq!(known.name, "standard");
+/// let options = ParseOptions::default().with_layout(known.layout);
+/// ```
+///
+/// # Errors
+/// An [`AbcParseError`] if the file cannot be read, or if fewer than half of the rows checked
+/// fit any known layout
+pub fn detect_layout(path: &str) -> Result<KnownLayout, AbcParseError> {
+    detect_layout_among(path, &known_layouts())
+}
+
+/// Like [`detect_layout`], but choosing between `candidates`, such as layouts for versions of
+/// ABC that this crate does not know about yet
+///
+/// # Errors
+/// See [`detect_layout`]
+pub fn detect_layout_among(
+    path: &str,
+    candidates: &[KnownLayout],
+) -> Result<KnownLayout, AbcParseError> {
+    detect_from_reader(open_data_file(path).map_err(csv::Error::from)?, candidates).map_err(|e| {
+        match e {
+            AbcParseError::UnknownLayout(None) => {
+                AbcParseError::UnknownLayout(Some(path.to_string()))
+            }
+            e => e,
+        }
+    })
+}
+
+/// Detect the layout of an export read from any reader. See [`detect_layout`]
+pub(crate) fn detect_from_reader(
+    data: impl Read,
+    candidates: &[KnownLayout],
+) -> Result<KnownLayout, AbcParseError> {
+    let sample = rows(data, "item.data", Encoding::Auto)?
+        .take(SNIFF_ROWS)
+        .collect::<Result<Vec<(usize, csv::StringRecord)>, _>>()?;
+    let mut best: Option<(usize, KnownLayout)> = None;
+    for candidate in candidates {
+        let item = sample
+            .iter()
+            .map(|(row, r)| ItemRecord::new(*row, r.clone()).with_layout(candidate.layout))
+            .filter(|r| candidate.fits_item(r))
+            .count();
+        let posted = sample
+            .iter()
+            .map(|(row, r)| PostedRecord::new(*row, r.clone()).with_layout(candidate.layout))
+            .filter(|r| candidate.fits_posted(r))
+            .count();
+        let score = item.max(posted);
+        if best.is_none_or(|(b, _)| score > b) {
+            best = Some((score, *candidate));
+        }
+    }
+    match best {
+        Some((score, layout)) if !sample.is_empty() && score * 2 >= sample.len() => Ok(layout),
+        _ => Err(AbcParseError::UnknownLayout(None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SAMPLE_ITEM_DATA, SAMPLE_ITEM_POSTED_DATA};
+
+    #[test]
+    fn test_records() {
+        let items: Vec<ItemRecord> = item_records(SAMPLE_ITEM_DATA, &ParseOptions::default())
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].sku().unwrap(), "123456");
+        assert_eq!(items[0].list().unwrap(), Decimal::new(599, 2));
+        assert_eq!(items[0].raw_upcs().unwrap(), vec!["085875500014"]);
+        assert_eq!(items[0].vendor(), Some("VENDOR CODE"));
+        assert_eq!(items[1].vendor(), None);
+
+        let posted: Vec<PostedRecord> =
+            posted_records(SAMPLE_ITEM_POSTED_DATA, &ParseOptions::default())
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(posted[1].sku().unwrap(), "ABC123");
+        assert_eq!(posted[1].stock().unwrap(), -6.0);
+        assert_eq!(posted[1].row(), 2);
+    }
+
+    #[test]
+    fn test_detect_layout() {
+        let standard = KnownLayout::standard();
+        assert_eq!(
+            detect_from_reader(SAMPLE_ITEM_DATA, &[standard]).unwrap(),
+            standard
+        );
+        assert_eq!(
+            detect_from_reader(SAMPLE_ITEM_POSTED_DATA, &[standard]).unwrap(),
+            standard
+        );
+
+        // A version that added a column in front of the UPCs
+        let shifted = KnownLayout {
+            name: "shifted",
+            item_width: 101,
+            layout: ColumnLayout {
+                upcs: item_columns::UPCS + 1,
+                weight: item_columns::WEIGHT + 1,
+                ..Default::default()
+            },
+            ..standard
+        };
+        let item = String::from_utf8(SAMPLE_ITEM_DATA.to_vec()).unwrap();
+        let item: String = item
+            .lines()
+            .map(|line| {
+                let mut fields: Vec<&str> = line.split('\t').collect();
+                fields.insert(item_columns::UPCS, "extra");
+                fields.join("\t") + "\n"
+            })
+            .collect();
+        assert_eq!(
+            detect_from_reader(item.as_bytes(), &[standard, shifted]).unwrap(),
+            shifted
+        );
+        assert!(detect_from_reader(item.as_bytes(), &[standard]).is_err());
+        assert!(detect_from_reader(&b""[..], &[standard]).is_err());
+
+        assert_eq!(
+            detect_from_reader(SAMPLE_ITEM_DATA, &known_layouts()).unwrap(),
+            standard
+        );
+        let moved = KnownLayout::moved_list_and_weight();
+        let item = crate::testing::moved_item_data();
+        assert_eq!(
+            detect_from_reader(item.as_bytes(), &known_layouts()).unwrap(),
+            moved
+        );
+        let options = ParseOptions::default().with_layout(moved.layout);
+        let records: Vec<ItemRecord> = item_records(item.as_bytes(), &options)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records[0].list().unwrap(), Decimal::new(599, 2));
+        assert_eq!(records[1].list().unwrap(), Decimal::new(812, 2));
+        assert_eq!(records[1].weight(), Some(2.5));
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Read,
+};
+
+use crate::{AbcParseError, AbcProductsBySku, Sku, analytics::Association, input};
+
+/// Links between products that go together, such as a faucet and its supply lines, used to fill
+/// in [`crate::AbcProduct::related`] for "goes with" suggestions.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{relations::Relations, testing};
+///
+/// let mut products = testing::sample_export();
+/// Relations::new()
+///     .with_link("123456", "ABC123")
+///     .apply(&mut products);
+/// assert_eq!(products["123456"].related(), vec!["ABC123".to_string()]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Relations {
+    links: BTreeMap<Sku, BTreeSet<Sku>>,
+}
+
+impl Relations {
+    /// Create an empty [`Relations`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Link `related` to `sku`, so that it is suggested alongside `sku`. Links only go one way,
+    /// see [`Relations::mirrored`]
+    pub fn with_link(self, sku: &str, related: &str) -> Self {
+        let mut links = self.links;
+        let (sku, related) = (Sku::new(sku), Sku::new(related));
+        if !sku.as_str().is_empty() && !related.as_str().is_empty() && sku != related {
+            links.entry(sku).or_default().insert(related);
+        }
+        Relations { links }
+    }
+
+    /// Add the reverse of every link, so that if A goes with B then B also goes with A
+    pub fn mirrored(self) -> Self {
+        let reversed: Vec<(String, String)> = self
+            .links
+            .iter()
+            .flat_map(|(sku, related)| {
+                related
+                    .iter()
+                    .map(move |r| (r.to_string(), sku.to_string()))
+            })
+            .collect();
+        reversed.iter().fold(self, |relations, (sku, related)| {
+            relations.with_link(sku, related)
+        })
+    }
+
+    /// Load links from a comma separated file. Each row starts with a sku, followed by one or more
+    /// skus that go with it. A header row starting with "sku" is skipped.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file of links
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if the file cannot be read
+    pub fn from_csv(path: &str) -> Result<Self, AbcParseError> {
+        Self::from_csv_reader(std::fs::File::open(path).map_err(csv::Error::from)?)
+    }
+
+    /// Like [`Relations::from_csv`], reading from any reader rather than a path on disk
+    pub(crate) fn from_csv_reader(data: impl Read) -> Result<Self, AbcParseError> {
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(input::normalize(data).map_err(csv::Error::from)?);
+        let mut relations = Relations::new();
+        for record in reader.into_records() {
+            let record = record?;
+            let mut fields = record.iter();
+            let Some(sku) = fields.next() else {
+                continue;
+            };
+            if sku.trim().eq_ignore_ascii_case("sku") {
+                continue;
+            }
+            for related in fields {
+                relations = relations.with_link(sku, related);
+            }
+        }
+        Ok(relations)
+    }
+
+    /// Link the products of every rule in `rules` that customers follow at least
+    /// `min_confidence` of the time, from 0 to 1. See
+    /// [`crate::analytics::frequently_bought_together`]
+    pub fn from_associations(rules: &[Association], min_confidence: f64) -> Self {
+        rules
+            .iter()
+            .filter(|r| r.confidence >= min_confidence)
+            .fold(Relations::new(), |relations, r| {
+                relations.with_link(&r.sku, &r.with)
+            })
+    }
+
+    /// The skus linked to `sku`, sorted. `sku` is normalized with [`Sku::new`] first
+    pub fn related(&self, sku: &str) -> Vec<String> {
+        self.links
+            .get(&Sku::new(sku))
+            .map(|r| r.iter().map(|s| s.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Set [`crate::AbcProduct::related`] on every product in `products`. Links to skus that are
+    /// not in `products` are left out, so exports never suggest a product that does not exist
+    pub fn apply(&self, products: &mut AbcProductsBySku) {
+        let skus: BTreeSet<String> = products.keys().map(|s| s.to_string()).collect();
+        for (sku, product) in products.iter_mut() {
+            product.related = self
+                .related(sku.as_str())
+                .into_iter()
+                .filter(|r| skus.contains(r))
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_relations() {
+        let data = "sku,related\n123456,ABC123,MISSING\n123456,123456\n";
+        let relations = Relations::from_csv_reader(data.as_bytes()).unwrap();
+        assert_eq!(relations.related("123456"), vec!["ABC123", "MISSING"]);
+        assert!(relations.related("ABC123").is_empty());
+
+        let mut products = sample_export();
+        relations.mirrored().apply(&mut products);
+        assert_eq!(products["123456"].related(), vec!["ABC123"]);
+        assert_eq!(products["ABC123"].related(), vec!["123456"]);
+
+        let padded = Relations::new()
+            .with_link("abc123", " 123456 ")
+            .with_link("Abc123", "ABC123 ");
+        assert_eq!(padded.related(" ABC123"), vec!["123456"]);
+        let mut products = sample_export();
+        padded.apply(&mut products);
+        assert_eq!(products["ABC123"].related(), vec!["123456"]);
+    }
+}
@@ -0,0 +1,409 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    AbcParseError, AbcProduct, AbcProductsBySku, Encoding, ParseOptions, input,
+    records::{ItemRecord, PostedRecord},
+};
+
+/// How many bytes at the start of a file, and just before a checkpoint, are hashed to tell
+/// whether the file changed
+const FINGERPRINT_BYTES: u64 = 64 * 1024;
+
+/// Where parsing stopped in one data file, along with enough about the file to tell whether it
+/// changed since
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMark {
+    /// The byte offset of the first row not yet parsed
+    pub offset: u64,
+    /// The length of the file in bytes
+    pub len: u64,
+    /// A hash of the start of the file and the bytes just before `offset`
+    pub fingerprint: u64,
+}
+
+/// How far a [`ResumableParser`] got, so that an interrupted parse can continue from there with
+/// [`ResumableParser::resume`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// How many products have been parsed
+    pub rows: usize,
+    /// Where parsing stopped in `item.data`
+    pub item: FileMark,
+    /// Where parsing stopped in `item_posted.data`
+    pub item_posted: FileMark,
+}
+
+impl Checkpoint {
+    /// Write this checkpoint as JSON, to be stored until the parse is resumed
+    pub fn to_json(&self) -> String {
+        let mark = |m: &FileMark| json!({ "offset": m.offset, "len": m.len, "fingerprint": format!("{:016x}", m.fingerprint) });
+        json!({
+            "rows": self.rows,
+            "item": mark(&self.item),
+            "item_posted": mark(&self.item_posted),
+        })
+        .to_string()
+    }
+
+    /// Read a checkpoint written by [`Checkpoint::to_json`]
+    ///
+    /// # Errors
+    /// An [`AbcParseError::InvalidCheckpoint`] if `s` is not a checkpoint
+    pub fn from_json(s: &str) -> Result<Self, AbcParseError> {
+        let invalid = |what: &str| AbcParseError::InvalidCheckpoint(what.to_string());
+        let value: Value = serde_json::from_str(s).map_err(|e| invalid(&e.to_string()))?;
+        let mark = |v: &Value| -> Result<FileMark, AbcParseError> {
+            Ok(FileMark {
+                offset: v["offset"].as_u64().ok_or(invalid("missing offset"))?,
+                len: v["len"].as_u64().ok_or(invalid("missing len"))?,
+                fingerprint: v["fingerprint"]
+                    .as_str()
+                    .and_then(|f| u64::from_str_radix(f, 16).ok())
+                    .ok_or(invalid("missing fingerprint"))?,
+            })
+        };
+        Ok(Checkpoint {
+            rows: value["rows"].as_u64().ok_or(invalid("missing rows"))? as usize,
+            item: mark(&value["item"])?,
+            item_posted: mark(&value["item_posted"])?,
+        })
+    }
+}
+
+/// Hash the first bytes of `file` and the bytes just before `offset` with 64 bit FNV-1a
+fn fingerprint(file: &mut fs::File, offset: u64) -> std::io::Result<u64> {
+    let mut bytes = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.by_ref()
+        .take(FINGERPRINT_BYTES)
+        .read_to_end(&mut bytes)?;
+    let tail_start = offset.saturating_sub(FINGERPRINT_BYTES);
+    file.seek(SeekFrom::Start(tail_start))?;
+    file.by_ref()
+        .take(offset - tail_start)
+        .read_to_end(&mut bytes)?;
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Ok(hash)
+}
+
+/// Reads the rows of one data file, keeping track of the byte offset of the next row
+struct LineReader {
+    path: String,
+    reader: BufReader<fs::File>,
+    offset: u64,
+}
+
+impl LineReader {
+    /// Open the file at `path` and start reading at `mark`, after checking it has not changed.
+    /// [`None`] starts at the beginning
+    fn open(path: &str, mark: Option<&FileMark>) -> Result<Self, AbcParseError> {
+        let mut file = fs::File::open(path).map_err(csv::Error::from)?;
+        let offset = match mark {
+            None => 0,
+            Some(mark) => {
+                let len = file.metadata().map_err(csv::Error::from)?.len();
+                let changed = len != mark.len
+                    || mark.offset > len
+                    || fingerprint(&mut file, mark.offset).map_err(csv::Error::from)?
+                        != mark.fingerprint;
+                if changed {
+                    return Err(AbcParseError::FileChanged(path.to_string()));
+                }
+                mark.offset
+            }
+        };
+        file.seek(SeekFrom::Start(offset))
+            .map_err(csv::Error::from)?;
+        Ok(LineReader {
+            path: path.to_string(),
+            reader: BufReader::new(file),
+            offset,
+        })
+    }
+
+    /// Open the file at `path` at a known good `offset`, without checking it
+    fn open_at(path: &str, offset: u64) -> Result<Self, AbcParseError> {
+        let mut file = fs::File::open(path).map_err(csv::Error::from)?;
+        file.seek(SeekFrom::Start(offset))
+            .map_err(csv::Error::from)?;
+        Ok(LineReader {
+            path: path.to_string(),
+            reader: BufReader::new(file),
+            offset,
+        })
+    }
+
+    /// Where this reader is, for a [`Checkpoint`]
+    fn mark(&self) -> Result<FileMark, AbcParseError> {
+        let mut file = fs::File::open(&self.path).map_err(csv::Error::from)?;
+        Ok(FileMark {
+            offset: self.offset,
+            len: file.metadata().map_err(csv::Error::from)?.len(),
+            fingerprint: fingerprint(&mut file, self.offset).map_err(csv::Error::from)?,
+        })
+    }
+
+    /// Read the next non-empty row, with any byte order mark and `\r` line ending removed, as
+    /// text in `encoding`
+    fn next_record(
+        &mut self,
+        encoding: Encoding,
+    ) -> Result<Option<csv::StringRecord>, AbcParseError> {
+        loop {
+            let mut line = Vec::new();
+            let at_start = self.offset == 0;
+            let n = self
+                .reader
+                .read_until(b'\n', &mut line)
+                .map_err(csv::Error::from)?;
+            if n == 0 {
+                return Ok(None);
+            }
+            self.offset += n as u64;
+
+            let mut line = line.as_slice();
+            if at_start {
+                line = line.strip_prefix(&[0xef, 0xbb, 0xbf]).unwrap_or(line);
+            }
+            while let Some(rest) = line
+                .strip_suffix(b"\n")
+                .or_else(|| line.strip_suffix(b"\r"))
+            {
+                line = rest;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let record = csv::ReaderBuilder::new()
+                .delimiter(b'\t')
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(input::decode(line, encoding))
+                .records()
+                .next()
+                .transpose()?;
+            return Ok(record);
+        }
+    }
+}
+
+/// Parses an ABC database export in a way that can be stopped and picked up again later, for
+/// very large files on slow network shares.
+///
+/// Like [`crate::AbcProductIter`], rows of the two files are joined in lockstep. At any point,
+/// [`ResumableParser::checkpoint`] records the byte offset reached in each file along with a
+/// fingerprint of each file. [`ResumableParser::resume`] seeks straight back to those offsets
+/// after checking the files have not changed. Keeping the products parsed so far is up to the
+/// caller, such as by storing them alongside the checkpoint with the `serde` feature.
+///
+/// Files are read line by line, so unlike [`crate::AbcProduct::from_db_export`], UTF-16 files,
+/// files with only `\r` line endings, and compressed files are not supported.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use abc_product::{AbcProductsBySku, resume::ResumableParser};
+///
+/// let mut parser = ResumableParser::start("./item.data", "./item_posted.data").unwrap();
+/// let mut products = AbcProductsBySku::new();
+/// while !parser.parse_for(Duration::from_secs(5), &mut products).unwrap() {
+///     let checkpoint = parser.checkpoint().unwrap().to_json();
+///     // Save `checkpoint` and `products` somewhere safe
+/// }
+/// ```
+pub struct ResumableParser {
+    item: LineReader,
+    item_posted: LineReader,
+    rows: usize,
+    options: ParseOptions,
+}
+
+impl ResumableParser {
+    /// Start parsing the export files at the given paths from the beginning
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file cannot be opened
+    pub fn start(item_path: &str, item_posted_path: &str) -> Result<Self, AbcParseError> {
+        Ok(ResumableParser {
+            item: LineReader::open(item_path, None)?,
+            item_posted: LineReader::open(item_posted_path, None)?,
+            rows: 0,
+            options: ParseOptions::default(),
+        })
+    }
+
+    /// Continue parsing the export files at the given paths from `checkpoint`
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file cannot be opened, or has changed since `checkpoint`
+    /// was taken
+    pub fn resume(
+        item_path: &str,
+        item_posted_path: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<Self, AbcParseError> {
+        Ok(ResumableParser {
+            item: LineReader::open(item_path, Some(&checkpoint.item))?,
+            item_posted: LineReader::open(item_posted_path, Some(&checkpoint.item_posted))?,
+            rows: checkpoint.rows,
+            options: ParseOptions::default(),
+        })
+    }
+
+    /// Read the files as set by `options`, such as an export with a different
+    /// [`ParseOptions::layout`] or [`ParseOptions::encoding`]. Rows that cannot be parsed always stop the parse, whatever
+    /// [`ParseOptions::on_error`] says, so that the checkpoint points at them
+    pub fn with_options(self, options: &ParseOptions) -> Self {
+        ResumableParser {
+            options: options.clone(),
+            ..self
+        }
+    }
+
+    /// Record how far parsing has gotten
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if either file can no longer be read to fingerprint it
+    pub fn checkpoint(&self) -> Result<Checkpoint, AbcParseError> {
+        Ok(Checkpoint {
+            rows: self.rows,
+            item: self.item.mark()?,
+            item_posted: self.item_posted.mark()?,
+        })
+    }
+
+    /// Parse products into `products` until the files are finished or `budget` runs out,
+    /// whichever comes first
+    ///
+    /// # Returns
+    /// `true` if every product has been parsed, or `false` if there is more to do
+    ///
+    /// # Errors
+    /// The first [`AbcParseError`] raised by a row. The checkpoint is left just after the last
+    /// good row
+    pub fn parse_for(
+        &mut self,
+        budget: Duration,
+        products: &mut AbcProductsBySku,
+    ) -> Result<bool, AbcParseError> {
+        let deadline = Instant::now() + budget;
+        while Instant::now() < deadline {
+            match self.next_product()? {
+                Some(product) => {
+                    products.insert(product.sku.clone(), product);
+                }
+                None => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+
+    /// Parse the next product, if there is one
+    fn next_product(&mut self) -> Result<Option<AbcProduct>, AbcParseError> {
+        let (item_offset, posted_offset) = (self.item.offset, self.item_posted.offset);
+        let encoding = self.options.encoding;
+        let result = (|| match (
+            self.item.next_record(encoding)?,
+            self.item_posted.next_record(encoding)?,
+        ) {
+            (None, None) => Ok(None),
+            (Some(item), Some(posted)) => {
+                let row = self.rows + 1;
+                let product = AbcProduct::try_from((
+                    &ItemRecord::new(row, item).with_layout(self.options.layout),
+                    &PostedRecord::new(row, posted).with_layout(self.options.layout),
+                ))?;
+                Ok(Some(product))
+            }
+            _ => Err(AbcParseError::MismatchedRowCounts {
+                first: "item.data".to_string(),
+                second: "item_posted.data".to_string(),
+            }),
+        })();
+        match &result {
+            Ok(Some(_)) => self.rows += 1,
+            Ok(None) => {}
+            Err(_) => {
+                // Rewind so a checkpoint taken after an error points at the bad row
+                self.item = LineReader::open_at(&self.item.path, item_offset)?;
+                self.item_posted = LineReader::open_at(&self.item_posted.path, posted_offset)?;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SAMPLE_ITEM_DATA, SAMPLE_ITEM_POSTED_DATA, sample_export};
+
+    #[test]
+    fn test_resume() {
+        let dir = std::env::temp_dir().join(format!("abc-product-resume-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let item = dir.join("item.data");
+        let posted = dir.join("item_posted.data");
+        fs::write(&item, SAMPLE_ITEM_DATA).unwrap();
+        fs::write(&posted, SAMPLE_ITEM_POSTED_DATA).unwrap();
+        let (item, posted) = (item.to_str().unwrap(), posted.to_str().unwrap());
+
+        let mut parser = ResumableParser::start(item, posted).unwrap();
+        let mut products = AbcProductsBySku::new();
+        let first = parser.next_product().unwrap().unwrap();
+        products.insert(first.sku().into(), first);
+        let checkpoint = Checkpoint::from_json(&parser.checkpoint().unwrap().to_json()).unwrap();
+        assert_eq!(checkpoint.rows, 1);
+        drop(parser);
+
+        let mut parser = ResumableParser::resume(item, posted, &checkpoint).unwrap();
+        assert!(
+            parser
+                .parse_for(Duration::from_secs(60), &mut products)
+                .unwrap()
+        );
+        assert_eq!(products, sample_export());
+
+        fs::write(item, crate::testing::moved_item_data()).unwrap();
+        let layout = crate::records::KnownLayout::moved_list_and_weight().layout;
+        let mut parser = ResumableParser::start(item, posted)
+            .unwrap()
+            .with_options(&ParseOptions::default().with_layout(layout));
+        let mut moved = AbcProductsBySku::new();
+        assert!(
+            parser
+                .parse_for(Duration::from_secs(60), &mut moved)
+                .unwrap()
+        );
+        assert_eq!(moved["ABC123"].list(), rust_decimal::Decimal::new(812, 2));
+        assert_eq!(moved["ABC123"].weight(), Some(2.5));
+
+        let mut cp1252 = SAMPLE_ITEM_DATA.to_vec();
+        let at = cp1252.windows(9).position(|w| w == b"PRODUCT A").unwrap() + 7;
+        cp1252.splice(at..at + 1, *b"\x96");
+        fs::write(item, cp1252).unwrap();
+        let parse = |encoding: Encoding| {
+            ResumableParser::start(item, posted)
+                .unwrap()
+                .with_options(&ParseOptions::default().with_encoding(encoding))
+                .parse_for(Duration::from_secs(60), &mut AbcProductsBySku::new())
+        };
+        assert!(parse(Encoding::Auto).unwrap());
+        assert!(parse(Encoding::Utf8).is_err());
+
+        fs::write(item, b"changed").unwrap();
+        assert!(ResumableParser::resume(item, posted, &checkpoint).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+use rust_decimal::Decimal;
+
+use crate::{AbcProductsBySku, Sku};
+
+/// A single line of a return merchandise authorization, as submitted by the returns portal
+#[derive(Debug, Clone, PartialEq)]
+pub struct RmaLine {
+    /// The number of the RMA this line belongs to
+    pub rma_number: String,
+    /// The sku of the product being returned
+    pub sku: String,
+    /// How many units are being returned. Must be greater than zero
+    pub qty: f64,
+    /// The serial number of the returned unit, if the product is serialized
+    pub serial: Option<String>,
+    /// Why the customer is returning the product
+    pub reason: String,
+    /// The unit price to credit. [`None`] to credit the product's current list price
+    pub credit_price: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RmaError {
+    /// The sku on the RMA line does not exist in the catalog
+    UnknownSku(String),
+    /// The quantity on the RMA line for the sku was zero, negative, or not a finite number
+    InvalidQuantity(String, f64),
+    /// The serial number given for the sku is blank or contains characters that cannot be
+    /// written to an import file
+    InvalidSerial(String, String),
+}
+
+impl std::fmt::Display for RmaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSku(sku) => write!(f, "Sku `{}` does not exist in the catalog", sku),
+            Self::InvalidQuantity(sku, qty) => {
+                write!(f, "Cannot return a quantity of {} for sku `{}`", qty, sku)
+            }
+            Self::InvalidSerial(sku, serial) => {
+                write!(f, "Serial `{}` for sku `{}` is not valid", serial, sku)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RmaError {}
+
+impl RmaLine {
+    /// Check this line against the catalog.
+    ///
+    /// The catalog does not track serial numbers, so serials are only checked for being
+    /// non-blank and free of tabs and newlines so that they survive the import file.
+    ///
+    /// # Errors
+    /// An [`RmaError`] describing the first problem found with the line
+    pub fn validate(&self, catalog: &AbcProductsBySku) -> Result<(), RmaError> {
+        if !catalog.contains_key(&Sku::new(&self.sku)) {
+            return Err(RmaError::UnknownSku(self.sku.to_string()));
+        }
+        if !self.qty.is_finite() || self.qty <= 0.0 {
+            return Err(RmaError::InvalidQuantity(self.sku.to_string(), self.qty));
+        }
+        if let Some(serial) = &self.serial
+            && (serial.trim().is_empty() || serial.contains(['\t', '\r', '\n']))
+        {
+            return Err(RmaError::InvalidSerial(
+                self.sku.to_string(),
+                serial.to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Generate credit memo import lines for a set of RMA lines.
+///
+/// Each output line is tab delimited: RMA number, sku, quantity, unit price credited, serial
+/// (empty if none), and reason.
+///
+/// # Arguments
+/// * `lines` - The RMA lines to credit
+/// * `catalog` - The products to validate the lines against and to look up list prices from
+///
+/// # Errors
+/// An [`RmaError`] if any line fails [`RmaLine::validate`]. Nothing is generated unless every
+/// line is valid
+pub fn credit_memo_lines(
+    lines: &[RmaLine],
+    catalog: &AbcProductsBySku,
+) -> Result<String, RmaError> {
+    let mut out = String::new();
+    for line in lines {
+        line.validate(catalog)?;
+        let price = match line.credit_price {
+            Some(p) => p,
+            None => catalog
+                .get(&Sku::new(&line.sku))
+                .ok_or(RmaError::UnknownSku(line.sku.to_string()))?
+                .list(),
+        };
+        out.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            line.rma_number,
+            line.sku,
+            line.qty,
+            price,
+            line.serial.as_deref().unwrap_or(""),
+            line.reason.replace(['\t', '\r', '\n'], " ")
+        ));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbcProduct;
+
+    #[test]
+    fn test_credit_memo_lines() {
+        let catalog = AbcProductsBySku::from([(
+            Sku::new("A1"),
+            AbcProduct::new()
+                .with_sku("A1")
+                .with_desc("DRILL")
+                .with_list(Decimal::new(9999, 2))
+                .with_cost(Decimal::new(5000, 2))
+                .with_stock(2.0)
+                .build()
+                .unwrap(),
+        )]);
+        let mut line = RmaLine {
+            rma_number: "R100".to_string(),
+            sku: "A1".to_string(),
+            qty: 1.0,
+            serial: Some("SN42".to_string()),
+            reason: "DEFECTIVE".to_string(),
+            credit_price: None,
+        };
+        assert_eq!(
+            credit_memo_lines(&[line.clone()], &catalog).unwrap(),
+            "R100\tA1\t1\t99.99\tSN42\tDEFECTIVE\n"
+        );
+
+        line.serial = Some(" ".to_string());
+        assert_eq!(
+            line.validate(&catalog),
+            Err(RmaError::InvalidSerial("A1".to_string(), " ".to_string()))
+        );
+
+        line.serial = None;
+        for qty in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            line.qty = qty;
+            assert!(matches!(
+                line.validate(&catalog),
+                Err(RmaError::InvalidQuantity(_, _))
+            ));
+        }
+    }
+}
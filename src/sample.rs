@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use crate::{AbcGroup, AbcProduct, AbcProductsBySku};
+
+/// How [`sample`] keeps a subset representative of the whole catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stratify {
+    /// Pick evenly across the whole catalog in sku order
+    None,
+    /// Give every group a share of the subset proportional to its size, with at least one
+    /// product from each group whenever `n` allows. Products without a group form their own
+    /// stratum
+    ByGroup,
+}
+
+/// Pick `n` products spread evenly through `products`, which must be sorted
+fn evenly_spaced<'a>(products: &[&'a AbcProduct], n: usize) -> Vec<&'a AbcProduct> {
+    let len = products.len();
+    if n >= len {
+        return products.to_vec();
+    }
+    (0..n).map(|i| products[i * len / n]).collect()
+}
+
+/// Extract a representative subset of `products`, such as for syncing a staging environment or
+/// sending a test feed to a marketplace.
+///
+/// Sampling is deterministic: the same catalog always gives the same subset, so repeated test
+/// syncs touch the same products.
+///
+/// # Arguments
+/// * `products` - The full catalog
+/// * `n` - How many products to pick. If the catalog has `n` products or fewer, all of them are
+///   returned
+/// * `stratify` - How to keep the subset representative
+///
+/// # Example
+/// ```rust
+/// use abc_product::{sample::{self, Stratify}, testing};
+///
+/// let subset = sample::sample(&testing::sample_export(), 1, Stratify::ByGroup);
+/// assert_eq!(subset.len(), 1);
+/// ```
+pub fn sample(products: &AbcProductsBySku, n: usize, stratify: Stratify) -> AbcProductsBySku {
+    let mut strata: BTreeMap<Option<AbcGroup>, Vec<&AbcProduct>> = BTreeMap::new();
+    for product in products.values() {
+        let key = match stratify {
+            Stratify::None => None,
+            Stratify::ByGroup => product.group(),
+        };
+        strata.entry(key).or_default().push(product);
+    }
+    for stratum in strata.values_mut() {
+        stratum.sort_by_key(|p| p.sku());
+    }
+
+    let total = products.len();
+    let n = n.min(total);
+    // Start with the whole part of each stratum's proportional share, then hand out what is
+    // left to the strata with no products yet, and then those with the largest remainders
+    let mut quotas: Vec<(usize, usize, usize)> = strata
+        .values()
+        .enumerate()
+        .map(|(i, s)| (i, s.len() * n / total.max(1), s.len() * n % total.max(1)))
+        .collect();
+    let mut left = n - quotas.iter().map(|(_, q, _)| q).sum::<usize>();
+    let mut order: Vec<usize> = (0..quotas.len()).collect();
+    order.sort_by_key(|i| (quotas[*i].1 > 0, std::cmp::Reverse(quotas[*i].2), *i));
+    for i in order {
+        if left == 0 {
+            break;
+        }
+        quotas[i].1 += 1;
+        left -= 1;
+    }
+
+    strata
+        .values()
+        .zip(quotas)
+        .flat_map(|(stratum, (_, quota, _))| evenly_spaced(stratum, quota))
+        .map(|p| (p.sku.clone(), p.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_sample_by_group() {
+        let mut products = AbcProductsBySku::new();
+        for (i, group) in ["A", "A", "A", "A", "A", "A", "B", "B", "B", "C"]
+            .iter()
+            .enumerate()
+        {
+            let product = AbcProduct::new()
+                .with_sku(&format!("{:02}", i))
+                .with_desc("THING")
+                .with_list(Decimal::ONE)
+                .with_cost(Decimal::ONE)
+                .with_stock(1.0)
+                .with_group(group.chars().next().unwrap())
+                .unwrap()
+                .build()
+                .unwrap();
+            products.insert(product.sku().into(), product);
+        }
+
+        let subset = sample(&products, 5, Stratify::ByGroup);
+        assert_eq!(subset.len(), 5);
+        let count = |g: &str| {
+            subset
+                .values()
+                .filter(|p| p.group().map(|pg| pg.to_string()).as_deref() == Some(g))
+                .count()
+        };
+        assert_eq!((count("A"), count("B"), count("C")), (3, 1, 1));
+        assert_eq!(subset, sample(&products, 5, Stratify::ByGroup));
+        assert_eq!(sample(&products, 50, Stratify::None).len(), 10);
+    }
+}
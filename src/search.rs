@@ -0,0 +1,389 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, BufRead, Write},
+};
+
+use crate::AbcProductsBySku;
+
+/// The first line of a serialized [`SkuTrie`], used to reject files that are not tries or were
+/// written in a different format
+const SKU_TRIE_HEADER: &str = "abc-product sku-trie v2";
+
+/// The first line of a serialized [`DescIndex`]
+const DESC_INDEX_HEADER: &str = "abc-product desc-index v1";
+
+/// An [`std::io::ErrorKind::InvalidData`] error for a serialized index that cannot be read
+fn invalid_data(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, what.to_string())
+}
+
+/// Check that the first of `lines` is `header`
+fn read_header(
+    lines: &mut impl Iterator<Item = io::Result<String>>,
+    header: &str,
+    what: &str,
+) -> io::Result<()> {
+    match lines.next() {
+        Some(Ok(line)) if line == header => Ok(()),
+        Some(Err(e)) => Err(e),
+        _ => Err(invalid_data(&format!("Data is not a serialized {}", what))),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TrieNode {
+    children: BTreeMap<char, TrieNode>,
+    /// The skus that end at this node. Usually one, but skus that differ only in case share a node
+    skus: Vec<String>,
+}
+
+impl TrieNode {
+    /// Push up to `limit` skus at or below this node onto `out`, in sorted order
+    fn collect(&self, limit: usize, out: &mut Vec<String>) {
+        for sku in &self.skus {
+            if out.len() >= limit {
+                return;
+            }
+            out.push(sku.to_string());
+        }
+        for child in self.children.values() {
+            if out.len() >= limit {
+                return;
+            }
+            child.collect(limit, out);
+        }
+    }
+
+    /// Write this node and everything below it, one node per line in depth first order. Each
+    /// line holds the character leading to the node, how many children it has, and its skus,
+    /// separated by tabs
+    fn write_to(&self, c: Option<char>, writer: &mut impl Write) -> io::Result<()> {
+        write!(
+            writer,
+            "{}\t{}",
+            c.map(String::from).unwrap_or_default(),
+            self.children.len()
+        )?;
+        for sku in &self.skus {
+            write!(writer, "\t{}", sku)?;
+        }
+        writeln!(writer)?;
+        for (c, child) in &self.children {
+            child.write_to(Some(*c), writer)?;
+        }
+        Ok(())
+    }
+
+    /// Read a node written by [`TrieNode::write_to`], along with the character leading to it
+    fn read_from(
+        lines: &mut impl Iterator<Item = io::Result<String>>,
+    ) -> io::Result<(Option<char>, TrieNode)> {
+        let line = lines
+            .next()
+            .ok_or_else(|| invalid_data("SkuTrie ends part way through"))??;
+        let mut fields = line.split('\t');
+        let mut c = fields.next().unwrap_or_default().chars();
+        let (c, rest) = (c.next(), c.next());
+        let children: usize = fields
+            .next()
+            .and_then(|n| n.parse().ok())
+            .filter(|_| rest.is_none())
+            .ok_or_else(|| invalid_data(&format!("Invalid SkuTrie node `{}`", line)))?;
+        let mut node = TrieNode {
+            children: BTreeMap::new(),
+            skus: fields.map(|s| s.to_string()).collect(),
+        };
+        for _ in 0..children {
+            match TrieNode::read_from(lines)? {
+                (Some(c), child) => {
+                    node.children.insert(c, child);
+                }
+                (None, _) => return Err(invalid_data("SkuTrie node has no character")),
+            }
+        }
+        Ok((c, node))
+    }
+}
+
+/// A prefix tree of skus for answering autocomplete queries without scanning the whole catalog.
+/// Matching is case insensitive, but skus are returned exactly as they were inserted
+///
+/// # Example
+/// ```rust
+/// use abc_product::{search::SkuTrie, testing};
+///
+/// let trie = SkuTrie::from_products(&testing::sample_export());
+/// assert_eq!(trie.complete("abc", 10), vec!["ABC123".to_string()]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SkuTrie {
+    root: TrieNode,
+    len: usize,
+}
+
+impl SkuTrie {
+    /// Create an empty [`SkuTrie`]
+    pub fn new() -> Self {
+        SkuTrie::default()
+    }
+
+    /// Build a [`SkuTrie`] holding the sku of every product in `products`
+    pub fn from_products(products: &AbcProductsBySku) -> Self {
+        let mut trie = SkuTrie::new();
+        for sku in products.keys() {
+            trie.insert(sku.as_str());
+        }
+        trie
+    }
+
+    /// Add `sku` to the trie. Inserting a sku that is already present does nothing
+    pub fn insert(&mut self, sku: &str) {
+        let mut node = &mut self.root;
+        for c in sku.chars().flat_map(char::to_uppercase) {
+            node = node.children.entry(c).or_default();
+        }
+        if !node.skus.iter().any(|s| s == sku) {
+            node.skus.push(sku.to_string());
+            node.skus.sort();
+            self.len += 1;
+        }
+    }
+
+    /// Find skus starting with `prefix`
+    ///
+    /// # Arguments
+    /// * `prefix` - The start of the sku typed so far. Matched case insensitively
+    /// * `limit` - The most skus to return
+    ///
+    /// # Returns
+    /// Up to `limit` matching skus in sorted order
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let mut node = &self.root;
+        for c in prefix.chars().flat_map(char::to_uppercase) {
+            match node.children.get(&c) {
+                Some(child) => node = child,
+                None => return Vec::new(),
+            }
+        }
+        let mut out = Vec::new();
+        node.collect(limit, &mut out);
+        out
+    }
+
+    /// How many skus are in the trie
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the trie holds no skus
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Save this trie so it can be reloaded with [`SkuTrie::read_from`] without reparsing the
+    /// export it was built from. The nodes are written as they are, so loading them does not
+    /// rebuild the trie
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised by `writer`
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", SKU_TRIE_HEADER)?;
+        writeln!(writer, "{}", self.len)?;
+        self.root.write_to(None, &mut writer)
+    }
+
+    /// Load a trie previously saved with [`SkuTrie::write_to`]
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised by `reader`, or an [`std::io::ErrorKind::InvalidData`]
+    /// error if the data is not a trie written by [`SkuTrie::write_to`]
+    pub fn read_from(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines();
+        read_header(&mut lines, SKU_TRIE_HEADER, "SkuTrie")?;
+        let len = lines
+            .next()
+            .transpose()?
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| invalid_data("SkuTrie has no sku count"))?;
+        match TrieNode::read_from(&mut lines)? {
+            (None, root) => Ok(SkuTrie { root, len }),
+            (Some(_), _) => Err(invalid_data("SkuTrie root has a character")),
+        }
+    }
+}
+
+/// The three character pieces of `text`, uppercased, with runs of whitespace collapsed to a
+/// single space
+fn trigrams(text: &str) -> BTreeSet<String> {
+    let chars: Vec<char> = text
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+        .flat_map(char::to_uppercase)
+        .collect();
+    chars.windows(3).map(|w| w.iter().collect()).collect()
+}
+
+/// An n-gram index of product descriptions for finding products by any part of their
+/// description, such as `hex bolt` matching `1/4 HEX BOLT ZINC`. Each three character piece of
+/// a description points to the skus whose description contains it.
+///
+/// Matching is case insensitive. A sku matches when its description has every three character
+/// piece of the query, which almost always means it has the query itself, but pieces that are
+/// spread across the description can occasionally match too.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{search::DescIndex, testing};
+///
+/// let index = DescIndex::from_products(&testing::sample_export());
+/// assert_eq!(index.search("duct b", 10), vec!["ABC123".to_string()]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DescIndex {
+    /// The skus whose description contains each piece, in sorted order
+    grams: BTreeMap<String, Vec<String>>,
+}
+
+impl DescIndex {
+    /// Create an empty [`DescIndex`]
+    pub fn new() -> Self {
+        DescIndex::default()
+    }
+
+    /// Build a [`DescIndex`] of the description of every product in `products`
+    pub fn from_products(products: &AbcProductsBySku) -> Self {
+        let mut index = DescIndex::new();
+        for (sku, product) in products {
+            index.insert(sku.as_str(), &product.desc());
+        }
+        index
+    }
+
+    /// Add `desc` as a description of `sku`. A sku inserted again keeps matching its earlier
+    /// descriptions too
+    pub fn insert(&mut self, sku: &str, desc: &str) {
+        for gram in trigrams(desc) {
+            let skus = self.grams.entry(gram).or_default();
+            if let Err(i) = skus.binary_search_by(|s| s.as_str().cmp(sku)) {
+                skus.insert(i, sku.to_string());
+            }
+        }
+    }
+
+    /// Find skus whose description contains `query`
+    ///
+    /// # Arguments
+    /// * `query` - Part of a description. Matched case insensitively. Queries shorter than three
+    ///   characters match nothing
+    /// * `limit` - The most skus to return
+    ///
+    /// # Returns
+    /// Up to `limit` matching skus in sorted order
+    pub fn search(&self, query: &str, limit: usize) -> Vec<String> {
+        let mut lists = Vec::new();
+        for gram in trigrams(query) {
+            match self.grams.get(&gram) {
+                Some(skus) => lists.push(skus),
+                None => return Vec::new(),
+            }
+        }
+        lists.sort_by_key(|skus| skus.len());
+        let Some((shortest, rest)) = lists.split_first() else {
+            return Vec::new();
+        };
+        shortest
+            .iter()
+            .filter(|sku| rest.iter().all(|skus| skus.binary_search(sku).is_ok()))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Whether no descriptions have been indexed
+    pub fn is_empty(&self) -> bool {
+        self.grams.is_empty()
+    }
+
+    /// Save this index so it can be reloaded with [`DescIndex::read_from`] without rebuilding
+    /// it. Each line after the header holds one piece followed by its skus, separated by tabs
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised by `writer`
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{}", DESC_INDEX_HEADER)?;
+        for (gram, skus) in &self.grams {
+            writeln!(writer, "{}\t{}", gram, skus.join("\t"))?;
+        }
+        Ok(())
+    }
+
+    /// Load an index previously saved with [`DescIndex::write_to`]
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised by `reader`, or an [`std::io::ErrorKind::InvalidData`]
+    /// error if the data is not an index written by [`DescIndex::write_to`]
+    pub fn read_from(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines();
+        read_header(&mut lines, DESC_INDEX_HEADER, "DescIndex")?;
+        let mut grams = BTreeMap::new();
+        for line in lines {
+            let line = line?;
+            let mut fields = line.split('\t');
+            let gram = fields.next().unwrap_or_default();
+            let skus: Vec<String> = fields.map(|s| s.to_string()).collect();
+            if gram.chars().count() != 3 || skus.is_empty() {
+                return Err(invalid_data(&format!("Invalid DescIndex line `{}`", line)));
+            }
+            grams.insert(gram.to_string(), skus);
+        }
+        Ok(DescIndex { grams })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete() {
+        let mut trie = SkuTrie::new();
+        for sku in [
+            "12-0100", "12-0200", "12-0201", "12-1000", "13-0001", "12-0200",
+        ] {
+            trie.insert(sku);
+        }
+        assert_eq!(trie.len(), 5);
+        assert_eq!(
+            trie.complete("12-0", 10),
+            vec!["12-0100", "12-0200", "12-0201"]
+        );
+        assert_eq!(trie.complete("12-0", 2), vec!["12-0100", "12-0200"]);
+        assert!(trie.complete("14", 10).is_empty());
+
+        let mut saved = Vec::new();
+        trie.write_to(&mut saved).unwrap();
+        assert_eq!(SkuTrie::read_from(saved.as_slice()).unwrap(), trie);
+        assert!(SkuTrie::read_from("not a trie\n".as_bytes()).is_err());
+        assert!(SkuTrie::read_from(&saved[..saved.len() - 10]).is_err());
+    }
+
+    #[test]
+    fn test_desc_index() {
+        let mut index = DescIndex::new();
+        index.insert("B-14", "1/4 HEX  BOLT ZINC");
+        index.insert("B-38", "3/8 hex bolt zinc");
+        index.insert("N-14", "1/4 HEX NUT");
+        assert_eq!(index.search("hex bolt", 10), vec!["B-14", "B-38"]);
+        assert_eq!(index.search("hex bolt", 1), vec!["B-14"]);
+        assert_eq!(index.search("1/4 hex", 10), vec!["B-14", "N-14"]);
+        assert!(index.search("washer", 10).is_empty());
+        assert!(index.search("he", 10).is_empty());
+
+        let mut saved = Vec::new();
+        index.write_to(&mut saved).unwrap();
+        assert_eq!(DescIndex::read_from(saved.as_slice()).unwrap(), index);
+        assert!(DescIndex::read_from("not an index\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use hmac::{Hmac, Mac};
+use serde_json::{Value, json};
+use sha2::Sha256;
+
+use crate::export::{WriteOptions, write_file};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// What a signed export looked like when it was written, stored next to it by [`sign`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// The file name of the signed export, without its directory
+    pub file: String,
+    /// The size of the export in bytes
+    pub size: u64,
+    /// The hex encoded HMAC-SHA256 of the export's file name and contents
+    pub hmac_sha256: String,
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    /// The export or its manifest could not be read
+    Io(io::Error),
+    /// The manifest is not one written by [`sign`]. Contains a description of the problem
+    InvalidManifest(String),
+    /// The manifest was written for a differently named file
+    WrongFile(String),
+    /// The export is not the size recorded in the manifest, usually because it was truncated
+    /// while being copied
+    SizeMismatch {
+        /// The size recorded in the manifest
+        expected: u64,
+        /// The size of the export on disk
+        actual: u64,
+    },
+    /// The export was changed after it was signed, or was signed with a different key
+    BadSignature,
+}
+
+impl std::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not read the export or its manifest: {}", e),
+            Self::InvalidManifest(reason) => write!(f, "Manifest is not valid: {}", reason),
+            Self::WrongFile(file) => write!(f, "Manifest belongs to `{}`", file),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "Export is {} bytes but was signed at {} bytes",
+                actual, expected
+            ),
+            Self::BadSignature => write!(f, "Export does not match its signature"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl From<io::Error> for VerifyError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Where the manifest of the export at `path` is stored: the same path with `.manifest.json`
+/// appended
+pub fn manifest_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.manifest.json", path.display()))
+}
+
+/// The file name of `path` as stored in manifests
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Compute the MAC of the export at `path`, returning it along with the size of the export
+fn mac(path: &Path, key: &[u8]) -> io::Result<(HmacSha256, u64)> {
+    // HMAC accepts keys of any length, so this cannot fail
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    // Including the name stops one signed export from being passed off as another
+    mac.update(file_name(path).as_bytes());
+    mac.update(&[0]);
+
+    let mut file = fs::File::open(path)?;
+    let mut buf = [0; 8192];
+    let mut size = 0;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        mac.update(&buf[..n]);
+        size += n as u64;
+    }
+    Ok((mac, size))
+}
+
+/// Sign the finished export at `path` with a shared secret `key`, and write its [`Manifest`] to
+/// [`manifest_path`].
+///
+/// Call this after the export is completely written. Consumers holding the same key can then
+/// detect a tampered or truncated copy with [`verify`].
+///
+/// # Arguments
+/// * `path` - The export to sign
+/// * `key` - The secret shared with consumers of the export
+/// * `options` - How the manifest is written. See [`WriteOptions`]
+///
+/// # Errors
+/// Any [`std::io::Error`] raised while reading the export or writing the manifest
+pub fn sign(path: &Path, key: &[u8], options: &WriteOptions) -> io::Result<Manifest> {
+    let (mac, size) = mac(path, key)?;
+    let manifest = Manifest {
+        file: file_name(path),
+        size,
+        hmac_sha256: mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    };
+    let value = json!({
+        "file": manifest.file,
+        "size": manifest.size,
+        "hmac_sha256": manifest.hmac_sha256,
+    });
+    write_file(&manifest_path(path), options, |w| {
+        w.write_all(value.to_string().as_bytes())
+    })?;
+    Ok(manifest)
+}
+
+/// Check the export at `path` against the manifest written for it by [`sign`].
+///
+/// # Errors
+/// A [`VerifyError`] if the manifest is missing or invalid, or the export does not match it
+pub fn verify(path: &Path, key: &[u8]) -> Result<Manifest, VerifyError> {
+    let value: Value = serde_json::from_str(&fs::read_to_string(manifest_path(path))?)
+        .map_err(|e| VerifyError::InvalidManifest(e.to_string()))?;
+    let missing = |key: &str| VerifyError::InvalidManifest(format!("missing `{}`", key));
+    let manifest = Manifest {
+        file: value["file"].as_str().ok_or(missing("file"))?.to_string(),
+        size: value["size"].as_u64().ok_or(missing("size"))?,
+        hmac_sha256: value["hmac_sha256"]
+            .as_str()
+            .ok_or(missing("hmac_sha256"))?
+            .to_string(),
+    };
+    if manifest.file != file_name(path) {
+        return Err(VerifyError::WrongFile(manifest.file));
+    }
+
+    let (mac, actual) = mac(path, key)?;
+    if actual != manifest.size {
+        return Err(VerifyError::SizeMismatch {
+            expected: manifest.size,
+            actual,
+        });
+    }
+    let hex = &manifest.hmac_sha256;
+    let expected: Vec<u8> = (0..hex.len() / 2)
+        .filter_map(|i| u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok())
+        .collect();
+    mac.verify_slice(&expected)
+        .map_err(|_| VerifyError::BadSignature)?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let dir = std::env::temp_dir().join(format!("abc-product-sign-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("products.txt");
+        fs::write(&path, "[123456]\nlist = 5.99\n").unwrap();
+
+        let manifest = sign(&path, b"secret", &WriteOptions::default()).unwrap();
+        assert_eq!(verify(&path, b"secret").unwrap(), manifest);
+        assert!(matches!(
+            verify(&path, b"wrong key"),
+            Err(VerifyError::BadSignature)
+        ));
+
+        fs::write(&path, "[123456]\nlist = 0.99\n").unwrap();
+        assert!(matches!(
+            verify(&path, b"secret"),
+            Err(VerifyError::BadSignature)
+        ));
+        fs::write(&path, "[123456]\n").unwrap();
+        assert!(matches!(
+            verify(&path, b"secret"),
+            Err(VerifyError::SizeMismatch { .. })
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use ean13::Ean13;
+
+use crate::{
+    AbcProduct, AbcProductsBySku,
+    export::{WriteOptions, write_file},
+    format::format_qty,
+    integrations::web::xml_escape,
+    upc::barcode_modules,
+};
+
+/// The name shown and used for the page of products that have no group
+const UNGROUPED: &str = "ungrouped";
+
+/// The supplemental attribute that overrides [`SiteOptions::unit`] for a single product
+pub const UNIT_ATTR: &str = "unit";
+
+/// Controls how [`generate`] renders and writes the catalog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteOptions {
+    /// The unit of measure stock quantities are shown in, unless a product sets its own with the
+    /// [`UNIT_ATTR`] attribute. Defaults to `EA`
+    pub unit: String,
+    /// How each page is written. See [`WriteOptions`]
+    pub write: WriteOptions,
+}
+
+impl Default for SiteOptions {
+    fn default() -> Self {
+        Self {
+            unit: "EA".to_string(),
+            write: WriteOptions::default(),
+        }
+    }
+}
+
+impl SiteOptions {
+    /// Show stock quantities in `unit` for products without their own [`UNIT_ATTR`]
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = unit.to_string();
+        self
+    }
+
+    /// Write pages according to `write`
+    pub fn with_write(mut self, write: WriteOptions) -> Self {
+        self.write = write;
+        self
+    }
+}
+
+/// Render `ean` as an inline SVG barcode
+fn barcode_svg(ean: &Ean13) -> Option<String> {
+    let modules = barcode_modules(ean)?;
+
+    // Merge each run of dark modules into a single bar, leaving a 9 module quiet zone on the left
+    let mut bars = String::new();
+    let mut run_start = None;
+    for (x, m) in modules.chars().chain(std::iter::once('0')).enumerate() {
+        match (m, run_start) {
+            ('1', None) => run_start = Some(x),
+            ('0', Some(start)) => {
+                bars.push_str(&format!(
+                    r#"<rect x="{}" y="0" width="{}" height="50"/>"#,
+                    start + 9,
+                    x - start
+                ));
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    Some(format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="226" height="66" viewBox="0 0 113 66"><g fill="black">{}</g><text x="56" y="62" font-family="monospace" font-size="9" text-anchor="middle">{}</text></svg>"#,
+        bars, ean
+    ))
+}
+
+/// Wrap `body` in a complete HTML page titled `title`. `depth` is how many directories below the
+/// output root the page lives, used to link back to the index
+fn page(title: &str, body: &str, depth: usize) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n<p><a href=\"{root}index.html\">All groups</a></p>\n<h1>{title}</h1>\n{body}</body>\n</html>\n",
+        title = xml_escape(title),
+        root = "../".repeat(depth),
+        body = body
+    )
+}
+
+/// The HTML page for a single product, with its stock shown in `unit` unless it has its own
+fn product_page(product: &AbcProduct, unit: &str) -> String {
+    let unit = product.attr(UNIT_ATTR).unwrap_or_else(|| unit.to_string());
+    let mut body = format!(
+        "<p>Sku: {}</p>\n<p>Price: ${}</p>\n<p>In stock: {}</p>\n",
+        xml_escape(&product.sku()),
+        product.list().round_dp(2),
+        format_qty(product.stock(), &unit)
+    );
+    for upc in product.upcs() {
+        if let Some(svg) = barcode_svg(&upc) {
+            body.push_str(&format!("<div>{}</div>\n", svg));
+        }
+    }
+    page(&product.desc(), &body, 1)
+}
+
+/// Write a static HTML catalog of `products` into `out_dir`, creating it if necessary.
+///
+/// The output contains an `index.html` linking to one page per product group under `groups/`,
+/// and one page per product under `products/` named by [`AbcProduct::slug`] showing its price,
+/// stock, and a scannable barcode for each UPC. Products without a group are listed under
+/// `groups/ungrouped.html`.
+///
+/// # Arguments
+/// * `products` - The products to include in the catalog
+/// * `out_dir` - The directory to write the catalog to. Existing pages are overwritten, but
+///   stale pages for products that no longer exist are not removed
+/// * `options` - The stock unit and how each page is written. See [`SiteOptions`]
+///
+/// # Errors
+/// * [`std::io::ErrorKind::InvalidInput`] if two products share a slug, since their pages would
+///   overwrite each other. Nothing is written in that case
+/// * Any other [`std::io::Error`] raised while creating directories or writing pages
+pub fn generate(
+    products: &AbcProductsBySku,
+    out_dir: &Path,
+    options: &SiteOptions,
+) -> io::Result<()> {
+    if let Some((slug, skus)) = AbcProduct::duplicate_slugs(products).into_iter().next() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "products {} share the slug \"{}\" and would overwrite each other's page",
+                skus.join(", "),
+                slug
+            ),
+        ));
+    }
+    let write = &options.write;
+
+    let mut groups: BTreeMap<String, Vec<&AbcProduct>> = BTreeMap::new();
+    for product in products.values() {
+        groups
+            .entry(
+                product
+                    .group()
+                    .map(|g| g.to_string())
+                    .unwrap_or(UNGROUPED.to_string()),
+            )
+            .or_default()
+            .push(product);
+    }
+
+    for dir in [out_dir.join("groups"), out_dir.join("products")] {
+        if write
+            .mode
+            .proceed("create directory", &dir.display().to_string(), None)
+        {
+            fs::create_dir_all(dir)?;
+        }
+    }
+
+    let mut index = String::from("<ul>\n");
+    for (group, group_products) in groups.iter_mut() {
+        group_products.sort_by_key(|p| p.sku());
+        index.push_str(&format!(
+            "<li><a href=\"groups/{group}.html\">{group}</a> ({})</li>\n",
+            group_products.len(),
+            group = xml_escape(group)
+        ));
+
+        let mut list = String::from("<ul>\n");
+        for product in group_products.iter() {
+            list.push_str(&format!(
+                "<li><a href=\"../products/{}.html\">{}</a> ${}</li>\n",
+                product.slug(),
+                xml_escape(&product.desc()),
+                product.list().round_dp(2)
+            ));
+            write_file(
+                &out_dir
+                    .join("products")
+                    .join(format!("{}.html", product.slug())),
+                write,
+                |w| w.write_all(product_page(product, &options.unit).as_bytes()),
+            )?;
+        }
+        list.push_str("</ul>\n");
+        write_file(
+            &out_dir.join("groups").join(format!("{}.html", group)),
+            write,
+            |w| w.write_all(page(&format!("Group {}", group), &list, 1).as_bytes()),
+        )?;
+    }
+    index.push_str("</ul>\n");
+    write_file(&out_dir.join("index.html"), write, |w| {
+        w.write_all(page("Catalog", &index, 0).as_bytes())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbcProductBuilder;
+    use crate::mode::{DryRun, Mode};
+
+    #[test]
+    fn test_barcode_svg() {
+        let ean = Ean13::from_str_nonstrict("0085875500015").unwrap();
+        let svg = barcode_svg(&ean).unwrap();
+        // The start, center, and end guards have 2 bars each, and every digit has 2 bars
+        assert_eq!(svg.matches("<rect").count(), 3 * 2 + 2 * 12);
+    }
+
+    #[test]
+    fn test_generate() {
+        let products = crate::testing::sample_export();
+        let dir = std::env::temp_dir().join(format!("abc-product-sitegen-{}", std::process::id()));
+
+        let dry_run = DryRun::new();
+        let options = SiteOptions::default().with_write(WriteOptions {
+            mode: Mode::DryRun(dry_run.clone()),
+            ..Default::default()
+        });
+        generate(&products, &dir, &options).unwrap();
+        assert!(!dir.exists());
+        // Two directories, two product pages, two group pages, and the index
+        assert_eq!(dry_run.planned().len(), 7);
+
+        generate(&products, &dir, &SiteOptions::default().with_unit("BX")).unwrap();
+        let index = fs::read_to_string(dir.join("index.html")).unwrap();
+        assert!(index.contains("<a href=\"groups/A.html\">A</a> (1)"));
+        assert!(index.contains(&format!(
+            "<a href=\"groups/{0}.html\">{0}</a> (1)",
+            UNGROUPED
+        )));
+        let group = fs::read_to_string(dir.join("groups").join("A.html")).unwrap();
+        assert!(group.contains(&format!("../products/{}.html", products["ABC123"].slug())));
+        let page = fs::read_to_string(
+            dir.join("products")
+                .join(format!("{}.html", products["123456"].slug())),
+        )
+        .unwrap();
+        assert!(page.contains("PRODUCT A"));
+        assert!(page.contains("<svg"));
+        assert!(page.contains("BX"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_product_page_unit() {
+        let products = crate::testing::sample_export();
+        let product = &products["123456"];
+        assert!(product_page(product, "EA").contains("EA</p>"));
+        let product = AbcProductBuilder::from(product.clone())
+            .with_attr(UNIT_ATTR, "FT")
+            .build()
+            .unwrap();
+        assert!(product_page(&product, "EA").contains("FT</p>"));
+    }
+
+    #[test]
+    fn test_generate_duplicate_slugs() {
+        let mut products = crate::testing::sample_export();
+        for sku in ["ABC-123", "ABC.123"] {
+            let dup = AbcProductBuilder::from(products["ABC123"].clone())
+                .with_sku(sku)
+                .build()
+                .unwrap();
+            products.insert(dup.sku().into(), dup);
+        }
+        let dir =
+            std::env::temp_dir().join(format!("abc-product-sitegen-dup-{}", std::process::id()));
+        let err = generate(&products, &dir, &SiteOptions::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,303 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+
+use crate::{AbcProductsBySku, Sku, po::AbcPurchaseOrder};
+
+/// Where a special order is in its life, from the customer asking for it to them taking it home
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SpecialOrderStatus {
+    /// The customer has asked for the item, but it has not been ordered from a vendor
+    Requested,
+    /// The item is on a purchase order
+    Ordered,
+    /// The item has arrived at the store
+    Received,
+    /// The customer has been told the item is ready
+    Notified,
+    /// The customer has taken the item. No further changes are allowed
+    PickedUp,
+    /// The order was called off. No further changes are allowed
+    Cancelled,
+}
+
+impl SpecialOrderStatus {
+    /// Whether an order in this status may move to `to`. Orders only move forward, may skip
+    /// notifying the customer when they pick up on delivery, and can be cancelled until they are
+    /// picked up
+    pub fn can_transition_to(self, to: SpecialOrderStatus) -> bool {
+        use SpecialOrderStatus::*;
+        matches!(
+            (self, to),
+            (Requested, Ordered)
+                | (Ordered, Received)
+                | (Received, Notified)
+                | (Received, PickedUp)
+                | (Notified, PickedUp)
+                | (Requested | Ordered | Received | Notified, Cancelled)
+        )
+    }
+
+    /// Whether the order still needs work
+    pub fn is_open(self) -> bool {
+        !matches!(self, Self::PickedUp | Self::Cancelled)
+    }
+}
+
+/// The item a customer asked for. It is usually not stocked and may not be in the catalog yet
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpecialOrderItem {
+    /// The sku of the item, if it has been set up in ABC
+    pub sku: Option<String>,
+    /// What the customer asked for, as written on the order
+    pub desc: String,
+    /// The code of the vendor to order from, matching [`crate::vendor::AbcVendor::code`]
+    pub vendor: Option<String>,
+    /// The vendor's part number for the item
+    pub vendor_part: Option<String>,
+    /// How many units the customer wants
+    pub qty: f64,
+    /// The price per unit quoted to the customer
+    pub quoted_price: Decimal,
+}
+
+/// A purchase order line that a special order is being filled by
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoLink {
+    /// The purchase order number. See [`AbcPurchaseOrder::number`]
+    pub number: String,
+    /// The sku the item was ordered under
+    pub sku: String,
+}
+
+/// One change of status, kept as the order's history
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusChange {
+    /// The new status
+    pub status: SpecialOrderStatus,
+    /// When the change happened
+    pub date: NaiveDate,
+    /// Anything worth remembering, such as who called the customer
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpecialOrderError {
+    /// The order cannot move from its current status to the requested one
+    InvalidTransition {
+        from: SpecialOrderStatus,
+        to: SpecialOrderStatus,
+    },
+    /// The order cannot be marked as ordered until it is linked to a purchase order
+    MissingPurchaseOrder,
+    /// The purchase order has no line for the sku being linked
+    NotOnPurchaseOrder { number: String, sku: String },
+}
+
+impl std::fmt::Display for SpecialOrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidTransition { from, to } => {
+                write!(f, "A special order cannot go from {:?} to {:?}", from, to)
+            }
+            Self::MissingPurchaseOrder => {
+                write!(
+                    f,
+                    "A special order must be linked to a purchase order first"
+                )
+            }
+            Self::NotOnPurchaseOrder { number, sku } => {
+                write!(
+                    f,
+                    "Purchase order `{}` has no line for sku `{}`",
+                    number, sku
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpecialOrderError {}
+
+/// A customer's order for an item the store does not keep in stock, replacing the carbon copy
+/// special order form.
+///
+/// With the `serde` feature, orders can be serialized to keep them between runs.
+///
+/// # Example
+/// ```rust
+/// use chrono::NaiveDate;
+/// use rust_decimal::Decimal;
+/// use abc_product::special_orders::{SpecialOrder, SpecialOrderItem, SpecialOrderStatus};
+///
+/// let today = NaiveDate::from_ymd_opt(2024, 11, 16).unwrap();
+/// let item = SpecialOrderItem {
+///     sku: None,
+///     desc: "PELLA 36X80 STORM DOOR".to_string(),
+///     vendor: Some("PELLA".to_string()),
+///     vendor_part: Some("SD3680".to_string()),
+///     qty: 1.0,
+///     quoted_price: Decimal::new(32999, 2),
+/// };
+/// let mut order = SpecialOrder::new("SO1", "1001", item, today);
+/// assert!(order.transition(SpecialOrderStatus::Ordered, today, None).is_err());
+/// order.transition(SpecialOrderStatus::Cancelled, today, Some("Found one elsewhere")).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpecialOrder {
+    /// The number written on the order
+    pub id: String,
+    /// The account number of the customer, matching
+    /// [`crate::customer::AbcCustomer::account`]
+    pub customer: String,
+    /// What was ordered
+    pub item: SpecialOrderItem,
+    /// The purchase order line filling this order, once it has been ordered
+    pub po: Option<PoLink>,
+    /// Every status the order has been in, oldest first. Never empty
+    pub history: Vec<StatusChange>,
+}
+
+impl SpecialOrder {
+    /// Create a new order in the [`SpecialOrderStatus::Requested`] status
+    pub fn new(id: &str, customer: &str, item: SpecialOrderItem, date: NaiveDate) -> Self {
+        SpecialOrder {
+            id: id.to_string(),
+            customer: customer.to_string(),
+            item,
+            po: None,
+            history: vec![StatusChange {
+                status: SpecialOrderStatus::Requested,
+                date,
+                note: None,
+            }],
+        }
+    }
+
+    /// The current status of the order
+    pub fn status(&self) -> SpecialOrderStatus {
+        self.history
+            .last()
+            .map(|c| c.status)
+            .unwrap_or(SpecialOrderStatus::Requested)
+    }
+
+    /// Move the order to the status `to`, recording the change in its history
+    ///
+    /// # Errors
+    /// A [`SpecialOrderError`] if the order cannot move to `to` from its current status, or if
+    /// `to` is [`SpecialOrderStatus::Ordered`] and no purchase order is linked. The order is left
+    /// unchanged
+    pub fn transition(
+        &mut self,
+        to: SpecialOrderStatus,
+        date: NaiveDate,
+        note: Option<&str>,
+    ) -> Result<(), SpecialOrderError> {
+        let from = self.status();
+        if !from.can_transition_to(to) {
+            return Err(SpecialOrderError::InvalidTransition { from, to });
+        }
+        if to == SpecialOrderStatus::Ordered && self.po.is_none() {
+            return Err(SpecialOrderError::MissingPurchaseOrder);
+        }
+        self.history.push(StatusChange {
+            status: to,
+            date,
+            note: note.map(|n| n.to_string()),
+        });
+        Ok(())
+    }
+
+    /// Link the order to the line of `po` for `sku`, and set the item's sku and vendor from it
+    ///
+    /// # Errors
+    /// [`SpecialOrderError::NotOnPurchaseOrder`] if `po` has no line for `sku`
+    pub fn link_po(&mut self, po: &AbcPurchaseOrder, sku: &str) -> Result<(), SpecialOrderError> {
+        if !po.lines().iter().any(|l| l.sku() == sku) {
+            return Err(SpecialOrderError::NotOnPurchaseOrder {
+                number: po.number(),
+                sku: sku.to_string(),
+            });
+        }
+        self.po = Some(PoLink {
+            number: po.number(),
+            sku: sku.to_string(),
+        });
+        self.item.sku = Some(sku.to_string());
+        if let Some(vendor) = po.vendor() {
+            self.item.vendor = Some(vendor);
+        }
+        Ok(())
+    }
+
+    /// Whether the item has been set up in `catalog`
+    pub fn in_catalog(&self, catalog: &AbcProductsBySku) -> bool {
+        self.item
+            .sku
+            .as_ref()
+            .is_some_and(|sku| catalog.contains_key(&Sku::new(sku)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample_export;
+
+    #[test]
+    fn test_special_order() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 11, d).unwrap();
+        let item = SpecialOrderItem {
+            sku: None,
+            desc: "PRODUCT A IN RED".to_string(),
+            vendor: None,
+            vendor_part: None,
+            qty: 2.0,
+            quoted_price: Decimal::new(699, 2),
+        };
+        let mut order = SpecialOrder::new("SO1", "1001", item, day(1));
+        assert!(!order.in_catalog(&sample_export()));
+        assert_eq!(
+            order.transition(SpecialOrderStatus::Ordered, day(2), None),
+            Err(SpecialOrderError::MissingPurchaseOrder)
+        );
+
+        let orders = AbcPurchaseOrder::from_readers(
+            "PO1\tACME\t2024-11-02\t\n".as_bytes(),
+            "PO1\t123456\tPRODUCT A\t2\t0\t1.23\n".as_bytes(),
+        )
+        .unwrap();
+        assert!(order.link_po(&orders["PO1"], "ABC123").is_err());
+        order.link_po(&orders["PO1"], "123456").unwrap();
+        assert_eq!(order.item.vendor, Some("ACME".to_string()));
+        assert!(order.in_catalog(&sample_export()));
+
+        order
+            .transition(SpecialOrderStatus::Ordered, day(2), None)
+            .unwrap();
+        order
+            .transition(SpecialOrderStatus::Received, day(9), None)
+            .unwrap();
+        assert_eq!(
+            order.transition(SpecialOrderStatus::Requested, day(9), None),
+            Err(SpecialOrderError::InvalidTransition {
+                from: SpecialOrderStatus::Received,
+                to: SpecialOrderStatus::Requested
+            })
+        );
+        order
+            .transition(SpecialOrderStatus::PickedUp, day(10), Some("Paid in full"))
+            .unwrap();
+        assert!(!order.status().is_open());
+        assert_eq!(order.history.len(), 4);
+    }
+}
@@ -0,0 +1,356 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use chrono::NaiveDate;
+use ean13::Ean13;
+use rusqlite::{Connection, params};
+use rust_decimal::Decimal;
+
+use crate::{
+    AbcGroup, AbcProduct, AbcProductsBySku, Sku, catalog::AbcCatalog, measure::NetContents,
+    mode::Mode,
+};
+
+/// The version of the schema written by [`AbcCatalog::save_to_sqlite`], stored as the
+/// database's `user_version` so that caches written by other versions of the crate are rejected
+/// rather than misread. Version 2 added the attributes table
+const SCHEMA_VERSION: i64 = 2;
+
+/// Tables of the cache. Lists are kept in their own tables in their original order, keyed by
+/// sku and position
+const SCHEMA: &str = "
+DROP TABLE IF EXISTS upcs;
+DROP TABLE IF EXISTS raw_upcs;
+DROP TABLE IF EXISTS alt_skus;
+DROP TABLE IF EXISTS related;
+DROP TABLE IF EXISTS attributes;
+DROP TABLE IF EXISTS products;
+CREATE TABLE products (
+    sku TEXT PRIMARY KEY,
+    desc TEXT NOT NULL,
+    list TEXT NOT NULL,
+    cost TEXT NOT NULL,
+    avg_cost TEXT,
+    stock REAL NOT NULL,
+    grp TEXT,
+    weight REAL,
+    last_sold TEXT,
+    core_sku TEXT,
+    warranty_months INTEGER,
+    net_contents TEXT,
+    country_of_origin TEXT,
+    min_stock REAL,
+    max_stock REAL
+);
+CREATE TABLE upcs (
+    sku TEXT NOT NULL REFERENCES products (sku),
+    position INTEGER NOT NULL,
+    upc TEXT NOT NULL,
+    PRIMARY KEY (sku, position)
+);
+CREATE TABLE raw_upcs (
+    sku TEXT NOT NULL REFERENCES products (sku),
+    position INTEGER NOT NULL,
+    raw_upc TEXT NOT NULL,
+    PRIMARY KEY (sku, position)
+);
+CREATE TABLE alt_skus (
+    sku TEXT NOT NULL REFERENCES products (sku),
+    position INTEGER NOT NULL,
+    alt_sku TEXT NOT NULL,
+    PRIMARY KEY (sku, position)
+);
+CREATE TABLE related (
+    sku TEXT NOT NULL REFERENCES products (sku),
+    position INTEGER NOT NULL,
+    related_sku TEXT NOT NULL,
+    PRIMARY KEY (sku, position)
+);
+CREATE TABLE attributes (
+    sku TEXT NOT NULL REFERENCES products (sku),
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (sku, key)
+);
+CREATE INDEX upcs_by_upc ON upcs (upc);
+CREATE INDEX alt_skus_by_alt_sku ON alt_skus (alt_sku);
+";
+
+/// The list tables of the cache, each with the column holding the list's values
+const LISTS: [(&str, &str); 4] = [
+    ("upcs", "upc"),
+    ("raw_upcs", "raw_upc"),
+    ("alt_skus", "alt_sku"),
+    ("related", "related_sku"),
+];
+
+#[derive(Debug)]
+pub enum SqliteError {
+    /// The database could not be opened, read, or written
+    Sqlite(rusqlite::Error),
+    /// The database was written by a different version of the crate. Contains the version found
+    SchemaVersion(i64),
+    /// A value in the database could not be parsed. Contains the sku and the column
+    Invalid(String, String),
+}
+
+impl std::fmt::Display for SqliteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sqlite(e) => write!(f, "SQLite error: {}", e),
+            Self::SchemaVersion(found) => write!(
+                f,
+                "Cache has schema version {}, but version {} is required",
+                found, SCHEMA_VERSION
+            ),
+            Self::Invalid(sku, column) => {
+                write!(f, "Cache has an invalid `{}` for sku `{}`", column, sku)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SqliteError {}
+
+impl From<rusqlite::Error> for SqliteError {
+    fn from(value: rusqlite::Error) -> Self {
+        Self::Sqlite(value)
+    }
+}
+
+/// Parse `value` from the `column` of the product with `sku`
+fn parse<T: FromStr>(sku: &str, column: &str, value: &str) -> Result<T, SqliteError> {
+    value
+        .parse()
+        .map_err(|_| SqliteError::Invalid(sku.to_string(), column.to_string()))
+}
+
+/// Parse the optional `value` from the `column` of the product with `sku`
+fn parse_opt<T: FromStr>(
+    sku: &str,
+    column: &str,
+    value: Option<String>,
+) -> Result<Option<T>, SqliteError> {
+    value.map(|v| parse(sku, column, &v)).transpose()
+}
+
+impl AbcCatalog {
+    /// Save every product to a SQLite database at `path`, so that later runs can load the
+    /// catalog with [`AbcCatalog::load_from_sqlite`] instead of parsing the export again. The
+    /// database is created if it does not exist, and any catalog already in it is replaced.
+    ///
+    /// # Arguments
+    /// * `path` - Where to save the database
+    /// * `mode` - Whether to actually write it. In [`Mode::DryRun`] the save is recorded and the
+    ///   database is left untouched
+    ///
+    /// # Errors
+    /// [`SqliteError::Sqlite`] if the database cannot be opened or written
+    pub fn save_to_sqlite(&self, path: &str, mode: &Mode) -> Result<(), SqliteError> {
+        if !mode.proceed("write", path, Some(format!("{} products", self.len()))) {
+            return Ok(());
+        }
+        let mut conn = Connection::open(path)?;
+        let tx = conn.transaction()?;
+        tx.execute_batch(SCHEMA)?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO products VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, \
+                 ?13, ?14, ?15)",
+            )?;
+            let mut lists = LISTS
+                .iter()
+                .map(|(table, column)| {
+                    tx.prepare(&format!(
+                        "INSERT INTO {} (sku, position, {}) VALUES (?1, ?2, ?3)",
+                        table, column
+                    ))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut attributes = tx.prepare("INSERT INTO attributes VALUES (?1, ?2, ?3)")?;
+            for product in self.iter() {
+                let sku = product.sku();
+                insert.execute(params![
+                    sku,
+                    product.desc(),
+                    product.list().to_string(),
+                    product.cost().to_string(),
+                    product.avg_cost().map(|c| c.to_string()),
+                    product.stock(),
+                    product.group().map(|g| g.to_string()),
+                    product.weight(),
+                    product.last_sold().map(|d| d.to_string()),
+                    product.core_sku(),
+                    product.warranty_months(),
+                    product.net_contents().map(|n| n.to_string()),
+                    product.country_of_origin(),
+                    product.min_stock(),
+                    product.max_stock(),
+                ])?;
+                for (key, value) in product.attributes() {
+                    attributes.execute(params![sku, key, value])?;
+                }
+                let values = [
+                    product.upcs().iter().map(|u| u.to_string()).collect(),
+                    product.raw_upcs(),
+                    product.alt_skus(),
+                    product.related(),
+                ];
+                for (statement, values) in lists.iter_mut().zip(values) {
+                    for (position, value) in values.iter().enumerate() {
+                        statement.execute(params![sku, position as i64, value])?;
+                    }
+                }
+            }
+        }
+        tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Load a catalog saved by [`AbcCatalog::save_to_sqlite`]
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use abc_product::{catalog::AbcCatalog, mode::Mode};
+    ///
+    /// let catalog = match AbcCatalog::load_from_sqlite("./catalog.db") {
+    ///     Ok(catalog) => catalog,
+    ///     Err(_) => {
+    ///         let catalog = AbcCatalog::from_db_export("./item.data", "./item_posted.data").unwrap();
+    ///         catalog.save_to_sqlite("./catalog.db", &Mode::Live).unwrap();
+    ///         catalog
+    ///     }
+    /// };
+    /// ```
+    ///
+    /// # Errors
+    /// * [`SqliteError::Sqlite`] if the database cannot be opened or read, or has no catalog
+    /// * [`SqliteError::SchemaVersion`] if the catalog was saved by a different version of the
+    ///   crate
+    /// * [`SqliteError::Invalid`] if a value was edited into something that cannot be parsed
+    pub fn load_from_sqlite(path: &str) -> Result<Self, SqliteError> {
+        let conn = Connection::open(path)?;
+        let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version != SCHEMA_VERSION {
+            return Err(SqliteError::SchemaVersion(version));
+        }
+
+        let mut products = AbcProductsBySku::new();
+        let mut select = conn.prepare("SELECT * FROM products")?;
+        let mut rows = select.query([])?;
+        while let Some(row) = rows.next()? {
+            let sku: String = row.get("sku")?;
+            let product = AbcProduct {
+                desc: row.get("desc")?,
+                upcs: Vec::new(),
+                raw_upcs: Vec::new(),
+                list: parse(&sku, "list", &row.get::<_, String>("list")?)?,
+                cost: parse(&sku, "cost", &row.get::<_, String>("cost")?)?,
+                avg_cost: parse_opt::<Decimal>(&sku, "avg_cost", row.get("avg_cost")?)?,
+                stock: row.get("stock")?,
+                group: parse_opt::<AbcGroup>(&sku, "grp", row.get("grp")?)?,
+                weight: row.get("weight")?,
+                last_sold: parse_opt::<NaiveDate>(&sku, "last_sold", row.get("last_sold")?)?,
+                alt_skus: Vec::new(),
+                core_sku: row.get("core_sku")?,
+                warranty_months: row.get("warranty_months")?,
+                net_contents: parse_opt::<NetContents>(
+                    &sku,
+                    "net_contents",
+                    row.get("net_contents")?,
+                )?,
+                country_of_origin: row.get("country_of_origin")?,
+                min_stock: row.get("min_stock")?,
+                max_stock: row.get("max_stock")?,
+                related: Vec::new(),
+                attributes: BTreeMap::new(),
+                sku: Sku::new(&sku),
+            };
+            products.insert(product.sku.clone(), product);
+        }
+
+        for (table, column) in LISTS {
+            let mut select = conn.prepare(&format!(
+                "SELECT sku, {} FROM {} ORDER BY sku, position",
+                column, table
+            ))?;
+            let mut rows = select.query([])?;
+            while let Some(row) = rows.next()? {
+                let sku: String = row.get(0)?;
+                let value: String = row.get(1)?;
+                let Some(product) = products.get_mut(&Sku::new(&sku)) else {
+                    continue;
+                };
+                match table {
+                    "upcs" => product.upcs.push(
+                        Ean13::from_str_nonstrict(&value)
+                            .map_err(|_| SqliteError::Invalid(sku, column.to_string()))?,
+                    ),
+                    "raw_upcs" => product.raw_upcs.push(value),
+                    "alt_skus" => product.alt_skus.push(Sku::new(&value)),
+                    _ => product.related.push(value),
+                }
+            }
+        }
+        let mut select = conn.prepare("SELECT sku, key, value FROM attributes")?;
+        let mut rows = select.query([])?;
+        while let Some(row) = rows.next()? {
+            let sku: String = row.get(0)?;
+            if let Some(product) = products.get_mut(&Sku::new(&sku)) {
+                product.attributes.insert(row.get(1)?, row.get(2)?);
+            }
+        }
+        Ok(AbcCatalog::new(products))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, mode::DryRun, testing::sample_export};
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        let mut products = sample_export();
+        let detailed = AbcProductBuilder::from(products["ABC123"].clone())
+            .with_weight(2.5)
+            .with_net_contents(NetContents::from_str("12 OZ").unwrap())
+            .with_related(&["123456".to_string()])
+            .with_warranty_months(12)
+            .with_attr("finish", "Brushed nickel")
+            .build()
+            .unwrap();
+        products.insert(Sku::new("ABC123"), detailed);
+        let catalog = AbcCatalog::new(products);
+
+        let path = std::env::temp_dir().join(format!("abc-product-{}.db", std::process::id()));
+        let path = path.to_str().unwrap();
+        let dry_run = DryRun::new();
+        catalog
+            .save_to_sqlite(path, &Mode::DryRun(dry_run.clone()))
+            .unwrap();
+        assert!(!std::path::Path::new(path).exists());
+        assert_eq!(
+            dry_run.planned()[0].to_string(),
+            format!("write {} (2 products)", path)
+        );
+
+        catalog.save_to_sqlite(path, &Mode::Live).unwrap();
+        // Saving again replaces the catalog rather than adding to it
+        catalog.save_to_sqlite(path, &Mode::Live).unwrap();
+        let loaded = AbcCatalog::load_from_sqlite(path).unwrap();
+        assert_eq!(loaded, catalog);
+
+        for version in [0, 1] {
+            Connection::open(path)
+                .unwrap()
+                .pragma_update(None, "user_version", version)
+                .unwrap();
+            assert!(matches!(
+                AbcCatalog::load_from_sqlite(path),
+                Err(SqliteError::SchemaVersion(found)) if found == version
+            ));
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
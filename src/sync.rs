@@ -0,0 +1,277 @@
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+
+use crate::{ProductField, diff::CatalogDiff};
+
+/// Limits on how much a [`CatalogDiff`] may change before a person has to look at it, so that a
+/// broken vendor price file cannot reach the web store unnoticed.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{
+///     AbcProductBuilder, ProductField,
+///     catalog::AbcCatalog,
+///     sync::{AutoApplyPolicy, Decision, HoldReason},
+///     testing,
+/// };
+///
+/// let old = AbcCatalog::new(testing::sample_export());
+/// let mut products = testing::sample_export();
+/// let renamed = AbcProductBuilder::from(products["ABC123"].clone())
+///     .with_desc("PRODUCT B, 2 PACK")
+///     .build()
+///     .unwrap();
+/// products.insert(renamed.sku().into(), renamed);
+/// let new = AbcCatalog::new(products);
+///
+/// let policy = AutoApplyPolicy::default()
+///     .with_max_changed_pct(50.0)
+///     .with_forbidden_field(ProductField::Desc);
+/// assert_eq!(
+///     policy.decide(&AbcCatalog::diff(&old, &new), old.len()),
+///     Decision::Hold(vec![HoldReason::ForbiddenField {
+///         sku: "ABC123".to_string(),
+///         field: ProductField::Desc,
+///     }])
+/// );
+///
+/// let mut products = testing::sample_export();
+/// products.remove("ABC123");
+/// let new = AbcCatalog::new(products);
+/// // Removing one of two products is far more than 10% of the catalog
+/// let diff = AbcCatalog::diff(&old, &new);
+/// assert!(!AutoApplyPolicy::default().decide(&diff, old.len()).is_apply());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoApplyPolicy {
+    /// The most products that may be added, removed, or changed, as a percent of the catalog
+    pub max_changed_pct: f64,
+    /// The largest change to any one list price, as a percent of the old price
+    pub max_price_swing_pct: f64,
+    /// Fields that may never change without review, such as the description
+    pub forbidden_fields: Vec<ProductField>,
+}
+
+impl Default for AutoApplyPolicy {
+    /// At most 10% of the catalog changed, no list price moved by more than 25%, and no
+    /// forbidden fields
+    fn default() -> Self {
+        AutoApplyPolicy {
+            max_changed_pct: 10.0,
+            max_price_swing_pct: 25.0,
+            forbidden_fields: Vec::new(),
+        }
+    }
+}
+
+/// Why a [`CatalogDiff`] was held for review
+#[derive(Debug, Clone, PartialEq)]
+pub enum HoldReason {
+    /// More of the catalog changed than [`AutoApplyPolicy::max_changed_pct`] allows
+    TooManyChanges {
+        /// How many products were added, removed, or changed
+        changed: usize,
+        /// How many products the catalog had before
+        catalog: usize,
+    },
+    /// A list price moved by more than [`AutoApplyPolicy::max_price_swing_pct`] allows
+    PriceSwing {
+        /// The sku whose price moved
+        sku: String,
+        /// The old list price
+        old: Decimal,
+        /// The new list price
+        new: Decimal,
+    },
+    /// One of [`AutoApplyPolicy::forbidden_fields`] changed
+    ForbiddenField {
+        /// The sku whose field changed
+        sku: String,
+        /// The field that changed
+        field: ProductField,
+    },
+}
+
+impl std::fmt::Display for HoldReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyChanges { changed, catalog } => write!(
+                f,
+                "{} of {} products changed, which is more than allowed",
+                changed, catalog
+            ),
+            Self::PriceSwing { sku, old, new } => write!(
+                f,
+                "List price of sku `{}` moved from {} to {}, which is more than allowed",
+                sku, old, new
+            ),
+            Self::ForbiddenField { sku, field } => write!(
+                f,
+                "Field `{}` of sku `{}` changed, which always needs review",
+                field.name(),
+                sku
+            ),
+        }
+    }
+}
+
+/// Whether a [`CatalogDiff`] can be applied without review, as decided by
+/// [`AutoApplyPolicy::decide`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    /// Every limit was respected, so the diff can be applied automatically
+    Apply,
+    /// At least one limit was broken, so the diff must be held for review
+    Hold(Vec<HoldReason>),
+}
+
+impl Decision {
+    /// Whether the diff can be applied automatically
+    pub fn is_apply(&self) -> bool {
+        *self == Decision::Apply
+    }
+}
+
+impl AutoApplyPolicy {
+    /// Set the most products that may change, as a percent of the catalog
+    pub fn with_max_changed_pct(self, max_changed_pct: f64) -> Self {
+        AutoApplyPolicy {
+            max_changed_pct,
+            ..self
+        }
+    }
+
+    /// Set the largest change to any one list price, as a percent of the old price
+    pub fn with_max_price_swing_pct(self, max_price_swing_pct: f64) -> Self {
+        AutoApplyPolicy {
+            max_price_swing_pct,
+            ..self
+        }
+    }
+
+    /// Add a field that may never change without review
+    pub fn with_forbidden_field(self, field: ProductField) -> Self {
+        let mut forbidden_fields = self.forbidden_fields;
+        forbidden_fields.push(field);
+        AutoApplyPolicy {
+            forbidden_fields,
+            ..self
+        }
+    }
+
+    /// Decide whether `diff` can be applied automatically
+    ///
+    /// # Arguments
+    /// * `diff` - The changes to check
+    /// * `catalog_len` - How many products the catalog had before the changes
+    ///
+    /// # Returns
+    /// [`Decision::Apply`] if every limit was respected, otherwise [`Decision::Hold`] with
+    /// every limit that was broken
+    pub fn decide(&self, diff: &CatalogDiff, catalog_len: usize) -> Decision {
+        let mut reasons = Vec::new();
+
+        let changed = diff.added.len() + diff.removed.len() + diff.changed.len();
+        let changed_pct = if catalog_len == 0 {
+            if changed == 0 { 0.0 } else { 100.0 }
+        } else {
+            changed as f64 / catalog_len as f64 * 100.0
+        };
+        if changed_pct > self.max_changed_pct {
+            reasons.push(HoldReason::TooManyChanges {
+                changed,
+                catalog: catalog_len,
+            });
+        }
+
+        let max_swing = Decimal::try_from(self.max_price_swing_pct).unwrap_or_default();
+        for product in &diff.changed {
+            if let Some(change) = product.get(ProductField::List) {
+                let old = Decimal::from_str(&change.old).unwrap_or_default();
+                let new = Decimal::from_str(&change.new).unwrap_or_default();
+                let too_far = if old.is_zero() {
+                    !new.is_zero()
+                } else {
+                    ((new - old) / old * Decimal::ONE_HUNDRED).abs() > max_swing
+                };
+                if too_far {
+                    reasons.push(HoldReason::PriceSwing {
+                        sku: product.sku.to_string(),
+                        old,
+                        new,
+                    });
+                }
+            }
+            for field in &self.forbidden_fields {
+                if product.get(*field).is_some() {
+                    reasons.push(HoldReason::ForbiddenField {
+                        sku: product.sku.to_string(),
+                        field: *field,
+                    });
+                }
+            }
+        }
+
+        if reasons.is_empty() {
+            Decision::Apply
+        } else {
+            Decision::Hold(reasons)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, AbcProductsBySku, Sku, diff::diff, testing::sample_export};
+
+    fn with_list(products: &AbcProductsBySku, sku: &str, list: Decimal) -> AbcProductsBySku {
+        let mut products = products.clone();
+        let changed = AbcProductBuilder::from(products[sku].clone())
+            .with_list(list)
+            .build()
+            .unwrap();
+        products.insert(Sku::new(sku), changed);
+        products
+    }
+
+    #[test]
+    fn test_auto_apply_policy() {
+        let old = sample_export();
+        let policy = AutoApplyPolicy::default().with_max_changed_pct(50.0);
+        assert!(policy.decide(&diff(&old, &old), old.len()).is_apply());
+
+        // 5.99 to 6.49 is about 8%
+        let small = with_list(&old, "123456", Decimal::new(649, 2));
+        assert!(policy.decide(&diff(&old, &small), old.len()).is_apply());
+
+        // 5.99 to 11.98 doubles the price
+        let doubled = with_list(&old, "123456", Decimal::new(1198, 2));
+        assert_eq!(
+            policy.decide(&diff(&old, &doubled), old.len()),
+            Decision::Hold(vec![HoldReason::PriceSwing {
+                sku: "123456".to_string(),
+                old: Decimal::new(599, 2),
+                new: Decimal::new(1198, 2),
+            }])
+        );
+
+        let strict = policy
+            .with_max_changed_pct(10.0)
+            .with_forbidden_field(ProductField::List);
+        assert_eq!(
+            strict.decide(&diff(&old, &small), old.len()),
+            Decision::Hold(vec![
+                HoldReason::TooManyChanges {
+                    changed: 1,
+                    catalog: 2
+                },
+                HoldReason::ForbiddenField {
+                    sku: "123456".to_string(),
+                    field: ProductField::List,
+                },
+            ])
+        );
+    }
+}
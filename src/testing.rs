@@ -0,0 +1,48 @@
+use crate::{AbcProduct, AbcProductsBySku};
+
+/// The contents of a tiny `item.data` export containing two products
+pub const SAMPLE_ITEM_DATA: &[u8] = include_bytes!("../item.data");
+
+/// The contents of the `item_posted.data` export that pairs with [`SAMPLE_ITEM_DATA`]
+pub const SAMPLE_ITEM_POSTED_DATA: &[u8] = include_bytes!("../item_posted.data");
+
+/// Parse the embedded sample export into a map of skus to [`AbcProduct`]s. Useful for examples
+/// and tests that should not depend on a real export existing on disk
+///
+/// # Returns
+/// The two products, "123456" and "ABC123", found in [`SAMPLE_ITEM_DATA`] and
+/// [`SAMPLE_ITEM_POSTED_DATA`]
+pub fn sample_export() -> AbcProductsBySku {
+    AbcProduct::from_readers(SAMPLE_ITEM_DATA, SAMPLE_ITEM_POSTED_DATA)
+        .expect("The embedded sample export should always parse")
+}
+
+/// [`SAMPLE_ITEM_DATA`] as written by versions of ABC that put the list price in column 7 and
+/// the weight, 2.5 for every product, in column 46. See
+/// [`crate::records::KnownLayout::moved_list_and_weight`]
+#[cfg(test)]
+pub(crate) fn moved_item_data() -> String {
+    String::from_utf8(SAMPLE_ITEM_DATA.to_vec())
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let mut fields: Vec<&str> = line.split('\t').collect();
+            fields.swap(crate::records::item_columns::LIST, 7);
+            fields[46] = "2.5";
+            fields.join("\t") + "\n"
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_export() {
+        assert_eq!(
+            sample_export(),
+            AbcProduct::from_db_export("./item.data", "./item_posted.data").unwrap()
+        );
+    }
+}
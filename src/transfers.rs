@@ -0,0 +1,181 @@
+use crate::{AbcProductsBySku, Sku};
+
+/// A request to move some quantity of a product from one company to another
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferRequest {
+    /// The sku being transferred. It must exist in both the sending and receiving catalogs
+    pub sku: String,
+    /// How many units to move. Must be greater than zero
+    pub qty: f64,
+}
+
+/// The files needed to record a transfer in both companies
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transfer {
+    /// Tab delimited lines of sku, quantity, and cost to import as an inventory adjustment in
+    /// the sending company. Quantities are negative since stock is leaving
+    pub adjustment: String,
+    /// Tab delimited lines of sku, quantity, and cost to import as a receipt in the receiving
+    /// company
+    pub receipt: String,
+    /// A human readable list of the skus, descriptions, and quantities to pull and pack
+    pub packing_list: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransferError {
+    /// The sku was not found in the sending company's catalog
+    UnknownSku(String),
+    /// The sku exists in the sending company, but not in the receiving company
+    MissingInDestination(String),
+    /// The quantity requested for the sku was zero or negative
+    InvalidQuantity(String, f64),
+}
+
+impl std::fmt::Display for TransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSku(sku) => {
+                write!(f, "Sku `{}` does not exist in the sending catalog", sku)
+            }
+            Self::MissingInDestination(sku) => {
+                write!(f, "Sku `{}` does not exist in the receiving catalog", sku)
+            }
+            Self::InvalidQuantity(sku, qty) => {
+                write!(f, "Cannot transfer a quantity of {} for sku `{}`", qty, sku)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TransferError {}
+
+/// Generate the paired import files and packing list for a transfer between two companies.
+///
+/// Goods are transferred at the sending company's last cost, so the adjustment and receipt
+/// always balance.
+///
+/// # Arguments
+/// * `from_catalog` - The products of the company sending the stock
+/// * `to_catalog` - The products of the company receiving the stock
+/// * `requests` - Each sku and quantity to move
+///
+/// # Returns
+/// A [`Transfer`] with one line per request in each of the generated files
+///
+/// # Errors
+/// A [`TransferError`] if any requested sku is missing from either catalog or if a quantity is
+/// not a positive, finite number. No files are generated unless every request is valid
+pub fn generate(
+    from_catalog: &AbcProductsBySku,
+    to_catalog: &AbcProductsBySku,
+    requests: &[TransferRequest],
+) -> Result<Transfer, TransferError> {
+    let mut adjustment = String::new();
+    let mut receipt = String::new();
+    let mut packing_list = String::new();
+    for request in requests {
+        if !request.qty.is_finite() || request.qty <= 0.0 {
+            return Err(TransferError::InvalidQuantity(
+                request.sku.to_string(),
+                request.qty,
+            ));
+        }
+        let product = from_catalog
+            .get(&Sku::new(&request.sku))
+            .ok_or(TransferError::UnknownSku(request.sku.to_string()))?;
+        if !to_catalog.contains_key(&Sku::new(&request.sku)) {
+            return Err(TransferError::MissingInDestination(request.sku.to_string()));
+        }
+        adjustment.push_str(&format!(
+            "{}\t{}\t{}\n",
+            product.sku(),
+            -request.qty,
+            product.cost()
+        ));
+        receipt.push_str(&format!(
+            "{}\t{}\t{}\n",
+            product.sku(),
+            request.qty,
+            product.cost()
+        ));
+        packing_list.push_str(&format!(
+            "{:<20}{:>10}  {}\n",
+            product.sku(),
+            request.qty,
+            product.desc()
+        ));
+    }
+    Ok(Transfer {
+        adjustment,
+        receipt,
+        packing_list,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbcProduct;
+    use rust_decimal::Decimal;
+
+    fn catalog(skus: &[&str]) -> AbcProductsBySku {
+        skus.iter()
+            .map(|sku| {
+                (
+                    Sku::new(sku),
+                    AbcProduct::new()
+                        .with_sku(sku)
+                        .with_desc("WIDGET")
+                        .with_list(Decimal::new(500, 2))
+                        .with_cost(Decimal::new(250, 2))
+                        .with_stock(10.0)
+                        .build()
+                        .unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_generate() {
+        let from = catalog(&["A1", "B2"]);
+        let to = catalog(&["A1"]);
+        let transfer = generate(
+            &from,
+            &to,
+            &[TransferRequest {
+                sku: "A1".to_string(),
+                qty: 3.0,
+            }],
+        )
+        .unwrap();
+        assert_eq!(transfer.adjustment, "A1\t-3\t2.50\n");
+        assert_eq!(transfer.receipt, "A1\t3\t2.50\n");
+
+        let missing = generate(
+            &from,
+            &to,
+            &[TransferRequest {
+                sku: "B2".to_string(),
+                qty: 1.0,
+            }],
+        );
+        assert_eq!(
+            missing,
+            Err(TransferError::MissingInDestination("B2".to_string()))
+        );
+
+        for qty in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let invalid = generate(
+                &from,
+                &to,
+                &[TransferRequest {
+                    sku: "A1".to_string(),
+                    qty,
+                }],
+            );
+            assert!(matches!(invalid, Err(TransferError::InvalidQuantity(sku, _)) if sku == "A1"));
+        }
+    }
+}
@@ -0,0 +1,430 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, io,
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use ean13::Ean13;
+
+use crate::{
+    AbcProduct, AbcProductsBySku,
+    export::{WriteOptions, write_file},
+    upc_from_str,
+};
+
+/// Left hand odd parity encodings of the digits 0-9 in an EAN-13
+const EAN_L: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011", "0110001", "0101111", "0111011",
+    "0110111", "0001011",
+];
+
+/// Left hand even parity encodings of the digits 0-9 in an EAN-13
+const EAN_G: [&str; 10] = [
+    "0100111", "0110011", "0011011", "0100001", "0011101", "0111001", "0000101", "0010001",
+    "0001001", "0010111",
+];
+
+/// Right hand encodings of the digits 0-9 in an EAN-13
+const EAN_R: [&str; 10] = [
+    "1110010", "1100110", "1101100", "1000010", "1011100", "1001110", "1010000", "1000100",
+    "1001000", "1110100",
+];
+
+/// Which of [`EAN_L`] (L) or [`EAN_G`] (G) encodes each left hand digit, chosen by the first digit
+const EAN_PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG", "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL",
+    "LGGLGL",
+];
+
+/// The bars of `ean` as a string of 95 modules, where `1` is a dark module and `0` is a light
+/// one, including the start, center, and end guards. Useful for drawing the barcode in any
+/// format. [`None`] if `ean` does not render as 13 digits
+pub fn barcode_modules(ean: &Ean13) -> Option<String> {
+    let d: Vec<usize> = ean
+        .to_string()
+        .chars()
+        .map(|c| c.to_digit(10).map(|n| n as usize))
+        .collect::<Option<Vec<usize>>>()?;
+    if d.len() != 13 {
+        return None;
+    }
+    let mut modules = String::from("101");
+    for (i, digit) in d[1..7].iter().enumerate() {
+        let table = if EAN_PARITY[d[0]].as_bytes()[i] == b'L' {
+            EAN_L
+        } else {
+            EAN_G
+        };
+        modules.push_str(table[*digit]);
+    }
+    modules.push_str("01010");
+    for digit in &d[7..13] {
+        modules.push_str(EAN_R[*digit]);
+    }
+    modules.push_str("101");
+    Some(modules)
+}
+
+/// The GS1 prefix of `ean`: its first 3 digits. The prefix identifies the GS1 member
+/// organization that issued the code, or marks it as restricted for in-house or coupon use
+pub fn gs1_prefix(ean: &Ean13) -> String {
+    ean.to_string().chars().take(3).collect()
+}
+
+/// The first `len` digits of `ean`, used to group codes that likely share a company prefix.
+///
+/// GS1 company prefixes vary in length and the exact length can only be known from GS1's own
+/// records. 7 digits, which is a leading 0 plus the 6 digit manufacturer code of a UPC-A, is a
+/// reasonable default for US products.
+pub fn company_prefix(ean: &Ean13, len: usize) -> String {
+    ean.to_string().chars().take(len).collect()
+}
+
+/// Whether `ean` falls in a range GS1 reserves for restricted circulation: in-store codes
+/// (`020`-`029`, `040`-`049`, `200`-`299`) and coupons (`050`-`059`, `981`-`984`, `99x`). These
+/// codes are only meaningful inside the store and must not be sent to marketplaces
+pub fn is_restricted_distribution(ean: &Ean13) -> bool {
+    let prefix: u32 = match gs1_prefix(ean).parse() {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+    matches!(prefix, 20..=29 | 40..=59 | 200..=299 | 981..=984 | 990..=999)
+}
+
+/// Every UPC in a catalog that shares one company prefix
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefixGroup {
+    /// The shared leading digits. See [`company_prefix`]
+    pub prefix: String,
+    /// Whether the codes are in a restricted distribution range. See
+    /// [`is_restricted_distribution`]
+    pub restricted: bool,
+    /// The sku and full 13 digit code of each UPC with this prefix, sorted
+    pub upcs: Vec<(String, String)>,
+}
+
+/// Group every UPC in `products` by its company prefix, to spot vendors that relabel product
+/// and in-house barcodes that must be kept out of marketplace feeds
+///
+/// # Arguments
+/// * `products` - The products whose UPCs should be grouped
+/// * `prefix_len` - How many leading digits make up a company prefix. See [`company_prefix`]
+///
+/// # Returns
+/// One [`PrefixGroup`] per prefix, sorted by prefix
+pub fn prefix_report(products: &AbcProductsBySku, prefix_len: usize) -> Vec<PrefixGroup> {
+    let mut groups: BTreeMap<String, PrefixGroup> = BTreeMap::new();
+    for product in products.values() {
+        for upc in product.upcs() {
+            let prefix = company_prefix(&upc, prefix_len);
+            groups
+                .entry(prefix.to_string())
+                .or_insert_with(|| PrefixGroup {
+                    prefix,
+                    restricted: is_restricted_distribution(&upc),
+                    upcs: Vec::new(),
+                })
+                .upcs
+                .push((product.sku(), upc.to_string()));
+        }
+    }
+    groups
+        .into_values()
+        .map(|mut g| {
+            g.upcs.sort();
+            g
+        })
+        .collect()
+}
+
+/// A reverse index of a catalog from barcode to product, for looking up what was scanned at
+/// the register.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{testing, upc::ProductIndex};
+///
+/// let products = testing::sample_export();
+/// let index = ProductIndex::new(&products);
+/// assert_eq!(index.get("085875500015").unwrap().sku(), "123456");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProductIndex<'a> {
+    products: &'a AbcProductsBySku,
+    by_upc: HashMap<String, Vec<String>>,
+}
+
+impl<'a> ProductIndex<'a> {
+    /// Index every UPC of every product in `products`
+    pub fn new(products: &'a AbcProductsBySku) -> Self {
+        let mut by_upc: HashMap<String, Vec<String>> = HashMap::new();
+        for product in products.values() {
+            for upc in product.upcs() {
+                by_upc
+                    .entry(upc.to_string())
+                    .or_default()
+                    .push(product.sku());
+            }
+        }
+        for skus in by_upc.values_mut() {
+            skus.sort();
+            skus.dedup();
+        }
+        ProductIndex { products, by_upc }
+    }
+
+    /// The product with the UPC `upc`. If more than one product shares the UPC, the one with the
+    /// lowest sku is returned. See [`ProductIndex::duplicates`]
+    pub fn get_by_upc(&self, upc: &Ean13) -> Option<&'a AbcProduct> {
+        self.by_upc
+            .get(&upc.to_string())
+            .and_then(|skus| skus.first())
+            .and_then(|sku| self.products.get(sku.as_str()))
+    }
+
+    /// The product with the barcode `scanned`, as read by a scanner or typed in. UPC-A codes
+    /// with or without their check digit are accepted, as are full EAN-13 codes
+    pub fn get(&self, scanned: &str) -> Option<&'a AbcProduct> {
+        upc_from_str(scanned.trim()).and_then(|upc| self.get_by_upc(&upc))
+    }
+
+    /// Every UPC that belongs to more than one product, alongside the skus that share it. Both
+    /// are sorted
+    pub fn duplicates(&self) -> Vec<(String, Vec<String>)> {
+        let mut dups: Vec<(String, Vec<String>)> = self
+            .by_upc
+            .iter()
+            .filter(|(_, skus)| skus.len() > 1)
+            .map(|(upc, skus)| (upc.to_string(), skus.clone()))
+            .collect();
+        dups.sort();
+        dups
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InhouseError {
+    /// The prefix range includes prefixes outside of `200`-`299`, which GS1 reserves for
+    /// in-store use
+    PrefixOutOfRange(u16),
+    /// Every code in the prefix range has already been used
+    Exhausted,
+}
+
+impl std::fmt::Display for InhouseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PrefixOutOfRange(p) => {
+                write!(f, "Prefix {} is not in the in-store range 200-299", p)
+            }
+            Self::Exhausted => write!(f, "Every in-house code in the prefix range is used"),
+        }
+    }
+}
+
+impl std::error::Error for InhouseError {}
+
+/// The in-house barcodes handed out to skus so far. Save it after every call to
+/// [`assign_inhouse`] so that the same sku always keeps the same code
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InhouseAssignments {
+    by_sku: BTreeMap<String, String>,
+}
+
+impl InhouseAssignments {
+    /// Create an empty store with no assignments
+    pub fn new() -> Self {
+        InhouseAssignments::default()
+    }
+
+    /// Load assignments saved by [`InhouseAssignments::save`]. A missing file is treated as an
+    /// empty store so the first run needs no setup
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised while reading the file, or
+    /// [`std::io::ErrorKind::InvalidData`] if a line is not a sku and a 13 digit code separated
+    /// by a tab
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        };
+        let mut by_sku = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            match line.split_once('\t') {
+                Some((sku, code))
+                    if code.len() == 13 && code.chars().all(|c| c.is_ascii_digit()) =>
+                {
+                    by_sku.insert(sku.to_string(), code.to_string());
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid in-house assignment on line {}", i + 1),
+                    ));
+                }
+            }
+        }
+        Ok(InhouseAssignments { by_sku })
+    }
+
+    /// Save the assignments as tab separated sku and code lines
+    ///
+    /// # Arguments
+    /// * `path` - Where to save the assignments
+    /// * `options` - How the file is written. Set [`WriteOptions::atomic`] so a failed save
+    ///   cannot lose assignments that were already handed out
+    ///
+    /// # Errors
+    /// Any [`std::io::Error`] raised while writing the file
+    pub fn save(&self, path: &Path, options: &WriteOptions) -> io::Result<()> {
+        write_file(path, options, |w| {
+            for (sku, code) in &self.by_sku {
+                writeln!(w, "{}\t{}", sku, code)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// The code assigned to `sku`, if any
+    pub fn get(&self, sku: &str) -> Option<Ean13> {
+        self.by_sku
+            .get(sku)
+            .and_then(|c| Ean13::from_str_nonstrict(c).ok())
+    }
+}
+
+/// Give every product without a UPC a valid EAN-13 from the in-store restricted range.
+///
+/// Skus already in `assignments` keep their code. New codes are handed out in sku order using
+/// the lowest unused item number under the first prefix in `prefix_range` that has room, and
+/// never reuse a code that is assigned or already on a product. New assignments are added to
+/// `assignments`, which should then be saved.
+///
+/// # Arguments
+/// * `products` - The catalog to find products without UPCs in
+/// * `prefix_range` - The 3 digit GS1 prefixes to draw codes from. Must be within `200..=299`
+/// * `assignments` - The persistent store of codes handed out so far
+///
+/// # Returns
+/// The sku and in-house code of every product in `products` without a UPC, sorted by sku
+///
+/// # Errors
+/// An [`InhouseError`] if the range is outside of the in-store prefixes or has no codes left
+pub fn assign_inhouse(
+    products: &AbcProductsBySku,
+    prefix_range: RangeInclusive<u16>,
+    assignments: &mut InhouseAssignments,
+) -> Result<Vec<(String, Ean13)>, InhouseError> {
+    for prefix in [*prefix_range.start(), *prefix_range.end()] {
+        if !(200..=299).contains(&prefix) {
+            return Err(InhouseError::PrefixOutOfRange(prefix));
+        }
+    }
+
+    // Compare on the first 12 digits so that check digits never matter
+    let mut used: HashSet<String> = assignments
+        .by_sku
+        .values()
+        .map(|c| c[..12].to_string())
+        .collect();
+    used.extend(
+        products
+            .values()
+            .flat_map(|p| p.upcs())
+            .map(|u| u.to_string().chars().take(12).collect::<String>()),
+    );
+
+    let mut needs_code: Vec<&AbcProduct> =
+        products.values().filter(|p| p.upcs().is_empty()).collect();
+    needs_code.sort_by_key(|p| p.sku());
+
+    let mut prefixes = prefix_range.clone();
+    let mut prefix = prefixes.next();
+    let mut item: u64 = 1;
+    let mut assigned = Vec::new();
+    for product in needs_code {
+        if let Some(ean) = assignments.get(&product.sku()) {
+            assigned.push((product.sku(), ean));
+            continue;
+        }
+        let code = loop {
+            let p = prefix.ok_or(InhouseError::Exhausted)?;
+            if item > 999_999_999 {
+                prefix = prefixes.next();
+                item = 1;
+                continue;
+            }
+            let candidate = format!("{:03}{:09}", p, item);
+            item += 1;
+            if !used.contains(&candidate) {
+                break candidate;
+            }
+        };
+        // The trailing 0 is a placeholder check digit that [`Ean13::from_str_nonstrict`] corrects
+        let ean = Ean13::from_str_nonstrict(&format!("{}0", code))
+            .map_err(|_| InhouseError::Exhausted)?;
+        used.insert(code);
+        assignments.by_sku.insert(product.sku(), ean.to_string());
+        assigned.push((product.sku(), ean));
+    }
+    Ok(assigned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restricted_distribution() {
+        let inhouse = Ean13::from_str_nonstrict("2001234567890").unwrap();
+        let product = Ean13::from_str_nonstrict("0085875500015").unwrap();
+        assert_eq!(gs1_prefix(&inhouse), "200");
+        assert!(is_restricted_distribution(&inhouse));
+        assert!(!is_restricted_distribution(&product));
+        assert_eq!(company_prefix(&product, 7), "0085875");
+    }
+
+    #[test]
+    fn test_assign_inhouse() {
+        let products = crate::testing::sample_export();
+        let mut assignments = InhouseAssignments::new();
+        let first = assign_inhouse(&products, 200..=201, &mut assignments).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].0, "ABC123");
+        assert!(first[0].1.to_string().starts_with("200000000001"));
+
+        let again = assign_inhouse(&products, 250..=250, &mut assignments).unwrap();
+        assert_eq!(again, first);
+
+        let path = std::env::temp_dir().join(format!("abc-product-upc-{}", std::process::id()));
+        let options = WriteOptions {
+            atomic: true,
+            ..Default::default()
+        };
+        assignments.save(&path, &options).unwrap();
+        let loaded = InhouseAssignments::load(&path).unwrap();
+        assert_eq!(
+            loaded.get("ABC123").map(|c| c.to_string()),
+            Some(first[0].1.to_string())
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            assign_inhouse(&products, 100..=200, &mut assignments),
+            Err(InhouseError::PrefixOutOfRange(100))
+        );
+    }
+
+    #[test]
+    fn test_product_index() {
+        let products = crate::testing::sample_export();
+        let index = ProductIndex::new(&products);
+        let upc = Ean13::from_str_nonstrict("0085875500015").unwrap();
+        assert_eq!(index.get_by_upc(&upc).unwrap().sku(), "123456");
+        assert_eq!(index.get("08587550001").unwrap().sku(), "123456");
+        assert!(index.get("123").is_none());
+        assert!(index.duplicates().is_empty());
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use ean13::Ean13;
+
+use crate::{
+    AbcParseError, AbcProduct, AbcProductsBySku, ParseWarnings, format::format_qty,
+    measure::NetContents, open_data_file,
+};
+
+pub use crate::{
+    AbcGroup, CsvError, ErrorPolicy, InvalidValue, MissingField, ParseOptions, Sku, ValueError,
+    price::AbcPrice,
+};
+
+/// A map of skus to the [`Product`] they belong to
+pub type ProductsBySku = HashMap<Sku, Product>;
+
+/// A number of units, such as the stock on hand. Fractional for products sold by length or
+/// weight, and negative when more was sold than was received
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quantity(f64);
+
+impl Quantity {
+    /// A quantity of `units`
+    pub fn new(units: f64) -> Self {
+        Quantity(units)
+    }
+
+    /// The number of units
+    pub fn value(&self) -> f64 {
+        self.0
+    }
+
+    /// Whether the quantity is below zero
+    pub fn is_negative(&self) -> bool {
+        self.0 < 0.0
+    }
+
+    /// The quantity followed by `uom`, rounded the way [`format_qty`] rounds
+    pub fn format(&self, uom: &str) -> String {
+        format_qty(self.0, uom)
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Quantity(value)
+    }
+}
+
+impl From<Quantity> for f64 {
+    fn from(value: Quantity) -> Self {
+        value.0
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A product or inventory item in ABC, with accessors that borrow rather than clone and prices
+/// and quantities that carry their own types.
+///
+/// Converts to and from [`AbcProduct`] for free, so code using either API can share products.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{AbcProduct, testing, v2::Product};
+///
+/// let products = testing::sample_export();
+/// let product = Product::from(products["ABC123"].clone());
+/// assert_eq!(product.desc(), "PRODUCT B");
+/// assert_eq!(product.list().to_string(), "$8.12");
+/// assert!(product.stock().is_negative());
+/// assert_eq!(AbcProduct::from(product), products["ABC123"]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Product {
+    inner: AbcProduct,
+}
+
+impl Product {
+    /// The sku of the product
+    pub fn sku(&self) -> &Sku {
+        &self.inner.sku
+    }
+
+    /// The description of the product
+    pub fn desc(&self) -> &str {
+        &self.inner.desc
+    }
+
+    /// The valid UPCs of the product
+    pub fn upcs(&self) -> &[Ean13] {
+        &self.inner.upcs
+    }
+
+    /// The UPCs of the product exactly as they appear in ABC
+    pub fn raw_upcs(&self) -> &[String] {
+        &self.inner.raw_upcs
+    }
+
+    /// The list price of the product
+    pub fn list(&self) -> AbcPrice {
+        AbcPrice::new(self.inner.list)
+    }
+
+    /// The last cost of the product
+    pub fn cost(&self) -> AbcPrice {
+        AbcPrice::new(self.inner.cost)
+    }
+
+    /// The average cost of the product, if ABC exported one
+    pub fn avg_cost(&self) -> Option<AbcPrice> {
+        self.inner.avg_cost.map(AbcPrice::new)
+    }
+
+    /// The units on hand
+    pub fn stock(&self) -> Quantity {
+        Quantity(self.inner.stock)
+    }
+
+    /// The discount group of the product
+    pub fn group(&self) -> Option<AbcGroup> {
+        self.inner.group
+    }
+
+    /// The weight of the product in pounds
+    pub fn weight(&self) -> Option<f64> {
+        self.inner.weight
+    }
+
+    /// The date the product was last sold
+    pub fn last_sold(&self) -> Option<NaiveDate> {
+        self.inner.last_sold
+    }
+
+    /// The alternate skus of the product
+    pub fn alt_skus(&self) -> &[Sku] {
+        &self.inner.alt_skus
+    }
+
+    /// The sku of the core charge sold alongside the product
+    pub fn core_sku(&self) -> Option<&str> {
+        self.inner.core_sku.as_deref()
+    }
+
+    /// How many months the product is under warranty from the day it is sold
+    pub fn warranty_months(&self) -> Option<u32> {
+        self.inner.warranty_months
+    }
+
+    /// The declared net quantity of the package, such as `12 OZ`
+    pub fn net_contents(&self) -> Option<&NetContents> {
+        self.inner.net_contents.as_ref()
+    }
+
+    /// The country the product was made in
+    pub fn country_of_origin(&self) -> Option<&str> {
+        self.inner.country_of_origin.as_deref()
+    }
+
+    /// The stock level at which the product should be reordered
+    pub fn min_stock(&self) -> Option<Quantity> {
+        self.inner.min_stock.map(Quantity)
+    }
+
+    /// The stock level that reorders should bring the product back up to
+    pub fn max_stock(&self) -> Option<Quantity> {
+        self.inner.max_stock.map(Quantity)
+    }
+
+    /// The skus of products that go with this one. See [`crate::relations`]
+    pub fn related(&self) -> &[String] {
+        &self.inner.related
+    }
+
+    /// The supplemental attributes of the product. See [`crate::attributes`]
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.inner.attributes
+    }
+
+    /// The product as the current API's [`AbcProduct`], for functions that have not moved to
+    /// this API yet
+    pub fn as_v1(&self) -> &AbcProduct {
+        &self.inner
+    }
+}
+
+impl From<AbcProduct> for Product {
+    fn from(value: AbcProduct) -> Self {
+        Product { inner: value }
+    }
+}
+
+impl From<Product> for AbcProduct {
+    fn from(value: Product) -> Self {
+        value.inner
+    }
+}
+
+/// Convert products parsed with the current API into [`ProductsBySku`]
+pub fn from_v1(products: AbcProductsBySku) -> ProductsBySku {
+    products
+        .into_iter()
+        .map(|(sku, product)| (sku, Product::from(product)))
+        .collect()
+}
+
+/// Convert [`ProductsBySku`] back into the current API's [`AbcProductsBySku`]
+pub fn into_v1(products: ProductsBySku) -> AbcProductsBySku {
+    products
+        .into_iter()
+        .map(|(sku, product)| (sku, AbcProduct::from(product)))
+        .collect()
+}
+
+/// Why an export could not be parsed, in a form that can be matched on rather than read
+#[derive(Debug)]
+pub enum ParseError {
+    /// A file could not be read or split into rows
+    Csv(Box<CsvError>),
+    /// A required field is missing from a row
+    MissingField(Box<MissingField>),
+    /// The rows of `item.data` and `item_posted.data` being joined are for different skus
+    MismatchedSkus,
+    /// A field is present but its value cannot be parsed
+    InvalidValue(Box<InvalidValue>),
+    /// Any other problem, as reported by the current API
+    Other(AbcParseError),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Csv(e) => write!(f, "Cannot read the export: {}", e),
+            Self::MissingField(e) => write!(f, "{}", e),
+            Self::MismatchedSkus => write!(
+                f,
+                "The rows of item.data and item_posted.data being joined have different skus"
+            ),
+            Self::InvalidValue(e) => write!(f, "{}", e),
+            Self::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Csv(e) => Some(&e.source),
+            Self::InvalidValue(e) => e.source(),
+            Self::Other(e) => e.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<AbcParseError> for ParseError {
+    fn from(value: AbcParseError) -> Self {
+        match value {
+            AbcParseError::CsvError(e) => Self::Csv(e),
+            AbcParseError::MissingField(e) => Self::MissingField(e),
+            AbcParseError::MisMatchedSkus => Self::MismatchedSkus,
+            AbcParseError::InvalidValue(e) => Self::InvalidValue(e),
+            e => Self::Other(e),
+        }
+    }
+}
+
+impl From<ParseError> for AbcParseError {
+    fn from(value: ParseError) -> Self {
+        match value {
+            ParseError::Csv(e) => Self::CsvError(e),
+            ParseError::MissingField(e) => Self::MissingField(e),
+            ParseError::MismatchedSkus => Self::MisMatchedSkus,
+            ParseError::InvalidValue(e) => Self::InvalidValue(e),
+            ParseError::Other(e) => e,
+        }
+    }
+}
+
+/// The products of an export along with the rows that could not be parsed and any warnings, as
+/// returned by [`from_db_export`]
+#[derive(Debug, Default)]
+pub struct Parsed {
+    /// Every product that was parsed
+    pub products: ProductsBySku,
+    /// Rows that were skipped or collected under [`ParseOptions::on_error`], as their row
+    /// number alongside what was wrong with them
+    pub errors: Vec<(usize, ParseError)>,
+    /// Data quality problems the parser worked around. See
+    /// [`AbcProduct::from_db_export_with_warnings`]
+    pub warnings: ParseWarnings,
+}
+
+/// Parse an ABC database export. See [`AbcProduct::from_db_export`] for how to produce one.
+///
+/// # Arguments
+/// * `item_path` - The path to the item.data file
+/// * `item_posted_path` - The path to the item_posted.data file
+/// * `options` - How to read the files and what to do with rows that cannot be parsed
+///
+/// # Errors
+/// A [`ParseError`] if either file cannot be read, or a row cannot be parsed under
+/// [`ErrorPolicy::Fail`]
+///
+/// # Example
+/// ```rust
+/// use abc_product::v2::{self, ParseOptions};
+///
+/// let parsed = v2::from_db_export("./item.data", "./item_posted.data", &ParseOptions::default())
+///     .unwrap();
+/// assert_eq!(parsed.products["123456"].desc(), "PRODUCT A");
+/// ```
+pub fn from_db_export(
+    item_path: &str,
+    item_posted_path: &str,
+    options: &ParseOptions,
+) -> Result<Parsed, ParseError> {
+    let item = open_data_file(item_path).map_err(|e| AbcParseError::from(csv::Error::from(e)))?;
+    let item_posted =
+        open_data_file(item_posted_path).map_err(|e| AbcParseError::from(csv::Error::from(e)))?;
+    from_readers(item, item_posted, options)
+}
+
+/// Like [`from_db_export`], reading from any readers rather than from paths on disk
+///
+/// # Errors
+/// See [`from_db_export`]
+pub fn from_readers(
+    item: impl std::io::Read,
+    item_posted: impl std::io::Read,
+    options: &ParseOptions,
+) -> Result<Parsed, ParseError> {
+    let (products, errors, warnings) =
+        AbcProduct::from_readers_with_options(item, item_posted, options)?;
+    Ok(Parsed {
+        products: from_v1(products),
+        errors: errors
+            .into_iter()
+            .map(|(row, e)| (row, ParseError::from(e)))
+            .collect(),
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, sample_export};
+
+    #[test]
+    fn test_v2_round_trip() {
+        let parsed = from_readers(
+            testing::SAMPLE_ITEM_DATA,
+            testing::SAMPLE_ITEM_POSTED_DATA,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert!(parsed.errors.is_empty());
+        assert!(parsed.warnings.is_empty());
+        let product = &parsed.products["ABC123"];
+        assert_eq!(product.alt_skus(), &[Sku::new("ALT SKU")]);
+        assert_eq!(product.group(), AbcGroup::new('A'));
+        assert_eq!(product.stock().format("ea"), "-6 EA");
+        assert!(product.related().is_empty());
+        assert_eq!(into_v1(parsed.products), sample_export());
+
+        let detailed = Product::from(
+            crate::AbcProductBuilder::from(sample_export()["123456"].clone())
+                .with_warranty_months(12)
+                .with_net_contents("12 OZ".parse().unwrap())
+                .with_country_of_origin("US")
+                .with_min_stock(2.0)
+                .with_max_stock(10.0)
+                .with_related(&["ABC123".to_string()])
+                .build()
+                .unwrap(),
+        );
+        assert_eq!(detailed.warranty_months(), Some(12));
+        assert_eq!(detailed.net_contents().unwrap().to_string(), "12 OZ");
+        assert_eq!(detailed.country_of_origin(), Some("US"));
+        assert_eq!(detailed.min_stock().map(|q| q.value()), Some(2.0));
+        assert_eq!(detailed.max_stock().map(|q| q.value()), Some(10.0));
+        assert_eq!(detailed.related(), &["ABC123".to_string()]);
+
+        let item = String::from_utf8(testing::SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("\t8.12\t", "\tabc\t");
+        let options = ParseOptions::default().with_on_error(ErrorPolicy::Collect);
+        let parsed =
+            from_readers(item.as_bytes(), testing::SAMPLE_ITEM_POSTED_DATA, &options).unwrap();
+        assert_eq!(parsed.products.len(), 1);
+        assert!(matches!(
+            &parsed.errors[0],
+            (2, ParseError::InvalidValue(e)) if matches!(e.source, ValueError::Price(_))
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use rust_decimal::{Decimal, RoundingStrategy, prelude::FromPrimitive};
+
+use crate::{AbcGroup, AbcProduct, CostBasis, catalog::AbcCatalog, classify::Classifier};
+
+/// The stock on hand of some part of the catalog and what it is worth
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StockValue {
+    /// How many products were counted
+    pub products: usize,
+    /// The total units on hand
+    pub units: f64,
+    /// The units on hand times their cost, rounded to the cent
+    pub cost: Decimal,
+    /// The units on hand times their list price, rounded to the cent
+    pub retail: Decimal,
+}
+
+impl StockValue {
+    /// Count the stock of `product`, valuing it at its cost on `basis`
+    fn add(&mut self, product: &AbcProduct, basis: CostBasis) {
+        let units = Decimal::from_f64(product.stock()).unwrap_or_default();
+        self.products += 1;
+        self.units += product.stock();
+        self.cost = (self.cost + units * product.cost_basis(basis))
+            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+        self.retail = (self.retail + units * product.list())
+            .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+    }
+}
+
+/// What the stock on hand of a catalog is worth, as returned by [`AbcCatalog::valuation`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Valuation {
+    /// The whole catalog
+    pub total: StockValue,
+    /// Each discount group, with products that have no group under [`None`]
+    pub by_group: BTreeMap<Option<AbcGroup>, StockValue>,
+}
+
+/// Which products [`AbcCatalog::valuation_with`] counts. The default counts every product
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValuationOptions {
+    /// Leave out products with negative stock rather than letting them lower the totals.
+    /// Negative stock usually means sales were rung up before the matching receipt was entered
+    pub skip_negative: bool,
+    /// Count only the products this classifier decides are
+    /// [`crate::classify::ItemKind::Inventory`], since labor, gift cards, and fees have no
+    /// meaningful stock. [`None`] to count every product
+    pub classifier: Option<Classifier>,
+}
+
+impl AbcCatalog {
+    /// Value the stock on hand of every product at cost and at list price, in total and for
+    /// each discount group
+    ///
+    /// # Arguments
+    /// * `basis` - Whether to value stock at average or last cost
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{CostBasis, catalog::AbcCatalog, testing};
+    /// use rust_decimal::Decimal;
+    ///
+    /// let catalog = AbcCatalog::new(testing::sample_export());
+    /// let valuation = catalog.valuation(CostBasis::Last);
+    /// // ABC123 has 6 units less than none at a cost of 5.23
+    /// assert_eq!(valuation.total.cost, Decimal::new(-3138, 2));
+    /// ```
+    pub fn valuation(&self, basis: CostBasis) -> Valuation {
+        self.valuation_with(basis, &ValuationOptions::default())
+    }
+
+    /// Like [`AbcCatalog::valuation`], counting only the products allowed by `options`
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{
+    ///     CostBasis, catalog::AbcCatalog, classify::Classifier, testing,
+    ///     valuation::ValuationOptions,
+    /// };
+    ///
+    /// let catalog = AbcCatalog::new(testing::sample_export());
+    /// let options = ValuationOptions {
+    ///     skip_negative: true,
+    ///     classifier: Some(Classifier::default()),
+    /// };
+    /// assert_eq!(catalog.valuation_with(CostBasis::Last, &options).total.products, 1);
+    /// ```
+    pub fn valuation_with(&self, basis: CostBasis, options: &ValuationOptions) -> Valuation {
+        let mut valuation = Valuation::default();
+        for product in self.iter() {
+            if (options.skip_negative && product.stock() < 0.0)
+                || options
+                    .classifier
+                    .as_ref()
+                    .is_some_and(|c| !c.is_inventory(product))
+            {
+                continue;
+            }
+            valuation.total.add(product, basis);
+            valuation
+                .by_group
+                .entry(product.group())
+                .or_default()
+                .add(product, basis);
+        }
+        valuation
+    }
+
+    /// Find dead stock: products with stock on hand that have not sold since before `cutoff`,
+    /// or have never sold at all
+    ///
+    /// # Arguments
+    /// * `cutoff` - Products last sold before this date are stale
+    ///
+    /// # Returns
+    /// The stale products, with the most cost tied up in stock first and by sku when the value
+    /// ties
+    ///
+    /// # Example
+    /// ```rust
+    /// use abc_product::{catalog::AbcCatalog, testing};
+    /// use chrono::NaiveDate;
+    ///
+    /// let catalog = AbcCatalog::new(testing::sample_export());
+    /// let cutoff = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    /// // Neither sample product has stock on hand
+    /// assert!(catalog.stale_products(cutoff).is_empty());
+    /// ```
+    pub fn stale_products(&self, cutoff: NaiveDate) -> Vec<&AbcProduct> {
+        let mut stale: Vec<(Decimal, &AbcProduct)> = self
+            .iter()
+            .filter(|p| p.stock() > 0.0)
+            .filter(|p| p.last_sold().is_none_or(|sold| sold < cutoff))
+            .map(|p| {
+                let units = Decimal::from_f64(p.stock()).unwrap_or_default();
+                (units * p.cost(), p)
+            })
+            .collect();
+        stale.sort_by(|(a, p), (b, q)| b.cmp(a).then_with(|| p.sku().cmp(&q.sku())));
+        stale.into_iter().map(|(_, p)| p).collect()
+    }
+
+    /// Like [`AbcCatalog::stale_products`], keeping only the products `classifier` decides are
+    /// [`crate::classify::ItemKind::Inventory`]
+    pub fn stale_inventory(&self, cutoff: NaiveDate, classifier: &Classifier) -> Vec<&AbcProduct> {
+        self.stale_products(cutoff)
+            .into_iter()
+            .filter(|p| classifier.is_inventory(p))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, Sku, classify::ItemKind, testing::sample_export};
+
+    #[test]
+    fn test_valuation() {
+        let mut products = sample_export();
+        let stocked = AbcProductBuilder::from(products["123456"].clone())
+            .with_stock(10.0)
+            .with_avg_cost(Decimal::new(150, 2))
+            .build()
+            .unwrap();
+        products.insert(Sku::new("123456"), stocked);
+        let labor = AbcProductBuilder::from(products["123456"].clone())
+            .with_sku("LABOR1")
+            .with_desc("INSTALL LABOR")
+            .build()
+            .unwrap();
+        products.insert(Sku::new("LABOR1"), labor);
+        let catalog = AbcCatalog::new(products);
+
+        let inventory = ValuationOptions {
+            classifier: Some(Classifier::default()),
+            ..Default::default()
+        };
+        let valuation = catalog.valuation_with(CostBasis::Last, &inventory);
+        assert_eq!(valuation.total.products, 2);
+        assert_eq!(valuation.total.units, 4.0);
+        // 10 x 1.23 less 6 x 5.23
+        assert_eq!(valuation.total.cost, Decimal::new(-1908, 2));
+        // 10 x 5.99 less 6 x 8.12
+        assert_eq!(valuation.total.retail, Decimal::new(1118, 2));
+        assert_eq!(valuation.by_group[&None].cost, Decimal::new(1230, 2));
+        assert_eq!(valuation.by_group[&AbcGroup::new('A')].units, -6.0);
+        // 10 x 1.50 on average. ABC123 has no average cost, so 6 x 5.23 is used
+        let average = catalog.valuation_with(CostBasis::Average, &inventory);
+        assert_eq!(average.total.cost, Decimal::new(-1638, 2));
+        assert_eq!(average.total.retail, valuation.total.retail);
+
+        let options = ValuationOptions {
+            skip_negative: true,
+            ..inventory
+        };
+        let valuation = catalog.valuation_with(CostBasis::Last, &options);
+        assert_eq!(valuation.total.cost, Decimal::new(1230, 2));
+        assert!(!valuation.by_group.contains_key(&AbcGroup::new('A')));
+
+        // Without a classifier, labor is valued like any other product
+        assert_eq!(catalog.valuation(CostBasis::Last).total.products, 3);
+        let naive = ValuationOptions {
+            skip_negative: true,
+            classifier: Some(Classifier::new()),
+        };
+        assert_eq!(
+            catalog
+                .valuation_with(CostBasis::Last, &naive)
+                .total
+                .products,
+            2
+        );
+        assert_eq!(
+            Classifier::default().classify(catalog.get_by_sku("LABOR1").unwrap()),
+            ItemKind::Labor
+        );
+    }
+
+    #[test]
+    fn test_stale_products() {
+        let day = |m: u32, d: u32| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let mut products = sample_export();
+        let product = |sku: &str, stock: f64, last_sold: Option<NaiveDate>| {
+            let builder = AbcProductBuilder::new()
+                .with_sku(sku)
+                .with_desc("WIDGET")
+                .with_list(Decimal::new(500, 2))
+                .with_cost(Decimal::new(250, 2))
+                .with_stock(stock);
+            match last_sold {
+                Some(date) => builder.with_last_sold(date),
+                None => builder,
+            }
+            .build()
+            .unwrap()
+        };
+        let stale = [
+            product("OLD", 2.0, Some(day(1, 5))),
+            product("NEVER", 5.0, None),
+            product("RECENT", 9.0, Some(day(6, 1))),
+            product("EMPTY", 0.0, Some(day(1, 5))),
+            AbcProductBuilder::from(product("GIFT", 4.0, None))
+                .with_desc("GIFT CARD")
+                .build()
+                .unwrap(),
+        ];
+        for p in stale {
+            products.insert(p.sku().into(), p);
+        }
+        let catalog = AbcCatalog::new(products);
+
+        let found: Vec<String> = catalog
+            .stale_products(day(3, 1))
+            .iter()
+            .map(|p| p.sku())
+            .collect();
+        assert_eq!(found, vec!["NEVER", "GIFT", "OLD"]);
+
+        let found: Vec<String> = catalog
+            .stale_inventory(day(3, 1), &Classifier::default())
+            .iter()
+            .map(|p| p.sku())
+            .collect();
+        assert_eq!(found, vec!["NEVER", "OLD"]);
+    }
+}
@@ -0,0 +1,203 @@
+use std::{collections::HashMap, io::Read};
+
+use crate::{
+    AbcParseError, Encoding, open_data_file,
+    records::{rows, text},
+};
+
+/// Column numbers of the fields this crate knows about in `vendor.data`, counting from zero
+pub mod vendor_columns {
+    pub const CODE: usize = 0;
+    pub const NAME: usize = 1;
+    pub const ADDRESS: [usize; 2] = [2, 3];
+    pub const CITY: usize = 4;
+    pub const STATE: usize = 5;
+    pub const ZIP: usize = 6;
+    pub const PHONE: usize = 7;
+    pub const FAX: usize = 8;
+    pub const CONTACT: usize = 9;
+    pub const ACCOUNT: usize = 10;
+    pub const TERMS: usize = 11;
+    pub const EMAIL: usize = 12;
+    pub const FREIGHT: usize = 13;
+}
+
+/// A map of vendor codes to the [`AbcVendor`] they belong to
+pub type AbcVendorsByCode = HashMap<String, AbcVendor>;
+
+/// A supplier, as exported from ABC's vendor file
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AbcVendor {
+    code: String,
+    name: String,
+    account: Option<String>,
+    terms: Option<String>,
+    contact: Option<String>,
+    phone: Option<String>,
+    fax: Option<String>,
+    email: Option<String>,
+    address: Vec<String>,
+    city: Option<String>,
+    state: Option<String>,
+    zip: Option<String>,
+    freight: Option<String>,
+}
+
+impl AbcVendor {
+    /// Create a map of vendor codes to [`AbcVendor`]s by parsing the vendor file of an ABC
+    /// database export.
+    ///
+    /// Run report 7-10 as described in [`crate::AbcProduct::from_db_export`], but select "V"
+    /// (Vendor) as the file to export. The file is written next to `item.data`, at
+    /// C:\ABC Software\Database Export\Company001\Data\vendor.data.
+    ///
+    /// Products are linked to their primary vendor through [`crate::records::ItemRecord::vendor`].
+    ///
+    /// # Arguments
+    /// * `vendor_path` - The path to the vendor.data file generated by the db export
+    ///
+    /// # Returns
+    /// A [`HashMap`] of vendor codes to the [`AbcVendor`] they belong to
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if the file cannot be read, or if any row is missing its code or name
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use abc_product::{records, vendor::AbcVendor};
+    ///
+    /// let vendors = AbcVendor::from_db_export("./vendor.data").unwrap();
+    /// for item in records::read_item_data("./item.data").unwrap() {
+    ///     let item = item.unwrap();
+    ///     if let Some(vendor) = item.vendor().and_then(|v| vendors.get(v)) {
+    ///         println!("{} is supplied by {}", item.sku().unwrap(), vendor.name());
+    ///     }
+    /// }
+    /// ```
+    pub fn from_db_export(vendor_path: &str) -> Result<AbcVendorsByCode, AbcParseError> {
+        Self::from_reader(open_data_file(vendor_path).map_err(csv::Error::from)?)
+    }
+
+    /// Like [`AbcVendor::from_db_export`], but reads the vendor file from any reader
+    pub(crate) fn from_reader(data: impl Read) -> Result<AbcVendorsByCode, AbcParseError> {
+        let mut vendors = AbcVendorsByCode::new();
+        for row in rows(data, "vendor.data", Encoding::Auto)? {
+            let (row, record) = row?;
+            let code = text(&record, vendor_columns::CODE).ok_or_else(|| {
+                AbcParseError::missing(Some("vendor.data"), row, Some(vendor_columns::CODE), "code")
+            })?;
+            let name = text(&record, vendor_columns::NAME).ok_or_else(|| {
+                AbcParseError::missing(Some("vendor.data"), row, Some(vendor_columns::NAME), "name")
+            })?;
+            let vendor = AbcVendor {
+                code: code.clone(),
+                name,
+                account: text(&record, vendor_columns::ACCOUNT),
+                terms: text(&record, vendor_columns::TERMS),
+                contact: text(&record, vendor_columns::CONTACT),
+                phone: text(&record, vendor_columns::PHONE),
+                fax: text(&record, vendor_columns::FAX),
+                email: text(&record, vendor_columns::EMAIL),
+                address: vendor_columns::ADDRESS
+                    .iter()
+                    .filter_map(|c| text(&record, *c))
+                    .collect(),
+                city: text(&record, vendor_columns::CITY),
+                state: text(&record, vendor_columns::STATE),
+                zip: text(&record, vendor_columns::ZIP),
+                freight: text(&record, vendor_columns::FREIGHT),
+            };
+            vendors.insert(code, vendor);
+        }
+        Ok(vendors)
+    }
+
+    /// The code ABC uses to identify the vendor
+    pub fn code(&self) -> String {
+        self.code.to_string()
+    }
+
+    /// The vendor's name
+    pub fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    /// The store's account number with the vendor
+    pub fn account(&self) -> Option<String> {
+        self.account.clone()
+    }
+
+    /// The vendor's payment terms, such as "NET 30"
+    pub fn terms(&self) -> Option<String> {
+        self.terms.clone()
+    }
+
+    /// The name of the store's contact at the vendor
+    pub fn contact(&self) -> Option<String> {
+        self.contact.clone()
+    }
+
+    /// The vendor's phone number
+    pub fn phone(&self) -> Option<String> {
+        self.phone.clone()
+    }
+
+    /// The vendor's fax number
+    pub fn fax(&self) -> Option<String> {
+        self.fax.clone()
+    }
+
+    /// The vendor's email address
+    pub fn email(&self) -> Option<String> {
+        self.email.clone()
+    }
+
+    /// The street lines of the vendor's address, skipping empty lines
+    pub fn address(&self) -> Vec<String> {
+        self.address.clone()
+    }
+
+    /// The city of the vendor's address
+    pub fn city(&self) -> Option<String> {
+        self.city.clone()
+    }
+
+    /// The state of the vendor's address
+    pub fn state(&self) -> Option<String> {
+        self.state.clone()
+    }
+
+    /// The zip code of the vendor's address
+    pub fn zip(&self) -> Option<String> {
+        self.zip.clone()
+    }
+
+    /// How freight is normally handled on orders from the vendor, such as "PREPAID" or "COLLECT"
+    pub fn freight(&self) -> Option<String> {
+        self.freight.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_parser() {
+        let data = "ACME\tACME SUPPLY CO\t12 MAIN ST\t\tSPRINGFIELD\tIL\t62701\t555-0100\tnull\tPAT\t88231\tNET 30\torders@acme.test\tPREPAID\r\n\
+                    BOLT\tBOLT DIST\t\t\t\t\t\t\t\t\t\t\t\t\n";
+        let vendors = AbcVendor::from_reader(data.as_bytes()).unwrap();
+        assert_eq!(vendors.len(), 2);
+        let acme = &vendors["ACME"];
+        assert_eq!(acme.name(), "ACME SUPPLY CO");
+        assert_eq!(acme.account(), Some("88231".to_string()));
+        assert_eq!(acme.terms(), Some("NET 30".to_string()));
+        assert_eq!(acme.fax(), None);
+        assert_eq!(acme.address(), vec!["12 MAIN ST".to_string()]);
+        assert_eq!(acme.freight(), Some("PREPAID".to_string()));
+        assert_eq!(vendors["BOLT"].terms(), None);
+
+        assert!(AbcVendor::from_reader("\tNO CODE\n".as_bytes()).is_err());
+    }
+}
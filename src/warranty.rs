@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, io::Read};
+
+use crate::{AbcParseError, AbcProduct, AbcProductsBySku, input};
+
+/// Warranty lengths kept outside of ABC, used to fill in [`AbcProduct::warranty_months`] for
+/// products whose export does not carry one.
+///
+/// # Example
+/// ```rust
+/// use abc_product::{testing, warranty::Warranties};
+/// use chrono::NaiveDate;
+///
+/// let mut products = testing::sample_export();
+/// Warranties::new()
+///     .with_group("A", 24)
+///     .apply(&mut products);
+/// let sold = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+/// assert_eq!(
+///     products["ABC123"].warranty_expiry(sold),
+///     NaiveDate::from_ymd_opt(2026, 3, 1)
+/// );
+/// assert_eq!(products["123456"].warranty_expiry(sold), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Warranties {
+    by_sku: BTreeMap<String, u32>,
+    by_group: BTreeMap<String, u32>,
+}
+
+impl Warranties {
+    /// Create an empty [`Warranties`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the warranty of a single sku, which takes priority over the warranty of its group
+    pub fn with_sku(self, sku: &str, months: u32) -> Self {
+        let mut by_sku = self.by_sku;
+        by_sku.insert(sku.trim().to_string(), months);
+        Warranties { by_sku, ..self }
+    }
+
+    /// Set the warranty of every product in a discount group
+    pub fn with_group(self, group: &str, months: u32) -> Self {
+        let mut by_group = self.by_group;
+        by_group.insert(group.trim().to_string(), months);
+        Warranties { by_group, ..self }
+    }
+
+    /// Load warranties from a comma separated file of skus and months, such as `123456,12`. A
+    /// header row starting with "sku" is skipped.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file of warranties
+    ///
+    /// # Errors
+    /// An [`AbcParseError`] if the file cannot be read, or if a row is missing its months or they
+    /// are not a whole number
+    pub fn from_csv(path: &str) -> Result<Self, AbcParseError> {
+        Self::from_csv_reader(std::fs::File::open(path).map_err(csv::Error::from)?)
+    }
+
+    /// Like [`Warranties::from_csv`], reading from any reader rather than a path on disk
+    pub(crate) fn from_csv_reader(data: impl Read) -> Result<Self, AbcParseError> {
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(input::normalize(data).map_err(csv::Error::from)?);
+        let mut warranties = Warranties::new();
+        for (i, record) in reader.into_records().enumerate() {
+            let record = record?;
+            let Some(sku) = record.get(0).map(str::trim).filter(|s| !s.is_empty()) else {
+                continue;
+            };
+            if sku.eq_ignore_ascii_case("sku") {
+                continue;
+            }
+            let months = record
+                .get(1)
+                .ok_or_else(|| AbcParseError::missing(None, i + 1, Some(1), "months"))?;
+            let months = months
+                .trim()
+                .parse()
+                .map_err(|e| AbcParseError::invalid(None, i + 1, 1, "months", months, e))?;
+            warranties = warranties.with_sku(sku, months);
+        }
+        Ok(warranties)
+    }
+
+    /// The warranty of `product` in months: its sku's if set, otherwise its group's
+    pub fn months(&self, product: &AbcProduct) -> Option<u32> {
+        self.by_sku.get(&product.sku()).copied().or_else(|| {
+            product
+                .group()
+                .and_then(|g| self.by_group.get(&g.to_string()))
+                .copied()
+        })
+    }
+
+    /// Set [`AbcProduct::warranty_months`] on every product in `products` that does not already
+    /// have one from the export
+    pub fn apply(&self, products: &mut AbcProductsBySku) {
+        for product in products.values_mut() {
+            if product.warranty_months.is_none() {
+                product.warranty_months = self.months(product);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbcProductBuilder, Sku, testing::sample_export};
+
+    #[test]
+    fn test_warranties() {
+        let data = "sku,months\n123456,12\nABC123,36\n";
+        let warranties = Warranties::from_csv_reader(data.as_bytes())
+            .unwrap()
+            .with_group("A", 24);
+        let mut products = sample_export();
+        let from_export = AbcProductBuilder::from(products["ABC123"].clone())
+            .with_warranty_months(6)
+            .build()
+            .unwrap();
+        products.insert(Sku::new("ABC123"), from_export);
+        warranties.apply(&mut products);
+        assert_eq!(products["123456"].warranty_months(), Some(12));
+        assert_eq!(products["ABC123"].warranty_months(), Some(6));
+
+        // The sku's own warranty wins over its group's
+        assert_eq!(warranties.months(&sample_export()["ABC123"]), Some(36));
+        assert_eq!(
+            Warranties::new()
+                .with_group("A", 24)
+                .months(&sample_export()["ABC123"]),
+            Some(24)
+        );
+
+        assert!(Warranties::from_csv_reader("123456,twelve\n".as_bytes()).is_err());
+    }
+}
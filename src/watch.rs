@@ -0,0 +1,210 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::{
+    AbcParseError, AbcProduct, AbcProductsBySku, Encoding, ParseOptions, catalog::AbcCatalog,
+    diff::CatalogDiff,
+};
+
+/// How long the export files must go without changing before they are parsed. ABC writes
+/// `item.data` and `item_posted.data` one after the other, a piece at a time, so parsing as soon
+/// as the first write lands would read a half written export
+pub const DEFAULT_SETTLE: Duration = Duration::from_secs(2);
+
+/// What an [`ExportWatcher`] found after the export files settled
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// The export was parsed
+    Reloaded {
+        /// Every product in the export
+        catalog: AbcCatalog,
+        /// What changed since the last export that parsed. Everything is added for the first
+        diff: CatalogDiff,
+    },
+    /// The export could not be parsed. The next reload is still compared against the last
+    /// export that parsed
+    Failed(AbcParseError),
+}
+
+/// Watches an ABC database export directory and parses the export again whenever `item.data`
+/// or `item_posted.data` is rewritten.
+///
+/// The export is parsed once as soon as the watcher starts, so the first event always holds the
+/// current catalog. Dropping the watcher stops it.
+///
+/// # Example
+/// ```rust,no_run
+/// use abc_product::watch::{ExportWatcher, WatchEvent};
+///
+/// let watcher =
+///     ExportWatcher::new("C:/ABC Software/Database Export/Company001/Data").unwrap();
+/// while let Some(event) = watcher.recv() {
+///     match event {
+///         WatchEvent::Reloaded { catalog, diff } => {
+///             println!("{} products, {} changed", catalog.len(), diff.changed.len())
+///         }
+///         WatchEvent::Failed(e) => eprintln!("{}", e),
+///     }
+/// }
+/// ```
+pub struct ExportWatcher {
+    _watcher: RecommendedWatcher,
+    events: Receiver<WatchEvent>,
+}
+
+impl ExportWatcher {
+    /// Start watching `dir`, the directory ABC writes `item.data` and `item_posted.data` to,
+    /// waiting [`DEFAULT_SETTLE`] for the files to stop changing before each reload
+    ///
+    /// # Errors
+    /// A [`notify::Error`] if `dir` cannot be watched
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, notify::Error> {
+        Self::with_settle(dir, DEFAULT_SETTLE)
+    }
+
+    /// Like [`ExportWatcher::new`], waiting `settle` for the files to stop changing instead
+    ///
+    /// # Errors
+    /// A [`notify::Error`] if `dir` cannot be watched
+    pub fn with_settle(dir: impl AsRef<Path>, settle: Duration) -> Result<Self, notify::Error> {
+        Self::with_encoding(dir, settle, Encoding::default())
+    }
+
+    /// Like [`ExportWatcher::with_settle`], reading the files as text in `encoding` instead of
+    /// detecting it
+    ///
+    /// # Errors
+    /// A [`notify::Error`] if `dir` cannot be watched
+    pub fn with_encoding(
+        dir: impl AsRef<Path>,
+        settle: Duration,
+        encoding: Encoding,
+    ) -> Result<Self, notify::Error> {
+        let dir = dir.as_ref().to_path_buf();
+        let (changes_tx, changes) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event
+                && rewrites_export(&event)
+            {
+                let _ = changes_tx.send(());
+            }
+        })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        let (events_tx, events) = mpsc::channel();
+        thread::spawn(move || reload_loop(&dir, settle, encoding, &changes, &events_tx));
+        Ok(ExportWatcher {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Wait for the next reload. [`None`] once the watcher has stopped
+    pub fn recv(&self) -> Option<WatchEvent> {
+        self.events.recv().ok()
+    }
+
+    /// The channel reloads are sent on, for waiting with a timeout or checking without blocking
+    pub fn events(&self) -> &Receiver<WatchEvent> {
+        &self.events
+    }
+}
+
+/// Whether `event` created or changed one of the files of the export
+fn rewrites_export(event: &Event) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|p| {
+            p.file_name()
+                .is_some_and(|name| name == "item.data" || name == "item_posted.data")
+        })
+}
+
+/// Parse the export in `dir` once, then again each time the files settle after a change, until
+/// either end of the channels is dropped
+fn reload_loop(
+    dir: &Path,
+    settle: Duration,
+    encoding: Encoding,
+    changes: &Receiver<()>,
+    events: &Sender<WatchEvent>,
+) {
+    let item: PathBuf = dir.join("item.data");
+    let item_posted: PathBuf = dir.join("item_posted.data");
+    let options = ParseOptions::default().with_encoding(encoding);
+    let mut last = AbcCatalog::new(AbcProductsBySku::new());
+    loop {
+        let event = match AbcProduct::from_db_export_with_options(
+            &item.to_string_lossy(),
+            &item_posted.to_string_lossy(),
+            &options,
+        ) {
+            Ok((products, _, _)) => {
+                let catalog = AbcCatalog::new(products);
+                let diff = AbcCatalog::diff(&last, &catalog);
+                last = catalog.clone();
+                WatchEvent::Reloaded { catalog, diff }
+            }
+            Err(e) => WatchEvent::Failed(e),
+        };
+        if events.send(event).is_err() || changes.recv().is_err() {
+            return;
+        }
+        loop {
+            match changes.recv_timeout(settle) {
+                Ok(()) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{SAMPLE_ITEM_DATA, SAMPLE_ITEM_POSTED_DATA};
+    use std::fs;
+
+    #[test]
+    fn test_export_watcher() {
+        let dir = std::env::temp_dir().join(format!("abc-product-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("item.data"), SAMPLE_ITEM_DATA).unwrap();
+        fs::write(dir.join("item_posted.data"), SAMPLE_ITEM_POSTED_DATA).unwrap();
+        let watcher = ExportWatcher::with_settle(&dir, Duration::from_millis(200)).unwrap();
+        let next = || {
+            watcher
+                .events()
+                .recv_timeout(Duration::from_secs(10))
+                .unwrap()
+        };
+
+        let WatchEvent::Reloaded { catalog, diff } = next() else {
+            panic!("the sample export should parse");
+        };
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(diff.added.len(), 2);
+
+        let item = String::from_utf8(SAMPLE_ITEM_DATA.to_vec())
+            .unwrap()
+            .replace("\t8.12\t", "\t9.12\t");
+        fs::write(dir.join("item.data"), item).unwrap();
+        let WatchEvent::Reloaded { diff, .. } = next() else {
+            panic!("the changed export should parse");
+        };
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].sku, "ABC123");
+
+        fs::write(dir.join("item.data"), "ABC123\n").unwrap();
+        assert!(matches!(next(), WatchEvent::Failed(_)));
+        drop(watcher);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}